@@ -0,0 +1,80 @@
+//! Block-wise sample conversion against the plain per-sample loops it
+//! replaced. Run with `cargo bench --bench convert`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+
+// Its unit tests come along but only run under `cargo test`
+#[allow(dead_code, unused_imports)]
+#[path = "../src/audio/convert.rs"]
+mod convert;
+
+/// One 20 ms Opus frame at 48 kHz.
+const FRAMES: usize = 960;
+
+fn samples(len: usize) -> Vec<i16> {
+    (0..len)
+        .map(|n| (n as i32 * 7_919 % 65_536 - 32_768) as i16)
+        .collect()
+}
+
+fn downmix(c: &mut Criterion) {
+    let stereo = samples(FRAMES * 2);
+    let mut mono = Vec::with_capacity(FRAMES);
+    let mut group = c.benchmark_group("downmix_stereo");
+    group.bench_function("blocks", |b| {
+        b.iter(|| convert::downmix_stereo(black_box(&stereo), &mut mono))
+    });
+    group.bench_function("scalar", |b| {
+        b.iter(|| {
+            mono.clear();
+            mono.extend(
+                black_box(&stereo)
+                    .chunks_exact(2)
+                    .map(|s| ((i32::from(s[0]) + i32::from(s[1])) / 2) as i16),
+            );
+        })
+    });
+    group.finish();
+}
+
+fn write(c: &mut Criterion) {
+    let frames: Vec<convert::Frame> = samples(FRAMES * 2)
+        .chunks_exact(2)
+        .map(|s| [s[0], s[1]])
+        .collect();
+    let mut group = c.benchmark_group("write_f32");
+    for channels in [1, 2] {
+        let mut out = vec![0f32; FRAMES * channels];
+        group.bench_with_input(BenchmarkId::new("blocks", channels), &channels, |b, &ch| {
+            b.iter(|| convert::write_f32(&mut out, ch, black_box(&frames)))
+        });
+        group.bench_with_input(BenchmarkId::new("scalar", channels), &channels, |b, &ch| {
+            b.iter(|| {
+                for (o, &[l, r]) in out.chunks_mut(ch).zip(black_box(&frames)) {
+                    if ch == 1 {
+                        o[0] = f32::from(((i32::from(l) + i32::from(r)) / 2) as i16) / 32_768.0;
+                    } else {
+                        o[0] = f32::from(l) / 32_768.0;
+                        o[1] = f32::from(r) / 32_768.0;
+                    }
+                }
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("write_16");
+    let mut out = vec![0i16; FRAMES * 2];
+    group.bench_function("i16_stereo", |b| {
+        b.iter(|| convert::write_i16(&mut out, 2, black_box(&frames)))
+    });
+    let mut out = vec![0u16; FRAMES * 2];
+    group.bench_function("u16_stereo", |b| {
+        b.iter(|| convert::write_u16(&mut out, 2, black_box(&frames)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, downmix, write);
+criterion_main!(benches);
@@ -2,26 +2,71 @@
 // App UI — iced 0.13 application with system tray integration
 // ---------------------------------------------------------------------------
 
-use crate::core::{CoreCommand, CoreController, SharedStatus, StatusSnapshot};
-use crate::TrayMessage;
-use cpal::traits::{DeviceTrait, HostTrait};
+use crate::app_settings::{self, AppSettings};
+use crate::appearance::{self, AccentColor, Appearance};
+use crate::audio::dsp::AgcConfig;
+use crate::audio::mixer::{ClientId, MAX_STEREO_WIDTH};
+use crate::audio::VirtualDevice;
+use crate::core::access::IpNet;
+use crate::core::errors::ErrorReport;
+use crate::core::history::{self, HistoryEntry};
+use crate::core::log_file::{self, LogFileSettings, Rotation};
+use crate::core::logs::{LogEntry, LogLevel};
+use crate::core::quality::{QualityPreset, LATENCY_RANGE_MS};
+use crate::core::recovery::{self, ListenState};
+use crate::core::signaling::control::LOW_BITRATE_KBPS;
+use crate::core::signaling::preferred_interface;
+use crate::core::signaling::udp_session::{UdpFormat, UdpIngest};
+use crate::core::signaling::user_cert::{self, UserCertificate};
+use crate::core::signaling::IceServer;
+use crate::core::{self, CoreCommand, CoreController, CoreEvent, SharedStatus, StatusSnapshot};
+use crate::icon::{TRAY_ICON_SIZE, WINDOW_ICON_SIZE};
+use crate::palette;
+use crate::platform::firewall::{self, RuleStatus};
+use crate::platform::linux_virtualmic;
+use crate::recorder::RecordingFormat;
+use crate::{TrayMenu, TrayMessage, TRAY_OUTPUT_PREFIX};
 use iced::{
     widget::{
-        button, checkbox, column, container, horizontal_space, pick_list, qr_code, row,
-        scrollable, text, text_input, vertical_space,
+        button, checkbox, column, container, horizontal_space, opaque, pick_list, qr_code, row,
+        scrollable, slider, stack, text, text_input, vertical_space,
     },
-    Alignment, Border, Color, Element, Length, Shadow, Subscription, Task, Theme,
+    keyboard, Alignment, Border, Color, Element, Length, Shadow, Subscription, Task, Theme,
 };
+use std::time::Duration;
 
 // ===========================================================================
 // Design Tokens — premium dark theme inspired by modern VPN / audio apps
 // ===========================================================================
 
-// Backgrounds
-const BG_PRIMARY: Color = Color::from_rgb(0.06, 0.07, 0.09);
-const BG_ELEVATED: Color = Color::from_rgb(0.10, 0.11, 0.14);
-const BG_INPUT: Color = Color::from_rgb(0.14, 0.15, 0.18);
-const BG_HOVER: Color = Color::from_rgb(0.16, 0.17, 0.21);
+// Backgrounds and the accent follow the user's appearance settings, so they
+// are read from the active palette instead of being constants.
+static APPEARANCE: parking_lot::RwLock<Appearance> =
+    parking_lot::const_rwlock(Appearance::DEFAULT);
+
+/// How much of the base brightness "darkness = 1" removes.
+const MAX_DARKENING: f32 = 0.7;
+
+fn background(r: f32, g: f32, b: f32) -> Color {
+    let k = 1.0 - APPEARANCE.read().darkness.clamp(0.0, 1.0) * MAX_DARKENING;
+    Color::from_rgb(r * k, g * k, b * k)
+}
+
+fn bg_primary() -> Color {
+    background(0.06, 0.07, 0.09)
+}
+
+fn bg_elevated() -> Color {
+    background(0.10, 0.11, 0.14)
+}
+
+fn bg_input() -> Color {
+    background(0.14, 0.15, 0.18)
+}
+
+fn bg_hover() -> Color {
+    background(0.16, 0.17, 0.21)
+}
 
 // Borders
 const BORDER_SUBTLE: Color = Color::from_rgb(0.20, 0.21, 0.25);
@@ -32,12 +77,63 @@ const TEXT_SECONDARY: Color = Color::from_rgb(0.55, 0.57, 0.63);
 const TEXT_TERTIARY: Color = Color::from_rgb(0.40, 0.42, 0.48);
 
 // Accents
-const ACCENT: Color = Color::from_rgb(0.25, 0.56, 0.97);
+fn accent() -> Color {
+    let (r, g, b) = APPEARANCE.read().accent.rgb();
+    Color::from_rgb(r, g, b)
+}
 
 const SUCCESS: Color = Color::from_rgb(0.20, 0.78, 0.55);
 const ERROR: Color = Color::from_rgb(0.95, 0.35, 0.40);
 const WARNING: Color = Color::from_rgb(0.95, 0.70, 0.25);
 
+/// Keyboard focus indicator.
+const FOCUS_RING: Color = Color::from_rgb(0.55, 0.75, 1.0);
+
+/// Default window size; below either compact threshold the main view
+/// switches to the condensed layout.
+const WINDOW_SIZE: iced::Size = iced::Size::new(400.0, 600.0);
+const COMPACT_MAX_WIDTH: f32 = 360.0;
+const COMPACT_MAX_HEIGHT: f32 = 480.0;
+
+/// Choices for stopping automatically after a while without a sender.
+const IDLE_STOP_CHOICES: [IdleStop; 6] = [
+    IdleStop(None),
+    IdleStop(Some(15)),
+    IdleStop(Some(30)),
+    IdleStop(Some(60)),
+    IdleStop(Some(120)),
+    IdleStop(Some(240)),
+];
+
+/// Matches shown in the command palette at once.
+const PALETTE_MAX_RESULTS: usize = 8;
+
+/// Pick-list entry meaning "mix into the main output device".
+const MAIN_OUTPUT_LABEL: &str = "Main output";
+
+/// Pick-list entry meaning "no monitor output".
+const MONITOR_OFF_LABEL: &str = "Off";
+
+/// Newest matching log entries shown in the Logs view.
+const LOG_VIEW_LINES: usize = 200;
+
+/// Bottom of the level meter scale, in dBFS.
+const METER_FLOOR_DB: f32 = -60.0;
+
+/// UI refresh interval while a sender is connected (meters, timeline).
+const ACTIVE_TICK: Duration = Duration::from_millis(50);
+/// Animation step of the "waiting for a sender" pulse.
+const PULSE_TICK: Duration = Duration::from_millis(250);
+/// Refresh when nothing animates. State changes arrive as core events, so
+/// this only picks up tray clicks, counters and the recording clock.
+const FALLBACK_TICK: Duration = Duration::from_millis(500);
+/// Tray level indicator refresh while audio is flowing.
+const TRAY_LEVEL_TICK: Duration = Duration::from_millis(200);
+
+/// Steps the tray level is quantized to, so the icon is only re-uploaded
+/// when the level visibly changes.
+const TRAY_LEVEL_STEPS: f32 = 8.0;
+
 // ===========================================================================
 // Launch
 // ===========================================================================
@@ -46,44 +142,144 @@ pub fn launch_app(
     controller: CoreController,
     shared: SharedStatus,
     tray_rx: std::sync::mpsc::Receiver<TrayMessage>,
+    tray_icon: tray_icon::TrayIcon,
+    tray_menu: TrayMenu,
 ) -> iced::Result {
     let output_devices = enumerate_output_devices();
     let selected_output = output_devices.first().cloned();
 
     // Create window icon (same design as tray, larger for clarity)
-    let win_icon_data = crate::icon::create_icon(64);
-    let win_icon = iced::window::icon::from_rgba(win_icon_data, 64, 64).ok();
+    let win_icon_data = crate::icon::create_icon(WINDOW_ICON_SIZE);
+    let win_icon =
+        iced::window::icon::from_rgba(win_icon_data, WINDOW_ICON_SIZE, WINDOW_ICON_SIZE).ok();
 
-    iced::application("LAN Mic Receiver", App::update, App::view)
+    let appearance = appearance::load();
+    *APPEARANCE.write() = appearance;
+    let settings = app_settings::load();
+
+    let profile = crate::profile::current();
+    let autostart = crate::platform::autostart::status();
+    let firewall = firewall::status();
+    if let Some(status @ (RuleStatus::Missing | RuleStatus::Stale)) = firewall {
+        shared.log_line(format!(
+            "{status}. Use Settings → Allow Through Firewall if senders can't connect."
+        ));
+    }
+    let virtual_mic = linux_virtualmic::available();
+    if virtual_mic && settings.virtual_mic {
+        match linux_virtualmic::create() {
+            Ok(()) => shared.log_line("Virtual microphone \"LAN Mic\" created"),
+            Err(e) => shared.log_line(format!("Failed to create the virtual microphone: {e:#}")),
+        }
+    }
+    iced::application(App::title, App::update, App::view)
         .subscription(App::subscription)
         .theme(|_| Theme::Dark)
         .window(iced::window::Settings {
-            size: iced::Size::new(400.0, 600.0),
-            resizable: false,
+            size: WINDOW_SIZE,
+            min_size: Some(iced::Size::new(240.0, 96.0)),
+            resizable: true,
             icon: win_icon,
             exit_on_close_request: false,
+            // Launched at login: wait in the tray
+            visible: !profile.minimized,
             ..Default::default()
         })
         .run_with(move || {
-            let status = shared.snapshot();
+            let status = shared.snapshot_without_logs();
+            let user_cert = user_cert::load();
             (
                 App {
                     controller,
                     shared,
-                    bind_addr: "0.0.0.0:9001".into(),
+                    bind_addr: profile.bind_addr(),
+                    network_interface: preferred_interface::load(),
+                    access_input: String::new(),
+                    access_error: None,
+                    tls_cert_path: user_cert
+                        .as_ref()
+                        .map(|c| c.cert_path.display().to_string())
+                        .unwrap_or_default(),
+                    tls_key_path: user_cert
+                        .as_ref()
+                        .map(|c| c.key_path.display().to_string())
+                        .unwrap_or_default(),
                     use_stun: false,
+                    ice_urls: String::new(),
+                    ice_username: String::new(),
+                    ice_credential: String::new(),
+                    allow_multiple_clients: false,
+                    require_approval: false,
+                    relay_target: String::new(),
+                    snapcast_target: String::new(),
+                    captions_model: String::new(),
+                    udp_port: String::new(),
+                    udp_format: UdpFormat::default(),
+                    recording_format: RecordingFormat::default(),
+                    quality: QualityPreset::default(),
+                    latency_ms: None,
+                    monitor_device: None,
+                    standby_device: None,
+                    echo_cancellation: false,
+                    agc: None,
+                    stereo_width: 1.0,
+                    advertise_mdns: true,
+                    udp_beacon: false,
+                    idle_stop: IdleStop(None),
+                    reconnect_grace: ReconnectGrace(core::signaling::DEFAULT_RECONNECT_GRACE_SECS),
+                    keepalive_timeout: KeepaliveTimeout(
+                        core::signaling::DEFAULT_KEEPALIVE_TIMEOUT_SECS,
+                    ),
+                    appearance,
+                    settings,
+                    launch_at_login: autostart.is_some(),
+                    firewall,
+                    firewall_busy: false,
+                    virtual_mic,
+                    virtual_mic_busy: false,
+                    start_minimized: autostart.unwrap_or(true),
+                    monitor_gain: 1.0,
+                    monitor_muted: false,
+                    output_gain: 1.0,
+                    output_muted: false,
                     output_devices,
                     selected_output,
                     active_view: ActiveView::Main,
                     status,
+                    log_lines: std::collections::VecDeque::new(),
+                    log_seq: 0,
+                    log_level: LogLevel::Debug,
+                    log_search: String::new(),
+                    log_autoscroll: true,
+                    log_file: log_file::load(),
                     pulse_phase: 0.0,
+                    history: Vec::new(),
+                    focus: None,
+                    interrupted: recovery::unclean_shutdown(),
+                    palette: None,
                     qr_data: None,
                     qr_url: None,
+                    qr_index: 0,
+                    qr_native: false,
                     tray_rx,
+                    tray_icon,
+                    tray_menu,
+                    tray_outputs: None,
+                    tray_level: None,
+                    tray_level_at: std::time::Instant::now(),
                     window_id: None,
+                    window_size: WINDOW_SIZE,
+                    window_hidden: profile.minimized,
                 },
-                // Fetch the main window ID immediately
-                iced::window::get_oldest().map(Message::GotWindowId),
+                Task::batch([
+                    // Fetch the main window ID immediately
+                    iced::window::get_oldest().map(Message::GotWindowId),
+                    if settings.auto_start {
+                        Task::done(Message::AutoStart)
+                    } else {
+                        Task::none()
+                    },
+                ]),
             )
         })
 }
@@ -98,13 +294,228 @@ enum ActiveView {
     Main,
     Settings,
     Logs,
+    Stats,
+    History,
+    Captions,
+    Clients,
     QrCode,
 }
 
+/// Controls reachable from the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FocusTarget {
+    StartStop,
+    Qr,
+    Settings,
+    Record,
+    Clients,
+    Stats,
+    History,
+    Captions,
+    Logs,
+    Back,
+    OutputDevice,
+    Quality,
+}
+
+impl FocusTarget {
+    /// Accessible name; also announced through the window title.
+    fn label(self) -> &'static str {
+        match self {
+            FocusTarget::StartStop => "Start/stop listening button",
+            FocusTarget::Qr => "QR code button",
+            FocusTarget::Settings => "Settings button",
+            FocusTarget::Record => "Record button",
+            FocusTarget::Clients => "Clients button",
+            FocusTarget::Stats => "Stats button",
+            FocusTarget::History => "History button",
+            FocusTarget::Captions => "Captions button",
+            FocusTarget::Logs => "View logs button",
+            FocusTarget::Back => "Back button",
+            FocusTarget::OutputDevice => "Output device list, arrow keys to change",
+            FocusTarget::Quality => "Quality preset list, arrow keys to change",
+        }
+    }
+}
+
+/// An entry of the output device list, marked when it is a virtual cable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OutputChoice {
+    name: String,
+    virtual_device: Option<VirtualDevice>,
+}
+
+impl OutputChoice {
+    fn new(name: String) -> Self {
+        let virtual_device = VirtualDevice::detect(&name);
+        Self {
+            name,
+            virtual_device,
+        }
+    }
+}
+
+impl std::fmt::Display for OutputChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.virtual_device {
+            Some(kind) => write!(f, "{}  · virtual mic ({kind})", self.name),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+/// Idle auto-stop timeout in minutes (`None` = never).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IdleStop(Option<u32>);
+
+impl std::fmt::Display for IdleStop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            None => write!(f, "Never"),
+            Some(m) if m % 60 == 0 => write!(f, "After {} h", m / 60),
+            Some(m) => write!(f, "After {m} min"),
+        }
+    }
+}
+
+const RECONNECT_GRACE_CHOICES: [ReconnectGrace; 5] = [
+    ReconnectGrace(0),
+    ReconnectGrace(10),
+    ReconnectGrace(20),
+    ReconnectGrace(30),
+    ReconnectGrace(60),
+];
+
+/// How long a dropped sender's session is kept for it to resume, in
+/// seconds (0 = off).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ReconnectGrace(u32);
+
+impl std::fmt::Display for ReconnectGrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            0 => write!(f, "Off"),
+            s => write!(f, "{s} s"),
+        }
+    }
+}
+
+/// An entry in the network interface picker: an interface and the address
+/// it would advertise, or automatic selection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InterfaceChoice {
+    name: Option<String>,
+    address: Option<String>,
+}
+
+impl std::fmt::Display for InterfaceChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.name, &self.address) {
+            (None, _) => write!(f, "Automatic"),
+            (Some(name), Some(address)) => write!(f, "{name} ({address})"),
+            (Some(name), None) => write!(f, "{name} (not connected)"),
+        }
+    }
+}
+
+const KEEPALIVE_TIMEOUT_CHOICES: [KeepaliveTimeout; 4] = [
+    KeepaliveTimeout(10),
+    KeepaliveTimeout(20),
+    KeepaliveTimeout(30),
+    KeepaliveTimeout(60),
+];
+
+/// Seconds a sender may go without answering pings before it counts as
+/// disconnected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct KeepaliveTimeout(u32);
+
+impl std::fmt::Display for KeepaliveTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "After {} s", self.0)
+    }
+}
+
+/// Open command palette (Ctrl+K / Cmd+K).
+#[derive(Debug, Default)]
+struct Palette {
+    query: String,
+    /// Highlighted entry among the current matches.
+    selected: usize,
+}
+
 #[derive(Debug, Clone)]
 enum Message {
     BindAddressChanged(String),
+    InterfaceSelected(InterfaceChoice),
+    /// Move the running listener to the entered bind address.
+    ApplyBindAddress,
+    TlsCertPathChanged(String),
+    TlsKeyPathChanged(String),
+    ApplyCertificate,
     UseStunChanged(bool),
+    IceUrlsChanged(String),
+    IceUsernameChanged(String),
+    IceCredentialChanged(String),
+    AllowMultipleClientsChanged(bool),
+    RequireApprovalChanged(bool),
+    AdvertiseMdnsChanged(bool),
+    UdpBeaconChanged(bool),
+    IdleStopChanged(IdleStop),
+    ReconnectGraceChanged(ReconnectGrace),
+    KeepaliveTimeoutChanged(KeepaliveTimeout),
+    RelayTargetChanged(String),
+    SnapcastTargetChanged(String),
+    CaptionsModelChanged(String),
+    UdpPortChanged(String),
+    UdpFormatChanged(UdpFormat),
+    RecordingFormatChanged(RecordingFormat),
+    ApprovalResolved(u64, bool),
+    ForgetTrustedDevice(String),
+    AccessRuleInputChanged(String),
+    /// Add the typed range to the allow (`true`) or deny list.
+    AddAccessRule(bool),
+    RemoveAccessRule(bool, IpNet),
+    QualityPresetChanged(QualityPreset),
+    CustomLatencyToggled(bool),
+    LatencyChanged(u32),
+    AccentChanged(AccentColor),
+    DarknessChanged(f32),
+    CompactLayoutChanged(bool),
+    ShowOnConnectChanged(bool),
+    AutoStartChanged(bool),
+    LaunchAtLoginToggled(bool),
+    AllowThroughFirewall,
+    FirewallRuleUpdated(Result<(), String>),
+    VirtualMicToggled(bool),
+    VirtualMicUpdated(bool, Result<(), String>),
+    StartMinimizedToggled(bool),
+    /// Darkness slider released; persist the final value.
+    SaveAppearance,
+    ClientGainChanged(ClientId, f32),
+    ClientMuteToggled(ClientId, bool),
+    ClientOutputChanged(ClientId, String),
+    ClientPriorityToggled(ClientId, bool),
+    /// Ask a sender for a lower bitrate (`Some`) or full bitrate again.
+    ClientBitrateCapChanged(ClientId, Option<u32>),
+    MonitorDeviceChanged(String),
+    StandbyDeviceChanged(String),
+    EchoCancellationChanged(bool),
+    AgcToggled(bool),
+    AgcChanged(AgcConfig),
+    StereoWidthChanged(f32),
+    MonitorGainChanged(f32),
+    MonitorMuteToggled,
+    OutputGainChanged(f32),
+    OutputMuteToggled,
+    ToggleRecording,
+    ExportStats,
+    ClearHistory,
+    ResumeInterrupted,
+    /// Start listening at launch with the last session's settings.
+    AutoStart,
+    DismissError,
+    DismissInterrupted,
     OutputDeviceChanged(String),
     RefreshDevices,
     StartServer,
@@ -112,10 +523,32 @@ enum Message {
     Navigate(ActiveView),
     OpenQr,
     CloseQr,
+    QrInterfaceSelected(usize),
+    QrNativeToggled(bool),
+    KeyPressed(keyboard::Key, keyboard::Modifiers),
+    TogglePalette,
+    ClosePalette,
+    PaletteQueryChanged(String),
+    /// Run the highlighted palette entry.
+    PaletteSubmit,
+    /// Run the palette entry at this index of the current matches.
+    PaletteRun(usize),
+    ExportLogs,
+    ExportTranscript,
+    LogLevelChanged(LogLevel),
+    LogSearchChanged(String),
+    LogAutoScrollToggled(bool),
+    LogFileToggled(bool),
+    LogRotationChanged(Rotation),
+    CreateDiagnosticBundle,
+    /// Issue a client certificate for `--mtls` senders.
+    ExportClientCertificate,
     Tick,
+    Core(CoreEvent),
     Tray(TrayMessage),
     GotWindowId(Option<iced::window::Id>),
     WindowCloseRequested(iced::window::Id),
+    WindowResized(iced::Size),
 }
 
 struct App {
@@ -124,22 +557,124 @@ struct App {
 
     // Settings
     bind_addr: String,
+    /// Interface whose address goes in URLs and QR codes; `None` = automatic.
+    network_interface: Option<String>,
+    /// Address range being typed into the access rules editor.
+    access_input: String,
+    access_error: Option<String>,
+    /// PEM files of a user-supplied TLS certificate; empty = self-signed.
+    tls_cert_path: String,
+    tls_key_path: String,
     use_stun: bool,
+    /// Custom STUN/TURN server: space- or comma-separated URLs and the
+    /// TURN credentials.
+    ice_urls: String,
+    ice_username: String,
+    ice_credential: String,
+    allow_multiple_clients: bool,
+    /// Ask before accepting senders that aren't trusted yet.
+    require_approval: bool,
+    /// Receiver to forward audio to; empty disables relay mode.
+    relay_target: String,
+    /// Snapcast server to feed for multi-room playback; empty disables it.
+    snapcast_target: String,
+    /// Vosk model folder for live captions; empty disables them.
+    captions_model: String,
+    /// UDP port for microcontroller senders; empty disables it.
+    udp_port: String,
+    udp_format: UdpFormat,
+    recording_format: RecordingFormat,
+    quality: QualityPreset,
+    /// Fixed latency target overriding the preset's buffering.
+    latency_ms: Option<u32>,
     output_devices: Vec<String>,
     selected_output: Option<String>,
+    /// Local listening device; its level never affects the main output.
+    monitor_device: Option<String>,
+    monitor_gain: f32,
+    monitor_muted: bool,
+    /// Master volume of the main output (and its copies).
+    output_gain: f32,
+    output_muted: bool,
+    /// Failover target if the main output stream dies.
+    standby_device: Option<String>,
+    /// Remove the monitor speakers' echo from senders in the same room.
+    echo_cancellation: bool,
+    /// Automatic gain control for every sender; `None` = off.
+    agc: Option<AgcConfig>,
+    /// Stereo width of stereo senders: 0 = mono, 1 = as sent, 1.5 = widened.
+    stereo_width: f32,
+    /// Announce the receiver over mDNS for sender auto-discovery.
+    advertise_mdns: bool,
+    /// Also announce over UDP broadcast, where mDNS is blocked.
+    udp_beacon: bool,
+    /// Stop listening after this long without a sender.
+    idle_stop: IdleStop,
+    /// Keep a dropped sender's session this long for it to resume.
+    reconnect_grace: ReconnectGrace,
+    /// Treat a sender as gone once it stops answering for this long.
+    keepalive_timeout: KeepaliveTimeout,
+    /// Accent colour and background darkness; mirrored in `APPEARANCE`.
+    appearance: Appearance,
+    /// Window and startup behaviour.
+    settings: AppSettings,
+    /// Registered to start with the operating system.
+    launch_at_login: bool,
+    /// Start hidden in the tray when launched at login.
+    start_minimized: bool,
+    /// Our Windows Firewall rule; `None` elsewhere.
+    firewall: Option<RuleStatus>,
+    /// Waiting for the UAC prompt that adds the rule.
+    firewall_busy: bool,
+    /// A sound server that can host the virtual microphone is running.
+    virtual_mic: bool,
+    /// Creating or removing the virtual microphone.
+    virtual_mic_busy: bool,
 
     // View state
     active_view: ActiveView,
     status: StatusSnapshot,
+    /// Log lines followed with `SharedStatus::logs_since`; the snapshot
+    /// in `status` leaves them out.
+    log_lines: std::collections::VecDeque<LogEntry>,
+    log_seq: u64,
+    /// Least severe level shown in the Logs view.
+    log_level: LogLevel,
+    log_search: String,
+    /// Keep the Logs view pinned to the newest entry.
+    log_autoscroll: bool,
+    /// Mirroring of the log to rotating files on disk.
+    log_file: LogFileSettings,
     pulse_phase: f32,
+    /// Past sessions, reloaded whenever the History view opens.
+    history: Vec<HistoryEntry>,
+    /// Control highlighted for keyboard operation, if any.
+    focus: Option<FocusTarget>,
+    /// Settings of a previous run that crashed or was killed while listening.
+    interrupted: Option<ListenState>,
+    palette: Option<Palette>,
 
     // QR code
     qr_data: Option<qr_code::Data>,
     qr_url: Option<String>,
+    /// Which of `status.sender_urls` the QR view shows.
+    qr_index: usize,
+    /// Encode the `lanmic://` deep link for native senders instead of the
+    /// web sender page.
+    qr_native: bool,
 
     // Window & Tray
     window_id: Option<iced::window::Id>,
+    window_size: iced::Size,
+    window_hidden: bool,
     tray_rx: std::sync::mpsc::Receiver<TrayMessage>,
+    tray_icon: tray_icon::TrayIcon,
+    tray_menu: TrayMenu,
+    /// Device list and selection the tray's output submenu was built from.
+    tray_outputs: Option<(Vec<String>, Option<String>)>,
+    /// Level step currently drawn in the tray icon (`None` = plain icon).
+    tray_level: Option<u8>,
+    tray_level_at: std::time::Instant,
 }
 
 // ===========================================================================
@@ -153,10 +688,439 @@ impl App {
                 self.bind_addr = addr;
                 Task::none()
             }
+            Message::ApplyBindAddress => {
+                let cmd = CoreCommand::Rebind {
+                    bind_addr: self.bind_addr.clone(),
+                };
+                if let Err(e) = self.controller.send(cmd) {
+                    log::warn!("Failed to send rebind command: {e}");
+                }
+                Task::none()
+            }
+            Message::InterfaceSelected(choice) => {
+                self.network_interface = choice.name;
+                let cmd = CoreCommand::SetInterface {
+                    interface: self.network_interface.clone(),
+                };
+                if let Err(e) = self.controller.send(cmd) {
+                    log::warn!("Failed to send interface command: {e}");
+                }
+                Task::none()
+            }
+            Message::TlsCertPathChanged(path) => {
+                self.tls_cert_path = path;
+                Task::none()
+            }
+            Message::TlsKeyPathChanged(path) => {
+                self.tls_key_path = path;
+                Task::none()
+            }
+            Message::ApplyCertificate => {
+                let (cert_path, key_path) = (self.tls_cert_path.trim(), self.tls_key_path.trim());
+                let cert =
+                    (!cert_path.is_empty() || !key_path.is_empty()).then(|| UserCertificate {
+                        cert_path: cert_path.into(),
+                        key_path: key_path.into(),
+                    });
+                if let Err(e) = self.controller.send(CoreCommand::SetCertificate { cert }) {
+                    log::warn!("Failed to send SetCertificate: {e}");
+                }
+                Task::none()
+            }
             Message::UseStunChanged(checked) => {
                 self.use_stun = checked;
                 Task::none()
             }
+            Message::IceUrlsChanged(urls) => {
+                self.ice_urls = urls;
+                Task::none()
+            }
+            Message::IceUsernameChanged(username) => {
+                self.ice_username = username;
+                Task::none()
+            }
+            Message::IceCredentialChanged(credential) => {
+                self.ice_credential = credential;
+                Task::none()
+            }
+            Message::AllowMultipleClientsChanged(checked) => {
+                self.allow_multiple_clients = checked;
+                Task::none()
+            }
+            Message::RequireApprovalChanged(checked) => {
+                self.require_approval = checked;
+                Task::none()
+            }
+            Message::AdvertiseMdnsChanged(checked) => {
+                self.advertise_mdns = checked;
+                Task::none()
+            }
+            Message::UdpBeaconChanged(checked) => {
+                self.udp_beacon = checked;
+                Task::none()
+            }
+            Message::IdleStopChanged(idle_stop) => {
+                self.idle_stop = idle_stop;
+                Task::none()
+            }
+            Message::ReconnectGraceChanged(grace) => {
+                self.reconnect_grace = grace;
+                Task::none()
+            }
+            Message::KeepaliveTimeoutChanged(timeout) => {
+                self.keepalive_timeout = timeout;
+                Task::none()
+            }
+            Message::RelayTargetChanged(target) => {
+                self.relay_target = target;
+                Task::none()
+            }
+            Message::SnapcastTargetChanged(target) => {
+                self.snapcast_target = target;
+                Task::none()
+            }
+            Message::CaptionsModelChanged(model) => {
+                self.captions_model = model;
+                Task::none()
+            }
+            Message::UdpPortChanged(port) => {
+                self.udp_port = port;
+                Task::none()
+            }
+            Message::UdpFormatChanged(format) => {
+                self.udp_format = format;
+                Task::none()
+            }
+            Message::RecordingFormatChanged(format) => {
+                self.recording_format = format;
+                if let Err(e) = self.controller.send(CoreCommand::SetRecordingFormat(format)) {
+                    log::warn!("Failed to send SetRecordingFormat: {e}");
+                }
+                Task::none()
+            }
+            Message::ApprovalResolved(request_id, allow) => {
+                if let Err(e) = self
+                    .controller
+                    .send(CoreCommand::ResolveApproval { request_id, allow })
+                {
+                    log::warn!("Failed to send ResolveApproval: {e}");
+                }
+                Task::none()
+            }
+            Message::ForgetTrustedDevice(device_id) => {
+                if let Err(e) = self
+                    .controller
+                    .send(CoreCommand::ForgetTrustedDevice { device_id })
+                {
+                    log::warn!("Failed to send ForgetTrustedDevice: {e}");
+                }
+                Task::none()
+            }
+            Message::AccessRuleInputChanged(input) => {
+                self.access_input = input;
+                self.access_error = None;
+                Task::none()
+            }
+            Message::AddAccessRule(allow) => {
+                let net = match self.access_input.parse::<IpNet>() {
+                    Ok(net) => net,
+                    Err(e) => {
+                        self.access_error = Some(e.to_string());
+                        return Task::none();
+                    }
+                };
+                let mut access = self.status.access.clone();
+                // A range is either allowed or denied, never both
+                access.allow.retain(|n| *n != net);
+                access.deny.retain(|n| *n != net);
+                if allow {
+                    access.allow.push(net);
+                } else {
+                    access.deny.push(net);
+                }
+                self.access_input.clear();
+                if let Err(e) = self.controller.send(CoreCommand::SetAccessList(access)) {
+                    log::warn!("Failed to send SetAccessList: {e}");
+                }
+                Task::none()
+            }
+            Message::RemoveAccessRule(allow, net) => {
+                let mut access = self.status.access.clone();
+                if allow {
+                    access.allow.retain(|n| *n != net);
+                } else {
+                    access.deny.retain(|n| *n != net);
+                }
+                if let Err(e) = self.controller.send(CoreCommand::SetAccessList(access)) {
+                    log::warn!("Failed to send SetAccessList: {e}");
+                }
+                Task::none()
+            }
+            Message::QualityPresetChanged(preset) => {
+                self.quality = preset;
+                Task::none()
+            }
+            Message::CustomLatencyToggled(on) => {
+                self.latency_ms = on.then(|| self.quality.params().jitter_target_ms);
+                Task::none()
+            }
+            Message::LatencyChanged(ms) => {
+                self.latency_ms = Some(ms);
+                Task::none()
+            }
+            Message::AccentChanged(accent) => {
+                self.appearance.accent = accent;
+                self.apply_appearance();
+                self.save_appearance();
+                Task::none()
+            }
+            Message::DarknessChanged(darkness) => {
+                self.appearance.darkness = darkness;
+                self.apply_appearance();
+                Task::none()
+            }
+            Message::CompactLayoutChanged(compact) => {
+                self.appearance.compact = compact;
+                self.apply_appearance();
+                self.save_appearance();
+                Task::none()
+            }
+            Message::ShowOnConnectChanged(show) => {
+                self.settings.show_on_connect = show;
+                self.save_settings();
+                Task::none()
+            }
+            Message::AutoStartChanged(auto_start) => {
+                self.settings.auto_start = auto_start;
+                self.save_settings();
+                Task::none()
+            }
+            Message::AllowThroughFirewall => {
+                self.firewall_busy = true;
+                Task::perform(
+                    async {
+                        tokio::task::spawn_blocking(firewall::allow)
+                            .await
+                            .map_err(|e| e.to_string())?
+                            .map_err(|e| format!("{e:#}"))
+                    },
+                    Message::FirewallRuleUpdated,
+                )
+            }
+            Message::FirewallRuleUpdated(result) => {
+                self.firewall_busy = false;
+                match result {
+                    Ok(()) => self
+                        .shared
+                        .log_line("Windows Firewall now allows senders on the local network"),
+                    Err(e) => self
+                        .shared
+                        .log_line(format!("Firewall rule not added: {e}")),
+                }
+                self.firewall = firewall::status();
+                Task::none()
+            }
+            Message::VirtualMicToggled(enabled) => {
+                self.virtual_mic_busy = true;
+                Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            if enabled {
+                                linux_virtualmic::create()
+                            } else {
+                                linux_virtualmic::remove()
+                            }
+                        })
+                        .await
+                        .map_err(|e| e.to_string())?
+                        .map_err(|e| format!("{e:#}"))
+                    },
+                    move |result| Message::VirtualMicUpdated(enabled, result),
+                )
+            }
+            Message::VirtualMicUpdated(enabled, result) => {
+                self.virtual_mic_busy = false;
+                match result {
+                    Ok(()) => {
+                        self.settings.virtual_mic = enabled;
+                        self.save_settings();
+                        self.shared.log_line(if enabled {
+                            "Virtual microphone \"LAN Mic\" created"
+                        } else {
+                            "Virtual microphone removed"
+                        });
+                        // Reopen the main output so its stream is routed
+                        if enabled && self.status.server_running {
+                            if let Err(e) = self.controller.send(CoreCommand::ChangeOutputDevice {
+                                device_name: self.status.output_device.clone(),
+                            }) {
+                                log::warn!("Failed to send ChangeOutputDevice: {e}");
+                            }
+                        }
+                    }
+                    Err(e) => self
+                        .shared
+                        .log_line(format!("Virtual microphone not changed: {e}")),
+                }
+                Task::none()
+            }
+            Message::LaunchAtLoginToggled(enabled) => {
+                self.launch_at_login = enabled;
+                self.apply_autostart();
+                Task::none()
+            }
+            Message::StartMinimizedToggled(minimized) => {
+                self.start_minimized = minimized;
+                self.apply_autostart();
+                Task::none()
+            }
+            Message::SaveAppearance => {
+                self.save_appearance();
+                Task::none()
+            }
+            Message::ClientGainChanged(client_id, gain) => {
+                if let Err(e) = self
+                    .controller
+                    .send(CoreCommand::SetClientGain { client_id, gain })
+                {
+                    log::warn!("Failed to send SetClientGain: {e}");
+                }
+                Task::none()
+            }
+            Message::ClientMuteToggled(client_id, muted) => {
+                if let Err(e) = self
+                    .controller
+                    .send(CoreCommand::SetClientMute { client_id, muted })
+                {
+                    log::warn!("Failed to send SetClientMute: {e}");
+                }
+                Task::none()
+            }
+            Message::ClientPriorityToggled(client_id, priority) => {
+                if let Err(e) = self.controller.send(CoreCommand::SetClientPriority {
+                    client_id,
+                    priority,
+                }) {
+                    log::warn!("Failed to send SetClientPriority: {e}");
+                }
+                Task::none()
+            }
+            Message::ClientBitrateCapChanged(client_id, max_kbps) => {
+                if let Err(e) = self.controller.send(CoreCommand::SetClientBitrateCap {
+                    client_id,
+                    max_kbps,
+                }) {
+                    log::warn!("Failed to send SetClientBitrateCap: {e}");
+                }
+                Task::none()
+            }
+            Message::ClientOutputChanged(client_id, device) => {
+                let device_name = (device != MAIN_OUTPUT_LABEL).then_some(device);
+                if let Err(e) = self.controller.send(CoreCommand::SetClientOutput {
+                    client_id,
+                    device_name,
+                }) {
+                    log::warn!("Failed to send SetClientOutput: {e}");
+                }
+                Task::none()
+            }
+            Message::MonitorDeviceChanged(device) => {
+                self.monitor_device = (device != MONITOR_OFF_LABEL).then_some(device);
+                if self.status.server_running {
+                    if let Err(e) = self.controller.send(CoreCommand::SetMonitorDevice {
+                        device_name: self.monitor_device.clone(),
+                    }) {
+                        log::warn!("Failed to send SetMonitorDevice: {e}");
+                    }
+                }
+                Task::none()
+            }
+            Message::StandbyDeviceChanged(device) => {
+                self.standby_device = (device != MONITOR_OFF_LABEL).then_some(device);
+                if self.status.server_running {
+                    if let Err(e) = self.controller.send(CoreCommand::SetStandbyDevice {
+                        device_name: self.standby_device.clone(),
+                    }) {
+                        log::warn!("Failed to send SetStandbyDevice: {e}");
+                    }
+                }
+                Task::none()
+            }
+            Message::EchoCancellationChanged(enabled) => {
+                self.echo_cancellation = enabled;
+                if self.status.server_running {
+                    if let Err(e) = self
+                        .controller
+                        .send(CoreCommand::SetEchoCancellation(enabled))
+                    {
+                        log::warn!("Failed to send SetEchoCancellation: {e}");
+                    }
+                }
+                Task::none()
+            }
+            Message::AgcToggled(on) => {
+                self.set_agc(on.then(AgcConfig::default));
+                Task::none()
+            }
+            Message::AgcChanged(config) => {
+                self.set_agc(Some(config));
+                Task::none()
+            }
+            Message::StereoWidthChanged(width) => {
+                self.set_stereo_width(width);
+                Task::none()
+            }
+            Message::MonitorGainChanged(gain) => {
+                self.monitor_gain = gain;
+                if let Err(e) = self.controller.send(CoreCommand::SetMonitorGain(gain)) {
+                    log::warn!("Failed to send SetMonitorGain: {e}");
+                }
+                Task::none()
+            }
+            Message::MonitorMuteToggled => {
+                self.monitor_muted = !self.monitor_muted;
+                if let Err(e) = self
+                    .controller
+                    .send(CoreCommand::SetMonitorMute(self.monitor_muted))
+                {
+                    log::warn!("Failed to send SetMonitorMute: {e}");
+                }
+                Task::none()
+            }
+            Message::OutputGainChanged(gain) => {
+                self.output_gain = gain;
+                if let Err(e) = self.controller.send(CoreCommand::SetGain(gain)) {
+                    log::warn!("Failed to send SetGain: {e}");
+                }
+                Task::none()
+            }
+            Message::OutputMuteToggled => {
+                self.output_muted = !self.output_muted;
+                if let Err(e) = self
+                    .controller
+                    .send(CoreCommand::SetMute(self.output_muted))
+                {
+                    log::warn!("Failed to send SetMute: {e}");
+                }
+                Task::none()
+            }
+            Message::ExportStats => {
+                if let Err(e) = self.controller.send(CoreCommand::ExportStats { path: None }) {
+                    log::warn!("Failed to send export command: {e}");
+                }
+                Task::none()
+            }
+            Message::ToggleRecording => {
+                let cmd = if self.status.recording_path.is_some() {
+                    CoreCommand::StopRecording
+                } else {
+                    CoreCommand::StartRecording { path: None }
+                };
+                if let Err(e) = self.controller.send(cmd) {
+                    log::warn!("Failed to send recording command: {e}");
+                }
+                Task::none()
+            }
             Message::OutputDeviceChanged(device) => {
                 self.selected_output = Some(device.clone());
                 if self.status.server_running {
@@ -176,18 +1140,85 @@ impl App {
                 Task::none()
             }
             Message::StartServer => {
+                let udp_port = self.udp_port.trim();
+                let udp_ingest = match udp_port.parse::<u16>() {
+                    Ok(port) if port != 0 => Some(UdpIngest {
+                        port,
+                        format: self.udp_format,
+                    }),
+                    _ if udp_port.is_empty() => None,
+                    _ => {
+                        self.shared
+                            .log_line(format!("Invalid UDP port '{udp_port}'; UDP ingest is off"));
+                        None
+                    }
+                };
                 if let Err(e) = self.controller.send(CoreCommand::Start {
                     bind_addr: self.bind_addr.clone(),
                     output_device: self.selected_output.clone(),
                     use_stun: self.use_stun,
+                    ice_servers: self.ice_servers(),
+                    allow_multiple_clients: self.allow_multiple_clients,
+                    monitor_device: self.monitor_device.clone(),
+                    monitor_gain: self.monitor_gain,
+                    monitor_muted: self.monitor_muted,
+                    quality: self.quality,
+                    latency_ms: self.latency_ms,
+                    require_approval: self.require_approval,
+                    relay_target: Some(self.relay_target.trim())
+                        .filter(|t| !t.is_empty())
+                        .map(str::to_string),
+                    snapcast_target: Some(self.snapcast_target.trim())
+                        .filter(|t| !t.is_empty())
+                        .map(str::to_string),
+                    captions_model: Some(self.captions_model.trim())
+                        .filter(|m| !m.is_empty())
+                        .map(str::to_string),
+                    standby_device: self.standby_device.clone(),
+                    echo_cancellation: self.echo_cancellation,
+                    advertise_mdns: self.advertise_mdns,
+                    udp_beacon: self.udp_beacon,
+                    udp_ingest,
+                    idle_stop_minutes: self.idle_stop.0,
+                    reconnect_grace_secs: self.reconnect_grace.0,
+                    keepalive_timeout_secs: self.keepalive_timeout.0,
                 }) {
                     log::warn!("Failed to send Start: {e}");
                 } else {
+                    self.interrupted = None;
                     // Auto-open QR code on start
                     self.active_view = ActiveView::QrCode;
                 }
                 Task::none()
             }
+            Message::ResumeInterrupted => {
+                let Some(state) = self.interrupted.take() else {
+                    return Task::none();
+                };
+                self.apply_listen_state(state);
+                self.update(Message::StartServer)
+            }
+            Message::AutoStart => {
+                if self.status.server_running {
+                    return Task::none();
+                }
+                // A crashed session is as recent as the last one that started
+                if let Some(state) = self.interrupted.take().or_else(recovery::last_session) {
+                    self.apply_listen_state(state);
+                }
+                self.shared.log_line("Starting automatically");
+                self.update(Message::StartServer)
+            }
+            Message::DismissError => {
+                self.shared.set_last_error(None);
+                self.status.last_error = None;
+                Task::none()
+            }
+            Message::DismissInterrupted => {
+                self.interrupted = None;
+                recovery::clear();
+                Task::none()
+            }
             Message::StopServer => {
                 if let Err(e) = self.controller.send(CoreCommand::Stop) {
                     log::warn!("Failed to send Stop: {e}");
@@ -195,62 +1226,161 @@ impl App {
                 Task::none()
             }
             Message::Navigate(view) => {
+                if view == ActiveView::History {
+                    self.reload_history();
+                }
                 self.active_view = view;
+                self.focus = None;
                 Task::none()
             }
-            Message::OpenQr => {
-                self.active_view = ActiveView::QrCode;
+            Message::KeyPressed(key, modifiers) => self.handle_key(key, modifiers),
+            Message::TogglePalette => {
+                if self.palette.take().is_some() {
+                    return Task::none();
+                }
+                self.palette = Some(Palette::default());
+                self.focus = None;
+                text_input::focus(palette_input_id())
+            }
+            Message::ClosePalette => {
+                self.palette = None;
                 Task::none()
             }
-            Message::CloseQr => {
-                self.active_view = ActiveView::Main;
+            Message::PaletteQueryChanged(query) => {
+                if let Some(p) = &mut self.palette {
+                    p.query = query;
+                    p.selected = 0;
+                }
                 Task::none()
             }
-            Message::Tick => {
-                self.status = self.shared.snapshot();
-                self.pulse_phase = (self.pulse_phase + 0.08) % (2.0 * std::f32::consts::PI);
-
-                // Regenerate QR code when the URL changes
-                let current_url = self.status.ws_url.as_ref().map(|ws| {
-                    if ws.starts_with("wss://") {
-                         // Convert wss://ip:port/ws -> https://ip:port
-                        format!("https://{}", ws.trim_start_matches("wss://").trim_end_matches("/ws"))
-                    } else {
-                        // Convert ws://ip:port/ws -> http://ip:port
-                        format!("http://{}", ws.trim_start_matches("ws://").trim_end_matches("/ws"))
-                    }
-                });
-                let http_url = current_url;
-                if http_url != self.qr_url {
-                    self.qr_url = http_url.clone();
-                    self.qr_data = http_url
-                        .and_then(|url| qr_code::Data::new(url).ok());
+            Message::PaletteSubmit => {
+                let selected = self.palette.as_ref().map_or(0, |p| p.selected);
+                self.update(Message::PaletteRun(selected))
+            }
+            Message::PaletteRun(index) => {
+                let action = self.palette_matches().into_iter().nth(index);
+                self.palette = None;
+                match action {
+                    Some((_, message)) => self.update(message),
+                    None => Task::none(),
                 }
-
-                // Poll tray messages (non-blocking)
-                if let Ok(msg) = self.tray_rx.try_recv() {
-                    return self.update(Message::Tray(msg));
+            }
+            Message::LogLevelChanged(level) => {
+                self.log_level = level;
+                Task::none()
+            }
+            Message::LogSearchChanged(query) => {
+                self.log_search = query;
+                Task::none()
+            }
+            Message::LogAutoScrollToggled(enabled) => {
+                self.log_autoscroll = enabled;
+                Task::none()
+            }
+            Message::LogFileToggled(enabled) => {
+                self.log_file.enabled = enabled;
+                self.apply_log_file();
+                Task::none()
+            }
+            Message::LogRotationChanged(rotation) => {
+                self.log_file.rotation = rotation;
+                self.apply_log_file();
+                Task::none()
+            }
+            Message::ExportLogs => {
+                if let Err(e) = self.controller.send(CoreCommand::ExportLogs { path: None }) {
+                    log::warn!("Failed to send export command: {e}");
                 }
                 Task::none()
             }
-            Message::Tray(msg) => match msg {
-                TrayMessage::Show => {
-                    if let Some(id) = self.window_id {
-                        iced::window::change_mode(id, iced::window::Mode::Windowed)
-                    } else {
-                        Task::none()
-                    }
+            Message::ExportTranscript => {
+                let cmd = CoreCommand::ExportTranscript { path: None };
+                if let Err(e) = self.controller.send(cmd) {
+                    log::warn!("Failed to send export command: {e}");
+                }
+                Task::none()
+            }
+            Message::ExportClientCertificate => {
+                let cmd = CoreCommand::ExportClientCertificate { path: None };
+                if let Err(e) = self.controller.send(cmd) {
+                    log::warn!("Failed to send certificate export command: {e}");
+                }
+                Task::none()
+            }
+            Message::CreateDiagnosticBundle => {
+                let cmd = CoreCommand::CreateDiagnosticBundle { path: None };
+                if let Err(e) = self.controller.send(cmd) {
+                    log::warn!("Failed to send diagnostic bundle command: {e}");
                 }
+                Task::none()
+            }
+            Message::ClearHistory => {
+                if let Err(e) = history::clear() {
+                    log::warn!("Failed to clear session history: {e:#}");
+                }
+                self.reload_history();
+                Task::none()
+            }
+            Message::OpenQr => {
+                self.active_view = ActiveView::QrCode;
+                self.focus = None;
+                Task::none()
+            }
+            Message::QrInterfaceSelected(index) => {
+                self.qr_index = index;
+                self.update(Message::Tick)
+            }
+            Message::QrNativeToggled(native) => {
+                self.qr_native = native;
+                self.update(Message::Tick)
+            }
+            Message::CloseQr => {
+                self.active_view = ActiveView::Main;
+                self.focus = None;
+                Task::none()
+            }
+            Message::Tick => {
+                // Advance the pulse by elapsed time so its speed doesn't
+                // depend on the current tick rate.
+                let step = 0.08 * self.tick_interval().as_secs_f32() / ACTIVE_TICK.as_secs_f32();
+                self.pulse_phase = (self.pulse_phase + step) % (2.0 * std::f32::consts::PI);
+
+                self.refresh()
+            }
+            Message::Core(CoreEvent::LogLine) => {
+                self.follow_log();
+                Task::none()
+            }
+            Message::Core(CoreEvent::StatusChanged) => self.refresh(),
+            Message::Tray(msg) => match msg {
+                TrayMessage::Show => self.show_window(),
                 TrayMessage::Hide => {
+                    self.window_hidden = true;
                     if let Some(id) = self.window_id {
                         iced::window::change_mode(id, iced::window::Mode::Hidden)
                     } else {
                         Task::none()
                     }
                 }
+                TrayMessage::Start if !self.status.server_running => {
+                    self.update(Message::StartServer)
+                }
+                TrayMessage::Stop if self.status.server_running => self.update(Message::StopServer),
+                TrayMessage::Start | TrayMessage::Stop => Task::none(),
+                TrayMessage::SelectOutput(device) => {
+                    // Clicking toggled the native check mark; redraw them all
+                    self.tray_outputs = None;
+                    self.update(Message::OutputDeviceChanged(device))
+                }
                 TrayMessage::Quit => {
-                    // Graceful stop then close
+                    // Graceful stop then close; a clean quit never offers recovery
                     let _ = self.controller.send(CoreCommand::Stop);
+                    recovery::clear();
+                    if self.settings.virtual_mic {
+                        if let Err(e) = linux_virtualmic::remove() {
+                            log::warn!("Failed to remove the virtual microphone: {e:#}");
+                        }
+                    }
                     if let Some(id) = self.window_id {
                         iced::window::close(id)
                     } else {
@@ -265,22 +1395,548 @@ impl App {
             Message::WindowCloseRequested(id) => {
                 // Hide to tray instead of closing
                 self.window_id = Some(id);
+                self.window_hidden = true;
                 iced::window::change_mode(id, iced::window::Mode::Hidden)
             },
+            Message::WindowResized(size) => {
+                self.window_size = size;
+                Task::none()
+            }
+        }
+    }
+
+    /// Window title: the profile name plus the connection state, and the
+    /// focused control while navigating by keyboard. Screen readers
+    /// announce the title, and iced has no accessibility tree of its own.
+    fn title(&self) -> String {
+        let profile = crate::profile::current();
+        let state = if self.status.client_connected {
+            "Connected"
+        } else if self.status.server_running {
+            "Waiting for device"
+        } else {
+            "Stopped"
+        };
+        if self.palette.is_some() {
+            return format!("{} — {state} — Command palette", profile.title);
+        }
+        match self.focus {
+            Some(target) => format!("{} — {state} — {}", profile.title, target.label()),
+            None => format!("{} — {state}", profile.title),
+        }
+    }
+
+    // =======================================================================
+    // Keyboard navigation
+    // =======================================================================
+
+    /// Tab order of the current view.
+    fn focus_order(&self) -> &'static [FocusTarget] {
+        match self.active_view {
+            ActiveView::Main if self.is_compact() => {
+                &[FocusTarget::StartStop, FocusTarget::Settings]
+            }
+            ActiveView::Main => &[
+                FocusTarget::StartStop,
+                FocusTarget::Qr,
+                FocusTarget::Settings,
+                FocusTarget::Record,
+                FocusTarget::Clients,
+                FocusTarget::Stats,
+                FocusTarget::History,
+                FocusTarget::Captions,
+                FocusTarget::Logs,
+            ],
+            ActiveView::Settings => &[
+                FocusTarget::Back,
+                FocusTarget::OutputDevice,
+                FocusTarget::Quality,
+            ],
+            ActiveView::QrCode => &[],
+            _ => &[FocusTarget::Back],
+        }
+    }
+
+    /// Tab / Shift+Tab and Left/Right move focus, Up/Down change the
+    /// focused list, Enter/Space activate, Escape goes back.
+    fn handle_key(&mut self, key: keyboard::Key, modifiers: keyboard::Modifiers) -> Task<Message> {
+        use keyboard::key::Named;
+
+        if modifiers.command() && matches!(&key, keyboard::Key::Character(c) if c.as_str() == "k")
+        {
+            return self.update(Message::TogglePalette);
+        }
+        let keyboard::Key::Named(named) = key else {
+            return Task::none();
+        };
+        if self.palette.is_some() {
+            let matches = self.palette_matches().len();
+            let Some(p) = &mut self.palette else {
+                return Task::none();
+            };
+            match named {
+                Named::ArrowUp => p.selected = p.selected.saturating_sub(1),
+                Named::ArrowDown => p.selected = (p.selected + 1).min(matches.saturating_sub(1)),
+                Named::Escape => self.palette = None,
+                _ => {}
+            }
+            return Task::none();
+        }
+        match named {
+            Named::Tab if modifiers.shift() => self.move_focus(-1),
+            Named::Tab | Named::ArrowRight => self.move_focus(1),
+            Named::ArrowLeft => self.move_focus(-1),
+            Named::ArrowUp => return self.step_focused(-1),
+            Named::ArrowDown => return self.step_focused(1),
+            Named::Enter | Named::Space => return self.activate_focused(),
+            Named::Escape if self.active_view == ActiveView::QrCode => {
+                return self.update(Message::CloseQr);
+            }
+            Named::Escape if self.active_view != ActiveView::Main => {
+                return self.update(Message::Navigate(ActiveView::Main));
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    fn move_focus(&mut self, delta: isize) {
+        let order = self.focus_order();
+        if order.is_empty() {
+            return;
+        }
+        let len = order.len() as isize;
+        let next = match self.focus.and_then(|f| order.iter().position(|&t| t == f)) {
+            Some(i) => (i as isize + delta).rem_euclid(len),
+            None if delta < 0 => len - 1,
+            None => 0,
+        };
+        self.focus = Some(order[next as usize]);
+    }
+
+    /// Up/Down: pick the previous/next entry of a focused list, otherwise
+    /// move focus like Left/Right.
+    fn step_focused(&mut self, delta: isize) -> Task<Message> {
+        fn step<T: Clone + PartialEq>(items: &[T], current: Option<&T>, delta: isize) -> Option<T> {
+            if items.is_empty() {
+                return None;
+            }
+            let len = items.len() as isize;
+            let next = match current.and_then(|c| items.iter().position(|i| i == c)) {
+                Some(i) => (i as isize + delta).clamp(0, len - 1),
+                None => 0,
+            };
+            Some(items[next as usize].clone())
+        }
+
+        match self.focus {
+            Some(FocusTarget::OutputDevice) => {
+                match step(&self.output_devices, self.selected_output.as_ref(), delta) {
+                    Some(device) => self.update(Message::OutputDeviceChanged(device)),
+                    None => Task::none(),
+                }
+            }
+            Some(FocusTarget::Quality) => {
+                match step(&QualityPreset::ALL, Some(&self.quality), delta) {
+                    Some(preset) => self.update(Message::QualityPresetChanged(preset)),
+                    None => Task::none(),
+                }
+            }
+            _ => {
+                self.move_focus(delta);
+                Task::none()
+            }
+        }
+    }
+
+    fn activate_focused(&mut self) -> Task<Message> {
+        let message = match self.focus {
+            Some(FocusTarget::StartStop) if self.status.server_running => Message::StopServer,
+            Some(FocusTarget::StartStop) => Message::StartServer,
+            Some(FocusTarget::Qr) => Message::OpenQr,
+            Some(FocusTarget::Settings) => Message::Navigate(ActiveView::Settings),
+            Some(FocusTarget::Record) if self.status.server_running => Message::ToggleRecording,
+            Some(FocusTarget::Clients) => Message::Navigate(ActiveView::Clients),
+            Some(FocusTarget::Stats) => Message::Navigate(ActiveView::Stats),
+            Some(FocusTarget::History) => Message::Navigate(ActiveView::History),
+            Some(FocusTarget::Captions) => Message::Navigate(ActiveView::Captions),
+            Some(FocusTarget::Logs) => Message::Navigate(ActiveView::Logs),
+            Some(FocusTarget::Back) => Message::Navigate(ActiveView::Main),
+            _ => return Task::none(),
+        };
+        self.update(message)
+    }
+
+    // =======================================================================
+    // Command palette
+    // =======================================================================
+
+    /// Every action available right now, labelled for searching.
+    fn palette_actions(&self) -> Vec<(String, Message)> {
+        let mut actions = Vec::new();
+        if self.status.server_running {
+            actions.push(("Stop listening".to_string(), Message::StopServer));
+            let rec = if self.status.recording_path.is_some() {
+                "Stop recording"
+            } else {
+                "Start recording"
+            };
+            actions.push((rec.to_string(), Message::ToggleRecording));
+        } else {
+            actions.push(("Start listening".to_string(), Message::StartServer));
+        }
+        let mute = if self.output_muted {
+            "Unmute output"
+        } else {
+            "Mute output"
+        };
+        actions.push((mute.to_string(), Message::OutputMuteToggled));
+        let mute = if self.monitor_muted {
+            "Unmute monitor"
+        } else {
+            "Mute monitor"
+        };
+        actions.push((mute.to_string(), Message::MonitorMuteToggled));
+        if let Some((name, kind)) = self.virtual_cable() {
+            if self.selected_output.as_deref() != Some(name) {
+                actions.push((
+                    format!("Use {kind} as virtual mic"),
+                    Message::OutputDeviceChanged(name.to_string()),
+                ));
+            }
+        }
+        for device in &self.output_devices {
+            if self.selected_output.as_ref() != Some(device) {
+                actions.push((
+                    format!("Switch output to {device}"),
+                    Message::OutputDeviceChanged(device.clone()),
+                ));
+            }
+        }
+        if crate::profile::current().mtls {
+            actions.push((
+                "Export client certificate".to_string(),
+                Message::ExportClientCertificate,
+            ));
+        }
+        actions.extend([
+            ("Refresh output devices".to_string(), Message::RefreshDevices),
+            ("Open QR code".to_string(), Message::OpenQr),
+            ("Export logs".to_string(), Message::ExportLogs),
+            ("Export transcript".to_string(), Message::ExportTranscript),
+            (
+                "Export diagnostics".to_string(),
+                Message::CreateDiagnosticBundle,
+            ),
+            ("Export stats (CSV)".to_string(), Message::ExportStats),
+            (
+                "Toggle compact layout".to_string(),
+                Message::CompactLayoutChanged(!self.appearance.compact),
+            ),
+        ]);
+        for (name, view) in [
+            ("Settings", ActiveView::Settings),
+            ("Clients", ActiveView::Clients),
+            ("Stats", ActiveView::Stats),
+            ("History", ActiveView::History),
+            ("Captions", ActiveView::Captions),
+            ("Logs", ActiveView::Logs),
+        ] {
+            actions.push((format!("Go to {name}"), Message::Navigate(view)));
+        }
+        actions
+    }
+
+    /// Actions matching the palette query, best first.
+    fn palette_matches(&self) -> Vec<(String, Message)> {
+        let query = self.palette.as_ref().map_or("", |p| p.query.as_str());
+        let actions = self.palette_actions();
+        let labels: Vec<&str> = actions.iter().map(|(label, _)| label.as_str()).collect();
+        let order = palette::rank(query, &labels);
+        order
+            .into_iter()
+            .take(PALETTE_MAX_RESULTS)
+            .map(|i| actions[i].clone())
+            .collect()
+    }
+
+    fn palette_overlay(&self, p: &Palette) -> Element<'_, Message> {
+        let input = text_input("Type a command…", &p.query)
+            .id(palette_input_id())
+            .on_input(Message::PaletteQueryChanged)
+            .on_submit(Message::PaletteSubmit)
+            .style(text_input_style)
+            .padding(12);
+
+        let matches = self.palette_matches();
+        let results: Element<'_, Message> = if matches.is_empty() {
+            text("No matching actions")
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                })
+                .into()
+        } else {
+            column(matches.into_iter().enumerate().map(|(i, (label, _))| {
+                let selected = i == p.selected;
+                button(text(label).size(13).style(|_| text::Style {
+                    color: Some(TEXT_PRIMARY),
+                }))
+                .on_press(Message::PaletteRun(i))
+                .style(move |_, status| button::Style {
+                    background: (selected || status == button::Status::Hovered)
+                        .then(|| bg_hover().into()),
+                    text_color: TEXT_PRIMARY,
+                    border: Border {
+                        color: if selected { accent() } else { Color::TRANSPARENT },
+                        width: 1.0,
+                        radius: 6.0.into(),
+                    },
+                    ..Default::default()
+                })
+                .width(Length::Fill)
+                .padding([6, 10])
+                .into()
+            }))
+            .spacing(2)
+            .into()
+        };
+
+        let card = container(
+            column![
+                input,
+                results,
+                text("↑↓ to choose · Enter to run · Esc to close")
+                    .size(11)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_TERTIARY),
+                    }),
+            ]
+            .spacing(10),
+        )
+        .style(card_style)
+        .padding(12)
+        .width(Length::Fill);
+
+        // Opaque so clicks don't reach the view underneath
+        opaque(
+            container(card)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .padding(24)
+                .style(|_| container::Style {
+                    background: Some(Color::BLACK.scale_alpha(0.6).into()),
+                    ..Default::default()
+                }),
+        )
+    }
+
+    /// Draw the output level into the tray icon while a sender is connected,
+    /// so a live mic is visible even with the window hidden.
+    fn update_tray_level(&mut self) {
+        let level = if self.status.client_connected {
+            if self.tray_level_at.elapsed() < TRAY_LEVEL_TICK {
+                return;
+            }
+            self.tray_level_at = std::time::Instant::now();
+            // Square root so quiet speech still shows
+            let peak = self.shared.take_output_peak().sqrt();
+            Some((peak * TRAY_LEVEL_STEPS).round() as u8)
+        } else {
+            None
+        };
+        if level == self.tray_level {
+            return;
+        }
+        self.tray_level = level;
+
+        let rgba = match level {
+            Some(step) => {
+                crate::icon::create_level_icon(TRAY_ICON_SIZE, step as f32 / TRAY_LEVEL_STEPS)
+            }
+            None => crate::icon::create_icon(TRAY_ICON_SIZE),
+        };
+        match tray_icon::Icon::from_rgba(rgba, TRAY_ICON_SIZE, TRAY_ICON_SIZE) {
+            Ok(icon) => {
+                if let Err(e) = self.tray_icon.set_icon(Some(icon)) {
+                    log::warn!("Failed to update tray icon: {e}");
+                }
+            }
+            Err(e) => log::warn!("Failed to build tray icon: {e}"),
+        }
+    }
+
+    /// Take a fresh status snapshot and react to what changed.
+    fn refresh(&mut self) -> Task<Message> {
+        let previous = std::mem::replace(&mut self.status, self.shared.snapshot_without_logs());
+        self.follow_log();
+        let sender_joined = self
+            .status
+            .clients
+            .iter()
+            .any(|c| !previous.clients.iter().any(|p| p.id == c.id));
+        // Follow devices being plugged in or removed, and a failover
+        // moving the output to another device
+        if self.status.output_devices != previous.output_devices
+            && !self.status.output_devices.is_empty()
+        {
+            self.output_devices = self.status.output_devices.clone();
+            // An unplugged selection starts on the system default
+            if !self.status.server_running
+                && self
+                    .selected_output
+                    .as_ref()
+                    .is_some_and(|d| !self.output_devices.contains(d))
+            {
+                self.selected_output = None;
+            }
+        }
+        if self.status.output_device != previous.output_device {
+            if let Some(device) = &self.status.output_device {
+                self.selected_output = Some(device.clone());
+            }
+        }
+
+        // Regenerate QR code when the URL changes. Prefer the
+        // selected interface; fall back to the primary ws_url.
+        if self.qr_index >= self.status.sender_urls.len() {
+            self.qr_index = 0;
+        }
+        let current_url = self.status.ws_url.as_ref().map(|ws| {
+            if ws.starts_with("wss://") {
+                 // Convert wss://ip:port/ws -> https://ip:port
+                format!("https://{}", ws.trim_start_matches("wss://").trim_end_matches("/ws"))
+            } else {
+                // Convert ws://ip:port/ws -> http://ip:port
+                format!("http://{}", ws.trim_start_matches("ws://").trim_end_matches("/ws"))
+            }
+        });
+        let http_url = if self.qr_native {
+            self.status
+                .deep_links
+                .get(self.qr_index)
+                .map(|(_, uri)| uri.clone())
+        } else {
+            self.status
+                .sender_urls
+                .get(self.qr_index)
+                .map(|(_, url)| url.clone())
+                .or(current_url)
+        };
+        if http_url != self.qr_url {
+            self.qr_url = http_url.clone();
+            self.qr_data = http_url
+                .and_then(|url| qr_code::Data::new(url).ok());
+        }
+
+        self.update_tray_level();
+        self.update_tray_menu();
+
+        // Poll tray messages (non-blocking)
+        if let Ok(msg) = self.tray_rx.try_recv() {
+            return self.update(Message::Tray(msg));
+        }
+        if sender_joined && self.window_hidden && self.settings.show_on_connect {
+            self.active_view = ActiveView::Main;
+            return self.show_window();
+        }
+        Task::none()
+    }
+
+    /// Append log lines added since the last call.
+    fn follow_log(&mut self) {
+        let (lines, next) = self.shared.logs_since(self.log_seq);
+        self.log_seq = next;
+        self.log_lines.extend(lines);
+        while self.log_lines.len() > core::MAX_LOG_LINES {
+            self.log_lines.pop_front();
+        }
+    }
+
+    /// Keep the tray's Start/Stop entries and output submenu in step with
+    /// the receiver.
+    fn update_tray_menu(&mut self) {
+        let running = self.status.server_running;
+        self.tray_menu.start.set_enabled(!running);
+        self.tray_menu.stop.set_enabled(running);
+
+        let outputs = (self.output_devices.clone(), self.selected_output.clone());
+        if self.tray_outputs.as_ref() == Some(&outputs) {
+            return;
+        }
+        let submenu = &self.tray_menu.outputs;
+        while submenu.remove_at(0).is_some() {}
+        for device in &outputs.0 {
+            let item = tray_icon::menu::CheckMenuItem::with_id(
+                format!("{TRAY_OUTPUT_PREFIX}{device}"),
+                device,
+                true,
+                outputs.1.as_ref() == Some(device),
+                None,
+            );
+            if let Err(e) = submenu.append(&item) {
+                log::warn!("Failed to update tray menu: {e}");
+            }
+        }
+        submenu.set_enabled(!outputs.0.is_empty());
+        self.tray_outputs = Some(outputs);
+    }
+
+    /// The STUN/TURN server entered in Settings, if any.
+    fn ice_servers(&self) -> Vec<IceServer> {
+        let urls: Vec<String> = self
+            .ice_urls
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|u| !u.is_empty())
+            .map(str::to_string)
+            .collect();
+        if urls.is_empty() {
+            return Vec::new();
+        }
+        vec![IceServer {
+            urls,
+            username: self.ice_username.trim().to_string(),
+            credential: self.ice_credential.clone(),
+        }]
+    }
+
+    /// Poll quickly only while there is live audio to show.
+    fn tick_interval(&self) -> Duration {
+        if self.window_hidden && self.status.client_connected {
+            TRAY_LEVEL_TICK
+        } else if self.window_hidden {
+            FALLBACK_TICK
+        } else if self.status.client_connected {
+            ACTIVE_TICK
+        } else if self.status.server_running {
+            PULSE_TICK
+        } else {
+            FALLBACK_TICK
         }
     }
 
     fn subscription(&self) -> Subscription<Message> {
         Subscription::batch(vec![
-            // Periodic status polling + tray message check
-            iced::time::every(std::time::Duration::from_millis(50)).map(|_| Message::Tick),
+            // Animation and fallback refresh + tray message check
+            iced::time::every(self.tick_interval()).map(|_| Message::Tick),
+            core_events(&self.shared),
+            // Keys not consumed by a widget (e.g. a focused text field)
+            keyboard::on_key_press(|key, modifiers| Some(Message::KeyPressed(key, modifiers))),
             // Intercept window close → hide to tray instead of quitting
-            iced::event::listen_with(|event, _status, id| {
-                if let iced::Event::Window(iced::window::Event::CloseRequested) = event {
+            // and track the size for the compact layout
+            iced::event::listen_with(|event, status, id| match event {
+                iced::Event::Window(iced::window::Event::CloseRequested) => {
                     Some(Message::WindowCloseRequested(id))
-                } else {
-                    None
                 }
+                iced::Event::Window(iced::window::Event::Resized(size)) => {
+                    Some(Message::WindowResized(size))
+                }
+                // The palette's search field swallows Escape
+                iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                    key: keyboard::Key::Named(keyboard::key::Named::Escape),
+                    ..
+                }) if status == iced::event::Status::Captured => Some(Message::ClosePalette),
+                _ => None,
             }),
         ])
     }
@@ -291,20 +1947,29 @@ impl App {
 
     fn view(&self) -> Element<'_, Message> {
         let content = match self.active_view {
+            ActiveView::Main if self.is_compact() => self.compact_view(),
             ActiveView::Main => self.main_view(),
             ActiveView::Settings => self.settings_view(),
             ActiveView::Logs => self.logs_view(),
+            ActiveView::Stats => self.stats_view(),
+            ActiveView::History => self.history_view(),
+            ActiveView::Captions => self.captions_view(),
+            ActiveView::Clients => self.clients_view(),
             ActiveView::QrCode => self.qr_view(),
         };
 
-        container(content)
+        let root = container(content)
             .width(Length::Fill)
             .height(Length::Fill)
             .style(|_| container::Style {
-                background: Some(BG_PRIMARY.into()),
+                background: Some(bg_primary().into()),
                 ..Default::default()
-            })
-            .into()
+            });
+
+        match &self.palette {
+            Some(p) => stack![root, self.palette_overlay(p)].into(),
+            None => root.into(),
+        }
     }
 
     // =======================================================================
@@ -322,7 +1987,36 @@ impl App {
         let cards = self.info_cards();
         let footer = self.footer_bar();
 
-        column![header, connection, cards, footer]
+        let mut content = column![header];
+        if let Some(err) = &self.status.http_error {
+            content = content.push(self.error_card("WEB SERVER UNAVAILABLE", err, None));
+        }
+        if let Some(fallback) = self.status.port_fallback {
+            content = content.push(
+                text(format!(
+                    "Port {} is in use by another program, so the receiver is on port {}. \
+                     Senders need the new address or QR code.",
+                    fallback.requested, fallback.port
+                ))
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(WARNING),
+                }),
+            );
+        }
+        if let Some(err) = &self.status.last_error {
+            content = content.push(self.error_card("ERROR", err, Some(Message::DismissError)));
+        }
+        if let Some(resume) = self.interrupted_card() {
+            content = content.push(resume);
+        }
+        if let Some(approval) = self.approval_card() {
+            content = content.push(approval);
+        }
+        content
+            .push(connection)
+            .push(cards)
+            .push(footer)
             .width(Length::Fill)
             .height(Length::Fill)
             .spacing(16)
@@ -331,38 +2025,141 @@ impl App {
     }
 
     // =======================================================================
-    // Connection Hero (big button + status)
+    // Compact View (small / secondary screens)
     // =======================================================================
 
-    fn connection_hero(&self) -> Element<'_, Message> {
-        let is_running = self.status.server_running;
-        let is_connected = self.status.client_connected;
+    /// Forced by the Appearance setting, or automatic in a small window.
+    fn is_compact(&self) -> bool {
+        self.appearance.compact
+            || self.window_size.width < COMPACT_MAX_WIDTH
+            || self.window_size.height < COMPACT_MAX_HEIGHT
+    }
 
-        let (state_color, state_label, btn_label) = if is_connected {
-            (SUCCESS, "Connected", "STOP")
-        } else if is_running {
-            (WARNING, "Waiting for device…", "STOP")
-        } else {
-            (TEXT_TERTIARY, "Disconnected", "START")
+    /// One status line and the essential buttons; no hero circle or cards.
+    fn compact_view(&self) -> Element<'_, Message> {
+        let (state_color, state_label, btn_label) = self.connection_state();
+
+        let dot = container(text(""))
+            .width(Length::Fixed(8.0))
+            .height(Length::Fixed(8.0))
+            .style(move |_| container::Style {
+                background: Some(state_color.into()),
+                border: Border {
+                    color: Color::TRANSPARENT,
+                    width: 0.0,
+                    radius: 4.0.into(),
+                },
+                ..Default::default()
+            });
+
+        let detail = match &self.status.client_addr {
+            Some(addr) if self.status.client_connected => format!("{state_label} · {addr}"),
+            _ => state_label.to_string(),
         };
 
-        // Animated glow intensity
-        let pulse = if is_running && !is_connected {
-            0.15 + 0.25 * ((self.pulse_phase.sin() + 1.0) / 2.0)
-        } else if is_connected {
-            0.25
+        let btn_color = if self.status.server_running {
+            ERROR
         } else {
-            0.0
+            accent()
         };
-
-        // --- Large circular button ---
-        let size = 140.0;
-        let btn_color = if is_running { ERROR } else { ACCENT };
-
-        let connect_btn = button(
-            container(
-                text(btn_label)
-                    .size(14)
+        let start_stop = button(text(btn_label).size(12).style(move |_| text::Style {
+            color: Some(btn_color),
+        }))
+        .on_press(if self.status.server_running {
+            Message::StopServer
+        } else {
+            Message::StartServer
+        })
+        .style(move |_, status| button::Style {
+            background: Some(
+                match status {
+                    button::Status::Hovered => bg_hover(),
+                    _ => bg_elevated(),
+                }
+                .into(),
+            ),
+            text_color: btn_color,
+            border: Border {
+                color: btn_color,
+                width: 1.5,
+                radius: 6.0.into(),
+            },
+            ..Default::default()
+        })
+        .padding([6, 14]);
+
+        let settings = button(text("Settings").size(12).style(|_| text::Style {
+            color: Some(TEXT_SECONDARY),
+        }))
+        .on_press(Message::Navigate(ActiveView::Settings))
+        .style(ghost_button_style)
+        .padding([6, 8]);
+
+        let status_line = row![
+            dot,
+            text(detail).size(13).style(move |_| text::Style {
+                color: Some(state_color),
+            }),
+            horizontal_space(),
+            self.focusable(FocusTarget::StartStop, start_stop, 8.0),
+            self.focusable(FocusTarget::Settings, settings, 6.0),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+        let mut content = column![status_line];
+        // Errors shrink to their title; approvals stay, since they block the sender
+        if let Some(err) = self.status.http_error.as_ref().or(self.status.last_error.as_ref()) {
+            content = content.push(text(err.title).size(12).style(|_| text::Style {
+                color: Some(ERROR),
+            }));
+        }
+        if let Some(approval) = self.approval_card() {
+            content = content.push(approval);
+        }
+        scrollable(content.spacing(8).padding(12))
+            .height(Length::Fill)
+            .into()
+    }
+
+    // =======================================================================
+    // Connection Hero (big button + status)
+    // =======================================================================
+
+    /// Status colour, status label and start/stop label.
+    fn connection_state(&self) -> (Color, &'static str, &'static str) {
+        if self.status.client_connected {
+            (SUCCESS, "Connected", "STOP")
+        } else if self.status.server_running {
+            (WARNING, "Waiting for device…", "STOP")
+        } else {
+            (TEXT_TERTIARY, "Disconnected", "START")
+        }
+    }
+
+    fn connection_hero(&self) -> Element<'_, Message> {
+        let is_running = self.status.server_running;
+        let is_connected = self.status.client_connected;
+
+        let (state_color, state_label, btn_label) = self.connection_state();
+
+        // Animated glow intensity
+        let pulse = if is_running && !is_connected {
+            0.15 + 0.25 * ((self.pulse_phase.sin() + 1.0) / 2.0)
+        } else if is_connected {
+            0.25
+        } else {
+            0.0
+        };
+
+        // --- Large circular button ---
+        let size = 140.0;
+        let btn_color = if is_running { ERROR } else { accent() };
+
+        let connect_btn = button(
+            container(
+                text(btn_label)
+                    .size(14)
                     .style(move |_| text::Style {
                         color: Some(btn_color),
                     }),
@@ -378,7 +2175,7 @@ impl App {
             Message::StartServer
         })
         .style(move |_, _| button::Style {
-            background: Some(BG_ELEVATED.into()),
+            background: Some(bg_elevated().into()),
             text_color: btn_color,
             border: Border {
                 color: btn_color,
@@ -394,6 +2191,8 @@ impl App {
         .width(Length::Fixed(size))
         .height(Length::Fixed(size));
 
+        let connect_btn = self.focusable(FocusTarget::StartStop, connect_btn, size / 2.0 + 4.0);
+
         // Outer glow ring
         let glow_ring = container(
             container(connect_btn)
@@ -440,11 +2239,16 @@ impl App {
 
         // Subtitle
         let subtitle = if is_connected {
-            self.status
-                .client_addr
-                .as_deref()
-                .map(|a| format!("Device connected from {a}"))
-                .unwrap_or_else(|| "Audio streaming active".into())
+            let device = self
+                .status
+                .clients
+                .first()
+                .and_then(|c| c.device.as_deref());
+            match (device, self.status.client_addr.as_deref()) {
+                (Some(device), _) => format!("{device} connected"),
+                (None, Some(addr)) => format!("Device connected from {addr}"),
+                (None, None) => "Audio streaming active".into(),
+            }
         } else if is_running {
             self.status
                 .ws_url
@@ -466,12 +2270,32 @@ impl App {
         .width(Length::Fill)
         .align_x(Alignment::Center);
 
+        // What senders report over their control channel; the name is
+        // already in the subtitle unless there are several
+        let several = self.status.clients.len() > 1;
+        let sender_info = self
+            .status
+            .clients
+            .iter()
+            .filter_map(|c| sender_summary(c, several))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let sender_info = (!sender_info.is_empty()).then(|| {
+            text(sender_info)
+                .size(12)
+                .align_x(iced::alignment::Horizontal::Center)
+                .style(|_| text::Style {
+                    color: Some(TEXT_TERTIARY),
+                })
+        });
+
         column![
             glow_ring,
             vertical_space().height(20),
             status_row,
             subtitle_text,
         ]
+        .push_maybe(sender_info)
         .align_x(Alignment::Center)
         .spacing(8)
         .into()
@@ -508,26 +2332,59 @@ impl App {
                     ..Default::default()
                 });
 
-                let url_label = text(url.as_str())
-                    .size(16)
+                // The pairing token is long and not for reading aloud
+                let shown_url = url.split("&token=").next().unwrap_or(url);
+                let url_label = text(shown_url)
+                    .size(if self.qr_native { 12 } else { 16 })
                     .font(iced::Font::MONOSPACE)
                     .style(|_| text::Style {
                         color: Some(TEXT_SECONDARY),
                     });
 
-                let instructions = text("Scan with your phone to open the web sender")
-                    .size(14)
-                    .align_x(iced::alignment::Horizontal::Center)
+                let interfaces = self.qr_interface_switcher();
+
+                // Shown as text only: browsers won't open the microphone
+                // on a plain page, so it is for senders that connect directly
+                let plain_label = self
+                    .status
+                    .plain_urls
+                    .get(self.qr_index)
+                    .filter(|_| !self.qr_native)
+                    .map(|(_, url)| {
+                        let ws = format!("ws://{}/ws", url.trim_start_matches("http://"));
+                        format!("Without TLS: {url} · {ws}")
+                    })
+                    .unwrap_or_default();
+                let plain_label = text(plain_label)
+                    .size(12)
+                    .font(iced::Font::MONOSPACE)
                     .style(|_| text::Style {
                         color: Some(TEXT_TERTIARY),
                     });
 
+                let instructions = text(if self.qr_native {
+                    "Scan with the LAN Mic app to connect without approval"
+                } else {
+                    "Scan with your phone to open the web sender"
+                })
+                .size(14)
+                .align_x(iced::alignment::Horizontal::Center)
+                .style(|_| text::Style {
+                    color: Some(TEXT_TERTIARY),
+                });
+
                 let close_btn = button(text("Close").size(14))
                     .on_press(Message::CloseQr)
                     .padding([10, 24])
                     .style(ghost_button_style);
 
-                column![qr, vertical_space().height(20), url_label, instructions, vertical_space().height(20), close_btn]
+                let target = row![
+                    self.qr_target_button("Web page", false),
+                    self.qr_target_button("Native app", true),
+                ]
+                .spacing(8);
+
+                column![target, qr, vertical_space().height(20), url_label, plain_label, interfaces, instructions, vertical_space().height(20), close_btn]
                     .spacing(12)
                     .align_x(Alignment::Center)
             }
@@ -542,151 +2399,1871 @@ impl App {
         container(content)
             .width(Length::Fill)
             .height(Length::Fill)
-            .align_x(Alignment::Center)
-            .align_y(Alignment::Center)
+            .align_x(Alignment::Center)
+            .align_y(Alignment::Center)
+            .into()
+    }
+
+    /// Picks what the QR code opens; the selected target is highlighted.
+    fn qr_target_button<'a>(&self, label: &'a str, native: bool) -> Element<'a, Message> {
+        let selected = self.qr_native == native;
+        button(text(label).size(13).style(move |_| text::Style {
+            color: Some(if selected { accent() } else { TEXT_SECONDARY }),
+        }))
+        .on_press(Message::QrNativeToggled(native))
+        .padding([4, 12])
+        .style(ghost_button_style)
+        .into()
+    }
+
+    /// Automatic plus each interface the listener is reachable on, with the
+    /// address it would advertise.
+    fn interface_choices(&self) -> Vec<InterfaceChoice> {
+        let mut choices = vec![InterfaceChoice {
+            name: None,
+            address: None,
+        }];
+        for (interface, url) in &self.status.sender_urls {
+            if choices.iter().any(|c| c.name.as_ref() == Some(interface)) {
+                continue;
+            }
+            let host = url.trim_start_matches("https://");
+            let address = host.rsplit_once(':').map_or(host, |(ip, _)| ip);
+            choices.push(InterfaceChoice {
+                name: Some(interface.clone()),
+                address: Some(address.to_string()),
+            });
+        }
+        choices
+    }
+
+    /// "‹ Wi-Fi (1/2) ›" switcher, shown only when several interfaces are
+    /// usable and the right one can't be guessed.
+    fn qr_interface_switcher(&self) -> Element<'_, Message> {
+        let urls = &self.status.sender_urls;
+        if urls.len() < 2 {
+            return horizontal_space().width(0).into();
+        }
+        let count = urls.len();
+        let prev = (self.qr_index + count - 1) % count;
+        let next = (self.qr_index + 1) % count;
+        let name = urls
+            .get(self.qr_index)
+            .map(|(iface, _)| iface.as_str())
+            .unwrap_or("");
+
+        row![
+            button(text("‹").size(16))
+                .on_press(Message::QrInterfaceSelected(prev))
+                .padding([4, 12])
+                .style(ghost_button_style),
+            text(format!("{name} ({}/{count})", self.qr_index + 1))
+                .size(13)
+                .style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                }),
+            button(text("›").size(16))
+                .on_press(Message::QrInterfaceSelected(next))
+                .padding([4, 12])
+                .style(ghost_button_style),
+        ]
+        .spacing(12)
+        .align_y(Alignment::Center)
+        .into()
+    }
+
+    // =======================================================================
+    // Info Cards (output device + packet stats)
+    // =======================================================================
+
+    fn info_cards(&self) -> Element<'_, Message> {
+        let device_name = self
+            .selected_output
+            .as_deref()
+            .unwrap_or("No device selected");
+        let device_label = truncate_str(device_name, 40);
+
+        let audio_card = self.card(
+            "OUTPUT DEVICE",
+            text(device_label)
+                .size(13)
+                .style(|_| text::Style {
+                    color: Some(TEXT_PRIMARY),
+                })
+                .into(),
+        );
+
+        let packets = self.status.audio_packets;
+        let mut packet_stats = column![text(packets.to_string())
+            .size(20)
+            .font(iced::Font::MONOSPACE)
+            .style(|_| text::Style {
+                color: Some(accent()),
+            })];
+        if self.status.concealed_packets > 0 {
+            let concealed = format!("{} lost, concealed", self.status.concealed_packets);
+            packet_stats = packet_stats.push(text(concealed).size(12).style(|_| text::Style {
+                color: Some(TEXT_SECONDARY),
+            }));
+        }
+        if self.status.decode_errors > 0 {
+            let corrupt = format!("{} corrupt, skipped", self.status.decode_errors);
+            packet_stats = packet_stats.push(text(corrupt).size(12).style(|_| text::Style {
+                color: Some(TEXT_SECONDARY),
+            }));
+        }
+        if crate::profile::current().rtp_port.is_some() {
+            let rtp = format!(
+                "Plain RTP: {} ({} invalid)",
+                self.status.rtp_packets, self.status.rtp_invalid_packets
+            );
+            packet_stats = packet_stats.push(text(rtp).size(12).style(|_| text::Style {
+                color: Some(TEXT_SECONDARY),
+            }));
+        }
+        let stats_card = self.card("PACKETS RECEIVED", packet_stats.into());
+
+        // Loudest sender, so the meter shows whether anything is arriving
+        let level = self.status.clients.iter().map(|c| c.level).fold(
+            core::AudioLevel::default(),
+            |a, b| core::AudioLevel {
+                peak: a.peak.max(b.peak),
+                rms: a.rms.max(b.rms),
+            },
+        );
+        let level_card = self.card("INPUT LEVEL", level_meter(level));
+
+        let mut cards = column![audio_card, level_card, self.output_level_card()].spacing(12);
+        if self.status.monitor_device.is_some() {
+            cards = cards.push(self.monitor_card());
+        }
+        if self.status.client_connected {
+            cards = cards.push(self.link_stats_card());
+        }
+        cards.push(stats_card).into()
+    }
+
+    /// A classified error: what happened, the raw detail, and what to try.
+    fn error_card<'a>(
+        &self,
+        header: &'a str,
+        err: &'a ErrorReport,
+        dismiss: Option<Message>,
+    ) -> Element<'a, Message> {
+        let mut title_row = row![text(err.title).size(13).style(|_| text::Style {
+            color: Some(ERROR),
+        })]
+        .align_y(Alignment::Center);
+        if let Some(msg) = dismiss {
+            title_row = title_row.push(horizontal_space()).push(
+                button(text("Dismiss").size(12).style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                }))
+                .on_press(msg)
+                .style(ghost_button_style)
+                .padding([4, 8]),
+            );
+        }
+
+        self.card(
+            header,
+            column![
+                title_row,
+                text(err.detail.as_str())
+                    .size(11)
+                    .font(iced::Font::MONOSPACE)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_TERTIARY),
+                    }),
+                text(format!("Try: {}", err.hint))
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+            ]
+            .spacing(4)
+            .into(),
+        )
+    }
+
+    /// Offer to resume after the previous run died while listening.
+    fn interrupted_card(&self) -> Option<Element<'_, Message>> {
+        if self.interrupted.is_none() || self.status.server_running {
+            return None;
+        }
+
+        Some(self.card(
+            "RECEIVER WAS INTERRUPTED",
+            row![
+                text("The last session ended unexpectedly. Resume listening?")
+                    .size(13)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_PRIMARY),
+                    }),
+                horizontal_space(),
+                button(text("Dismiss").size(12).style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                }))
+                .on_press(Message::DismissInterrupted)
+                .style(ghost_button_style)
+                .padding([4, 8]),
+                button(text("Resume").size(12).style(|_| text::Style {
+                    color: Some(SUCCESS),
+                }))
+                .on_press(Message::ResumeInterrupted)
+                .style(ghost_button_style)
+                .padding([4, 8]),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into(),
+        ))
+    }
+
+    /// Prompt for the oldest sender waiting to be allowed, if any.
+    fn approval_card(&self) -> Option<Element<'_, Message>> {
+        let request = self.status.pending_approvals.first()?;
+        let id = request.id;
+
+        Some(self.card(
+            "NEW DEVICE WANTS TO CONNECT",
+            row![
+                text(format!("{} ({})", request.device_name, request.addr))
+                    .size(13)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_PRIMARY),
+                    }),
+                horizontal_space(),
+                button(text("Deny").size(12).style(|_| text::Style {
+                    color: Some(ERROR),
+                }))
+                .on_press(Message::ApprovalResolved(id, false))
+                .style(ghost_button_style)
+                .padding([4, 8]),
+                button(text("Allow").size(12).style(|_| text::Style {
+                    color: Some(SUCCESS),
+                }))
+                .on_press(Message::ApprovalResolved(id, true))
+                .style(ghost_button_style)
+                .padding([4, 8]),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into(),
+        ))
+    }
+
+    /// Monitor volume + mute, independent of the main (cable) output.
+    /// Bitrate, loss and jitter of the incoming audio, colored when they
+    /// point at a poor Wi-Fi link.
+    fn link_stats_card(&self) -> Element<'_, Message> {
+        let status = &self.status;
+        let stat = |label: &'static str, value: String, color: Color| {
+            column![
+                text(value)
+                    .size(16)
+                    .font(iced::Font::MONOSPACE)
+                    .style(move |_| text::Style { color: Some(color) }),
+                text(label).size(11).style(|_| text::Style {
+                    color: Some(TEXT_TERTIARY),
+                }),
+            ]
+            .spacing(2)
+            .width(Length::Fill)
+        };
+        let level = |value: f32, warn: f32, bad: f32| {
+            if value >= bad {
+                ERROR
+            } else if value >= warn {
+                WARNING
+            } else {
+                TEXT_PRIMARY
+            }
+        };
+
+        let bitrate = stat("kbps", format!("{:.0}", status.bitrate_kbps), TEXT_PRIMARY);
+        let loss = match status.packet_loss_pct {
+            Some(pct) => stat("% loss", format!("{pct:.1}"), level(pct, 1.0, 5.0)),
+            None => stat("% loss", "–".to_string(), TEXT_SECONDARY),
+        };
+        let jitter = stat(
+            "ms jitter",
+            format!("{:.0}", status.jitter_ms),
+            level(status.jitter_ms, 30.0, 60.0),
+        );
+        self.card(
+            "LINK QUALITY",
+            row![bitrate, loss, jitter].spacing(12).into(),
+        )
+    }
+
+    /// Latency target slider and what it sets, while Custom latency is on.
+    fn latency_slider(&self) -> Element<'_, Message> {
+        let Some(ms) = self.latency_ms else {
+            return column![].into();
+        };
+        let params = self.quality.params().with_latency(ms);
+        let range = *LATENCY_RANGE_MS.start() as f32..=*LATENCY_RANGE_MS.end() as f32;
+        column![
+            row![
+                slider(range, ms as f32, |v| Message::LatencyChanged(v as u32))
+                    .step(10.0)
+                    .style(slider_style),
+                text(format!("{ms:>3} ms"))
+                    .size(12)
+                    .font(iced::Font::MONOSPACE)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            text(format!(
+                "Plays after {ms} ms is buffered · holds up to {} ms · {} frame device buffer. \
+                 Overrides the preset's buffering; applies on next Start.",
+                params.max_buffer_ms, params.output_buffer_frames
+            ))
+            .size(12)
+            .style(|_| text::Style {
+                color: Some(TEXT_SECONDARY),
+            }),
+        ]
+        .spacing(6)
+        .into()
+    }
+
+    /// Width of stereo senders, from folded to mono to widened.
+    fn stereo_width_slider(&self) -> Element<'_, Message> {
+        let percent = (self.stereo_width * 100.0).round();
+        column![
+            row![
+                text("Stereo width").size(12).style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                }),
+                slider(0.0..=MAX_STEREO_WIDTH * 100.0, percent, |v| {
+                    Message::StereoWidthChanged(v / 100.0)
+                })
+                .step(5.0)
+                .style(slider_style),
+                text(format!("{percent:>3.0} %"))
+                    .size(12)
+                    .font(iced::Font::MONOSPACE)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+            text(
+                "For senders that send stereo (e.g. an external interface). \
+                 0 % folds them to mono, 100 % plays them as sent and higher \
+                 widens them. Mono mics are unaffected."
+            )
+            .size(12)
+            .style(|_| text::Style {
+                color: Some(TEXT_SECONDARY),
+            }),
+        ]
+        .spacing(6)
+        .into()
+    }
+
+    /// Target level and attack/release sliders, while AGC is on.
+    fn agc_controls(&self) -> Element<'_, Message> {
+        let Some(agc) = self.agc else {
+            return column![].into();
+        };
+        let setting = |name: &'static str,
+                       slider: iced::widget::Slider<'static, f32, Message>,
+                       value: String| {
+            row![
+                text(name)
+                    .size(12)
+                    .width(Length::Fixed(56.0))
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                slider.style(slider_style),
+                text(value)
+                    .size(12)
+                    .font(iced::Font::MONOSPACE)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center)
+        };
+        let target = slider(-30.0..=-10.0, agc.target_dbfs, move |v| {
+            Message::AgcChanged(AgcConfig {
+                target_dbfs: v,
+                ..agc
+            })
+        })
+        .step(1.0);
+        let attack = slider(5.0..=200.0, agc.attack_ms, move |v| {
+            Message::AgcChanged(AgcConfig {
+                attack_ms: v,
+                ..agc
+            })
+        })
+        .step(5.0);
+        let release = slider(100.0..=2000.0, agc.release_ms, move |v| {
+            Message::AgcChanged(AgcConfig {
+                release_ms: v,
+                ..agc
+            })
+        })
+        .step(50.0);
+        column![
+            setting("Target", target, format!("{:>3.0} dBFS", agc.target_dbfs)),
+            setting("Attack", attack, format!("{:>4.0} ms", agc.attack_ms)),
+            setting("Release", release, format!("{:>4.0} ms", agc.release_ms)),
+            text(
+                "Brings quiet phone mics up to the target level and loud ones down, \
+                 before they are mixed. Attack sets how fast loud senders are turned \
+                 down, release how fast quiet ones come back up."
+            )
+            .size(12)
+            .style(|_| text::Style {
+                color: Some(TEXT_SECONDARY),
+            }),
+        ]
+        .spacing(6)
+        .into()
+    }
+
+    /// A detected virtual cable, offered as the output so other apps can
+    /// use the receiver as their microphone.
+    fn virtual_cable_row(&self) -> Element<'_, Message> {
+        let hint = |line: String| {
+            text(line).size(12).style(|_| text::Style {
+                color: Some(TEXT_SECONDARY),
+            })
+        };
+        let selected = self.selected_output.as_deref();
+        if let Some(kind) = selected.and_then(VirtualDevice::detect) {
+            return hint(format!(
+                "Other apps can pick \"{}\" as their microphone. \
+                 Choose a Monitor Output below to keep hearing the mix.",
+                kind.recording_side()
+            ))
+            .into();
+        }
+        let Some((name, kind)) = self.virtual_cable() else {
+            return column![].into();
+        };
+        row![
+            hint(format!("{kind} detected: {name}")),
+            horizontal_space(),
+            button(text("Use as Virtual Mic").size(12).style(|_| text::Style {
+                color: Some(accent()),
+            }))
+            .on_press(Message::OutputDeviceChanged(name.to_string()))
+            .style(ghost_button_style)
+            .padding([4, 8]),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into()
+    }
+
+    /// First virtual cable in the device list.
+    fn virtual_cable(&self) -> Option<(&str, VirtualDevice)> {
+        self.output_devices
+            .iter()
+            .find_map(|d| VirtualDevice::detect(d).map(|kind| (d.as_str(), kind)))
+    }
+
+    fn output_level_card(&self) -> Element<'_, Message> {
+        let (mute_label, mute_color) = if self.output_muted {
+            ("Unmute", ERROR)
+        } else {
+            ("Mute", TEXT_SECONDARY)
+        };
+
+        self.card(
+            "OUTPUT LEVEL",
+            row![
+                slider(0.0..=2.0, self.output_gain, Message::OutputGainChanged)
+                    .step(0.01)
+                    .style(slider_style),
+                text(format!("{:>3.0}%", self.output_gain * 100.0))
+                    .size(12)
+                    .font(iced::Font::MONOSPACE)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                button(text(mute_label).size(12).style(move |_| text::Style {
+                    color: Some(mute_color),
+                }))
+                .on_press(Message::OutputMuteToggled)
+                .style(ghost_button_style)
+                .padding([4, 8]),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center)
+            .into(),
+        )
+    }
+
+    fn monitor_card(&self) -> Element<'_, Message> {
+        let (mute_label, mute_color) = if self.monitor_muted {
+            ("Unmute", ERROR)
+        } else {
+            ("Mute", TEXT_SECONDARY)
+        };
+
+        self.card(
+            "MONITOR LEVEL",
+            row![
+                slider(0.0..=1.0, self.monitor_gain, Message::MonitorGainChanged)
+                    .step(0.01)
+                    .style(slider_style),
+                text(format!("{:>3.0}%", self.monitor_gain * 100.0))
+                    .size(12)
+                    .font(iced::Font::MONOSPACE)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                button(text(mute_label).size(12).style(move |_| text::Style {
+                    color: Some(mute_color),
+                }))
+                .on_press(Message::MonitorMuteToggled)
+                .style(ghost_button_style)
+                .padding([4, 8]),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center)
+            .into(),
+        )
+    }
+
+    // =======================================================================
+    // Settings View
+    // =======================================================================
+
+    fn settings_view(&self) -> Element<'_, Message> {
+        let header = self.header_bar("Settings", Some(ActiveView::Main), "Back");
+
+        // Server configuration
+        let interface_choices = self.interface_choices();
+        let selected_interface = interface_choices
+            .iter()
+            .find(|c| c.name == self.network_interface)
+            .cloned()
+            .unwrap_or_else(|| InterfaceChoice {
+                name: self.network_interface.clone(),
+                address: None,
+            });
+        let server_card = container(
+            column![
+                section_title("Server Configuration"),
+                vertical_space().height(16),
+                label("Bind Address"),
+                vertical_space().height(6),
+                row![
+                    text_input("0.0.0.0:9001", &self.bind_addr)
+                        .on_input(Message::BindAddressChanged)
+                        .on_submit(Message::ApplyBindAddress)
+                        .style(text_input_style)
+                        .padding(12),
+                    button(text("Apply").size(13).style(|_| text::Style {
+                        color: Some(accent()),
+                    }))
+                    .on_press(Message::ApplyBindAddress)
+                    .style(ghost_button_style)
+                    .padding([8, 12]),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+                vertical_space().height(16),
+                label("Network Interface"),
+                vertical_space().height(6),
+                pick_list(
+                    interface_choices,
+                    Some(selected_interface),
+                    Message::InterfaceSelected
+                )
+                .style(pick_list_style)
+                .width(Length::Fill),
+                text("Address shown in the QR code and advertised over mDNS. Pick one if a VPN adapter gets chosen.")
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                vertical_space().height(16),
+                label("TLS Certificate (optional)"),
+                vertical_space().height(6),
+                text_input("/path/to/fullchain.pem", &self.tls_cert_path)
+                    .on_input(Message::TlsCertPathChanged)
+                    .style(text_input_style)
+                    .padding(12),
+                vertical_space().height(6),
+                row![
+                    text_input("/path/to/key.pem", &self.tls_key_path)
+                        .on_input(Message::TlsKeyPathChanged)
+                        .on_submit(Message::ApplyCertificate)
+                        .style(text_input_style)
+                        .padding(12),
+                    button(text("Apply").size(13).style(|_| text::Style {
+                        color: Some(accent()),
+                    }))
+                    .on_press(Message::ApplyCertificate)
+                    .style(ghost_button_style)
+                    .padding([8, 12]),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+                text("PEM certificate and key from a CA your phones trust. Leave both empty for the self-signed one.")
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                vertical_space().height(16),
+                checkbox("Use STUN server for NAT traversal", self.use_stun)
+                    .on_toggle(Message::UseStunChanged)
+                    .style(checkbox_style),
+                vertical_space().height(16),
+                label("STUN/TURN Server (optional)"),
+                vertical_space().height(6),
+                text_input("turn:turn.example.com:3478", &self.ice_urls)
+                    .on_input(Message::IceUrlsChanged)
+                    .style(text_input_style)
+                    .padding(12),
+                vertical_space().height(6),
+                row![
+                    text_input("Username", &self.ice_username)
+                        .on_input(Message::IceUsernameChanged)
+                        .style(text_input_style)
+                        .padding(12),
+                    text_input("Credential", &self.ice_credential)
+                        .on_input(Message::IceCredentialChanged)
+                        .secure(true)
+                        .style(text_input_style)
+                        .padding(12),
+                ]
+                .spacing(8),
+                text("Relays audio when the sender can't reach this PC directly. Applies on next Start.")
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                vertical_space().height(16),
+                checkbox("Allow multiple senders (mix audio)", self.allow_multiple_clients)
+                    .on_toggle(Message::AllowMultipleClientsChanged)
+                    .style(checkbox_style),
+                vertical_space().height(8),
+                checkbox("Ask before accepting new devices", self.require_approval)
+                    .on_toggle(Message::RequireApprovalChanged)
+                    .style(checkbox_style),
+                vertical_space().height(8),
+                checkbox("Advertise on the network (mDNS)", self.advertise_mdns)
+                    .on_toggle(Message::AdvertiseMdnsChanged)
+                    .style(checkbox_style),
+                text("Turn off where mDNS traffic is flagged; QR and manual connections still work.")
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                vertical_space().height(8),
+                checkbox("UDP broadcast beacon (if mDNS is blocked)", self.udp_beacon)
+                    .on_toggle(Message::UdpBeaconChanged)
+                    .style(checkbox_style),
+                vertical_space().height(16),
+                label("Stop When No Sender Connects"),
+                vertical_space().height(6),
+                pick_list(IDLE_STOP_CHOICES, Some(self.idle_stop), Message::IdleStopChanged)
+                    .style(pick_list_style)
+                    .width(Length::Fill),
+                text("Releases the audio device if the receiver is left listening.")
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                vertical_space().height(16),
+                label("Keep Session When a Sender Drops"),
+                vertical_space().height(6),
+                pick_list(
+                    RECONNECT_GRACE_CHOICES,
+                    Some(self.reconnect_grace),
+                    Message::ReconnectGraceChanged
+                )
+                .style(pick_list_style)
+                .width(Length::Fill),
+                text("A sender that loses Wi-Fi briefly picks up where it left off.")
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                vertical_space().height(16),
+                label("Drop Unresponsive Senders"),
+                vertical_space().height(6),
+                pick_list(
+                    KEEPALIVE_TIMEOUT_CHOICES,
+                    Some(self.keepalive_timeout),
+                    Message::KeepaliveTimeoutChanged
+                )
+                .style(pick_list_style)
+                .width(Length::Fill),
+                text("Senders are pinged; one that stops answering is disconnected.")
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                vertical_space().height(16),
+                label("Quality Preset"),
+                vertical_space().height(6),
+                self.focusable(
+                    FocusTarget::Quality,
+                    pick_list(
+                        QualityPreset::ALL,
+                        Some(self.quality),
+                        Message::QualityPresetChanged,
+                    )
+                    .style(pick_list_style)
+                    .width(Length::Fill),
+                    8.0,
+                ),
+                text(match self.status.jitter_target_ms {
+                    Some(ms) if self.quality == QualityPreset::Auto => {
+                        format!("Auto: jitter buffer currently {ms} ms")
+                    }
+                    _ => self.quality.describe(),
+                })
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                }),
+                vertical_space().height(8),
+                checkbox("Custom latency", self.latency_ms.is_some())
+                    .on_toggle(Message::CustomLatencyToggled)
+                    .style(checkbox_style),
+                self.latency_slider(),
+                vertical_space().height(8),
+                checkbox("Automatic gain control", self.agc.is_some())
+                    .on_toggle(Message::AgcToggled)
+                    .style(checkbox_style),
+                self.agc_controls(),
+                vertical_space().height(8),
+                self.stereo_width_slider(),
+                vertical_space().height(16),
+                label("Relay To Another Receiver (optional)"),
+                vertical_space().height(6),
+                text_input("192.168.1.20:9001", &self.relay_target)
+                    .on_input(Message::RelayTargetChanged)
+                    .style(text_input_style)
+                    .padding(12),
+                text(match &self.status.relay_state {
+                    Some(state) => format!("Relay: {state}"),
+                    None => "Forwards the incoming stream to another LAN Mic receiver.".to_string(),
+                })
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                }),
+                vertical_space().height(16),
+                label("Snapcast Multi-Room Output (optional)"),
+                vertical_space().height(6),
+                text_input("tcp://192.168.1.5:4953 or /tmp/snapfifo", &self.snapcast_target)
+                    .on_input(Message::SnapcastTargetChanged)
+                    .style(text_input_style)
+                    .padding(12),
+                text("Plays the mix in sync on every Snapcast client. Applies on next Start.")
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                vertical_space().height(16),
+                label("Live Captions Model (optional)"),
+                vertical_space().height(6),
+                text_input("Folder of a Vosk model, e.g. vosk-model-small-en-us-0.15", &self.captions_model)
+                    .on_input(Message::CaptionsModelChanged)
+                    .style(text_input_style)
+                    .padding(12),
+                text("Transcribes the mix on this computer; needs libvosk. Applies on next Start.")
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                vertical_space().height(16),
+                label("UDP Input for Microcontrollers (optional)"),
+                vertical_space().height(6),
+                row![
+                    text_input("UDP port, e.g. 9006", &self.udp_port)
+                        .on_input(Message::UdpPortChanged)
+                        .style(text_input_style)
+                        .padding(12),
+                    pick_list(UdpFormat::ALL, Some(self.udp_format), Message::UdpFormatChanged)
+                        .style(pick_list_style),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+                text("Length-prefixed 48 kHz packets from senders without WebRTC. Applies on next Start.")
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                vertical_space().height(16),
+                label("Recording Format"),
+                vertical_space().height(6),
+                pick_list(
+                    RecordingFormat::ALL,
+                    Some(self.recording_format),
+                    Message::RecordingFormatChanged,
+                )
+                .style(pick_list_style)
+                .width(Length::Fill),
+                text("FLAC files are encoded when the recording stops.")
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                vertical_space().height(16),
+                label("Log File"),
+                vertical_space().height(6),
+                row![
+                    checkbox("Save logs to disk", self.log_file.enabled)
+                        .on_toggle(Message::LogFileToggled)
+                        .style(checkbox_style),
+                    horizontal_space(),
+                    pick_list(
+                        Rotation::ALL,
+                        Some(self.log_file.rotation),
+                        Message::LogRotationChanged
+                    )
+                    .style(pick_list_style),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+                text(format!(
+                    "Written to {}; the 7 previous files are kept.",
+                    log_file::log_dir()
+                        .map(|d| d.display().to_string())
+                        .unwrap_or_else(|| "the settings folder".to_string())
+                ))
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                }),
+                vertical_space().height(16),
+                label("Web Admin (open from another device)"),
+                vertical_space().height(6),
+                text(self.status.admin_url.as_deref().unwrap_or("Starting…"))
+                    .size(12)
+                    .font(iced::Font::MONOSPACE)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+            ]
+            .push_maybe(crate::profile::current().mtls.then(|| {
+                column![
+                    vertical_space().height(16),
+                    label("Client Certificates (mTLS)"),
+                    vertical_space().height(6),
+                    button(text("Export client certificate").size(12).style(|_| {
+                        text::Style {
+                            color: Some(accent()),
+                        }
+                    }))
+                    .on_press(Message::ExportClientCertificate)
+                    .style(ghost_button_style)
+                    .padding([4, 8]),
+                    text(
+                        "Only senders with a certificate from this receiver can connect. \
+                         Install the .p12 on the phone with the password shown here."
+                    )
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                ]
+                .push_maybe(self.status.client_certificate.as_ref().map(|cert| {
+                    column![
+                        text(&cert.path).size(12).style(|_| text::Style {
+                            color: Some(TEXT_SECONDARY),
+                        }),
+                        text(format!("Password: {}", cert.password))
+                            .size(13)
+                            .font(iced::Font::MONOSPACE),
+                    ]
+                    .spacing(2)
+                }))
+                .spacing(4)
+            }))
+            .push_maybe(self.firewall.map(|status| {
+                let allow = (status != RuleStatus::Present && !self.firewall_busy)
+                    .then_some(Message::AllowThroughFirewall);
+                column![
+                    vertical_space().height(16),
+                    label("Windows Firewall"),
+                    vertical_space().height(6),
+                    row![
+                        text(status.to_string()).size(12).style(|_| text::Style {
+                            color: Some(TEXT_SECONDARY),
+                        }),
+                        horizontal_space(),
+                        button(
+                            text(if self.firewall_busy {
+                                "Waiting for permission…"
+                            } else {
+                                "Allow Through Firewall"
+                            })
+                            .size(12)
+                            .style(|_| text::Style {
+                                color: Some(accent()),
+                            })
+                        )
+                        .on_press_maybe(allow)
+                        .style(ghost_button_style)
+                        .padding([4, 8]),
+                    ]
+                    .align_y(Alignment::Center),
+                    text(
+                        "Lets devices on the local network reach this app. \
+                         Windows asks for administrator permission."
+                    )
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                ]
+                .spacing(4)
+            }))
+            .spacing(4),
+        )
+        .style(card_style)
+        .padding(20)
+        .width(Length::Fill);
+
+        // Audio output
+        let audio_card = container(
+            column![
+                row![
+                    section_title("Audio Output"),
+                    horizontal_space(),
+                    button(text("Refresh").size(12).style(|_| text::Style {
+                        color: Some(accent()),
+                    }))
+                    .on_press(Message::RefreshDevices)
+                    .style(ghost_button_style)
+                    .padding([4, 8]),
+                ]
+                .align_y(Alignment::Center),
+                vertical_space().height(16),
+                self.focusable(
+                    FocusTarget::OutputDevice,
+                    pick_list(
+                        self.output_devices
+                            .iter()
+                            .cloned()
+                            .map(OutputChoice::new)
+                            .collect::<Vec<_>>(),
+                        self.selected_output.clone().map(OutputChoice::new),
+                        |choice| Message::OutputDeviceChanged(choice.name),
+                    )
+                    .style(pick_list_style)
+                    .placeholder("Select audio device…")
+                    .width(Length::Fill),
+                    8.0,
+                ),
+                self.virtual_cable_row(),
+                vertical_space().height(16),
+                label("Standby Output (used if the main device fails)"),
+                vertical_space().height(6),
+                pick_list(
+                    std::iter::once(MONITOR_OFF_LABEL.to_string())
+                        .chain(self.output_devices.iter().cloned())
+                        .collect::<Vec<_>>(),
+                    Some(
+                        self.standby_device
+                            .clone()
+                            .unwrap_or_else(|| MONITOR_OFF_LABEL.to_string()),
+                    ),
+                    Message::StandbyDeviceChanged,
+                )
+                .style(pick_list_style)
+                .width(Length::Fill),
+                vertical_space().height(16),
+                label("Monitor Output (local listening)"),
+                vertical_space().height(6),
+                pick_list(
+                    std::iter::once(MONITOR_OFF_LABEL.to_string())
+                        .chain(self.output_devices.iter().cloned())
+                        .collect::<Vec<_>>(),
+                    Some(
+                        self.monitor_device
+                            .clone()
+                            .unwrap_or_else(|| MONITOR_OFF_LABEL.to_string()),
+                    ),
+                    Message::MonitorDeviceChanged,
+                )
+                .style(pick_list_style)
+                .width(Length::Fill),
+                vertical_space().height(8),
+                checkbox(
+                    "Cancel speaker echo (phone in the same room as the monitor)",
+                    self.echo_cancellation,
+                )
+                .on_toggle(Message::EchoCancellationChanged)
+                .style(checkbox_style),
+            ]
+            .push_maybe(self.virtual_mic.then(|| {
+                column![
+                    vertical_space().height(16),
+                    label("Virtual Microphone"),
+                    vertical_space().height(6),
+                    checkbox(
+                        "Create a \"LAN Mic\" microphone for other apps",
+                        self.settings.virtual_mic,
+                    )
+                    .on_toggle_maybe((!self.virtual_mic_busy).then_some(Message::VirtualMicToggled))
+                    .style(checkbox_style),
+                    text(
+                        "Plays the main output into a PulseAudio/PipeWire source that \
+                         video-call and recording apps can pick as their microphone, while \
+                         it is on the default device. Removed on Quit."
+                    )
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+                ]
+                .spacing(4)
+            }))
+            .spacing(4),
+        )
+        .style(card_style)
+        .padding(20)
+        .width(Length::Fill);
+
+        // Appearance
+        let appearance_card = container(
+            column![
+                section_title("Appearance"),
+                vertical_space().height(16),
+                label("Accent Color"),
+                vertical_space().height(6),
+                pick_list(
+                    AccentColor::ALL,
+                    Some(self.appearance.accent),
+                    Message::AccentChanged,
+                )
+                .style(pick_list_style)
+                .width(Length::Fill),
+                vertical_space().height(16),
+                label("Background Darkness"),
+                vertical_space().height(6),
+                row![
+                    slider(0.0..=1.0, self.appearance.darkness, Message::DarknessChanged)
+                        .step(0.05)
+                        .on_release(Message::SaveAppearance)
+                        .style(slider_style),
+                    text(format!("{:>3.0}%", self.appearance.darkness * 100.0))
+                        .size(12)
+                        .font(iced::Font::MONOSPACE)
+                        .style(|_| text::Style {
+                            color: Some(TEXT_SECONDARY),
+                        }),
+                ]
+                .spacing(12)
+                .align_y(Alignment::Center),
+                vertical_space().height(16),
+                checkbox(
+                    "Show window when a sender connects",
+                    self.settings.show_on_connect,
+                )
+                .on_toggle(Message::ShowOnConnectChanged)
+                .style(checkbox_style),
+                vertical_space().height(8),
+                checkbox(
+                    "Start listening when the app opens",
+                    self.settings.auto_start,
+                )
+                .on_toggle(Message::AutoStartChanged)
+                .style(checkbox_style),
+                vertical_space().height(8),
+                checkbox("Launch at login", self.launch_at_login)
+                    .on_toggle(Message::LaunchAtLoginToggled)
+                    .style(checkbox_style),
+                vertical_space().height(8),
+                checkbox("Start minimized to tray", self.start_minimized)
+                    .on_toggle_maybe(
+                        self.launch_at_login
+                            .then_some(Message::StartMinimizedToggled),
+                    )
+                    .style(checkbox_style),
+                vertical_space().height(8),
+                checkbox("Compact layout (always)", self.appearance.compact)
+                    .on_toggle(Message::CompactLayoutChanged)
+                    .style(checkbox_style),
+                text(format!(
+                    "Used automatically when the window is narrower than {COMPACT_MAX_WIDTH} \
+                     or shorter than {COMPACT_MAX_HEIGHT} pixels."
+                ))
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                }),
+            ]
+            .spacing(4),
+        )
+        .style(card_style)
+        .padding(20)
+        .width(Length::Fill);
+
+        // Trusted devices
+        let devices: Element<'_, Message> = if self.status.trusted_devices.is_empty() {
+            text("No trusted devices yet. Devices you allow are remembered here.")
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                })
+                .into()
+        } else {
+            column(self.status.trusted_devices.iter().map(|device| {
+                row![
+                    text(truncate_str(&device.name, 40))
+                        .size(13)
+                        .style(|_| text::Style {
+                            color: Some(TEXT_PRIMARY),
+                        }),
+                    horizontal_space(),
+                    button(text("Forget").size(12).style(|_| text::Style {
+                        color: Some(ERROR),
+                    }))
+                    .on_press(Message::ForgetTrustedDevice(device.id.clone()))
+                    .style(ghost_button_style)
+                    .padding([4, 8]),
+                ]
+                .align_y(Alignment::Center)
+                .into()
+            }))
+            .spacing(6)
+            .into()
+        };
+        let trusted_card = container(
+            column![
+                section_title("Trusted Devices"),
+                vertical_space().height(12),
+                devices,
+            ]
+            .spacing(4),
+        )
+        .style(card_style)
+        .padding(20)
+        .width(Length::Fill);
+
+        // Sender access rules
+        let access = &self.status.access;
+        let rules = access
+            .allow
+            .iter()
+            .map(|net| (true, *net))
+            .chain(access.deny.iter().map(|net| (false, *net)));
+        let rules: Element<'_, Message> = if access.allow.is_empty() && access.deny.is_empty() {
+            text("Any address may connect.")
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                })
+                .into()
+        } else {
+            column(rules.map(|(allow, net)| {
+                let (kind, color) = if allow {
+                    ("Allow", SUCCESS)
+                } else {
+                    ("Deny", ERROR)
+                };
+                row![
+                    text(kind).size(12).width(48).style(move |_| text::Style {
+                        color: Some(color),
+                    }),
+                    text(net.to_string())
+                        .size(13)
+                        .font(iced::Font::MONOSPACE)
+                        .style(|_| text::Style {
+                            color: Some(TEXT_PRIMARY),
+                        }),
+                    horizontal_space(),
+                    button(text("Remove").size(12).style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }))
+                    .on_press(Message::RemoveAccessRule(allow, net))
+                    .style(ghost_button_style)
+                    .padding([4, 8]),
+                ]
+                .align_y(Alignment::Center)
+                .into()
+            }))
+            .spacing(6)
+            .into()
+        };
+        let access_hint_color = if self.access_error.is_some() {
+            ERROR
+        } else {
+            TEXT_SECONDARY
+        };
+        let access_card = container(
+            column![
+                section_title("Sender Access"),
+                vertical_space().height(12),
+                rules,
+                vertical_space().height(8),
+                row![
+                    text_input("192.168.1.0/24", &self.access_input)
+                        .on_input(Message::AccessRuleInputChanged)
+                        .on_submit(Message::AddAccessRule(true))
+                        .style(text_input_style)
+                        .padding(12),
+                    button(text("Allow").size(13).style(|_| text::Style {
+                        color: Some(accent()),
+                    }))
+                    .on_press(Message::AddAccessRule(true))
+                    .style(ghost_button_style)
+                    .padding([8, 12]),
+                    button(
+                        text("Deny")
+                            .size(13)
+                            .style(|_| text::Style { color: Some(ERROR) })
+                    )
+                    .on_press(Message::AddAccessRule(false))
+                    .style(ghost_button_style)
+                    .padding([8, 12]),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+                text(self.access_error.as_deref().unwrap_or(
+                    "Denied ranges are always rejected. Once any range is allowed, \
+                     only allowed addresses may connect.",
+                ))
+                .size(12)
+                .style(move |_| text::Style {
+                    color: Some(access_hint_color),
+                }),
+            ]
+            .spacing(4),
+        )
+        .style(card_style)
+        .padding(20)
+        .width(Length::Fill);
+
+        // Tip
+        let tip_card = container(
+            column![
+                text("Tip").size(12).style(|_| text::Style {
+                    color: Some(WARNING),
+                }),
+                vertical_space().height(6),
+                text("Install VB-Cable and select 'CABLE Input' to route audio to other apps like Discord or OBS.")
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_SECONDARY),
+                    }),
+            ]
+            .spacing(4),
+        )
+        .style(card_style)
+        .padding(20)
+        .width(Length::Fill);
+
+        let content = column![
+            header,
+            server_card,
+            audio_card,
+            appearance_card,
+            trusted_card,
+            access_card,
+            tip_card
+        ]
+        .spacing(12);
+
+        scrollable(content.padding(24))
+            .height(Length::Fill)
+            .into()
+    }
+
+    // =======================================================================
+    // Logs View
+    // =======================================================================
+
+    fn logs_view(&self) -> Element<'_, Message> {
+        let header = self.header_bar("System Logs", Some(ActiveView::Main), "Back");
+
+        let filters = row![
+            pick_list(
+                LogLevel::ALL,
+                Some(self.log_level),
+                Message::LogLevelChanged
+            )
+            .style(pick_list_style),
+            text_input("Search logs…", &self.log_search)
+                .on_input(Message::LogSearchChanged)
+                .style(text_input_style)
+                .padding(8),
+            checkbox("Auto-scroll", self.log_autoscroll)
+                .on_toggle(Message::LogAutoScrollToggled)
+                .style(checkbox_style),
+        ]
+        .spacing(12)
+        .align_y(Alignment::Center);
+
+        let query = self.log_search.trim().to_lowercase();
+        let mut shown: Vec<&LogEntry> = self
+            .log_lines
+            .iter()
+            .rev()
+            .filter(|e| e.level <= self.log_level && e.matches(&query))
+            .take(LOG_VIEW_LINES)
+            .collect();
+        shown.reverse();
+
+        let lines: Element<'_, Message> = if shown.is_empty() {
+            let empty = if self.log_lines.is_empty() {
+                "No logs yet…"
+            } else {
+                "No log entries match."
+            };
+            text(empty)
+                .font(iced::Font::MONOSPACE)
+                .size(11)
+                .style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                })
+                .into()
+        } else {
+            column(shown.into_iter().map(log_entry_row))
+                .spacing(2)
+                .into()
+        };
+
+        let log_scroll =
+            scrollable(container(lines).padding(16).width(Length::Fill)).height(Length::Fill);
+        let log_container = container(if self.log_autoscroll {
+            log_scroll.anchor_bottom()
+        } else {
+            log_scroll
+        })
+        .style(card_style)
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        let bundle_row = row![
+            button(text("Export diagnostics").size(12).style(|_| text::Style {
+                color: Some(accent()),
+            }))
+            .on_press(Message::CreateDiagnosticBundle)
+            .style(ghost_button_style)
+            .padding([4, 8]),
+            text("Zips logs, status, settings, audio devices and network interfaces for a bug report.")
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                }),
+        ]
+        .spacing(12)
+        .align_y(Alignment::Center);
+
+        column![
+            header,
+            vertical_space().height(12),
+            filters,
+            vertical_space().height(12),
+            log_container,
+            vertical_space().height(12),
+            bundle_row
+        ]
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(24)
+        .into()
+    }
+
+    // =======================================================================
+    // History View (sessions stored across restarts)
+    // =======================================================================
+
+    /// Un-hide the window from the tray and bring it to the front.
+    fn show_window(&mut self) -> Task<Message> {
+        self.window_hidden = false;
+        match self.window_id {
+            Some(id) => Task::batch([
+                iced::window::change_mode(id, iced::window::Mode::Windowed),
+                iced::window::gain_focus(id),
+            ]),
+            None => Task::none(),
+        }
+    }
+
+    /// Make the style functions pick up `self.appearance`.
+    fn apply_appearance(&self) {
+        *APPEARANCE.write() = self.appearance;
+    }
+
+    fn save_appearance(&self) {
+        if let Err(e) = appearance::save(&self.appearance) {
+            log::warn!("Failed to save appearance: {e:#}");
+        }
+    }
+
+    fn save_settings(&self) {
+        if let Err(e) = app_settings::save(&self.settings) {
+            log::warn!("Failed to save settings: {e:#}");
+        }
+    }
+
+    /// Change the AGC setting; the core applies it right away while
+    /// listening.
+    fn set_agc(&mut self, agc: Option<AgcConfig>) {
+        self.agc = agc;
+        if let Err(e) = self.controller.send(CoreCommand::SetAgc(agc)) {
+            log::warn!("Failed to send SetAgc: {e}");
+        }
+    }
+
+    /// Change the stereo width; the core applies it right away while
+    /// listening.
+    fn set_stereo_width(&mut self, width: f32) {
+        self.stereo_width = width;
+        if let Err(e) = self.controller.send(CoreCommand::SetStereoWidth(width)) {
+            log::warn!("Failed to send SetStereoWidth: {e}");
+        }
+    }
+
+    /// Load the settings of an earlier session into the controls.
+    fn apply_listen_state(&mut self, state: ListenState) {
+        if state.output_device.is_some() {
+            self.selected_output = state.output_device;
+        }
+        self.use_stun = state.use_stun;
+        let ice = state.ice_servers.into_iter().next().unwrap_or_default();
+        self.ice_urls = ice.urls.join(" ");
+        self.ice_username = ice.username;
+        self.ice_credential = ice.credential;
+        self.allow_multiple_clients = state.allow_multiple_clients;
+        self.monitor_device = state.monitor_device;
+        self.monitor_gain = state.monitor_gain;
+        self.monitor_muted = state.monitor_muted;
+        self.quality = state.quality;
+        self.latency_ms = state.latency_ms;
+        self.require_approval = state.require_approval;
+        self.relay_target = state.relay_target.unwrap_or_default();
+        self.snapcast_target = state.snapcast_target.unwrap_or_default();
+        self.captions_model = state.captions_model.unwrap_or_default();
+        self.standby_device = state.standby_device;
+        self.echo_cancellation = state.echo_cancellation;
+        self.set_agc(state.agc);
+        self.set_stereo_width(state.stereo_width);
+        self.advertise_mdns = state.advertise_mdns;
+        self.udp_beacon = state.udp_beacon;
+        if let Some(ingest) = state.udp_ingest {
+            self.udp_port = ingest.port.to_string();
+            self.udp_format = ingest.format;
+        } else {
+            self.udp_port.clear();
+        }
+        self.idle_stop = IdleStop(state.idle_stop_minutes);
+        self.reconnect_grace = ReconnectGrace(state.reconnect_grace_secs);
+        self.keepalive_timeout = KeepaliveTimeout(state.keepalive_timeout_secs);
+    }
+
+    /// Persist the log file settings and reopen the file to match.
+    fn apply_log_file(&mut self) {
+        if let Err(e) = log_file::save(&self.log_file) {
+            log::warn!("Failed to save log file settings: {e:#}");
+        }
+        if let Err(e) = log_file::configure(self.log_file) {
+            self.shared
+                .log_line(format!("Failed to open the log file: {e:#}"));
+        }
+    }
+
+    /// Register or unregister the login entry to match the settings,
+    /// reverting the toggle if that fails.
+    fn apply_autostart(&mut self) {
+        use crate::platform::autostart;
+
+        let res = if self.launch_at_login {
+            autostart::enable(self.start_minimized)
+        } else {
+            autostart::disable()
+        };
+        if let Err(e) = res {
+            self.shared
+                .log_line(format!("Failed to update launch at login: {e:#}"));
+            let registered = autostart::status();
+            self.launch_at_login = registered.is_some();
+            self.start_minimized = registered.unwrap_or(self.start_minimized);
+        }
+    }
+
+    fn reload_history(&mut self) {
+        self.history = history::recent(history::RECENT_LIMIT).unwrap_or_else(|e| {
+            log::warn!("Failed to load session history: {e:#}");
+            Vec::new()
+        });
+    }
+
+    fn history_view(&self) -> Element<'_, Message> {
+        let header = self.header_bar("Session History", Some(ActiveView::Main), "Back");
+
+        let body: Element<'_, Message> = if self.history.is_empty() {
+            text("No sessions recorded yet.")
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(TEXT_TERTIARY),
+                })
+                .into()
+        } else {
+            let lines = self
+                .history
+                .iter()
+                .map(|entry| {
+                    let r = &entry.record;
+                    let mut line = format!(
+                        "{}  {:>8}  {:<21}  {:<24}  {:>7} pkts  {:>4.0} kbps  {:>7.1} MB",
+                        entry.started,
+                        core::format_offset(r.duration_secs() * 1000),
+                        r.client,
+                        truncate_str(r.device_label().unwrap_or("—"), 24),
+                        r.packets,
+                        r.average_kbps(),
+                        r.bytes as f64 / 1_000_000.0
+                    );
+                    if let Some(err) = &r.error {
+                        line.push_str(&format!("  error: {err}"));
+                    }
+                    line
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            text(lines)
+                .font(iced::Font::MONOSPACE)
+                .size(11)
+                .style(|_| text::Style {
+                    color: Some(TEXT_SECONDARY),
+                })
+                .into()
+        };
+
+        let list = container(
+            scrollable(container(body).padding(16).width(Length::Fill)).height(Length::Fill),
+        )
+        .style(card_style)
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        let mut clear = button(text("Clear History").size(12).style(|_| text::Style {
+            color: Some(ERROR),
+        }))
+        .style(ghost_button_style)
+        .padding([4, 8]);
+        if !self.history.is_empty() {
+            clear = clear.on_press(Message::ClearHistory);
+        }
+
+        column![header, list, row![horizontal_space(), clear]]
+            .spacing(12)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(24)
+            .into()
+    }
+
+    // =======================================================================
+    // Captions View (live speech-to-text of the mix)
+    // =======================================================================
+
+    fn captions_view(&self) -> Element<'_, Message> {
+        let header = self.header_bar("Live Captions", Some(ActiveView::Main), "Back");
+
+        let status = &self.status;
+        let body: Element<'_, Message> =
+            if status.captions.is_empty() && status.caption_partial.is_none() {
+                let hint = if status.captions_running {
+                    "Listening… captions appear as senders talk."
+                } else {
+                    "Captions are off. Set a Vosk model folder in Settings and start listening."
+                };
+                text(hint)
+                    .size(12)
+                    .style(|_| text::Style {
+                        color: Some(TEXT_TERTIARY),
+                    })
+                    .into()
+            } else {
+                let lines = status.captions.iter().map(|line| {
+                    row![
+                        text(core::format_offset(line.offset_ms))
+                            .font(iced::Font::MONOSPACE)
+                            .size(11)
+                            .style(|_| text::Style {
+                                color: Some(TEXT_TERTIARY),
+                            }),
+                        text(&line.text).size(14).style(|_| text::Style {
+                            color: Some(TEXT_PRIMARY),
+                        }),
+                    ]
+                    .spacing(12)
+                    .into()
+                });
+                column(lines)
+                    .push_maybe(status.caption_partial.as_ref().map(|partial| {
+                        text(partial).size(14).style(|_| text::Style {
+                            color: Some(TEXT_SECONDARY),
+                        })
+                    }))
+                    .spacing(6)
+                    .into()
+            };
+
+        let list = container(
+            scrollable(container(body).padding(16).width(Length::Fill))
+                .anchor_bottom()
+                .height(Length::Fill),
+        )
+        .style(card_style)
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        let mut export = button(text("Export Transcript").size(12).style(|_| text::Style {
+            color: Some(accent()),
+        }))
+        .style(ghost_button_style)
+        .padding([4, 8]);
+        if !status.captions.is_empty() {
+            export = export.on_press(Message::ExportTranscript);
+        }
+
+        column![header, list, row![horizontal_space(), export]]
+            .spacing(12)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(24)
             .into()
     }
 
     // =======================================================================
-    // Info Cards (output device + packet stats)
+    // Clients View (per-sender gain + mute)
     // =======================================================================
 
-    fn info_cards(&self) -> Element<'_, Message> {
-        let device_name = self
-            .selected_output
-            .as_deref()
-            .unwrap_or("No device selected");
-        let device_label = truncate_str(device_name, 40);
+    fn clients_view(&self) -> Element<'_, Message> {
+        let header = self.header_bar("Clients", Some(ActiveView::Main), "Back");
 
-        let audio_card = self.card(
-            "OUTPUT DEVICE",
-            text(device_label)
-                .size(13)
+        let list: Element<'_, Message> = if self.status.clients.is_empty() {
+            text("No senders connected.")
+                .size(12)
                 .style(|_| text::Style {
-                    color: Some(TEXT_PRIMARY),
+                    color: Some(TEXT_TERTIARY),
                 })
-                .into(),
-        );
-
-        let packets = self.status.audio_packets;
-        let stats_card = self.card(
-            "PACKETS RECEIVED",
-            text(packets.to_string())
-                .size(20)
-                .font(iced::Font::MONOSPACE)
-                .style(|_| text::Style {
-                    color: Some(ACCENT),
+                .into()
+        } else {
+            self.status
+                .clients
+                .iter()
+                .fold(column![].spacing(12), |col, client| {
+                    col.push(self.client_row(client))
                 })
-                .into(),
-        );
+                .into()
+        };
 
-        column![audio_card, stats_card].spacing(12).into()
-    }
+        let hint = text("Priority senders duck the others on the same output while talking.")
+            .size(11)
+            .style(|_| text::Style {
+                color: Some(TEXT_TERTIARY),
+            });
 
-    // =======================================================================
-    // Settings View
-    // =======================================================================
+        let content = column![header, list, hint].spacing(12);
 
-    fn settings_view(&self) -> Element<'_, Message> {
-        let header = self.header_bar("Settings", Some(ActiveView::Main), "Back");
+        scrollable(content.padding(24))
+            .height(Length::Fill)
+            .into()
+    }
 
-        // Server configuration
-        let server_card = container(
-            column![
-                section_title("Server Configuration"),
-                vertical_space().height(16),
-                label("Bind Address"),
-                vertical_space().height(6),
-                text_input("0.0.0.0:9001", &self.bind_addr)
-                    .on_input(Message::BindAddressChanged)
-                    .style(text_input_style)
-                    .padding(12),
-                vertical_space().height(16),
-                checkbox("Use STUN server for NAT traversal", self.use_stun)
-                    .on_toggle(Message::UseStunChanged)
-                    .style(checkbox_style),
-            ]
-            .spacing(4),
-        )
-        .style(card_style)
-        .padding(20)
-        .width(Length::Fill);
+    fn client_row<'a>(&self, client: &'a core::ClientStatus) -> Element<'a, Message> {
+        let id = client.id;
+        let muted = client.muted;
+        let (mute_label, mute_color) = if muted {
+            ("Unmute", ERROR)
+        } else {
+            ("Mute", TEXT_SECONDARY)
+        };
+        let priority = client.priority;
+        let priority_color = if priority { WARNING } else { TEXT_SECONDARY };
+        let name = match &client.device {
+            Some(device) => format!("{device} · {}", client.addr),
+            None => client.addr.clone(),
+        };
+        let codec = client
+            .codec
+            .as_ref()
+            .map(|c| format!("  {c}"))
+            .unwrap_or_default();
+        // Only senders with a control channel can be asked
+        let bitrate_button = client.control.then(|| {
+            let capped = client.bitrate_cap_kbps.is_some();
+            let (label, label_color, cap) = if capped {
+                ("Full Bitrate", WARNING, None)
+            } else {
+                ("Lower Bitrate", TEXT_SECONDARY, Some(LOW_BITRATE_KBPS))
+            };
+            button(text(label).size(12).style(move |_| text::Style {
+                color: Some(label_color),
+            }))
+            .on_press(Message::ClientBitrateCapChanged(id, cap))
+            .style(ghost_button_style)
+            .padding([4, 8])
+        });
 
-        // Audio output
-        let audio_card = container(
+        container(
             column![
                 row![
-                    section_title("Audio Output"),
+                    text(format!("#{id}  {name}"))
+                        .size(13)
+                        .style(|_| text::Style {
+                            color: Some(TEXT_PRIMARY),
+                        }),
+                    text(codec)
+                        .size(11)
+                        .font(iced::Font::MONOSPACE)
+                        .style(|_| text::Style {
+                            color: Some(TEXT_TERTIARY),
+                        }),
                     horizontal_space(),
-                    button(text("Refresh").size(12).style(|_| text::Style {
-                        color: Some(ACCENT),
+                    button(text("Priority").size(12).style(move |_| text::Style {
+                        color: Some(priority_color),
                     }))
-                    .on_press(Message::RefreshDevices)
+                    .on_press(Message::ClientPriorityToggled(id, !priority))
+                    .style(ghost_button_style)
+                    .padding([4, 8]),
+                    button(text(mute_label).size(12).style(move |_| text::Style {
+                        color: Some(mute_color),
+                    }))
+                    .on_press(Message::ClientMuteToggled(id, !muted))
                     .style(ghost_button_style)
                     .padding([4, 8]),
                 ]
+                .push_maybe(bitrate_button)
                 .align_y(Alignment::Center),
-                vertical_space().height(16),
+                level_meter(client.level),
                 pick_list(
-                    self.output_devices.clone(),
-                    self.selected_output.clone(),
-                    Message::OutputDeviceChanged,
+                    self.client_output_options(),
+                    Some(
+                        client
+                            .output
+                            .clone()
+                            .unwrap_or_else(|| MAIN_OUTPUT_LABEL.to_string()),
+                    ),
+                    move |d| Message::ClientOutputChanged(id, d),
                 )
                 .style(pick_list_style)
-                .placeholder("Select audio device…")
+                .text_size(12)
                 .width(Length::Fill),
+                row![
+                    slider(0.0..=2.0, client.gain, move |g| Message::ClientGainChanged(id, g))
+                        .step(0.01)
+                        .style(slider_style),
+                    text(format!("{:>3.0}%", client.gain * 100.0))
+                        .size(12)
+                        .font(iced::Font::MONOSPACE)
+                        .style(|_| text::Style {
+                            color: Some(TEXT_SECONDARY),
+                        }),
+                ]
+                .spacing(12)
+                .align_y(Alignment::Center),
             ]
-            .spacing(4),
-        )
-        .style(card_style)
-        .padding(20)
-        .width(Length::Fill);
-
-        // Tip
-        let tip_card = container(
-            column![
-                text("Tip").size(12).style(|_| text::Style {
-                    color: Some(WARNING),
-                }),
-                vertical_space().height(6),
-                text("Install VB-Cable and select 'CABLE Input' to route audio to other apps like Discord or OBS.")
-                    .size(12)
-                    .style(|_| text::Style {
-                        color: Some(TEXT_SECONDARY),
-                    }),
-            ]
-            .spacing(4),
+            .spacing(8),
         )
         .style(card_style)
-        .padding(20)
-        .width(Length::Fill);
-
-        let content = column![header, server_card, audio_card, tip_card].spacing(12);
+        .padding(16)
+        .width(Length::Fill)
+        .into()
+    }
 
-        scrollable(content.padding(24))
-            .height(Length::Fill)
-            .into()
+    /// Output choices for a client: the main mix plus every other device.
+    fn client_output_options(&self) -> Vec<String> {
+        std::iter::once(MAIN_OUTPUT_LABEL.to_string())
+            .chain(
+                self.output_devices
+                    .iter()
+                    .filter(|d| Some(*d) != self.selected_output.as_ref())
+                    .cloned(),
+            )
+            .collect()
     }
 
     // =======================================================================
-    // Logs View
+    // Stats View
     // =======================================================================
 
-    fn logs_view(&self) -> Element<'_, Message> {
-        let header = self.header_bar("System Logs", Some(ActiveView::Main), "Back");
+    fn stats_view(&self) -> Element<'_, Message> {
+        let header = self.header_bar("Session Stats", Some(ActiveView::Main), "Back");
 
-        let log_text = if self.status.log_lines.is_empty() {
-            "No logs yet…".to_string()
+        let timelines = &self.status.voice_timelines;
+
+        let timeline: Element<'_, Message> = if timelines.is_empty() {
+            text("No session yet — connect a device to record voice activity.")
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(TEXT_TERTIARY),
+                })
+                .into()
         } else {
-            // Show up to last 100 lines
-            let start = self.status.log_lines.len().saturating_sub(100);
-            self.status.log_lines[start..].join("\n")
+            timelines
+                .iter()
+                .fold(column![].spacing(12), |col, t| col.push(voice_timeline(t)))
+                .into()
         };
 
-        let log_container = container(
+        let talk_ms: u64 = timelines
+            .iter()
+            .flat_map(|t| {
+                t.segments
+                    .iter()
+                    .filter(|s| s.talking)
+                    .map(|s| s.end_ms.unwrap_or(t.duration_ms).saturating_sub(s.start_ms))
+            })
+            .sum();
+
+        let timeline_card = self.card("VOICE ACTIVITY", timeline);
+
+        let summary_card = self.card(
+            "TALK TIME",
+            text(core::format_offset(talk_ms))
+                .size(20)
+                .font(iced::Font::MONOSPACE)
+                .style(|_| text::Style {
+                    color: Some(accent()),
+                })
+                .into(),
+        );
+
+        // Newest segments first, per sender
+        let segment_lines = timelines
+            .iter()
+            .flat_map(|t| {
+                t.segments.iter().rev().map(|seg| {
+                    let end = seg
+                        .end_ms
+                        .map(core::format_offset)
+                        .unwrap_or_else(|| "now".into());
+                    let label = if seg.talking { "talking" } else { "silence" };
+                    format!(
+                        "#{}  {} – {}  {label}",
+                        t.client,
+                        core::format_offset(seg.start_ms),
+                        end
+                    )
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let segment_list = container(
             scrollable(
                 container(
-                    text(log_text)
+                    text(segment_lines)
                         .font(iced::Font::MONOSPACE)
                         .size(11)
                         .style(|_| text::Style {
@@ -702,7 +4279,27 @@ impl App {
         .width(Length::Fill)
         .height(Length::Fill);
 
-        column![header, vertical_space().height(12), log_container]
+        let export_row = row![
+            button(text("Export CSV").size(12).style(|_| text::Style {
+                color: Some(accent()),
+            }))
+            .on_press(Message::ExportStats)
+            .style(ghost_button_style)
+            .padding([4, 8]),
+            text(match &self.status.stats_export_path {
+                Some(path) => format!("Saved to {path}"),
+                None => "Per-second packets, bitrate, jitter, drops and buffer depth.".to_string(),
+            })
+            .size(12)
+            .style(|_| text::Style {
+                color: Some(TEXT_SECONDARY),
+            }),
+        ]
+        .spacing(12)
+        .align_y(Alignment::Center);
+
+        column![header, timeline_card, summary_card, export_row, segment_list]
+            .spacing(12)
             .width(Length::Fill)
             .height(Length::Fill)
             .padding(24)
@@ -728,14 +4325,16 @@ impl App {
             Some(target) if nav_label == "Back" => {
                 // Back button on the left
                 row![
-                    button(
-                        text("< Back").size(13).style(|_| text::Style {
+                    self.focusable(
+                        FocusTarget::Back,
+                        button(text("< Back").size(13).style(|_| text::Style {
                             color: Some(TEXT_SECONDARY),
-                        }),
-                    )
-                    .on_press(Message::Navigate(target))
-                    .style(ghost_button_style)
-                    .padding([6, 6]),
+                        }))
+                        .on_press(Message::Navigate(target))
+                        .style(ghost_button_style)
+                        .padding([6, 6]),
+                        6.0,
+                    ),
                     horizontal_space().width(8),
                     title_text,
                 ]
@@ -756,17 +4355,19 @@ impl App {
 
                 // Add QR button if we are on the main screen (indicated by "Settings" label)
                 if nav_label == "Settings" {
-                    row_content = row_content.push(
+                    row_content = row_content.push(self.focusable(
+                        FocusTarget::Qr,
                         button(text("QR").size(14).style(|_| text::Style {
                             color: Some(TEXT_SECONDARY),
                         }))
                         .on_press(Message::OpenQr)
                         .style(ghost_button_style)
                         .padding([6, 10]),
-                    );
+                        6.0,
+                    ));
                 }
 
-                row_content.push(settings_btn)
+                row_content.push(self.focusable(FocusTarget::Settings, settings_btn, 6.0))
                     .align_y(Alignment::Center)
                     .width(Length::Fill)
                     .into()
@@ -798,8 +4399,64 @@ impl App {
         .into()
     }
 
-    /// Footer bar with version and log button.
+    fn footer_link<'a>(
+        &self,
+        target: FocusTarget,
+        label: &'a str,
+        view: ActiveView,
+    ) -> Element<'a, Message> {
+        self.focusable(
+            target,
+            button(text(label).size(11).style(|_| text::Style {
+                color: Some(TEXT_SECONDARY),
+            }))
+            .on_press(Message::Navigate(view))
+            .style(ghost_button_style)
+            .padding([4, 8]),
+            6.0,
+        )
+    }
+
+    /// Draw the keyboard focus ring around a control when it has focus.
+    /// The ring's space is always reserved so focusing doesn't shift layout.
+    fn focusable<'a>(
+        &self,
+        target: FocusTarget,
+        content: impl Into<Element<'a, Message>>,
+        radius: f32,
+    ) -> Element<'a, Message> {
+        let focused = self.focus == Some(target);
+        container(content)
+            .padding(2)
+            .style(move |_| container::Style {
+                border: Border {
+                    color: if focused { FOCUS_RING } else { Color::TRANSPARENT },
+                    width: 2.0,
+                    radius: radius.into(),
+                },
+                ..Default::default()
+            })
+            .into()
+    }
+
+    /// Footer bar with version, record toggle, and navigation buttons.
     fn footer_bar(&self) -> Element<'_, Message> {
+        let recording = self.status.recording_path.is_some();
+        let (rec_label, rec_color) = if recording {
+            let secs = self.status.recording_elapsed_ms / 1000;
+            (format!("● {}:{:02}  Stop Rec", secs / 60, secs % 60), ERROR)
+        } else {
+            ("Rec".to_string(), TEXT_SECONDARY)
+        };
+        let mut rec_btn = button(text(rec_label).size(11).style(move |_| text::Style {
+            color: Some(rec_color),
+        }))
+        .style(ghost_button_style)
+        .padding([4, 8]);
+        if self.status.server_running {
+            rec_btn = rec_btn.on_press(Message::ToggleRecording);
+        }
+
         row![
             text("LAN Mic Receiver v0.1")
                 .size(11)
@@ -807,12 +4464,12 @@ impl App {
                     color: Some(TEXT_TERTIARY),
                 }),
             horizontal_space(),
-            button(text("View Logs").size(11).style(|_| text::Style {
-                color: Some(TEXT_SECONDARY),
-            }))
-            .on_press(Message::Navigate(ActiveView::Logs))
-            .style(ghost_button_style)
-            .padding([4, 8]),
+            self.focusable(FocusTarget::Record, rec_btn, 6.0),
+            self.footer_link(FocusTarget::Clients, "Clients", ActiveView::Clients),
+            self.footer_link(FocusTarget::Stats, "Stats", ActiveView::Stats),
+            self.footer_link(FocusTarget::History, "History", ActiveView::History),
+            self.footer_link(FocusTarget::Captions, "Captions", ActiveView::Captions),
+            self.footer_link(FocusTarget::Logs, "View Logs", ActiveView::Logs),
         ]
         .align_y(Alignment::Center)
         .into()
@@ -823,25 +4480,214 @@ impl App {
 // Helpers
 // ===========================================================================
 
+/// One Logs view line: time, level, module and message.
+fn log_entry_row(entry: &LogEntry) -> Element<'_, Message> {
+    let level_color = match entry.level {
+        LogLevel::Error => ERROR,
+        LogLevel::Warn => WARNING,
+        LogLevel::Info => TEXT_SECONDARY,
+        LogLevel::Debug => TEXT_TERTIARY,
+    };
+    let mono = |content: String, color: Color| {
+        text(content)
+            .font(iced::Font::MONOSPACE)
+            .size(11)
+            .style(move |_| text::Style { color: Some(color) })
+    };
+    row![
+        mono(entry.clock(), TEXT_TERTIARY),
+        mono(format!("{:<8}", entry.target), TEXT_TERTIARY),
+        mono(entry.message.clone(), level_color).width(Length::Fill),
+    ]
+    .spacing(10)
+    .into()
+}
+
+/// Redraw as soon as the core reports a change, instead of polling for it.
+fn core_events(shared: &SharedStatus) -> Subscription<Message> {
+    use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+    let events = shared.subscribe();
+    Subscription::run_with_id(
+        "core-events",
+        iced::futures::stream::unfold(events, |mut events| async move {
+            let mut event = match events.recv().await {
+                Ok(event) => event,
+                // Missed some; a full refresh catches up on all of them
+                Err(RecvError::Lagged(_)) => CoreEvent::StatusChanged,
+                Err(RecvError::Closed) => return None,
+            };
+            // Fold a burst into one update
+            loop {
+                match events.try_recv() {
+                    Ok(CoreEvent::LogLine) => {}
+                    Ok(CoreEvent::StatusChanged) | Err(TryRecvError::Lagged(_)) => {
+                        event = CoreEvent::StatusChanged;
+                    }
+                    Err(_) => break,
+                }
+            }
+            Some((Message::Core(event), events))
+        }),
+    )
+}
+
 fn enumerate_output_devices() -> Vec<String> {
-    match cpal::default_host().output_devices() {
-        Ok(devs) => {
-            let mut devices: Vec<String> = devs.filter_map(|d| d.name().ok()).collect();
-            devices.sort();
-            devices
-        }
-        Err(_) => vec!["(could not enumerate devices)".into()],
+    crate::audio::output_device_names()
+        .unwrap_or_else(|_| vec!["(could not enumerate devices)".into()])
+}
+
+fn palette_input_id() -> text_input::Id {
+    text_input::Id::new("command-palette")
+}
+
+/// "Pixel 8 · battery 42%, charging · muted on the device", from what a
+/// sender reported; `None` if it reported nothing.
+fn sender_summary(client: &core::ClientStatus, with_name: bool) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(device) = client.device.as_ref().filter(|_| with_name) {
+        parts.push(device.clone());
+    }
+    if let Some(battery) = client.battery {
+        let charging = if client.charging { ", charging" } else { "" };
+        parts.push(format!("battery {:.0}%{charging}", battery * 100.0));
     }
+    if client.sender_muted {
+        parts.push("muted on the device".to_string());
+    }
+    (!parts.is_empty()).then(|| parts.join(" · "))
 }
 
 fn truncate_str(s: &str, max: usize) -> String {
-    if s.len() > max {
-        format!("{}…", &s[..max.saturating_sub(1)])
+    if s.chars().count() > max {
+        let kept: String = s.chars().take(max.saturating_sub(1)).collect();
+        format!("{kept}…")
     } else {
         s.to_string()
     }
 }
 
+/// Level meter: an RMS bar on a dBFS scale, coloured by the peak, with the
+/// peak in dB beside it.
+fn level_meter<'a>(level: core::AudioLevel) -> Element<'a, Message> {
+    const STEPS: u16 = 1000;
+    let to_db = |v: f32| 20.0 * v.max(1e-6).log10();
+    let fill = ((to_db(level.rms) - METER_FLOOR_DB) / -METER_FLOOR_DB).clamp(0.0, 1.0);
+    let filled = (fill * f32::from(STEPS)).round() as u16;
+    let peak_db = to_db(level.peak);
+    let color = if level.peak >= 0.99 {
+        ERROR
+    } else if peak_db > -6.0 {
+        WARNING
+    } else {
+        SUCCESS
+    };
+
+    let bar = iced::widget::Row::new()
+        .push_maybe((filled > 0).then(|| {
+            container(horizontal_space())
+                .width(Length::FillPortion(filled))
+                .height(6)
+                .style(move |_| container::Style {
+                    background: Some(color.into()),
+                    border: Border {
+                        radius: 3.0.into(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+        }))
+        .push_maybe(
+            (filled < STEPS).then(|| horizontal_space().width(Length::FillPortion(STEPS - filled))),
+        );
+    let peak_label = if level.peak < 0.001 {
+        "   — dB".to_string()
+    } else {
+        format!("{peak_db:>4.0} dB")
+    };
+
+    row![
+        container(bar)
+            .width(Length::Fill)
+            .height(6)
+            .style(|_| container::Style {
+                background: Some(bg_input().into()),
+                border: Border {
+                    radius: 3.0.into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        text(peak_label)
+            .size(12)
+            .font(iced::Font::MONOSPACE)
+            .style(|_| text::Style {
+                color: Some(TEXT_SECONDARY),
+            }),
+    ]
+    .spacing(12)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// One sender's voice activity as a bar from its connect time: each span
+/// gets a width proportional to its duration, and spans dropped from the
+/// start of a long session leave a blank gap.
+fn voice_timeline(timeline: &core::VoiceTimeline) -> Element<'_, Message> {
+    let total = timeline.duration_ms.max(1);
+    let portion = |ms: u64| (ms * 1000 / total).max(1) as u16;
+    let dropped = timeline.segments.first().map_or(0, |s| s.start_ms);
+    let gap =
+        (dropped > 0).then(|| horizontal_space().width(Length::FillPortion(portion(dropped))));
+    let bars = timeline
+        .segments
+        .iter()
+        .fold(row![].push_maybe(gap), |bar, seg| {
+            let span = seg.end_ms.unwrap_or(total).saturating_sub(seg.start_ms);
+            let color = if seg.talking { SUCCESS } else { bg_input() };
+            bar.push(
+                container(text(""))
+                    .width(Length::FillPortion(portion(span)))
+                    .height(Length::Fixed(18.0))
+                    .style(move |_| container::Style {
+                        background: Some(color.into()),
+                        ..Default::default()
+                    }),
+            )
+        });
+
+    column![
+        text(format!("#{} · {}", timeline.client, timeline.addr))
+            .size(11)
+            .style(|_| text::Style {
+                color: Some(TEXT_SECONDARY),
+            }),
+        container(bars.width(Length::Fill))
+            .style(|_| container::Style {
+                border: Border {
+                    color: BORDER_SUBTLE,
+                    width: 1.0,
+                    radius: 4.0.into(),
+                },
+                ..Default::default()
+            })
+            .padding(1),
+        row![
+            text("0:00.0").size(10).style(|_| text::Style {
+                color: Some(TEXT_TERTIARY),
+            }),
+            horizontal_space(),
+            text(core::format_offset(total))
+                .size(10)
+                .style(|_| text::Style {
+                    color: Some(TEXT_TERTIARY),
+                }),
+        ],
+    ]
+    .spacing(4)
+    .into()
+}
+
 fn section_title(label: &str) -> Element<'_, Message> {
     text(label)
         .size(14)
@@ -866,7 +4712,7 @@ fn label(label: &str) -> Element<'_, Message> {
 
 fn card_style(_: &Theme) -> container::Style {
     container::Style {
-        background: Some(BG_ELEVATED.into()),
+        background: Some(bg_elevated().into()),
         border: Border {
             color: BORDER_SUBTLE,
             width: 1.0,
@@ -878,7 +4724,7 @@ fn card_style(_: &Theme) -> container::Style {
 
 fn ghost_button_style(_: &Theme, status: button::Status) -> button::Style {
     let bg = match status {
-        button::Status::Hovered => BG_HOVER,
+        button::Status::Hovered => bg_hover(),
         _ => Color::TRANSPARENT,
     };
     button::Style {
@@ -895,11 +4741,11 @@ fn ghost_button_style(_: &Theme, status: button::Status) -> button::Style {
 
 fn text_input_style(_: &Theme, status: text_input::Status) -> text_input::Style {
     let (border_color, border_width) = match status {
-        text_input::Status::Focused => (ACCENT, 1.5),
+        text_input::Status::Focused => (accent(), 1.5),
         _ => (BORDER_SUBTLE, 1.0),
     };
     text_input::Style {
-        background: BG_INPUT.into(),
+        background: bg_input().into(),
         border: Border {
             color: border_color,
             width: border_width,
@@ -908,7 +4754,7 @@ fn text_input_style(_: &Theme, status: text_input::Status) -> text_input::Style
         icon: TEXT_SECONDARY,
         placeholder: TEXT_TERTIARY,
         value: TEXT_PRIMARY,
-        selection: ACCENT.scale_alpha(0.3),
+        selection: accent().scale_alpha(0.3),
     }
 }
 
@@ -921,9 +4767,9 @@ fn checkbox_style(_: &Theme, status: checkbox::Status) -> checkbox::Style {
 
     checkbox::Style {
         background: if is_checked {
-            ACCENT.scale_alpha(0.2).into()
+            accent().scale_alpha(0.2).into()
         } else {
-            BG_INPUT.into()
+            bg_input().into()
         },
         icon_color: if is_checked {
             TEXT_PRIMARY
@@ -931,7 +4777,7 @@ fn checkbox_style(_: &Theme, status: checkbox::Status) -> checkbox::Style {
             Color::TRANSPARENT
         },
         border: Border {
-            color: if is_checked { ACCENT } else { BORDER_SUBTLE },
+            color: if is_checked { accent() } else { BORDER_SUBTLE },
             width: 1.5,
             radius: 4.0.into(),
         },
@@ -941,11 +4787,11 @@ fn checkbox_style(_: &Theme, status: checkbox::Status) -> checkbox::Style {
 
 fn pick_list_style(_: &Theme, status: pick_list::Status) -> pick_list::Style {
     let border_color = match status {
-        pick_list::Status::Hovered | pick_list::Status::Opened => ACCENT,
+        pick_list::Status::Hovered | pick_list::Status::Opened => accent(),
         _ => BORDER_SUBTLE,
     };
     pick_list::Style {
-        background: BG_INPUT.into(),
+        background: bg_input().into(),
         border: Border {
             color: border_color,
             width: 1.0,
@@ -956,3 +4802,27 @@ fn pick_list_style(_: &Theme, status: pick_list::Status) -> pick_list::Style {
         text_color: TEXT_PRIMARY,
     }
 }
+
+fn slider_style(_: &Theme, status: slider::Status) -> slider::Style {
+    let handle_color = match status {
+        slider::Status::Hovered | slider::Status::Dragged => TEXT_PRIMARY,
+        _ => accent(),
+    };
+    slider::Style {
+        rail: slider::Rail {
+            backgrounds: (accent().into(), bg_input().into()),
+            width: 4.0,
+            border: Border {
+                color: Color::TRANSPARENT,
+                width: 0.0,
+                radius: 2.0.into(),
+            },
+        },
+        handle: slider::Handle {
+            shape: slider::HandleShape::Circle { radius: 7.0 },
+            background: handle_color.into(),
+            border_width: 0.0,
+            border_color: Color::TRANSPARENT,
+        },
+    }
+}
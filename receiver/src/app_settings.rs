@@ -0,0 +1,32 @@
+use crate::config_store;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// User's window and startup choices, persisted per profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Bring the window back from the tray when a sender connects.
+    pub show_on_connect: bool,
+    /// Start listening at launch with the last session's settings.
+    pub auto_start: bool,
+    /// Keep a "LAN Mic" virtual microphone while the app runs (Linux).
+    pub virtual_mic: bool,
+}
+
+const STORE: &str = "settings.json";
+
+/// Where these were kept alongside the appearance before they had a file
+/// of their own.
+const LEGACY_STORE: &str = "appearance.json";
+
+/// Load the saved settings; a missing or unreadable file means defaults.
+pub fn load() -> AppSettings {
+    config_store::load(STORE)
+        .or_else(|| config_store::load(LEGACY_STORE))
+        .unwrap_or_default()
+}
+
+pub fn save(settings: &AppSettings) -> Result<()> {
+    config_store::save(STORE, settings)
+}
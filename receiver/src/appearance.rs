@@ -0,0 +1,91 @@
+use crate::config_store;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Accent colours offered in Settings, e.g. to match stream branding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AccentColor {
+    #[default]
+    Blue,
+    Teal,
+    Green,
+    Orange,
+    Red,
+    Pink,
+    Purple,
+}
+
+impl AccentColor {
+    pub const ALL: [AccentColor; 7] = [
+        AccentColor::Blue,
+        AccentColor::Teal,
+        AccentColor::Green,
+        AccentColor::Orange,
+        AccentColor::Red,
+        AccentColor::Pink,
+        AccentColor::Purple,
+    ];
+
+    pub fn rgb(self) -> (f32, f32, f32) {
+        match self {
+            AccentColor::Blue => (0.25, 0.56, 0.97),
+            AccentColor::Teal => (0.16, 0.72, 0.76),
+            AccentColor::Green => (0.30, 0.78, 0.36),
+            AccentColor::Orange => (0.98, 0.55, 0.20),
+            AccentColor::Red => (0.93, 0.27, 0.33),
+            AccentColor::Pink => (0.93, 0.36, 0.68),
+            AccentColor::Purple => (0.58, 0.42, 0.96),
+        }
+    }
+}
+
+impl fmt::Display for AccentColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AccentColor::Blue => "Blue",
+            AccentColor::Teal => "Teal",
+            AccentColor::Green => "Green",
+            AccentColor::Orange => "Orange",
+            AccentColor::Red => "Red",
+            AccentColor::Pink => "Pink",
+            AccentColor::Purple => "Purple",
+        })
+    }
+}
+
+/// User's look-and-feel choices, persisted per profile.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    pub accent: AccentColor,
+    /// 0 = the default dark theme, 1 = near-black backgrounds.
+    pub darkness: f32,
+    /// Always use the condensed main view, whatever the window size.
+    pub compact: bool,
+}
+
+impl Appearance {
+    pub const DEFAULT: Appearance = Appearance {
+        accent: AccentColor::Blue,
+        darkness: 0.0,
+        compact: false,
+    };
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+const STORE: &str = "appearance.json";
+
+/// Load the saved appearance; a missing or unreadable file means defaults.
+pub fn load() -> Appearance {
+    config_store::load(STORE).unwrap_or_default()
+}
+
+pub fn save(appearance: &Appearance) -> Result<()> {
+    config_store::save(STORE, appearance)
+}
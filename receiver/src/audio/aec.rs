@@ -0,0 +1,392 @@
+//! Acoustic echo cancellation for senders picking up the monitor speakers.
+//!
+//! The monitor plays the main mix; a phone in the same room hears it and
+//! sends it straight back, which builds into feedback. Both the monitor
+//! reference and the senders' audio run on the main output's clock inside
+//! [`Mixer::mix_into`](super::mixer::Mixer::mix_into), so the echo sits at a
+//! nearly constant delay behind the reference (monitor queue + room + phone
+//! capture + network + jitter buffer). Each sender gets an NLMS adaptive
+//! filter placed at that delay, which is found by correlating the energy
+//! envelopes of the two signals.
+
+/// Adaptive filter length (16 ms) around the estimated bulk delay.
+const TAPS: usize = 768;
+
+/// Taps placed before the estimated delay to absorb estimation error.
+const PRE_TAPS: usize = BLOCK;
+
+/// Envelope block for delay estimation (5 ms).
+const BLOCK: usize = 240;
+
+/// Longest echo path searched (500 ms).
+const MAX_DELAY_BLOCKS: usize = 100;
+
+/// Shortest usable delay: the filter must only read reference samples that
+/// were already played, i.e. from before the current mix chunk.
+const MIN_DELAY: usize = PRE_TAPS + super::mixer::MIX_CHUNK + 1;
+
+/// Reference history: max delay + filter length, rounded up to a power of two.
+const REF_CAPACITY: usize = 32_768;
+
+/// Blocks a filter window can touch: its 768 taps plus partial blocks at
+/// either end.
+const WINDOW_BLOCKS: u64 = (TAPS.div_ceil(BLOCK) + 1) as u64;
+
+/// Envelope history in blocks (2.56 s).
+const ENV_CAPACITY: usize = 512;
+
+/// Blocks of envelope correlated per delay estimate (1.5 s).
+const ESTIMATE_WINDOW: usize = 300;
+
+/// Re-estimate the delay this often, in blocks (1 s).
+const ESTIMATE_EVERY: u64 = 200;
+
+/// Minimum envelope correlation to trust a delay estimate.
+const MIN_CORRELATION: f32 = 0.4;
+
+/// NLMS step size.
+const STEP: f32 = 0.3;
+
+/// Geigel double-talk threshold: near-end louder than this fraction of the
+/// reference peak means the phone user is talking over the echo.
+const DOUBLE_TALK_RATIO: f32 = 0.6;
+
+/// Keep adaptation frozen this long after double talk (30 ms).
+const DOUBLE_TALK_HOLD: usize = 1_440;
+
+const SCALE: f32 = 1.0 / 32768.0;
+
+/// What the monitor speakers played, on the main output's timeline.
+pub struct EchoReference {
+    samples: Vec<f32>,
+    envelope: Vec<f32>,
+    /// Largest magnitude per block, for the double-talk detector.
+    peaks: Vec<f32>,
+    /// Samples ever pushed; the next sample's timeline position.
+    written: u64,
+    block_sum: f32,
+    block_peak: f32,
+}
+
+impl Default for EchoReference {
+    fn default() -> Self {
+        Self {
+            samples: vec![0.0; REF_CAPACITY],
+            envelope: vec![0.0; ENV_CAPACITY],
+            peaks: vec![0.0; ENV_CAPACITY],
+            written: 0,
+            block_sum: 0.0,
+            block_peak: 0.0,
+        }
+    }
+}
+
+impl EchoReference {
+    /// Append a chunk of the main mix as heard on the monitor (`gain` is the
+    /// monitor volume, 0 when muted).
+    pub fn push(&mut self, mix: &[i16], gain: f32) {
+        for &s in mix {
+            let v = s as f32 * SCALE * gain;
+            self.samples[self.written as usize % REF_CAPACITY] = v;
+            self.block_sum += v.abs();
+            self.block_peak = self.block_peak.max(v.abs());
+            self.written += 1;
+            if self.written.is_multiple_of(BLOCK as u64) {
+                let block = (self.written / BLOCK as u64 - 1) as usize % ENV_CAPACITY;
+                self.envelope[block] = self.block_sum / BLOCK as f32;
+                self.peaks[block] = self.block_peak;
+                self.block_sum = 0.0;
+                self.block_peak = 0.0;
+            }
+        }
+    }
+
+    /// Start the timeline over, reusing the buffers.
+    pub fn reset(&mut self) {
+        self.samples.fill(0.0);
+        self.envelope.fill(0.0);
+        self.peaks.fill(0.0);
+        self.written = 0;
+        self.block_sum = 0.0;
+        self.block_peak = 0.0;
+    }
+
+    fn at(&self, pos: u64) -> f32 {
+        self.samples[pos as usize % REF_CAPACITY]
+    }
+
+    /// Largest magnitude in the blocks around the filter window ending at
+    /// `newest`; a slight overestimate, which only makes double talk
+    /// harder to trigger.
+    fn window_peak(&self, newest: u64) -> f32 {
+        let last = newest / BLOCK as u64;
+        let filling = self.written / BLOCK as u64;
+        (last.saturating_sub(WINDOW_BLOCKS - 1)..=last)
+            .map(|b| {
+                if b == filling {
+                    self.block_peak
+                } else {
+                    self.peaks[b as usize % ENV_CAPACITY]
+                }
+            })
+            .fold(0.0, f32::max)
+    }
+}
+
+/// Per-sender echo canceller.
+pub struct EchoCanceller {
+    weights: Vec<f32>,
+    /// Bulk echo delay in samples, once estimated.
+    delay: Option<usize>,
+    envelope: Vec<f32>,
+    block: u64,
+    block_sum: f32,
+    last_estimate: u64,
+    double_talk_hold: usize,
+    /// Reference energy over the filter window, slid one sample at a time,
+    /// and the newest tap it was summed for.
+    energy: f64,
+    energy_at: Option<u64>,
+}
+
+impl Default for EchoCanceller {
+    fn default() -> Self {
+        Self {
+            weights: vec![0.0; TAPS],
+            delay: None,
+            envelope: vec![0.0; ENV_CAPACITY],
+            block: 0,
+            block_sum: 0.0,
+            last_estimate: 0,
+            double_talk_hold: 0,
+            energy: 0.0,
+            energy_at: None,
+        }
+    }
+}
+
+impl EchoCanceller {
+    /// Remove the echo from `near` in place. `near[0]` plays at the
+    /// reference's next position, `reference.written`.
+    pub fn process(&mut self, near: &mut [f32], reference: &EchoReference) {
+        let base = reference.written;
+        // The window's blocks only change every BLOCK samples
+        let mut peak = 0.0;
+        let mut peak_block = None;
+        for (i, sample) in near.iter_mut().enumerate() {
+            let pos = base + i as u64;
+            let d = *sample * SCALE;
+            self.track_envelope(pos, d);
+
+            let Some(delay) = self.delay else {
+                continue;
+            };
+            // Newest tap; always at least one chunk in the past
+            let newest = pos - (delay - PRE_TAPS) as u64;
+            let energy = self.slide_energy(newest, reference);
+            if peak_block != Some(newest / BLOCK as u64) {
+                peak_block = Some(newest / BLOCK as u64);
+                peak = reference.window_peak(newest);
+            }
+            let estimate: f32 = self
+                .weights
+                .iter()
+                .enumerate()
+                .map(|(k, w)| w * reference.at(newest - k as u64))
+                .sum();
+            let error = d - estimate;
+
+            if d.abs() > DOUBLE_TALK_RATIO * peak {
+                self.double_talk_hold = DOUBLE_TALK_HOLD;
+            } else if self.double_talk_hold > 0 {
+                self.double_talk_hold -= 1;
+            } else if energy > 1e-6 {
+                let mu = STEP * error / (energy + 1e-3);
+                for (k, w) in self.weights.iter_mut().enumerate() {
+                    *w += mu * reference.at(newest - k as u64);
+                }
+            }
+
+            *sample = error / SCALE;
+        }
+
+        let current = (base + near.len() as u64) / BLOCK as u64;
+        if current >= self.last_estimate + ESTIMATE_EVERY {
+            self.last_estimate = current;
+            // Re-sum now and then so rounding can't pile up
+            self.energy_at = None;
+            self.estimate_delay(current, reference);
+        }
+    }
+
+    /// Reference energy over the taps ending at `newest`. Consecutive
+    /// samples add the newest tap and drop the oldest; after a gap or a
+    /// delay change the window is summed afresh.
+    fn slide_energy(&mut self, newest: u64, reference: &EchoReference) -> f32 {
+        let oldest = |n: u64| reference.at(n - (TAPS - 1) as u64);
+        if self.energy_at == Some(newest - 1) {
+            let (add, drop) = (reference.at(newest), oldest(newest - 1));
+            self.energy += f64::from(add * add) - f64::from(drop * drop);
+        } else {
+            self.energy = (0..TAPS as u64)
+                .map(|k| f64::from(reference.at(newest - k)).powi(2))
+                .sum();
+        }
+        self.energy_at = Some(newest);
+        // Rounding can leave a tiny negative remainder after silence
+        self.energy.max(0.0) as f32
+    }
+
+    /// Accumulate the near-end envelope on the reference's block grid.
+    fn track_envelope(&mut self, pos: u64, d: f32) {
+        let block = pos / BLOCK as u64;
+        if block != self.block {
+            self.envelope[self.block as usize % ENV_CAPACITY] = self.block_sum / BLOCK as f32;
+            // Blocks skipped while this sender was silent or rebuffering
+            for b in (self.block + 1..block).take(ENV_CAPACITY) {
+                self.envelope[b as usize % ENV_CAPACITY] = 0.0;
+            }
+            self.block = block;
+            self.block_sum = 0.0;
+        }
+        self.block_sum += d.abs();
+    }
+
+    /// Find the lag at which the near-end envelope best follows the
+    /// reference envelope. A changed delay invalidates the filter.
+    fn estimate_delay(&mut self, current: u64, reference: &EchoReference) {
+        // Skip the block still being filled on either side
+        let end = current.saturating_sub(1);
+        let min_lag = MIN_DELAY.div_ceil(BLOCK);
+        if end < (ESTIMATE_WINDOW + MAX_DELAY_BLOCKS) as u64 {
+            return;
+        }
+        // Runs on the audio callback, so no allocation
+        let mut near = [0.0f32; ESTIMATE_WINDOW];
+        for (n, b) in near.iter_mut().zip(end - ESTIMATE_WINDOW as u64..end) {
+            *n = self.envelope[b as usize % ENV_CAPACITY];
+        }
+
+        let mut best = (0.0f32, 0usize);
+        for lag in min_lag..=MAX_DELAY_BLOCKS {
+            let far = (end - ESTIMATE_WINDOW as u64..end)
+                .map(|b| reference.envelope[(b - lag as u64) as usize % ENV_CAPACITY]);
+            let c = correlation(&near, far);
+            if c > best.0 {
+                best = (c, lag);
+            }
+        }
+
+        let (score, lag) = best;
+        if score < MIN_CORRELATION {
+            return;
+        }
+        let delay = (lag * BLOCK).max(MIN_DELAY);
+        let moved = self.delay.is_none_or(|d| d.abs_diff(delay) > BLOCK);
+        if moved {
+            log::info!(
+                "Echo path delay: {} ms (correlation {score:.2})",
+                delay / 48
+            );
+            self.weights.fill(0.0);
+            self.delay = Some(delay);
+            self.energy_at = None;
+        }
+    }
+}
+
+/// Pearson correlation of two equally long series.
+fn correlation(a: &[f32], b: impl Iterator<Item = f32>) -> f32 {
+    let n = a.len() as f32;
+    let (mut sa, mut sb, mut saa, mut sbb, mut sab) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for (&x, y) in a.iter().zip(b) {
+        sa += x;
+        sb += y;
+        saa += x * x;
+        sbb += y * y;
+        sab += x * y;
+    }
+    let cov = sab - sa * sb / n;
+    let var = (saa - sa * sa / n) * (sbb - sb * sb / n);
+    if var <= 1e-12 {
+        return 0.0;
+    }
+    cov / var.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::mixer::MIX_CHUNK;
+
+    /// Noise with a speech-like envelope: 5 ms blocks at random levels.
+    fn reference_signal(len: usize) -> Vec<i16> {
+        let mut seed = 0x2545_f491_u32;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+        };
+        let mut level = 0.0;
+        (0..len)
+            .map(|n| {
+                if n % BLOCK == 0 {
+                    level = next().abs() * 12_000.0;
+                }
+                (next() * level) as i16
+            })
+            .collect()
+    }
+
+    fn energy(samples: &[f32]) -> f64 {
+        samples.iter().map(|&s| f64::from(s) * f64::from(s)).sum()
+    }
+
+    #[test]
+    fn cancels_a_delayed_echo() {
+        // 100 ms echo path at half level, 6 s of audio
+        const ECHO_DELAY: usize = 4_800;
+        let far = reference_signal(48_000 * 6);
+        let echo: Vec<f32> = (0..far.len())
+            .map(|n| {
+                n.checked_sub(ECHO_DELAY)
+                    .map_or(0.0, |m| far[m] as f32 * 0.5)
+            })
+            .collect();
+
+        let mut reference = EchoReference::default();
+        let mut canceller = EchoCanceller::default();
+        let mut residual = Vec::with_capacity(echo.len());
+        for (far, echo) in far.chunks(MIX_CHUNK).zip(echo.chunks(MIX_CHUNK)) {
+            let mut near = echo.to_vec();
+            canceller.process(&mut near, &reference);
+            residual.extend_from_slice(&near);
+            reference.push(far, 1.0);
+        }
+
+        let delay = canceller.delay.expect("delay estimated");
+        assert!(delay.abs_diff(ECHO_DELAY) <= BLOCK, "delay {delay}");
+        let last_second = echo.len() - 48_000..;
+        let erle = energy(&echo[last_second.clone()]) / energy(&residual[last_second]);
+        assert!(
+            erle > 100.0,
+            "echo reduced by only {:.1} dB",
+            10.0 * erle.log10()
+        );
+    }
+
+    #[test]
+    fn sliding_energy_matches_a_full_sum() {
+        let mut reference = EchoReference::default();
+        reference.push(&reference_signal(REF_CAPACITY / 2), 1.0);
+        let mut canceller = EchoCanceller::default();
+        for newest in TAPS as u64..(TAPS + 5_000) as u64 {
+            let slid = canceller.slide_energy(newest, &reference);
+            let summed: f32 = (0..TAPS as u64)
+                .map(|k| reference.at(newest - k).powi(2))
+                .sum();
+            assert!((slid - summed).abs() <= summed * 1e-4 + 1e-6);
+        }
+    }
+}
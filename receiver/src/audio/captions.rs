@@ -0,0 +1,324 @@
+//! Live captions: local speech-to-text of the main mix with Vosk.
+//!
+//! The Vosk library is loaded at run time, so captions cost nothing unless
+//! a model is configured, and builds don't need it installed. Get
+//! `libvosk` from <https://github.com/alphacep/vosk-api/releases> and a
+//! model from <https://alphacephei.com/vosk/models>.
+
+use anyhow::{bail, Context, Result};
+use crossbeam_queue::ArrayQueue;
+use libloading::Library;
+use std::ffi::{c_char, c_float, c_int, c_void, CStr, CString};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Tap buffer capacity (mono i16 @ 48 kHz, ~2 s): the recognizer may lag
+/// behind on slow machines, and dropping audio loses words.
+const TAP_CAPACITY: usize = 96_000;
+
+/// Rate the recognizer runs at; small Vosk models are trained on 16 kHz.
+const RECOGNIZER_RATE: f32 = 16_000.0;
+
+/// Mix samples averaged into one recognizer sample (48 kHz → 16 kHz).
+const DOWNSAMPLE: usize = 3;
+
+/// Recognizer samples fed per call (100 ms).
+const CHUNK: usize = 1_600;
+
+/// Sleep when the tap is empty; the main output refills it every callback.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+#[cfg(target_os = "windows")]
+const LIBRARY_NAME: &str = "libvosk.dll";
+#[cfg(target_os = "macos")]
+const LIBRARY_NAME: &str = "libvosk.dylib";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const LIBRARY_NAME: &str = "libvosk.so";
+
+/// Recognized speech.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptionEvent {
+    /// The utterance so far; replaced by the next event.
+    Partial(String),
+    /// A finished utterance.
+    Final(String),
+}
+
+/// Copy of the main mix for the recognizer.
+///
+/// Like the Snapcast tap, the output callback pushes into a lock-free queue
+/// drained by the recognizer thread, so recognition never blocks audio.
+pub struct CaptionTap {
+    queue: ArrayQueue<i16>,
+}
+
+impl CaptionTap {
+    fn new() -> Self {
+        Self {
+            queue: ArrayQueue::new(TAP_CAPACITY),
+        }
+    }
+
+    /// Copy mixed samples from the main output. Oldest samples are dropped
+    /// while the recognizer is behind.
+    pub fn push(&self, samples: &[i16]) {
+        for &s in samples {
+            self.queue.force_push(s);
+        }
+    }
+}
+
+/// A running recognizer, stopped when dropped.
+pub struct Captioner {
+    model: PathBuf,
+    tap: Arc<CaptionTap>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Captioner {
+    /// Start recognizing with the Vosk model in `model`. `libvosk` is
+    /// looked for next to the executable, then on the system library path.
+    /// The model loads on the recognizer thread (large models take a while);
+    /// `report` receives load failures and state changes for the log.
+    pub fn start(
+        model: PathBuf,
+        on_caption: impl Fn(CaptionEvent) + Send + 'static,
+        report: impl Fn(String) + Send + 'static,
+    ) -> Result<Self> {
+        if !model.is_dir() {
+            bail!("model folder {} not found", model.display());
+        }
+        let vosk = Vosk::load()?;
+        let tap = Arc::new(CaptionTap::new());
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (thread_model, thread_tap, thread_stop) =
+            (model.clone(), Arc::clone(&tap), Arc::clone(&stop));
+        std::thread::Builder::new()
+            .name("captions".into())
+            .spawn(move || {
+                if let Err(e) = recognize(
+                    &vosk,
+                    &thread_model,
+                    &thread_tap,
+                    &thread_stop,
+                    &on_caption,
+                    &report,
+                ) {
+                    report(format!("Captions stopped: {e:#}"));
+                }
+            })?;
+
+        Ok(Self { model, tap, stop })
+    }
+
+    pub fn model(&self) -> &Path {
+        &self.model
+    }
+
+    pub fn tap(&self) -> Arc<CaptionTap> {
+        Arc::clone(&self.tap)
+    }
+}
+
+impl Drop for Captioner {
+    fn drop(&mut self) {
+        // Not joined: the thread finishes the utterance in progress and
+        // exits on its own
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Recognizer thread
+// ---------------------------------------------------------------------------
+
+fn recognize(
+    vosk: &Vosk,
+    model: &Path,
+    tap: &CaptionTap,
+    stop: &AtomicBool,
+    on_caption: &dyn Fn(CaptionEvent),
+    report: &dyn Fn(String),
+) -> Result<()> {
+    let mut recognizer = vosk.recognizer(model)?;
+    report(format!("Captions: model {} loaded", model.display()));
+    // Audio queued while the model loaded is stale
+    while tap.queue.pop().is_some() {}
+
+    let mut chunk = Vec::with_capacity(CHUNK);
+    let mut partial = String::new();
+    while !stop.load(Ordering::Relaxed) {
+        chunk.clear();
+        while chunk.len() < CHUNK && tap.queue.len() >= DOWNSAMPLE {
+            let sum: i32 = (0..DOWNSAMPLE)
+                .filter_map(|_| tap.queue.pop())
+                .map(i32::from)
+                .sum();
+            chunk.push((sum / DOWNSAMPLE as i32) as i16);
+        }
+        if chunk.is_empty() {
+            std::thread::sleep(POLL_INTERVAL);
+            continue;
+        }
+        if recognizer.accept(&chunk)? {
+            partial.clear();
+            if let Some(text) = recognizer.result() {
+                on_caption(CaptionEvent::Final(text));
+            }
+        } else if let Some(text) = recognizer.partial() {
+            if text != partial {
+                partial.clone_from(&text);
+                on_caption(CaptionEvent::Partial(text));
+            }
+        }
+    }
+    if let Some(text) = recognizer.final_result() {
+        on_caption(CaptionEvent::Final(text));
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Vosk C API
+// ---------------------------------------------------------------------------
+
+type ModelNew = unsafe extern "C" fn(*const c_char) -> *mut c_void;
+type ModelFree = unsafe extern "C" fn(*mut c_void);
+type RecognizerNew = unsafe extern "C" fn(*mut c_void, c_float) -> *mut c_void;
+type RecognizerFree = unsafe extern "C" fn(*mut c_void);
+type AcceptWaveform = unsafe extern "C" fn(*mut c_void, *const i16, c_int) -> c_int;
+type RecognizerResult = unsafe extern "C" fn(*mut c_void) -> *const c_char;
+type SetLogLevel = unsafe extern "C" fn(c_int);
+
+/// Entry points of a loaded `libvosk`.
+struct Vosk {
+    model_new: ModelNew,
+    model_free: ModelFree,
+    recognizer_new: RecognizerNew,
+    recognizer_free: RecognizerFree,
+    accept_waveform: AcceptWaveform,
+    result: RecognizerResult,
+    partial_result: RecognizerResult,
+    final_result: RecognizerResult,
+    /// Keeps the function pointers above valid.
+    _library: Library,
+}
+
+impl Vosk {
+    fn load() -> Result<Self> {
+        let beside_exe = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.join(LIBRARY_NAME)));
+        // SAFETY: loading runs libvosk's initializers, which have no
+        // preconditions
+        let library = beside_exe
+            .filter(|path| path.is_file())
+            .map_or_else(
+                || unsafe { Library::new(LIBRARY_NAME) },
+                |path| unsafe { Library::new(path) },
+            )
+            .with_context(|| format!("load {LIBRARY_NAME}"))?;
+
+        // SAFETY: the types match the declarations in vosk_api.h, and the
+        // pointers are only used while `library` is alive
+        unsafe {
+            let set_log_level = *library.get::<SetLogLevel>(b"vosk_set_log_level\0")?;
+            // Kaldi logs every model detail to stderr otherwise
+            set_log_level(-1);
+            Ok(Self {
+                model_new: *library.get(b"vosk_model_new\0")?,
+                model_free: *library.get(b"vosk_model_free\0")?,
+                recognizer_new: *library.get(b"vosk_recognizer_new\0")?,
+                recognizer_free: *library.get(b"vosk_recognizer_free\0")?,
+                accept_waveform: *library.get(b"vosk_recognizer_accept_waveform_s\0")?,
+                result: *library.get(b"vosk_recognizer_result\0")?,
+                partial_result: *library.get(b"vosk_recognizer_partial_result\0")?,
+                final_result: *library.get(b"vosk_recognizer_final_result\0")?,
+                _library: library,
+            })
+        }
+    }
+
+    fn recognizer(&self, model: &Path) -> Result<Recognizer<'_>> {
+        let path = CString::new(model.to_string_lossy().as_bytes())
+            .context("model path contains a NUL byte")?;
+        // SAFETY: `path` is a valid C string for the duration of the call
+        let model_ptr = unsafe { (self.model_new)(path.as_ptr()) };
+        if model_ptr.is_null() {
+            bail!("{} is not a Vosk model", model.display());
+        }
+        // SAFETY: `model_ptr` is a live model; the recognizer keeps its own
+        // reference, so freeing the model right away is allowed
+        let recognizer = unsafe { (self.recognizer_new)(model_ptr, RECOGNIZER_RATE) };
+        unsafe { (self.model_free)(model_ptr) };
+        if recognizer.is_null() {
+            bail!("failed to create a recognizer");
+        }
+        Ok(Recognizer {
+            vosk: self,
+            ptr: recognizer,
+        })
+    }
+}
+
+/// A `VoskRecognizer`, freed on drop.
+struct Recognizer<'a> {
+    vosk: &'a Vosk,
+    ptr: *mut c_void,
+}
+
+impl Recognizer<'_> {
+    /// Feed 16 kHz samples; true once an utterance ended and its
+    /// [`Self::result`] is ready.
+    fn accept(&mut self, samples: &[i16]) -> Result<bool> {
+        // SAFETY: `samples` is valid for `len` reads; `ptr` is live
+        let ended = unsafe {
+            (self.vosk.accept_waveform)(self.ptr, samples.as_ptr(), samples.len() as c_int)
+        };
+        if ended < 0 {
+            bail!("recognizer error");
+        }
+        Ok(ended > 0)
+    }
+
+    fn result(&mut self) -> Option<String> {
+        // SAFETY: `ptr` is live; the returned string is owned by the
+        // recognizer and copied out before the next call
+        let json = unsafe { (self.vosk.result)(self.ptr) };
+        read_text(json, "text")
+    }
+
+    fn partial(&mut self) -> Option<String> {
+        // SAFETY: as in `result`
+        let json = unsafe { (self.vosk.partial_result)(self.ptr) };
+        read_text(json, "partial")
+    }
+
+    fn final_result(&mut self) -> Option<String> {
+        // SAFETY: as in `result`
+        let json = unsafe { (self.vosk.final_result)(self.ptr) };
+        read_text(json, "text")
+    }
+}
+
+impl Drop for Recognizer<'_> {
+    fn drop(&mut self) {
+        // SAFETY: `ptr` came from `vosk_recognizer_new` and is freed once
+        unsafe { (self.vosk.recognizer_free)(self.ptr) };
+    }
+}
+
+/// The non-empty `field` of a Vosk JSON result, e.g. `{"text": "hello"}`.
+fn read_text(json: *const c_char, field: &str) -> Option<String> {
+    if json.is_null() {
+        return None;
+    }
+    // SAFETY: Vosk returns a NUL-terminated string
+    let json = unsafe { CStr::from_ptr(json) };
+    let value: serde_json::Value = serde_json::from_slice(json.to_bytes()).ok()?;
+    let text = value.get(field)?.as_str()?.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
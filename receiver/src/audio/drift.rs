@@ -0,0 +1,101 @@
+use super::resample::Interpolator;
+use super::Frame;
+
+/// Largest playback-rate correction (±0.1 %): about 2 cents of pitch,
+/// inaudible, yet five times the drift of typical consumer clocks.
+const MAX_CORRECTION: f64 = 0.001;
+
+/// Rate correction per second of queue depth off target: 50 ms too deep
+/// plays 0.05 % faster.
+const CORRECTION_PER_SEC: f64 = 0.01;
+
+/// Weight of each new depth reading in the running average. Mixing runs
+/// in 256-sample chunks, so this averages over roughly 5 s, smoothing out
+/// the sawtooth of packets arriving every 20 ms.
+const DEPTH_SMOOTHING: f64 = 0.001;
+
+/// Keeps a queue's depth steady when the clock filling it and the output
+/// device's clock run at slightly different rates: a sender against the
+/// main output, or the main output against the monitor device.
+///
+/// Without it the queue slowly fills (adding latency until samples are
+/// dropped) or drains (underrunning every few minutes). The queue is read
+/// at a rate nudged by how far the averaged depth is from the target,
+/// interpolating between samples, so there are no dropped or repeated
+/// samples to hear.
+#[derive(Debug)]
+pub struct DriftCorrector {
+    /// Averaged queue depth in frames; `None` until the first reading.
+    depth: Option<f64>,
+    /// Input frames consumed per output frame.
+    ratio: f64,
+    interpolator: Interpolator,
+}
+
+impl Default for DriftCorrector {
+    fn default() -> Self {
+        Self {
+            depth: None,
+            ratio: 1.0,
+            interpolator: Interpolator::default(),
+        }
+    }
+}
+
+impl DriftCorrector {
+    /// Update the read rate from the current queue depth, once per mixed
+    /// chunk or device callback.
+    pub fn update(&mut self, buffered: usize, target: usize) {
+        let depth = match self.depth {
+            Some(avg) => avg + (buffered as f64 - avg) * DEPTH_SMOOTHING,
+            None => buffered as f64,
+        };
+        self.depth = Some(depth);
+        let error_secs = (depth - target as f64) / 48_000.0;
+        self.ratio = 1.0 + (error_secs * CORRECTION_PER_SEC).clamp(-MAX_CORRECTION, MAX_CORRECTION);
+    }
+
+    /// The next output frame, popping input frames with `pop` as the read
+    /// position advances. `None` when the input ran dry; reading resumes
+    /// at the same position and rate once it refills.
+    pub fn next(&mut self, pop: impl FnMut() -> Option<Frame>) -> Option<[f32; 2]> {
+        self.interpolator.next(self.ratio, pop)
+    }
+
+    /// Start over after the queue was cleared. The rate is kept: the
+    /// clocks drift apart just as before.
+    pub fn reset(&mut self) {
+        self.depth = None;
+        self.interpolator = Interpolator::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deep_queue_is_read_faster() {
+        let mut drift = DriftCorrector::default();
+        drift.update(4_800, 2_400);
+        assert!(drift.ratio > 1.0 && drift.ratio <= 1.0 + MAX_CORRECTION);
+        drift.reset();
+        drift.update(0, 2_400);
+        assert!(drift.ratio < 1.0);
+    }
+
+    #[test]
+    fn underrun_keeps_the_rate() {
+        let mut drift = DriftCorrector::default();
+        drift.update(4_800, 2_400);
+        let ratio = drift.ratio;
+        let mut queue = vec![[1_000i16; 2]; 8];
+        while drift.next(|| queue.pop()).is_some() {}
+        assert_eq!(drift.ratio, ratio);
+
+        // Refilled, it plays on instead of stalling
+        let mut queue = vec![[1_000i16; 2]; 64];
+        let played = (0..32).filter_map(|_| drift.next(|| queue.pop())).count();
+        assert_eq!(played, 32);
+    }
+}
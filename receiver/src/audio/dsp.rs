@@ -0,0 +1,221 @@
+//! Per-sender signal processing applied before audio is queued for output.
+
+use serde::{Deserialize, Serialize};
+
+/// Mixer sample rate the time constants are converted at.
+const SAMPLE_RATE: f32 = 48_000.0;
+
+/// Frames quieter than this (dBFS RMS) leave the gain alone, so silence
+/// and background hiss aren't pumped up between words.
+const NOISE_GATE_DBFS: f32 = -50.0;
+
+/// Never attenuate by more than this; AGC is for quiet senders, and the
+/// limiter handles the occasional shout.
+const MIN_GAIN: f32 = 0.25;
+
+/// Limiter ceiling (-1 dBFS): boosted peaks are turned down to this
+/// instead of being clipped at full scale.
+const CEILING: f32 = 29_204.0;
+
+/// Limiter gain reduction starts up to this long before a peak (1 ms),
+/// looking ahead within the frame.
+const LIMIT_ATTACK_SAMPLES: f32 = 48.0;
+
+/// Limiter gain recovers this fast after a peak (50 ms per 6 dB).
+const LIMIT_RELEASE_SAMPLES: f32 = 2_400.0;
+
+/// Automatic gain control settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AgcConfig {
+    /// Speech level (dBFS RMS) the gain steers toward.
+    pub target_dbfs: f32,
+    /// How quickly gain comes down when a sender gets louder.
+    pub attack_ms: f32,
+    /// How quickly gain rises when a sender gets quieter.
+    pub release_ms: f32,
+    /// Most boost applied to a quiet sender.
+    pub max_gain_db: f32,
+}
+
+impl Default for AgcConfig {
+    fn default() -> Self {
+        Self {
+            target_dbfs: -20.0,
+            attack_ms: 20.0,
+            release_ms: 500.0,
+            max_gain_db: 24.0,
+        }
+    }
+}
+
+/// Automatic gain control for one sender: brings a quiet phone mic up to
+/// the target level and a hot one down, smoothing gain changes with
+/// separate attack and release times. A look-ahead limiter keeps the
+/// boosted peaks under [`CEILING`].
+#[derive(Debug, Clone)]
+pub struct Agc {
+    config: AgcConfig,
+    gain: f32,
+    /// Limiter gain (at most 1) on top of `gain`, carried across frames.
+    limit: f32,
+    /// Per-sample limiter bounds for the current frame.
+    bounds: Vec<f32>,
+}
+
+impl Agc {
+    pub fn new(config: AgcConfig) -> Self {
+        Self {
+            config,
+            gain: 1.0,
+            limit: 1.0,
+            bounds: Vec::new(),
+        }
+    }
+
+    pub fn set_config(&mut self, config: AgcConfig) {
+        self.config = config;
+    }
+
+    /// Level `samples` (`channels` interleaved) in place. Gain is ramped
+    /// across the frame so changes don't click, and every channel gets the
+    /// same gain so the stereo image stays put.
+    pub fn process(&mut self, samples: &mut [i16], channels: usize) {
+        let channels = channels.max(1);
+        let len = samples.len() / channels;
+        if len == 0 {
+            return;
+        }
+        let sum: f64 = samples.iter().map(|&s| f64::from(s) * f64::from(s)).sum();
+        let rms = (sum / samples.len() as f64).sqrt() as f32 / 32_768.0;
+
+        let from = self.gain;
+        if rms > db_to_gain(NOISE_GATE_DBFS) {
+            let max_gain = db_to_gain(self.config.max_gain_db);
+            let wanted = (db_to_gain(self.config.target_dbfs) / rms).clamp(MIN_GAIN, max_gain);
+            let time_ms = if wanted < self.gain {
+                self.config.attack_ms
+            } else {
+                self.config.release_ms
+            };
+            let frame_ms = len as f32 * 1000.0 / SAMPLE_RATE;
+            let coeff = 1.0 - (-frame_ms / time_ms.max(1.0)).exp();
+            self.gain += (wanted - self.gain) * coeff;
+        }
+
+        let step = (self.gain - from) / len as f32;
+        let ramp = |n: usize| from + step * (n + 1) as f32;
+
+        // Backward pass: the most limiter gain each sample allows, eased
+        // so gain reduction starts ahead of a peak instead of on it
+        let attack = db_to_gain(6.0 / LIMIT_ATTACK_SAMPLES);
+        self.bounds.clear();
+        self.bounds.resize(len, 1.0);
+        let mut bound = 1.0f32;
+        for (n, frame) in samples.chunks_exact(channels).enumerate().rev() {
+            let peak = frame
+                .iter()
+                .map(|&s| f32::from(s).abs())
+                .fold(0.0, f32::max)
+                * ramp(n);
+            let allowed = if peak > CEILING { CEILING / peak } else { 1.0 };
+            bound = (bound * attack).min(allowed);
+            self.bounds[n] = bound;
+        }
+
+        // Forward pass: follow the bounds down at once, recover slowly
+        let release = db_to_gain(6.0 / LIMIT_RELEASE_SAMPLES);
+        for (n, frame) in samples.chunks_exact_mut(channels).enumerate() {
+            self.limit = (self.limit * release).min(self.bounds[n]).min(1.0);
+            let gain = ramp(n) * self.limit;
+            for s in frame {
+                *s = (f32::from(*s) * gain).clamp(-32_768.0, 32_767.0) as i16;
+            }
+        }
+    }
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 20 ms of a 1 kHz tone at `amplitude`.
+    fn frame(amplitude: f32) -> Vec<i16> {
+        (0..960)
+            .map(|n| (amplitude * (n as f32 * std::f32::consts::TAU / 48.0).sin()) as i16)
+            .collect()
+    }
+
+    fn peak(samples: &[i16]) -> f32 {
+        samples
+            .iter()
+            .map(|&s| f32::from(s).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn quiet_sender_is_ramped_up_smoothly() {
+        let mut agc = Agc::new(AgcConfig::default());
+        let (mut last, mut tail) = (0.0, 1_000.0);
+        for _ in 0..100 {
+            let mut samples = frame(1_000.0);
+            agc.process(&mut samples, 1);
+            let level = peak(&samples);
+            assert!(level >= last - 1.0, "gain fell from {last} to {level}");
+            // The gain is ramped, so a frame starts where the last one ended
+            let head = peak(&samples[..48]);
+            assert!((head / tail - 1.0).abs() < 0.01, "{tail} then {head}");
+            tail = peak(&samples[samples.len() - 48..]);
+            last = level;
+        }
+        // -20 dBFS RMS is a 4 634 peak for a sine
+        assert!((4_000.0..5_000.0).contains(&last), "settled at {last}");
+    }
+
+    #[test]
+    fn boosted_peaks_stay_under_the_ceiling() {
+        let mut agc = Agc::new(AgcConfig::default());
+        // Let the gain climb to its maximum on a whisper
+        for _ in 0..500 {
+            agc.process(&mut frame(150.0), 1);
+        }
+        assert!(agc.gain > 10.0);
+
+        // Then shout: the AGC needs a few frames to come down, and the
+        // limiter holds the peaks meanwhile
+        for _ in 0..10 {
+            let mut samples = frame(20_000.0);
+            agc.process(&mut samples, 1);
+            assert!(peak(&samples) <= CEILING, "peak {}", peak(&samples));
+        }
+    }
+
+    #[test]
+    fn limiter_stays_out_of_normal_speech() {
+        let mut agc = Agc::new(AgcConfig::default());
+        for _ in 0..100 {
+            agc.process(&mut frame(4_634.0), 1);
+        }
+        assert_eq!(agc.limit, 1.0);
+    }
+
+    #[test]
+    fn stereo_sides_get_the_same_gain() {
+        let mut agc = Agc::new(AgcConfig::default());
+        for _ in 0..100 {
+            // A loud left side and a quiet right one
+            let mut samples: Vec<i16> = frame(1_000.0)
+                .into_iter()
+                .flat_map(|s| [s, s / 4])
+                .collect();
+            agc.process(&mut samples, 2);
+            for pair in samples.chunks_exact(2) {
+                assert!((i32::from(pair[0]) / 4 - i32::from(pair[1])).abs() <= 8);
+            }
+        }
+        assert!(agc.gain > 2.0);
+    }
+}
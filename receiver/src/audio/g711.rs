@@ -0,0 +1,76 @@
+//! G.711 (PCMU/PCMA) decoding for senders that can't do Opus, e.g. SIP
+//! phones and intercoms.
+
+/// Mixer samples per G.711 sample: 8 kHz up to 48 kHz.
+const UPSAMPLE: usize = 6;
+
+/// Companding law of a G.711 stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Law {
+    /// PCMU, used in North America and Japan.
+    MuLaw,
+    /// PCMA, used everywhere else.
+    ALaw,
+}
+
+/// Expands 8 kHz G.711 packets to 48 kHz linear PCM.
+pub struct Decoder {
+    law: Law,
+    /// Last sample of the previous packet, interpolated from so packet
+    /// boundaries don't click.
+    prev: i16,
+}
+
+impl Decoder {
+    pub fn new(law: Law) -> Self {
+        Self { law, prev: 0 }
+    }
+
+    /// Decode `payload` into `out` at 48 kHz; returns the samples written.
+    /// Bytes that don't fit in `out` are dropped.
+    pub fn decode(&mut self, payload: &[u8], out: &mut [i16]) -> usize {
+        let mut written = 0;
+        for (&byte, chunk) in payload.iter().zip(out.chunks_exact_mut(UPSAMPLE)) {
+            let sample = match self.law {
+                Law::MuLaw => ulaw_to_linear(byte),
+                Law::ALaw => alaw_to_linear(byte),
+            };
+            // Linear interpolation: cheap, and G.711 is band-limited to
+            // 4 kHz telephone audio anyway
+            let (from, to) = (i32::from(self.prev), i32::from(sample));
+            for (i, out) in chunk.iter_mut().enumerate() {
+                *out = (from + (to - from) * (i as i32 + 1) / UPSAMPLE as i32) as i16;
+            }
+            self.prev = sample;
+            written += UPSAMPLE;
+        }
+        written
+    }
+}
+
+fn ulaw_to_linear(byte: u8) -> i16 {
+    let u = !byte;
+    let exponent = (u >> 4) & 0x07;
+    let mantissa = i16::from(u & 0x0f);
+    let magnitude = (((mantissa << 3) + 0x84) << exponent) - 0x84;
+    if u & 0x80 != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+fn alaw_to_linear(byte: u8) -> i16 {
+    let a = byte ^ 0x55;
+    let exponent = (a >> 4) & 0x07;
+    let mantissa = i16::from(a & 0x0f);
+    let magnitude = match exponent {
+        0 => (mantissa << 4) + 0x08,
+        _ => ((mantissa << 4) + 0x108) << (exponent - 1),
+    };
+    if a & 0x80 != 0 {
+        magnitude
+    } else {
+        -magnitude
+    }
+}
@@ -0,0 +1,724 @@
+use super::aec::{EchoCanceller, EchoReference};
+use super::captions::CaptionTap;
+use super::drift::DriftCorrector;
+use super::dsp::{Agc, AgcConfig};
+use super::monitor::MonitorTap;
+use super::snapcast::SnapcastTap;
+use super::{convert, AudioTuning, Frame};
+use crate::recorder::RecordingTap;
+use arc_swap::{ArcSwap, ArcSwapOption};
+use crossbeam_queue::ArrayQueue;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Identifies one connected sender for the lifetime of its WebSocket.
+pub type ClientId = u64;
+
+/// Gain applied to other senders while a priority sender talks (≈ -12 dB).
+const DUCK_GAIN: f32 = 0.25;
+
+/// Per-chunk smoothing toward the duck target. Attack is fast so the
+/// priority voice is clear from its first syllable; release is slow so the
+/// others fade back in rather than jump.
+const DUCK_ATTACK: f32 = 0.5;
+const DUCK_RELEASE: f32 = 0.04;
+
+/// Level (of i16 full scale) above which the mix is bent smoothly toward
+/// full scale instead of hard-clipping.
+const SOFT_CLIP_KNEE: f32 = 0.8 * 32_767.0;
+
+/// Playout buffering per ms of a sender's RTP interarrival jitter. The
+/// RFC 3550 estimate is a mean deviation, so 4× covers nearly every late
+/// packet.
+const JITTER_MULTIPLE: f32 = 4.0;
+
+/// Jitter-driven targets are rounded up to this step (10 ms) so the
+/// target doesn't move with every packet.
+const JITTER_STEP: usize = 480;
+
+/// Samples mixed per inner step of [`Mixer::mix_into`].
+pub(super) const MIX_CHUNK: usize = 256;
+
+/// Widest stereo image [`Mixer::set_stereo_width`] allows (150 %).
+pub const MAX_STEREO_WIDTH: f32 = 1.5;
+
+/// One sender's decoded audio plus its gain/mute/routing controls.
+///
+/// The decode task pushes stereo frames; the output callback pops them. Gain is
+/// stored as `f32` bits and the route behind an `ArcSwapOption` so the
+/// callback can read them without locking.
+pub struct MixerInput {
+    pub id: ClientId,
+    queue: ArrayQueue<Frame>,
+    gain: AtomicU32,
+    muted: AtomicBool,
+    /// Output device this sender is routed to; `None` = main output.
+    route: ArcSwapOption<String>,
+    /// Priority senders duck the other inputs on their route while talking.
+    priority: AtomicBool,
+    /// Set by the decode task from voice-activity detection.
+    talking: AtomicBool,
+    /// Current ducking gain (f32 bits), smoothed toward its target.
+    duck: AtomicU32,
+    /// Samples to buffer before playing; re-armed after every underrun.
+    jitter_target: AtomicUsize,
+    /// Buffering this sender's measured network jitter calls for; raises
+    /// the target above the preset's when larger.
+    jitter_floor: AtomicUsize,
+    primed: AtomicBool,
+    /// Bumped when the queue is cleared, so the output callback restarts
+    /// its drift correction.
+    cleared: AtomicU64,
+    /// Automatic gain control, while enabled; only touched by the decode
+    /// task.
+    agc: Mutex<Option<Agc>>,
+}
+
+impl MixerInput {
+    fn new(
+        id: ClientId,
+        tuning: &AudioTuning,
+        jitter_target: usize,
+        agc: Option<AgcConfig>,
+    ) -> Self {
+        Self {
+            id,
+            queue: ArrayQueue::new(tuning.input_capacity.max(1)),
+            gain: AtomicU32::new(1.0f32.to_bits()),
+            muted: AtomicBool::new(false),
+            route: ArcSwapOption::empty(),
+            priority: AtomicBool::new(false),
+            talking: AtomicBool::new(false),
+            duck: AtomicU32::new(1.0f32.to_bits()),
+            jitter_target: AtomicUsize::new(jitter_target),
+            jitter_floor: AtomicUsize::new(0),
+            primed: AtomicBool::new(false),
+            cleared: AtomicU64::new(0),
+            agc: Mutex::new(agc.map(Agc::new)),
+        }
+    }
+
+    /// Queue one decoded frame. Returns `false` if the buffer was full and
+    /// the oldest frame had to be dropped to bound latency.
+    fn push(&self, frame: Frame) -> bool {
+        self.queue.force_push(frame).is_none()
+    }
+
+    /// Level a decoded frame of `channels` interleaved channels with the
+    /// AGC, if enabled, and queue it. Mono is queued on both sides; only
+    /// the first two channels of anything wider are kept. Returns how many
+    /// old samples were dropped on overflow.
+    pub fn push_frame(&self, samples: &mut [i16], channels: usize) -> u64 {
+        if let Some(agc) = self.agc.lock().as_mut() {
+            agc.process(samples, channels);
+        }
+        let dropped = match channels {
+            0 | 1 => samples.iter().filter(|&&s| !self.push([s, s])).count(),
+            _ => samples
+                .chunks_exact(channels)
+                .filter(|frame| !self.push([frame[0], frame[1]]))
+                .count(),
+        };
+        dropped as u64
+    }
+
+    /// Take the oldest queued frame, as the output callback would.
+    #[cfg(test)]
+    pub fn pop(&self) -> Option<Frame> {
+        self.queue.pop()
+    }
+
+    /// Frames waiting to be played.
+    pub fn buffered(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Samples to buffer: the preset's target, or more on a jittery
+    /// network.
+    pub fn jitter_target(&self) -> usize {
+        self.jitter_target
+            .load(Ordering::Relaxed)
+            .max(self.jitter_floor.load(Ordering::Relaxed))
+    }
+
+    /// Adapt buffering to the sender's RTP interarrival jitter. Capped at
+    /// half the queue, which still leaves room to absorb a burst.
+    pub fn adapt_to_jitter(&self, jitter_ms: f32) {
+        let wanted = (jitter_ms.max(0.0) * JITTER_MULTIPLE * 48.0) as usize;
+        let floor = wanted
+            .next_multiple_of(JITTER_STEP)
+            .min(self.queue.capacity() / 2);
+        let previous = self.jitter_floor.swap(floor, Ordering::Relaxed);
+        if floor != previous {
+            log::debug!(
+                "Client #{} jitter {jitter_ms:.1} ms: buffering at least {} ms",
+                self.id,
+                floor / 48
+            );
+        }
+    }
+
+    pub fn gain(&self) -> f32 {
+        f32::from_bits(self.gain.load(Ordering::Relaxed))
+    }
+
+    pub fn set_gain(&self, gain: f32) {
+        self.gain.store(gain.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted.load(Ordering::Relaxed)
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        self.muted.store(muted, Ordering::Relaxed);
+    }
+
+    pub fn route(&self) -> Option<String> {
+        self.route.load().as_deref().cloned()
+    }
+
+    pub fn set_route(&self, route: Option<String>) {
+        self.route.store(route.map(Arc::new));
+    }
+
+    fn is_on_route(&self, route: Option<&str>) -> bool {
+        self.route.load().as_deref().map(String::as_str) == route
+    }
+
+    pub fn is_priority(&self) -> bool {
+        self.priority.load(Ordering::Relaxed)
+    }
+
+    pub fn set_priority(&self, priority: bool) {
+        self.priority.store(priority, Ordering::Relaxed);
+    }
+
+    pub fn set_talking(&self, talking: bool) {
+        self.talking.store(talking, Ordering::Relaxed);
+    }
+
+    fn is_ducking_others(&self) -> bool {
+        self.is_priority() && !self.is_muted() && self.talking.load(Ordering::Relaxed)
+    }
+
+    fn clear(&self) {
+        while self.queue.pop().is_some() {}
+        self.primed.store(false, Ordering::Relaxed);
+        self.cleared.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Whether enough audio is buffered to play without immediately
+    /// running dry again.
+    fn ready(&self) -> bool {
+        if self.primed.load(Ordering::Relaxed) {
+            return true;
+        }
+        let ready = self.queue.len() >= self.jitter_target();
+        if ready {
+            self.primed.store(true, Ordering::Relaxed);
+        }
+        ready
+    }
+}
+
+/// Per-sender DSP state of one output, owned by that output's callback.
+struct InputState {
+    id: ClientId,
+    /// The input's `cleared` count this state was started at.
+    cleared: u64,
+    drift: DriftCorrector,
+    /// Created on first use while echo cancellation is on.
+    aec: Option<EchoCanceller>,
+}
+
+/// What one output's callback keeps between calls to
+/// [`Mixer::mix_into`]: drift correction and echo cancellation for each
+/// sender it plays. Owning it there keeps locks out of the mix.
+#[derive(Default)]
+pub struct MixState {
+    inputs: Vec<InputState>,
+    /// What the monitor played, for the echo cancellers (main output only).
+    echo_reference: EchoReference,
+    /// The mixer's `echo_epoch` the cancellers were started at.
+    echo_epoch: u64,
+}
+
+impl MixState {
+    /// Drop the state of senders that left and start it for new ones.
+    fn sync(&mut self, inputs: &[Arc<MixerInput>]) {
+        self.inputs
+            .retain(|state| inputs.iter().any(|i| i.id == state.id));
+        for input in inputs {
+            if !self.inputs.iter().any(|state| state.id == input.id) {
+                self.inputs.push(InputState {
+                    id: input.id,
+                    cleared: input.cleared.load(Ordering::Relaxed),
+                    drift: DriftCorrector::default(),
+                    aec: None,
+                });
+            }
+        }
+    }
+}
+
+/// Sums the audio of every connected sender into a single stereo stream.
+/// Mono senders sit in the middle; stereo ones keep their image, scaled by
+/// the stereo width.
+///
+/// Each client gets its own input queue so a slow or bursty sender cannot
+/// starve the others; gain, mute and priority ducking are applied here,
+/// before output. The input list and taps are swapped atomically, so the
+/// output callbacks read them without locking.
+#[derive(Default)]
+pub struct Mixer {
+    tuning: AudioTuning,
+    inputs: ArcSwap<Vec<Arc<MixerInput>>>,
+    /// Receives a copy of the main-output mix when a monitor is active.
+    monitor: ArcSwapOption<MonitorTap>,
+    /// Receives the main-output mix and event markers while recording.
+    recorder: ArcSwapOption<RecordingTap>,
+    /// Receives the main-output mix while feeding a Snapcast server.
+    snapcast: ArcSwapOption<SnapcastTap>,
+    /// Receives the main-output mix while live captions are on.
+    captions: ArcSwapOption<CaptionTap>,
+    /// Cancel the monitor speakers' echo from senders on the main output.
+    echo_cancellation: AtomicBool,
+    /// Bumped on every echo cancellation toggle; the main output restarts
+    /// its cancellers when it changes.
+    echo_epoch: AtomicU64,
+    /// Automatic gain control for new inputs; `None` = off.
+    agc: Mutex<Option<AgcConfig>>,
+    /// Current jitter target for new and existing inputs; starts at
+    /// `tuning.jitter_target_samples` and may be retuned (Auto latency).
+    jitter_target: AtomicUsize,
+    /// Times any input ran dry while playing, since the mixer was created.
+    underruns: AtomicU64,
+    /// Main-output peak (absolute sample) since the last [`Mixer::take_peak`].
+    peak: AtomicU32,
+    /// Master volume (f32 bits) applied to every route after mixing.
+    output_gain: AtomicU32,
+    output_muted: AtomicBool,
+    /// Stereo width (f32 bits): 0 folds every sender to mono, 1 plays them
+    /// as sent, above 1 widens.
+    stereo_width: AtomicU32,
+}
+
+impl Mixer {
+    pub fn new(tuning: AudioTuning) -> Self {
+        Self {
+            tuning,
+            jitter_target: AtomicUsize::new(tuning.jitter_target_samples),
+            output_gain: AtomicU32::new(1.0f32.to_bits()),
+            stereo_width: AtomicU32::new(1.0f32.to_bits()),
+            ..Default::default()
+        }
+    }
+
+    pub fn tuning(&self) -> AudioTuning {
+        self.tuning
+    }
+
+    /// Register a new sender and return its input handle.
+    pub fn add_input(&self, id: ClientId) -> Arc<MixerInput> {
+        let input = Arc::new(MixerInput::new(
+            id,
+            &self.tuning,
+            self.jitter_target.load(Ordering::Relaxed),
+            *self.agc.lock(),
+        ));
+        self.inputs.rcu(|inputs| {
+            let mut inputs = Vec::clone(inputs);
+            inputs.push(Arc::clone(&input));
+            inputs
+        });
+        input
+    }
+
+    pub fn remove_input(&self, id: ClientId) {
+        self.inputs.rcu(|inputs| {
+            let mut inputs = Vec::clone(inputs);
+            inputs.retain(|i| i.id != id);
+            inputs
+        });
+    }
+
+    pub fn input(&self, id: ClientId) -> Option<Arc<MixerInput>> {
+        self.inputs.load().iter().find(|i| i.id == id).cloned()
+    }
+
+    /// Master volume; above 1.0 boosts, with soft clipping on peaks.
+    pub fn set_output_gain(&self, gain: f32) {
+        self.output_gain
+            .store(gain.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn set_output_muted(&self, muted: bool) {
+        self.output_muted.store(muted, Ordering::Relaxed);
+    }
+
+    /// Scale the side (left minus right) of stereo senders: 0 = mono,
+    /// 1 = as sent, up to [`MAX_STEREO_WIDTH`].
+    pub fn set_stereo_width(&self, width: f32) {
+        let width = width.clamp(0.0, MAX_STEREO_WIDTH);
+        self.stereo_width.store(width.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn set_monitor(&self, tap: Option<Arc<MonitorTap>>) {
+        self.monitor.store(tap);
+    }
+
+    /// Change how much every input buffers before playing.
+    pub fn set_jitter_target(&self, samples: usize) {
+        self.jitter_target.store(samples, Ordering::Relaxed);
+        for input in self.inputs.load().iter() {
+            input.jitter_target.store(samples, Ordering::Relaxed);
+        }
+    }
+
+    /// Deepest input queue, in frames.
+    pub fn max_buffered(&self) -> usize {
+        self.inputs
+            .load()
+            .iter()
+            .map(|i| i.buffered())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Main-output peak level (0–1) since the previous call.
+    pub fn take_peak(&self) -> f32 {
+        self.peak.swap(0, Ordering::Relaxed) as f32 / 32768.0
+    }
+
+    pub fn underruns(&self) -> u64 {
+        self.underruns.load(Ordering::Relaxed)
+    }
+
+    /// Enable or disable echo cancellation. Every toggle starts the
+    /// cancellers over, since their reference timeline is reset.
+    pub fn set_echo_cancellation(&self, enabled: bool) {
+        self.echo_epoch.fetch_add(1, Ordering::Relaxed);
+        self.echo_cancellation.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Enable, retune or disable automatic gain control on every input.
+    /// Retuning keeps each input's current gain so levels don't jump.
+    pub fn set_agc(&self, config: Option<AgcConfig>) {
+        *self.agc.lock() = config;
+        for input in self.inputs.load().iter() {
+            let mut agc = input.agc.lock();
+            match (agc.as_mut(), config) {
+                (Some(agc), Some(config)) => agc.set_config(config),
+                (_, config) => *agc = config.map(Agc::new),
+            }
+        }
+    }
+
+    pub fn set_recorder(&self, tap: Option<Arc<RecordingTap>>) {
+        self.recorder.store(tap);
+    }
+
+    pub fn set_snapcast(&self, tap: Option<Arc<SnapcastTap>>) {
+        self.snapcast.store(tap);
+    }
+
+    pub fn set_captions(&self, tap: Option<Arc<CaptionTap>>) {
+        self.captions.store(tap);
+    }
+
+    /// Drop a cue marker into the active recording, if any.
+    pub fn mark(&self, label: impl Into<String>) {
+        if let Some(tap) = self.recorder.load().as_ref() {
+            tap.mark(label);
+        }
+    }
+
+    /// Drop buffered samples of every input on `route` (e.g. while
+    /// switching that output's device).
+    pub fn clear(&self, route: Option<&str>) {
+        for input in self.inputs.load().iter() {
+            if input.is_on_route(route) {
+                input.clear();
+            }
+        }
+    }
+
+    /// Distinct device routes currently requested by any input.
+    pub fn routes(&self) -> Vec<String> {
+        let mut routes: Vec<String> = self
+            .inputs
+            .load()
+            .iter()
+            .filter_map(|i| i.route())
+            .collect();
+        routes.sort();
+        routes.dedup();
+        routes
+    }
+
+    /// Fill `out` with the next mixed frames of the inputs routed to
+    /// `route` (`None` = main output). Missing frames are treated as silence.
+    /// `state` belongs to the calling output and carries its DSP state from
+    /// one call to the next.
+    pub fn mix_into(&self, route: Option<&str>, out: &mut [Frame], state: &mut MixState) {
+        let inputs = self.inputs.load();
+        state.sync(&inputs);
+        let mut acc = [[0f32; 2]; MIX_CHUNK];
+        // Each sender's chunk as mid (L+R)/2 and side (L-R)/2: echo
+        // cancellation works on the mid, and the width scales the side
+        let mut mid = [0f32; MIX_CHUNK];
+        let mut side = [0f32; MIX_CHUNK];
+        // The mixed chunk folded to mono, for the echo reference and taps
+        let mut mono = [0i16; MIX_CHUNK];
+
+        // The monitor output is the echo reference, so cancellation only
+        // applies to the main route while a monitor is playing.
+        let monitor = if route.is_none() {
+            self.monitor.load_full()
+        } else {
+            None
+        };
+        let (recorder, snapcast, captions) = if route.is_none() {
+            (
+                self.recorder.load_full(),
+                self.snapcast.load_full(),
+                self.captions.load_full(),
+            )
+        } else {
+            (None, None, None)
+        };
+        let epoch = self.echo_epoch.load(Ordering::Relaxed);
+        if state.echo_epoch != epoch {
+            // Toggled: the reference timeline starts over
+            state.echo_epoch = epoch;
+            state.echo_reference.reset();
+            for input in &mut state.inputs {
+                input.aec = None;
+            }
+        }
+        let mut echo = monitor
+            .as_ref()
+            .filter(|_| self.echo_cancellation.load(Ordering::Relaxed))
+            .map(|tap| (tap, &mut state.echo_reference));
+
+        let on_route = |i: &&Arc<MixerInput>| i.is_on_route(route);
+        let ducking = inputs
+            .iter()
+            .filter(on_route)
+            .any(|i| i.is_ducking_others());
+        let master = if self.output_muted.load(Ordering::Relaxed) {
+            0.0
+        } else {
+            f32::from_bits(self.output_gain.load(Ordering::Relaxed))
+        };
+        let width = f32::from_bits(self.stereo_width.load(Ordering::Relaxed));
+
+        for chunk in out.chunks_mut(acc.len()) {
+            let acc = &mut acc[..chunk.len()];
+            acc.fill([0.0; 2]);
+
+            for input in inputs.iter().filter(on_route) {
+                // Ramp the duck gain across the chunk to avoid zipper noise.
+                let duck_from = f32::from_bits(input.duck.load(Ordering::Relaxed));
+                let duck_to = if ducking && !input.is_priority() {
+                    duck_from + (DUCK_GAIN - duck_from) * DUCK_ATTACK
+                } else {
+                    duck_from + (1.0 - duck_from) * DUCK_RELEASE
+                };
+                input.duck.store(duck_to.to_bits(), Ordering::Relaxed);
+
+                // Always drain muted inputs so they don't build up latency.
+                let gain = if input.is_muted() { 0.0 } else { input.gain() };
+                let step = (duck_to - duck_from) / acc.len() as f32;
+                if !input.ready() {
+                    continue;
+                }
+                let Some(dsp) = state.inputs.iter_mut().find(|s| s.id == input.id) else {
+                    continue;
+                };
+                let cleared = input.cleared.load(Ordering::Relaxed);
+                if dsp.cleared != cleared {
+                    dsp.cleared = cleared;
+                    dsp.drift.reset();
+                }
+                // Read slightly faster or slower to hold the queue at its
+                // target while the sender's clock drifts from the device's
+                let drift = &mut dsp.drift;
+                drift.update(input.buffered(), input.jitter_target());
+                let mut popped = 0;
+                for (m, s) in mid[..acc.len()].iter_mut().zip(&mut side[..acc.len()]) {
+                    match drift.next(|| input.queue.pop()) {
+                        Some([l, r]) => {
+                            *m = (l + r) * 0.5;
+                            *s = (l - r) * 0.5;
+                        }
+                        None => {
+                            // Underrun: rebuffer up to the jitter target.
+                            input.primed.store(false, Ordering::Relaxed);
+                            self.underruns.fetch_add(1, Ordering::Relaxed);
+                            break;
+                        }
+                    }
+                    popped += 1;
+                }
+                let mid = &mut mid[..popped];
+                if let Some((_, reference)) = &echo {
+                    dsp.aec
+                        .get_or_insert_with(EchoCanceller::default)
+                        .process(mid, reference);
+                }
+                for (n, (a, (m, s))) in acc.iter_mut().zip(mid.iter().zip(&side)).enumerate() {
+                    let gain = gain * (duck_from + step * n as f32);
+                    let s = s * width;
+                    a[0] += (m + s) * gain;
+                    a[1] += (m - s) * gain;
+                }
+            }
+
+            let mono = &mut mono[..chunk.len()];
+            for ((o, a), m) in chunk.iter_mut().zip(acc.iter()).zip(mono.iter_mut()) {
+                *o = a.map(|a| soft_clip(a * master));
+                *m = convert::mid(*o);
+            }
+            if let Some((tap, reference)) = &mut echo {
+                let gain = if tap.is_muted() { 0.0 } else { tap.gain() };
+                reference.push(mono, gain);
+            }
+            if let Some(tap) = recorder.as_ref() {
+                tap.push(mono);
+            }
+            if let Some(tap) = snapcast.as_ref() {
+                tap.push(mono);
+            }
+            if let Some(tap) = captions.as_ref() {
+                tap.push(mono);
+            }
+        }
+
+        if route.is_none() {
+            let peak = out
+                .iter()
+                .flatten()
+                .map(|s| s.unsigned_abs())
+                .max()
+                .unwrap_or(0);
+            self.peak.fetch_max(peak as u32, Ordering::Relaxed);
+            if let Some(tap) = monitor.as_ref() {
+                tap.push(out);
+            }
+        }
+    }
+}
+
+/// Convert to i16, compressing peaks above [`SOFT_CLIP_KNEE`] with a tanh
+/// curve so boosted or summed audio saturates gently rather than clipping.
+fn soft_clip(x: f32) -> i16 {
+    let headroom = i16::MAX as f32 - SOFT_CLIP_KNEE;
+    let magnitude = x.abs();
+    let y = if magnitude <= SOFT_CLIP_KNEE {
+        magnitude
+    } else {
+        SOFT_CLIP_KNEE + headroom * ((magnitude - SOFT_CLIP_KNEE) / headroom).tanh()
+    };
+    y.copysign(x) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jitter_raises_the_target_above_the_preset() {
+        let tuning = AudioTuning {
+            input_capacity: 48_000,
+            jitter_target_samples: 1_920,
+            ..Default::default()
+        };
+        let mixer = Mixer::new(tuning);
+        let input = mixer.add_input(1);
+
+        // A steady network leaves the preset alone
+        input.adapt_to_jitter(2.0);
+        assert_eq!(input.jitter_target(), 1_920);
+
+        // 25 ms of jitter buffers 100 ms
+        input.adapt_to_jitter(25.0);
+        assert_eq!(input.jitter_target(), 4_800);
+
+        // Never more than half the queue
+        input.adapt_to_jitter(1_000.0);
+        assert_eq!(input.jitter_target(), 24_000);
+
+        // And back down once the network calms
+        input.adapt_to_jitter(0.0);
+        assert_eq!(input.jitter_target(), 1_920);
+    }
+
+    #[test]
+    fn each_output_keeps_state_for_its_current_senders() {
+        let mixer = Mixer::new(AudioTuning::default());
+        let (a, b) = (mixer.add_input(1), mixer.add_input(2));
+        b.set_route(Some("Headset".into()));
+        for _ in 0..1_024 {
+            a.push([1_000; 2]);
+            b.push([2_000; 2]);
+        }
+
+        let mut state = MixState::default();
+        let mut out = [[0i16; 2]; 512];
+        mixer.mix_into(None, &mut out, &mut state);
+        // Sender 1 alone, after the interpolator's short lead-in
+        assert_eq!(out[out.len() - 1], [1_000; 2]);
+        assert_eq!(b.buffered(), 1_024);
+
+        mixer.remove_input(1);
+        mixer.mix_into(None, &mut out, &mut state);
+        assert!(out.iter().all(|&s| s == [0; 2]));
+        assert_eq!(state.inputs.iter().map(|s| s.id).collect::<Vec<_>>(), [2]);
+    }
+
+    #[test]
+    fn senders_on_a_route_are_summed() {
+        let mixer = Mixer::new(AudioTuning::default());
+        let (a, b) = (mixer.add_input(1), mixer.add_input(2));
+        b.set_gain(0.5);
+        for _ in 0..64 {
+            a.push([1_000; 2]);
+            b.push([2_000; 2]);
+        }
+
+        let mut state = MixState::default();
+        let mut out = [[0i16; 2]; 32];
+        mixer.mix_into(None, &mut out, &mut state);
+        assert_eq!(out[out.len() - 1], [2_000; 2]);
+
+        // A muted sender is still drained, so it doesn't build up latency
+        b.set_muted(true);
+        mixer.mix_into(None, &mut out, &mut state);
+        assert_eq!(out[out.len() - 1], [1_000; 2]);
+        assert_eq!(a.buffered(), b.buffered());
+    }
+
+    #[test]
+    fn width_scales_the_stereo_image() {
+        let mixer = Mixer::new(AudioTuning::default());
+        let input = mixer.add_input(1);
+        let mut state = MixState::default();
+        let mut out = [[0i16; 2]; 16];
+        for (width, expected) in [
+            (1.0, [3_000, 1_000]),
+            (0.0, [2_000; 2]),
+            (1.5, [3_500, 500]),
+        ] {
+            mixer.set_stereo_width(width);
+            for _ in 0..out.len() {
+                input.push([3_000, 1_000]);
+            }
+            mixer.mix_into(None, &mut out, &mut state);
+            // Skip the interpolator's lead-in on the first pass
+            assert_eq!(out[out.len() - 1], expected, "width {width}");
+        }
+    }
+}
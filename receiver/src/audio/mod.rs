@@ -1,15 +1,126 @@
+pub mod aec;
+pub mod captions;
+pub mod convert;
+pub mod drift;
+pub mod dsp;
+pub mod g711;
+pub mod mixer;
+pub mod monitor;
+pub mod resample;
+pub mod snapcast;
+pub mod stretch;
+pub mod vad;
+
+pub use convert::Frame;
+
 use anyhow::{anyhow, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use crossbeam_queue::ArrayQueue;
+use mixer::{MixState, Mixer};
+use monitor::MonitorTap;
+use resample::{Resampler, SOURCE_RATE};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A running stream whose callback hasn't fired for this long is treated as
+/// dead (device unplugged without an error being reported).
+const STALL_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Buffering knobs shared by the mixer inputs and the output streams.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioTuning {
+    /// Samples each input buffers before it starts (or resumes) playing.
+    pub jitter_target_samples: usize,
+    /// Maximum samples an input may hold; older audio is dropped past this.
+    pub input_capacity: usize,
+    /// Requested device buffer size in frames (`None` = device default).
+    pub output_buffer_frames: Option<u32>,
+}
+
+impl Default for AudioTuning {
+    fn default() -> Self {
+        Self {
+            jitter_target_samples: 0,
+            input_capacity: 48_000,
+            output_buffer_frames: None,
+        }
+    }
+}
 
-/// Plays mono i16 @ 48 kHz into a selected output device.
+/// Plays stereo i16 @ 48 kHz into a selected output device.
 ///
 /// Use a virtual cable (e.g. VB-Cable) and select "CABLE Input" if you want
 /// other apps to see it as a microphone.
 pub struct AudioOutput {
     _stream: Option<cpal::Stream>,
     device_name: String,
+    health: Arc<StreamHealth>,
+}
+
+/// Liveness of an output stream, updated from its callbacks.
+struct StreamHealth {
+    started: Instant,
+    errored: AtomicBool,
+    /// Milliseconds after `started` of the most recent callback.
+    last_callback_ms: AtomicU64,
+    paused: AtomicBool,
+}
+
+impl StreamHealth {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            errored: AtomicBool::new(false),
+            last_callback_ms: AtomicU64::new(0),
+            paused: AtomicBool::new(false),
+        }
+    }
+
+    fn touch(&self) {
+        let now = self.started.elapsed().as_millis() as u64;
+        self.last_callback_ms.store(now, Ordering::Relaxed);
+    }
+
+    fn is_failed(&self) -> bool {
+        if self.errored.load(Ordering::Relaxed) {
+            return true;
+        }
+        let now = self.started.elapsed().as_millis() as u64;
+        let silent_for = now.saturating_sub(self.last_callback_ms.load(Ordering::Relaxed));
+        !self.paused.load(Ordering::Relaxed) && silent_for > STALL_TIMEOUT.as_millis() as u64
+    }
+}
+
+/// Extra output streams for senders routed away from the main output,
+/// keyed by device name. Each stream plays only the inputs on its route.
+#[derive(Default)]
+pub struct RoutedOutputs {
+    streams: HashMap<String, AudioOutput>,
+}
+
+impl RoutedOutputs {
+    /// Open a stream for `device_name` unless one is already running.
+    pub fn ensure(&mut self, device_name: &str, mixer: &Arc<Mixer>) -> Result<()> {
+        if !self.streams.contains_key(device_name) {
+            let output = AudioOutput::start_route(
+                Some(device_name),
+                Arc::clone(mixer),
+                Some(device_name.to_string()),
+            )?;
+            self.streams.insert(device_name.to_string(), output);
+        }
+        Ok(())
+    }
+
+    /// Close streams whose device no longer has any routed input.
+    pub fn retain(&mut self, routes: &[String]) {
+        self.streams.retain(|name, _| routes.contains(name));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AudioOutput> {
+        self.streams.values()
+    }
 }
 
 impl AudioOutput {
@@ -23,12 +134,93 @@ impl AudioOutput {
         Self {
             _stream: None,
             device_name: "(stopped)".to_string(),
+            health: Arc::new(StreamHealth::new()),
+        }
+    }
+
+    /// Whether this is the [`AudioOutput::stopped`] placeholder.
+    pub fn is_stopped(&self) -> bool {
+        self._stream.is_none()
+    }
+
+    /// Whether the stream reported an error or its callback stopped firing
+    /// while playing — the cue to fail over to a standby device.
+    pub fn is_failed(&self) -> bool {
+        self._stream.is_some() && self.health.is_failed()
+    }
+
+    /// Suspend or resume the device stream. A paused stream stops invoking
+    /// its callback, so an idle receiver costs no audio CPU.
+    pub fn set_paused(&self, paused: bool) -> Result<()> {
+        if let Some(stream) = &self._stream {
+            if paused {
+                stream.pause()?;
+            } else {
+                // Don't count the paused time as a stall
+                self.health.touch();
+                stream.play()?;
+            }
+            self.health.paused.store(paused, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    /// Open the specified (or default) output device and start playing the
+    /// mixed output of `mixer`. Frames are stereo i16 @ 48 kHz. On the
+    /// default device the stream plays into the Linux virtual microphone
+    /// while that is on.
+    pub fn start(output_device_name: Option<&str>, mixer: Arc<Mixer>) -> Result<Self> {
+        let default = cpal::default_host()
+            .default_output_device()
+            .and_then(|d| d.name().ok());
+        if output_device_name.is_none_or(|name| Some(name) == default.as_deref()) {
+            crate::platform::linux_virtualmic::route_new_streams(|| {
+                Self::start_route(output_device_name, mixer, None)
+            })
+        } else {
+            Self::start_route(output_device_name, mixer, None)
         }
     }
 
-    /// Open the specified (or default) output device and start playing samples
-    /// from `queue`. Samples are mono i16 @ 48 kHz.
-    pub fn start(output_device_name: Option<&str>, queue: Arc<ArrayQueue<i16>>) -> Result<Self> {
+    /// Like [`AudioOutput::start`], but plays only the mixer inputs routed to
+    /// `route` (`None` = main output).
+    pub fn start_route(
+        output_device_name: Option<&str>,
+        mixer: Arc<Mixer>,
+        route: Option<String>,
+    ) -> Result<Self> {
+        let buffer_frames = mixer.tuning().output_buffer_frames;
+        let mut state = MixState::default();
+        Self::start_source(
+            output_device_name,
+            buffer_frames,
+            move |out: &mut [Frame]| mixer.mix_into(route.as_deref(), out, &mut state),
+        )
+    }
+
+    /// Play the local monitor copy of the main mix on a separate device.
+    pub fn start_monitor(
+        output_device_name: Option<&str>,
+        tap: Arc<MonitorTap>,
+        buffer_frames: Option<u32>,
+    ) -> Result<Self> {
+        Self::start_source(
+            output_device_name,
+            buffer_frames,
+            move |out: &mut [Frame]| tap.fill(out),
+        )
+    }
+
+    /// Open the device and play whatever `fill` writes into each callback's
+    /// buffer, resampled if the device doesn't run at 48 kHz.
+    fn start_source<F>(
+        output_device_name: Option<&str>,
+        buffer_frames: Option<u32>,
+        mut fill: F,
+    ) -> Result<Self>
+    where
+        F: FnMut(&mut [Frame]) + Send + 'static,
+    {
         let host = cpal::default_host();
 
         let device = match output_device_name {
@@ -46,27 +238,63 @@ impl AudioOutput {
         // Pick the best config that supports 48 kHz.
         let supported = pick_output_config(&device)?;
         let sample_format = supported.sample_format();
-        let config: cpal::StreamConfig = supported.into();
+        let buffer_size = pick_buffer_size(supported.buffer_size(), buffer_frames);
+        let mut config: cpal::StreamConfig = supported.into();
+        config.buffer_size = buffer_size;
         let channels = config.channels as usize;
 
-        let err_fn = |err| log::error!("cpal stream error: {err}");
+        // Devices that can't open at 48 kHz get the stream converted to
+        // their rate instead of playing it at the wrong pitch
+        let device_rate = config.sample_rate.0;
+        let mut resampler = (device_rate != SOURCE_RATE).then(|| {
+            log::info!(
+                "'{device_name}' runs at {device_rate} Hz; resampling from {SOURCE_RATE} Hz"
+            );
+            Resampler::new(device_rate)
+        });
+        let mut fill = move |out: &mut [Frame]| match &mut resampler {
+            Some(resampler) => resampler.process(out, &mut fill),
+            None => fill(out),
+        };
+
+        let health = Arc::new(StreamHealth::new());
+        let err_health = Arc::clone(&health);
+        let err_fn = move |err| {
+            log::error!("cpal stream error: {err}");
+            err_health.errored.store(true, Ordering::Relaxed);
+        };
+        // Scratch buffer for one callback's worth of frames
+        let mut frames = Vec::new();
 
+        let cb_health = Arc::clone(&health);
         let stream = match sample_format {
             cpal::SampleFormat::F32 => device.build_output_stream(
                 &config,
-                move |data: &mut [f32], _| write_data_f32(data, channels, &queue),
+                move |data: &mut [f32], _| {
+                    cb_health.touch();
+                    let frames = fill_frames(&mut fill, data.len() / channels, &mut frames);
+                    convert::write_f32(data, channels, frames)
+                },
                 err_fn,
                 None,
             )?,
             cpal::SampleFormat::I16 => device.build_output_stream(
                 &config,
-                move |data: &mut [i16], _| write_data_i16(data, channels, &queue),
+                move |data: &mut [i16], _| {
+                    cb_health.touch();
+                    let frames = fill_frames(&mut fill, data.len() / channels, &mut frames);
+                    convert::write_i16(data, channels, frames)
+                },
                 err_fn,
                 None,
             )?,
             cpal::SampleFormat::U16 => device.build_output_stream(
                 &config,
-                move |data: &mut [u16], _| write_data_u16(data, channels, &queue),
+                move |data: &mut [u16], _| {
+                    cb_health.touch();
+                    let frames = fill_frames(&mut fill, data.len() / channels, &mut frames);
+                    convert::write_u16(data, channels, frames)
+                },
                 err_fn,
                 None,
             )?,
@@ -78,6 +306,64 @@ impl AudioOutput {
         Ok(Self {
             _stream: Some(stream),
             device_name,
+            health,
+        })
+    }
+}
+
+/// Names of the output devices currently present, sorted.
+pub fn output_device_names() -> Result<Vec<String>> {
+    let mut names: Vec<String> = cpal::default_host()
+        .output_devices()?
+        .filter_map(|d| d.name().ok())
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// A virtual audio cable: what is played into it can be recorded from its
+/// other end, so it turns the receiver into a microphone for other apps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VirtualDevice {
+    VbCable,
+    VoiceMeeter,
+    BlackHole,
+}
+
+impl VirtualDevice {
+    /// Recognize a virtual cable from its output device name, e.g.
+    /// "CABLE Input (VB-Audio Virtual Cable)" or "BlackHole 2ch".
+    pub fn detect(device_name: &str) -> Option<Self> {
+        let name = device_name.to_lowercase();
+        if name.contains("voicemeeter") {
+            Some(Self::VoiceMeeter)
+        } else if name.contains("blackhole") {
+            Some(Self::BlackHole)
+        } else if (name.starts_with("cable") && name.contains("input"))
+            || (name.contains("vb-audio") && name.contains("cable"))
+        {
+            Some(Self::VbCable)
+        } else {
+            None
+        }
+    }
+
+    /// The input device other apps pick as their microphone.
+    pub fn recording_side(self) -> &'static str {
+        match self {
+            Self::VbCable => "CABLE Output",
+            Self::VoiceMeeter => "VoiceMeeter Output",
+            Self::BlackHole => "BlackHole",
+        }
+    }
+}
+
+impl std::fmt::Display for VirtualDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::VbCable => "VB-Cable",
+            Self::VoiceMeeter => "VoiceMeeter",
+            Self::BlackHole => "BlackHole",
         })
     }
 }
@@ -86,7 +372,9 @@ impl AudioOutput {
 fn pick_output_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig> {
     if let Ok(ranges) = device.supported_output_configs() {
         let mut candidates: Vec<_> = ranges
-            .filter(|r| r.min_sample_rate().0 <= 48_000 && r.max_sample_rate().0 >= 48_000)
+            .filter(|r| {
+                r.min_sample_rate().0 <= SOURCE_RATE && r.max_sample_rate().0 >= SOURCE_RATE
+            })
             .collect();
 
         // Lower penalty = better. Prefer stereo, then f32.
@@ -106,7 +394,7 @@ fn pick_output_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConf
         });
 
         if let Some(best) = candidates.first() {
-            return Ok(best.with_sample_rate(cpal::SampleRate(48_000)));
+            return Ok(best.with_sample_rate(cpal::SampleRate(SOURCE_RATE)));
         }
     }
 
@@ -115,27 +403,30 @@ fn pick_output_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConf
         .map_err(|e| anyhow!("No suitable output config: {e}"))
 }
 
-// ---------------------------------------------------------------------------
-// Write callbacks — pop mono i16 samples from the queue into device frames.
-// ---------------------------------------------------------------------------
-
-fn write_data_f32(out: &mut [f32], channels: usize, q: &Arc<ArrayQueue<i16>>) {
-    for frame in out.chunks_mut(channels) {
-        let v = q.pop().unwrap_or(0) as f32 / 32768.0;
-        frame.fill(v);
+/// Use the requested buffer size when the device supports it, else default.
+fn pick_buffer_size(
+    supported: &cpal::SupportedBufferSize,
+    frames: Option<u32>,
+) -> cpal::BufferSize {
+    match (frames, supported) {
+        (Some(n), cpal::SupportedBufferSize::Range { min, max }) => {
+            cpal::BufferSize::Fixed(n.clamp(*min, *max))
+        }
+        _ => cpal::BufferSize::Default,
     }
 }
 
-fn write_data_i16(out: &mut [i16], channels: usize, q: &Arc<ArrayQueue<i16>>) {
-    for frame in out.chunks_mut(channels) {
-        let s = q.pop().unwrap_or(0);
-        frame.fill(s);
-    }
-}
+// ---------------------------------------------------------------------------
+// Write callbacks — one stereo frame per device frame.
+// ---------------------------------------------------------------------------
 
-fn write_data_u16(out: &mut [u16], channels: usize, q: &Arc<ArrayQueue<i16>>) {
-    for frame in out.chunks_mut(channels) {
-        let v = (q.pop().unwrap_or(0) as i32 + 32768).clamp(0, 65535) as u16;
-        frame.fill(v);
-    }
+/// Pull `frames` frames from the source into the reusable scratch buffer.
+fn fill_frames<'a>(
+    fill: &mut impl FnMut(&mut [Frame]),
+    frames: usize,
+    scratch: &'a mut Vec<Frame>,
+) -> &'a [Frame] {
+    scratch.resize(frames, [0; 2]);
+    fill(scratch);
+    scratch
 }
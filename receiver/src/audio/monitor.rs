@@ -0,0 +1,94 @@
+use super::drift::DriftCorrector;
+use super::Frame;
+use crossbeam_queue::ArrayQueue;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Monitor buffer capacity (stereo frames @ 48 kHz, ~250 ms).
+const TAP_CAPACITY: usize = 12_000;
+
+/// Queue depth the drift correction steers towards (~40 ms).
+const TARGET_FILL: usize = 1_920;
+
+/// Copy of the main mix for local listening on a second device.
+///
+/// The main output callback pushes every mixed frame here; the monitor
+/// stream pops them and applies its own volume/mute, so turning the monitor
+/// down never changes what the virtual cable (Discord/OBS) receives.
+///
+/// The two devices run on independent clocks, so over a long session one
+/// consumes slightly faster than the other. Rather than letting the queue
+/// slowly drain (dropouts) or overflow (jumps), the monitor side reads it
+/// through a [`DriftCorrector`] that holds the queue at [`TARGET_FILL`].
+pub struct MonitorTap {
+    queue: ArrayQueue<Frame>,
+    gain: AtomicU32,
+    muted: AtomicBool,
+    /// Only touched by the monitor callback.
+    drift: Mutex<DriftCorrector>,
+    /// Buffered up to the target; cleared on underrun.
+    primed: AtomicBool,
+}
+
+impl MonitorTap {
+    pub fn new(gain: f32, muted: bool) -> Self {
+        Self {
+            queue: ArrayQueue::new(TAP_CAPACITY),
+            gain: AtomicU32::new(gain.max(0.0).to_bits()),
+            muted: AtomicBool::new(muted),
+            drift: Mutex::new(DriftCorrector::default()),
+            primed: AtomicBool::new(false),
+        }
+    }
+
+    /// Copy mixed frames from the main output. Oldest frames are dropped
+    /// when the monitor device falls behind.
+    pub fn push(&self, frames: &[Frame]) {
+        for &frame in frames {
+            self.queue.force_push(frame);
+        }
+    }
+
+    /// Fill the monitor device buffer with gain and drift correction applied.
+    pub fn fill(&self, out: &mut [Frame]) {
+        let gain = if self.is_muted() { 0.0 } else { self.gain() };
+        let mut drift = self.drift.lock();
+
+        let fill = self.queue.len();
+        if !self.primed.load(Ordering::Relaxed) {
+            if fill < TARGET_FILL {
+                out.fill([0; 2]);
+                return;
+            }
+            self.primed.store(true, Ordering::Relaxed);
+        }
+        // Too full → the monitor clock is slow → consume a little faster
+        drift.update(fill, TARGET_FILL);
+
+        for (i, o) in out.iter_mut().enumerate() {
+            let Some(frame) = drift.next(|| self.queue.pop()) else {
+                // Underrun: rebuffer up to the target
+                self.primed.store(false, Ordering::Relaxed);
+                out[i..].fill([0; 2]);
+                return;
+            };
+            *o = frame.map(|s| (s * gain).clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+        }
+    }
+
+    pub fn gain(&self) -> f32 {
+        f32::from_bits(self.gain.load(Ordering::Relaxed))
+    }
+
+    pub fn set_gain(&self, gain: f32) {
+        self.gain.store(gain.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted.load(Ordering::Relaxed)
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        self.muted.store(muted, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,134 @@
+use super::Frame;
+
+/// Rate the mixer and every source run at.
+pub const SOURCE_RATE: u32 = 48_000;
+
+/// Converts the 48 kHz stream to the rate an output device actually
+/// opened at, for devices that can't run at 48 kHz (e.g. 44.1 kHz-only USB
+/// headsets).
+///
+/// 4-point cubic (Hermite) interpolation: cheap enough for the audio
+/// callback and transparent for speech. Input is pulled on demand, so the
+/// mixer still sees plain 48 kHz reads.
+pub struct Resampler {
+    /// Input frames advanced per output frame.
+    step: f64,
+    interpolator: Interpolator,
+    /// Input pulled from the source but not consumed yet.
+    input: Vec<Frame>,
+    consumed: usize,
+}
+
+impl Resampler {
+    pub fn new(device_rate: u32) -> Self {
+        Self {
+            step: f64::from(SOURCE_RATE) / f64::from(device_rate),
+            interpolator: Interpolator::default(),
+            input: Vec::new(),
+            consumed: 0,
+        }
+    }
+
+    /// Fill `out` at the device rate, pulling 48 kHz frames from `fill`.
+    pub fn process(&mut self, out: &mut [Frame], fill: &mut impl FnMut(&mut [Frame])) {
+        let Self {
+            step,
+            interpolator,
+            input,
+            consumed,
+        } = self;
+        let len = out.len();
+        for (i, frame) in out.iter_mut().enumerate() {
+            // The source always fills, so this never runs dry
+            let value = interpolator
+                .next(*step, || {
+                    // Read about enough for the remaining output frames
+                    // whenever the buffer is used up
+                    if *consumed == input.len() {
+                        let want = ((len - i) as f64 * *step).ceil() as usize + 1;
+                        input.resize(want, [0; 2]);
+                        fill(input);
+                        *consumed = 0;
+                    }
+                    *consumed += 1;
+                    Some(input[*consumed - 1])
+                })
+                .unwrap_or_default();
+            *frame = value.map(|v| v.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+        }
+    }
+}
+
+/// Reads a stream of frames at a fractional rate, interpolating each
+/// channel between input frames. Shared by the device-rate [`Resampler`] and the clock-drift
+/// correctors, which read at a rate a fraction of a percent off 1.
+#[derive(Debug, Clone, Default)]
+pub struct Interpolator {
+    /// Position of the next output frame between `history[1]` and
+    /// `history[2]`, in `[0, 1)` once the history is filled.
+    pos: f64,
+    /// The four input frames around `pos`.
+    history: [[f32; 2]; 4],
+}
+
+impl Interpolator {
+    /// The next output frame, then advance `step` input frames. Input is
+    /// popped with `pop` as needed; `None` when it runs dry, and the next
+    /// call carries on from the same position.
+    pub fn next(&mut self, step: f64, mut pop: impl FnMut() -> Option<Frame>) -> Option<[f32; 2]> {
+        while self.pos >= 1.0 {
+            let frame = pop()?;
+            self.history.rotate_left(1);
+            self.history[3] = frame.map(f32::from);
+            self.pos -= 1.0;
+        }
+        let [a, b, c, d] = self.history;
+        let t = self.pos as f32;
+        let value = [0, 1].map(|ch| hermite(a[ch], b[ch], c[ch], d[ch], t));
+        self.pos += step;
+        Some(value)
+    }
+}
+
+/// Catmull-Rom interpolation between `b` and `c` at `t` in `[0, 1)`.
+fn hermite(a: f32, b: f32, c: f32, d: f32, t: f32) -> f32 {
+    let c1 = 0.5 * (c - a);
+    let c2 = a - 2.5 * b + 2.0 * c - 0.5 * d;
+    let c3 = 0.5 * (d - a) + 1.5 * (b - c);
+    ((c3 * t + c2) * t + c1) * t + b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_step_passes_samples_through() {
+        let mut interpolator = Interpolator::default();
+        let mut input = (1..=100i16).map(|s| [s * 100, -s]);
+        let out: Vec<[f32; 2]> = (0..100)
+            .map_while(|_| interpolator.next(1.0, || input.next()))
+            .collect();
+        // Three frames of history delay, then the input unchanged
+        assert_eq!(out[..3], [[0.0; 2]; 3]);
+        let expected: Vec<[f32; 2]> = (1..=97i16)
+            .map(|s| [f32::from(s * 100), f32::from(-s)])
+            .collect();
+        assert_eq!(out[3..], expected[..]);
+    }
+
+    #[test]
+    fn running_dry_resumes_where_it_stopped() {
+        let mut interpolator = Interpolator::default();
+        let mut input = vec![[100i16; 2], [200; 2], [300; 2]];
+        let mut pop = || (!input.is_empty()).then(|| input.remove(0));
+        let first: Vec<_> = (0..5)
+            .map(|_| interpolator.next(1.0, &mut pop).map(|[l, _]| l))
+            .collect();
+        assert_eq!(first, [Some(0.0), Some(0.0), Some(0.0), Some(100.0), None]);
+        input.extend([[400; 2], [500; 2]]);
+        let mut pop = || (!input.is_empty()).then(|| input.remove(0));
+        assert_eq!(interpolator.next(1.0, &mut pop), Some([200.0; 2]));
+        assert_eq!(interpolator.next(1.0, &mut pop), Some([300.0; 2]));
+    }
+}
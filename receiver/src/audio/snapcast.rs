@@ -0,0 +1,201 @@
+use anyhow::{bail, Context, Result};
+use crossbeam_queue::ArrayQueue;
+use std::io::Write;
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Tap buffer capacity (mono i16 @ 48 kHz, ~250 ms).
+const TAP_CAPACITY: usize = 12_000;
+
+/// Samples written per chunk (10 ms).
+const CHUNK: usize = 480;
+
+/// Sleep when the tap is empty; the main output refills it every callback.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Wait before reconnecting after the server went away or refused us.
+const RETRY_AFTER: Duration = Duration::from_secs(2);
+
+/// Where Snapcast reads the stream from.
+///
+/// Snapserver's default sample format is `48000:16:2`, so the mono mix is
+/// written as interleaved stereo signed 16-bit little-endian.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapcastTarget {
+    /// `tcp://HOST:PORT` — a snapserver `tcp://` source in server mode.
+    Tcp(String),
+    /// A named pipe read by a snapserver `pipe://` source.
+    Pipe(PathBuf),
+}
+
+impl SnapcastTarget {
+    pub fn parse(target: &str) -> Result<Self> {
+        let target = target.trim();
+        if let Some(addr) = target.strip_prefix("tcp://") {
+            if addr
+                .rsplit_once(':')
+                .is_none_or(|(_, port)| port.parse::<u16>().is_err())
+            {
+                bail!("expected tcp://HOST:PORT, got '{target}'");
+            }
+            return Ok(Self::Tcp(addr.to_string()));
+        }
+        let path = target.strip_prefix("pipe://").unwrap_or(target);
+        if path.is_empty() {
+            bail!("empty Snapcast target");
+        }
+        Ok(Self::Pipe(PathBuf::from(path)))
+    }
+
+    fn open(&self) -> Result<Box<dyn Write + Send>> {
+        match self {
+            Self::Tcp(addr) => {
+                let stream = TcpStream::connect(addr).with_context(|| format!("connect {addr}"))?;
+                stream.set_nodelay(true)?;
+                Ok(Box::new(stream))
+            }
+            // Blocks until snapserver has the pipe open for reading
+            Self::Pipe(path) => Ok(Box::new(
+                std::fs::OpenOptions::new()
+                    .write(true)
+                    .open(path)
+                    .with_context(|| format!("open {}", path.display()))?,
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for SnapcastTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "tcp://{addr}"),
+            Self::Pipe(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Copy of the main mix for a Snapcast server, which plays it in sync on
+/// every room's client.
+///
+/// The output callback pushes into a lock-free queue; a writer thread
+/// drains it to the pipe or socket so a stalled server never blocks audio.
+pub struct SnapcastTap {
+    queue: ArrayQueue<i16>,
+}
+
+impl SnapcastTap {
+    fn new() -> Self {
+        Self {
+            queue: ArrayQueue::new(TAP_CAPACITY),
+        }
+    }
+
+    /// Copy mixed samples from the main output. Oldest samples are dropped
+    /// while the server is unreachable or slow.
+    pub fn push(&self, samples: &[i16]) {
+        for &s in samples {
+            self.queue.force_push(s);
+        }
+    }
+
+    fn clear(&self) {
+        while self.queue.pop().is_some() {}
+    }
+}
+
+/// A running feed into Snapcast, reconnecting until dropped.
+pub struct SnapcastOutput {
+    target: SnapcastTarget,
+    tap: Arc<SnapcastTap>,
+    stop: Arc<AtomicBool>,
+}
+
+impl SnapcastOutput {
+    /// Start the writer thread. `report` receives connection state changes
+    /// for the log.
+    pub fn start(target: SnapcastTarget, report: impl Fn(String) + Send + 'static) -> Result<Self> {
+        let tap = Arc::new(SnapcastTap::new());
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (thread_target, thread_tap, thread_stop) =
+            (target.clone(), Arc::clone(&tap), Arc::clone(&stop));
+        std::thread::Builder::new()
+            .name("snapcast".into())
+            .spawn(move || write_loop(&thread_target, &thread_tap, &thread_stop, &report))?;
+
+        Ok(Self { target, tap, stop })
+    }
+
+    pub fn target(&self) -> &SnapcastTarget {
+        &self.target
+    }
+
+    pub fn tap(&self) -> Arc<SnapcastTap> {
+        Arc::clone(&self.tap)
+    }
+}
+
+impl Drop for SnapcastOutput {
+    fn drop(&mut self) {
+        // Not joined: opening a pipe blocks until a reader appears, and the
+        // thread exits on its own once that returns.
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Writer thread
+// ---------------------------------------------------------------------------
+
+fn write_loop(
+    target: &SnapcastTarget,
+    tap: &SnapcastTap,
+    stop: &AtomicBool,
+    report: &dyn Fn(String),
+) {
+    let mut last_error = None;
+    while !stop.load(Ordering::Relaxed) {
+        let res = target.open().and_then(|mut out| {
+            if stop.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            report(format!("Snapcast: streaming to {target}"));
+            last_error = None;
+            // Audio queued while disconnected is stale
+            tap.clear();
+            pump(&mut *out, tap, stop)
+        });
+        if let Err(e) = res {
+            // Only log a failure once until it changes or recovers
+            let msg = format!("{e:#}");
+            if last_error.as_ref() != Some(&msg) {
+                report(format!("Snapcast: {msg} — retrying"));
+                last_error = Some(msg);
+            }
+            std::thread::sleep(RETRY_AFTER);
+        }
+    }
+}
+
+fn pump(out: &mut dyn Write, tap: &SnapcastTap, stop: &AtomicBool) -> Result<()> {
+    let mut bytes = Vec::with_capacity(CHUNK * 4);
+    while !stop.load(Ordering::Relaxed) {
+        bytes.clear();
+        while bytes.len() < CHUNK * 4 {
+            let Some(s) = tap.queue.pop() else {
+                break;
+            };
+            let le = s.to_le_bytes();
+            bytes.extend_from_slice(&[le[0], le[1], le[0], le[1]]);
+        }
+        if bytes.is_empty() {
+            std::thread::sleep(POLL_INTERVAL);
+            continue;
+        }
+        out.write_all(&bytes).context("write")?;
+    }
+    Ok(())
+}
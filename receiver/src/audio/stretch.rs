@@ -0,0 +1,108 @@
+/// Shortest period removed per splice (2.5 ms, a 400 Hz pitch).
+const MIN_PERIOD: usize = 120;
+
+/// Longest period removed per splice (10 ms, a 100 Hz pitch).
+const MAX_PERIOD: usize = 480;
+
+/// Crossfade length at the splice (5 ms).
+const OVERLAP: usize = 240;
+
+/// Shorten a block of decoded audio (`channels` interleaved) by about one
+/// pitch period.
+///
+/// WSOLA-style: find the lag at which the signal best repeats itself, then
+/// crossfade from the start of the block into that later point. Because the
+/// removed stretch is a whole period, voiced speech keeps its pitch and no
+/// click is heard — unlike dropping samples off the buffer. The block's
+/// first and last samples are untouched, so it still joins its neighbours.
+/// The lag is found on the sum of the channels and every channel is
+/// spliced at it.
+///
+/// Returns the number of frames removed (0 if the block is too short).
+pub fn remove_period(samples: &mut Vec<i16>, channels: usize) -> usize {
+    let channels = channels.max(1);
+    if samples.len() / channels < MAX_PERIOD + OVERLAP {
+        return 0;
+    }
+
+    let mixed: Vec<f32> = samples
+        .chunks_exact(channels)
+        .take(MAX_PERIOD + OVERLAP)
+        .map(|frame| frame.iter().map(|&s| s as f32).sum())
+        .collect();
+    let head = &mixed[..OVERLAP];
+    let mut best = (f32::MIN, MIN_PERIOD);
+    for lag in MIN_PERIOD..=MAX_PERIOD {
+        let (mut xy, mut yy) = (0.0f32, 0.0f32);
+        for (&a, &b) in head.iter().zip(&mixed[lag..lag + OVERLAP]) {
+            xy += a * b;
+            yy += b * b;
+        }
+        // The head's energy is the same for every lag, so it drops out
+        let score = xy / (yy.sqrt() + 1.0);
+        if score > best.0 {
+            best = (score, lag);
+        }
+    }
+
+    let lag = best.1;
+    for j in 0..OVERLAP * channels {
+        let w = (j / channels) as f32 / OVERLAP as f32;
+        let mixed = samples[j] as f32 * (1.0 - w) + samples[lag * channels + j] as f32 * w;
+        samples[j] = mixed as i16;
+    }
+    samples.drain(OVERLAP * channels..(OVERLAP + lag) * channels);
+    lag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frames of a tone repeating every `period` frames.
+    fn tone(frames: usize, period: usize) -> Vec<i16> {
+        (0..frames)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * (i % period) as f32 / period as f32;
+                (8_000.0 * phase.sin()) as i16
+            })
+            .collect()
+    }
+
+    #[test]
+    fn removes_whole_periods() {
+        let original = tone(960, 200);
+        let mut samples = original.clone();
+        let removed = remove_period(&mut samples, 1);
+
+        assert!(removed == 200 || removed == 400, "removed {removed}");
+        assert_eq!(samples.len(), original.len() - removed);
+        assert_eq!(samples[0], original[0]);
+        assert_eq!(samples.last(), original.last());
+    }
+
+    #[test]
+    fn short_blocks_are_untouched() {
+        for channels in [1, 2] {
+            let original = tone((MAX_PERIOD + OVERLAP - 1) * channels, 200);
+            let mut samples = original.clone();
+            assert_eq!(remove_period(&mut samples, channels), 0);
+            assert_eq!(samples, original);
+        }
+    }
+
+    #[test]
+    fn stereo_sides_are_spliced_together() {
+        let left = tone(960, 200);
+        let original: Vec<i16> = left.iter().flat_map(|&l| [l, l / 2]).collect();
+        let mut samples = original.clone();
+        let removed = remove_period(&mut samples, 2);
+
+        assert!(removed > 0);
+        assert_eq!(samples.len(), original.len() - 2 * removed);
+        // Same lag and crossfade on both sides: right stays half of left
+        for frame in samples.chunks_exact(2) {
+            assert!((i32::from(frame[0]) / 2 - i32::from(frame[1])).abs() <= 1);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+/// RMS level (0.0–1.0 of full scale) above which a frame counts as speech.
+/// Roughly -40 dBFS — comfortably above phone mic noise floors.
+const SPEECH_THRESHOLD: f32 = 0.01;
+
+/// How long the level must stay below the threshold before a talking
+/// segment is closed. Bridges the short gaps between words.
+const HANGOVER_MS: u64 = 400;
+
+/// Energy-based voice activity detector for mono i16 @ 48 kHz.
+///
+/// Deliberately simple: it only needs to tell "audio is flowing" apart from
+/// silence for the session timeline, not drive any DSP.
+pub struct VoiceActivityDetector {
+    talking: bool,
+    quiet_samples: u64,
+    hangover_samples: u64,
+}
+
+impl VoiceActivityDetector {
+    pub fn new() -> Self {
+        Self {
+            talking: false,
+            quiet_samples: 0,
+            hangover_samples: HANGOVER_MS * 48,
+        }
+    }
+
+    /// Feed one decoded frame. Returns `Some(talking)` when the state flips.
+    pub fn process(&mut self, samples: &[i16]) -> Option<bool> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let speech = rms(samples) >= SPEECH_THRESHOLD;
+
+        if speech {
+            self.quiet_samples = 0;
+            if !self.talking {
+                self.talking = true;
+                return Some(true);
+            }
+        } else if self.talking {
+            self.quiet_samples += samples.len() as u64;
+            if self.quiet_samples >= self.hangover_samples {
+                self.talking = false;
+                self.quiet_samples = 0;
+                return Some(false);
+            }
+        }
+
+        None
+    }
+}
+
+impl Default for VoiceActivityDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Root-mean-square level of a frame, normalised to 0.0–1.0.
+pub fn rms(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = samples
+        .iter()
+        .map(|&s| {
+            let v = s as f64 / 32768.0;
+            v * v
+        })
+        .sum();
+    (sum / samples.len() as f64).sqrt() as f32
+}
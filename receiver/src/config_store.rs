@@ -0,0 +1,59 @@
+//! Settings kept as JSON files in the current profile's config directory,
+//! so each setting only names its file.
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Where `name` lives in the current profile's config directory.
+pub fn path(name: &str) -> Option<PathBuf> {
+    crate::profile::current().config_dir().map(|d| d.join(name))
+}
+
+/// Load `name`; `None` if it is missing or can't be read. A corrupt file
+/// is logged and ignored, so the setting falls back to its default.
+pub fn load<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let path = path(name)?;
+    let bytes = std::fs::read(&path).ok()?;
+    serde_json::from_slice(&bytes)
+        .inspect_err(|e| log::warn!("Ignoring corrupt {}: {e}", path.display()))
+        .ok()
+}
+
+/// Like [`load`], but only a missing file is `Ok(None)`; one that can't be
+/// read or parsed is an error, for settings that must fail closed.
+pub fn load_strict<T: DeserializeOwned>(name: &str) -> Result<Option<T>> {
+    let Some(path) = path(name) else {
+        return Ok(None);
+    };
+    match std::fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .with_context(|| format!("parse {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("read {}", path.display())),
+    }
+}
+
+/// Write `value` to `name` as pretty-printed JSON, creating the directory.
+pub fn save<T: Serialize + ?Sized>(name: &str, value: &T) -> Result<()> {
+    let path = path(name).context("no config directory")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    let json = serde_json::to_vec_pretty(value)?;
+    std::fs::write(&path, json).with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
+
+/// Delete `name`; one that doesn't exist is already removed.
+pub fn remove(name: &str) -> Result<()> {
+    let path = path(name).context("no config directory")?;
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("remove {}", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,243 @@
+use crate::config_store;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// An address range in CIDR notation, e.g. `192.168.1.0/24`. A bare address
+/// is a single host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpNet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNet {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        // Dual-stack sockets report IPv4 senders as ::ffff:a.b.c.d
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr
+            .parse()
+            .with_context(|| format!("'{addr}' is not an IP address"))?;
+        let bits = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(p) => match p.parse::<u8>() {
+                Ok(p) if p <= bits => p,
+                _ => bail!("prefix length must be 0 to {bits}, got '{p}'"),
+            },
+            None => bits,
+        };
+        // ::ffff:a.b.c.d/N is stored as a.b.c.d/(N - 96), matching how
+        // `contains` sees dual-stack senders
+        let canonical = addr.to_canonical();
+        let prefix = match (addr, canonical) {
+            (IpAddr::V6(_), IpAddr::V4(_)) => match prefix.checked_sub(96) {
+                Some(p) => p,
+                None => bail!("prefix length of an IPv4-mapped address must be 96 to 128"),
+            },
+            _ => prefix,
+        };
+        Ok(Self {
+            addr: canonical,
+            prefix,
+        })
+    }
+}
+
+impl TryFrom<String> for IpNet {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<IpNet> for String {
+    fn from(net: IpNet) -> Self {
+        net.to_string()
+    }
+}
+
+impl fmt::Display for IpNet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// Which sender addresses may open a WebSocket.
+///
+/// A match in `deny` always rejects. If `allow` is not empty, only
+/// addresses matching one of its ranges are accepted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessList {
+    #[serde(default)]
+    pub allow: Vec<IpNet>,
+    #[serde(default)]
+    pub deny: Vec<IpNet>,
+}
+
+impl AccessList {
+    /// Rules rejecting every address, used when the saved rules can't be
+    /// read so a damaged file never opens the receiver up.
+    pub fn deny_all() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: vec![
+                IpNet {
+                    addr: IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
+                    prefix: 0,
+                },
+                IpNet {
+                    addr: IpAddr::V6(std::net::Ipv6Addr::UNSPECIFIED),
+                    prefix: 0,
+                },
+            ],
+        }
+    }
+
+    pub fn permits(&self, ip: IpAddr) -> bool {
+        if self.deny.iter().any(|net| net.contains(ip)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|net| net.contains(ip))
+    }
+}
+
+const STORE: &str = "access.json";
+
+/// Load the access rules. No saved rules allows everyone; a file that
+/// can't be read or parsed is an error, so the caller can fail closed.
+pub fn load() -> Result<AccessList> {
+    Ok(config_store::load_strict(STORE)?.unwrap_or_default())
+}
+
+pub fn save(list: &AccessList) -> Result<()> {
+    config_store::save(STORE, list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn net(s: &str) -> IpNet {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn ipv4_ranges() {
+        let lan = net("192.168.1.0/24");
+        assert!(lan.contains(ip("192.168.1.40")));
+        assert!(!lan.contains(ip("192.168.2.40")));
+        assert!(net("0.0.0.0/0").contains(ip("8.8.8.8")));
+        assert!(net("10.0.0.7").contains(ip("10.0.0.7")));
+        assert!(!net("10.0.0.7").contains(ip("10.0.0.8")));
+    }
+
+    #[test]
+    fn ipv6_ranges() {
+        let site = net("fd00:1::/64");
+        assert!(site.contains(ip("fd00:1::42")));
+        assert!(!site.contains(ip("fd00:2::42")));
+        assert!(!site.contains(ip("192.168.1.40")));
+    }
+
+    #[test]
+    fn dual_stack_senders_match_ipv4_ranges() {
+        assert!(net("192.168.1.0/24").contains(ip("::ffff:192.168.1.40")));
+    }
+
+    #[test]
+    fn ipv4_mapped_ranges_convert_their_prefix() {
+        let mapped = net("::ffff:10.0.0.0/104");
+        assert_eq!(mapped, net("10.0.0.0/8"));
+        assert!(mapped.contains(ip("10.20.30.40")));
+        assert!(!mapped.contains(ip("192.168.5.5")));
+        assert_eq!(net("::ffff:10.0.0.1"), net("10.0.0.1/32"));
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        for bad in [
+            "10.0.0.0/33",
+            "::/129",
+            "::ffff:10.0.0.0/80",
+            "10.0.0.0/x",
+            "lan",
+        ] {
+            assert!(bad.parse::<IpNet>().is_err(), "{bad} parsed");
+        }
+    }
+
+    #[test]
+    fn deny_overrides_allow() {
+        let list = AccessList {
+            allow: vec![net("192.168.1.0/24")],
+            deny: vec![net("192.168.1.13")],
+        };
+        assert!(list.permits(ip("192.168.1.40")));
+        assert!(!list.permits(ip("192.168.1.13")));
+        assert!(!list.permits(ip("10.0.0.1")));
+    }
+
+    #[test]
+    fn empty_allow_list_permits_everyone_not_denied() {
+        let list = AccessList {
+            allow: Vec::new(),
+            deny: vec![net("10.0.0.0/8")],
+        };
+        assert!(list.permits(ip("192.168.1.40")));
+        assert!(!list.permits(ip("10.1.2.3")));
+        assert!(AccessList::default().permits(ip("10.1.2.3")));
+    }
+
+    #[test]
+    fn deny_all_rejects_both_families() {
+        let list = AccessList::deny_all();
+        for addr in ["192.168.1.40", "::ffff:192.168.1.40", "fd00::1", "::1"] {
+            assert!(!list.permits(ip(addr)), "{addr} permitted");
+        }
+    }
+
+    #[test]
+    fn rules_round_trip_through_json() {
+        let list = AccessList {
+            allow: vec![net("192.168.1.0/24"), net("fd00::/8")],
+            deny: vec![net("::ffff:192.168.1.13")],
+        };
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(serde_json::from_str::<AccessList>(&json).unwrap(), list);
+        assert!(serde_json::from_str::<AccessList>(r#"{"allow":["10.0.0.0/40"]}"#).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+use super::recovery::ListenState;
+use super::SharedStatus;
+use anyhow::{Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+
+/// Most recent session log files included in a bundle.
+const RECENT_SESSION_LOGS: usize = 5;
+
+/// Zip everything a bug report needs into `path`: version info, the app
+/// log and recent session logs, the current status, sanitized settings, the
+/// audio devices, the network interfaces, and the last negotiated SDP.
+/// Tokens are redacted throughout.
+pub fn create_bundle(
+    path: &Path,
+    shared: &SharedStatus,
+    settings: Option<&ListenState>,
+) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    let file = std::fs::File::create(path).with_context(|| format!("create {}", path.display()))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut add = |name: &str, contents: &str| -> Result<()> {
+        zip.start_file(name, options)?;
+        zip.write_all(redact_tokens(contents).as_bytes())?;
+        Ok(())
+    };
+
+    add("version.txt", &version_info())?;
+    let log: Vec<String> = shared
+        .snapshot()
+        .log_lines
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    add("logs/app.log", &log.join("\n"))?;
+    for log in recent_session_logs() {
+        let Some(name) = log.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        match std::fs::read_to_string(&log) {
+            Ok(text) => add(&format!("logs/{name}"), &text)?,
+            Err(e) => log::warn!("Skipping {}: {e}", log.display()),
+        }
+    }
+    add(
+        "status.json",
+        &serde_json::to_string_pretty(&shared.snapshot_without_logs()).unwrap_or_default(),
+    )?;
+    add("config.json", &sanitized_config(shared, settings))?;
+    add("audio-devices.txt", &audio_devices())?;
+    add("network-interfaces.txt", &network_interfaces())?;
+    add("sdp.txt", &negotiated_sdp(shared))?;
+
+    zip.finish()
+        .with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
+
+fn version_info() -> String {
+    let profile = crate::profile::current();
+    format!(
+        "lan-mic-webrtc-receiver {}\nos: {} ({})\nprofile: {}\nport: {}\ncreated: unix {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        profile.name.as_deref().unwrap_or("default"),
+        profile.port,
+        super::history::unix_now()
+    )
+}
+
+fn recent_session_logs() -> Vec<PathBuf> {
+    let Some(dir) = crate::profile::current().data_dir().map(|d| d.join("logs")) else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "log"))
+        .collect();
+    // Names start with the unix time, so the newest sort last
+    logs.sort();
+    let skip = logs.len().saturating_sub(RECENT_SESSION_LOGS);
+    logs.split_off(skip)
+}
+
+/// Settings without secrets: the relay target becomes a flag and trusted
+/// devices a count.
+fn sanitized_config(shared: &SharedStatus, settings: Option<&ListenState>) -> String {
+    let status = shared.snapshot();
+    let profile = crate::profile::current();
+    let listen = settings.map(|s| {
+        serde_json::json!({
+            "output_device": s.output_device,
+            "use_stun": s.use_stun,
+            "ice_servers": s.ice_servers.iter().map(|i| &i.urls).collect::<Vec<_>>(),
+            "allow_multiple_clients": s.allow_multiple_clients,
+            "monitor_device": s.monitor_device,
+            "monitor_gain": s.monitor_gain,
+            "monitor_muted": s.monitor_muted,
+            "quality": s.quality,
+            "latency_ms": s.latency_ms,
+            "require_approval": s.require_approval,
+            "relay_enabled": s.relay_target.is_some(),
+            "snapcast_target": s.snapcast_target,
+            "captions": s.captions_model.is_some(),
+            "standby_device": s.standby_device,
+            "echo_cancellation": s.echo_cancellation,
+            "agc": s.agc,
+            "advertise_mdns": s.advertise_mdns,
+            "udp_beacon": s.udp_beacon,
+            "udp_ingest": s.udp_ingest,
+            "idle_stop_minutes": s.idle_stop_minutes,
+            "reconnect_grace_secs": s.reconnect_grace_secs,
+            "keepalive_timeout_secs": s.keepalive_timeout_secs,
+        })
+    });
+    let config = serde_json::json!({
+        "profile": {
+            "name": profile.name,
+            "port": profile.port,
+            "custom_display_name": profile.display_name.is_some(),
+        },
+        "appearance": crate::appearance::load(),
+        "settings": crate::app_settings::load(),
+        "log_file": super::log_file::load(),
+        "launch_at_login": crate::platform::autostart::status().is_some(),
+        "last_start": listen,
+        "server_running": status.server_running,
+        "connected_clients": status.clients.len(),
+        "trusted_devices": status.trusted_devices.len(),
+        "jitter_target_ms": status.jitter_target_ms,
+        "last_error": status.last_error,
+        "http_error": status.http_error,
+    });
+    serde_json::to_string_pretty(&config).unwrap_or_default()
+}
+
+/// Every interface address, marking the ones offered to senders.
+fn network_interfaces() -> String {
+    let offered = super::signaling::list_lan_addresses();
+    let list = match local_ip_address::list_afinet_netifas() {
+        Ok(list) => list,
+        Err(e) => return format!("Failed to list interfaces: {e}\n"),
+    };
+    let mut out = String::new();
+    for (name, ip) in list {
+        let mark = if offered.iter().any(|a| a.ip == ip.to_string()) {
+            "  (offered to senders)"
+        } else {
+            ""
+        };
+        let _ = writeln!(out, "{name}: {ip}{mark}");
+    }
+    out
+}
+
+fn audio_devices() -> String {
+    let mut out = String::new();
+    for host_id in cpal::available_hosts() {
+        let _ = writeln!(out, "Host: {}", host_id.name());
+        let host = match cpal::host_from_id(host_id) {
+            Ok(host) => host,
+            Err(e) => {
+                let _ = writeln!(out, "  unavailable: {e}");
+                continue;
+            }
+        };
+        let default = host.default_output_device().and_then(|d| d.name().ok());
+        let _ = writeln!(
+            out,
+            "  Default output: {}",
+            default.as_deref().unwrap_or("none")
+        );
+        let devices = match host.output_devices() {
+            Ok(devices) => devices,
+            Err(e) => {
+                let _ = writeln!(out, "  Cannot enumerate outputs: {e}");
+                continue;
+            }
+        };
+        for device in devices {
+            let name = device.name().unwrap_or_else(|_| "(unnamed)".into());
+            match device.default_output_config() {
+                Ok(c) => {
+                    let _ = writeln!(
+                        out,
+                        "  - {name}: {} Hz, {} ch, {:?}",
+                        c.sample_rate().0,
+                        c.channels(),
+                        c.sample_format()
+                    );
+                }
+                Err(e) => {
+                    let _ = writeln!(out, "  - {name}: no default config ({e})");
+                }
+            }
+        }
+    }
+    out
+}
+
+fn negotiated_sdp(shared: &SharedStatus) -> String {
+    let (local, remote) = shared.negotiated_sdp();
+    format!(
+        "=== Local (receiver) ===\n{}\n\n=== Remote (sender) ===\n{}\n",
+        local.as_deref().unwrap_or("(none)"),
+        remote.as_deref().unwrap_or("(none)")
+    )
+}
+
+/// Replace the value of every `token=` parameter (admin URL, deep links)
+/// and of exported client certificate passwords.
+fn redact_tokens(text: &str) -> String {
+    redact_after(&redact_after(text, "token="), "password: ")
+}
+
+/// Replace the alphanumeric run following each `key` with `REDACTED`.
+fn redact_after(text: &str, key: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find(key) {
+        out.push_str(&rest[..i + key.len()]);
+        out.push_str("REDACTED");
+        rest = &rest[i + key.len()..];
+        let end = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,137 @@
+use serde::Serialize;
+
+/// User-facing kind of failure, each with its own "what to try" hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    PortInUse,
+    PortPermission,
+    AddressUnavailable,
+    NoOutputDevice,
+    OutputDeviceFailed,
+    IceFailed,
+    TlsRejected,
+    DecodeFailed,
+    Other,
+}
+
+impl ErrorCategory {
+    /// Sort a raw error message into a category. Messages come from anyhow
+    /// chains and OS errors, so this matches on their wording (both the Unix
+    /// and the Windows phrasing where they differ).
+    pub fn classify(message: &str) -> Self {
+        let m = message.to_ascii_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| m.contains(n));
+
+        if has(&[
+            "address already in use",
+            "only one usage of each socket address",
+        ]) {
+            ErrorCategory::PortInUse
+        } else if has(&[
+            "permission denied",
+            "access is denied",
+            "forbidden by its access",
+        ]) {
+            ErrorCategory::PortPermission
+        } else if has(&[
+            "cannot assign requested address",
+            "requested address is not valid",
+        ]) {
+            ErrorCategory::AddressUnavailable
+        } else if has(&["no default output device", "output device not found"]) {
+            ErrorCategory::NoOutputDevice
+        } else if has(&[
+            "output config",
+            "sample format",
+            "output stream",
+            "standby output",
+        ]) {
+            ErrorCategory::OutputDeviceFailed
+        } else if has(&["ice connection failed", "ice failed"]) {
+            ErrorCategory::IceFailed
+        } else if has(&["certificate", "handshake", "tls"]) {
+            ErrorCategory::TlsRejected
+        } else if has(&["opus decode", "opus decoder"]) {
+            ErrorCategory::DecodeFailed
+        } else {
+            ErrorCategory::Other
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ErrorCategory::PortInUse => "Port already in use",
+            ErrorCategory::PortPermission => "Not allowed to use this port",
+            ErrorCategory::AddressUnavailable => "Network address not available",
+            ErrorCategory::NoOutputDevice => "No audio output device",
+            ErrorCategory::OutputDeviceFailed => "Audio output failed",
+            ErrorCategory::IceFailed => "Could not connect to the sender",
+            ErrorCategory::TlsRejected => "Secure connection rejected",
+            ErrorCategory::DecodeFailed => "Audio could not be decoded",
+            ErrorCategory::Other => "Something went wrong",
+        }
+    }
+
+    pub fn hint(self) -> &'static str {
+        match self {
+            ErrorCategory::PortInUse => {
+                "Another program (or another LAN Mic receiver) is using the port. Close it, \
+                 or start this receiver with a different --port. It will keep retrying."
+            }
+            ErrorCategory::PortPermission => {
+                "Ports below 1024 need administrator rights, and security software can block \
+                 listening. Use a port above 1024 or allow LAN Mic in your firewall."
+            }
+            ErrorCategory::AddressUnavailable => {
+                "The network interface isn't up yet or the address changed. Check that Wi-Fi or \
+                 Ethernet is connected; the receiver retries automatically."
+            }
+            ErrorCategory::NoOutputDevice => {
+                "Connect speakers or install a virtual audio cable, then press Refresh in \
+                 Settings and pick the device."
+            }
+            ErrorCategory::OutputDeviceFailed => {
+                "The device may be unplugged or in use exclusively by another app. Pick a \
+                 different output, or set a standby device for automatic failover."
+            }
+            ErrorCategory::IceFailed => {
+                "Phone and PC must be on the same network, without client isolation. Try \
+                 turning on the STUN option, or switch the phone off mobile data."
+            }
+            ErrorCategory::TlsRejected => {
+                "The receiver uses a self-signed certificate. Open the sender page in the \
+                 browser and accept the certificate warning, then connect again."
+            }
+            ErrorCategory::DecodeFailed => {
+                "The sender's audio stream is corrupt or uses unsupported settings. Reconnect \
+                 the sender, or choose a different quality preset."
+            }
+            ErrorCategory::Other => {
+                "Check the logs for details, then try stopping and starting again."
+            }
+        }
+    }
+}
+
+/// An error as shown to the user: category, hint, and the raw message.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorReport {
+    pub category: ErrorCategory,
+    pub title: &'static str,
+    pub hint: &'static str,
+    pub detail: String,
+}
+
+impl ErrorReport {
+    pub fn new(detail: impl Into<String>) -> Self {
+        let detail = detail.into();
+        let category = ErrorCategory::classify(&detail);
+        Self {
+            category,
+            title: category.title(),
+            hint: category.hint(),
+            detail,
+        }
+    }
+}
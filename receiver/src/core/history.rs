@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use std::path::PathBuf;
+
+/// Sessions shown in the History view.
+pub const RECENT_LIMIT: usize = 200;
+
+/// One finished sender connection, as stored in the history database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionRecord {
+    /// Unix seconds when the sender connected.
+    pub started_at: u64,
+    /// Unix seconds when it disconnected.
+    pub ended_at: u64,
+    /// Sender address.
+    pub client: String,
+    /// Name the sender gave in its `hello`, e.g. "iPhone (Safari)".
+    pub device: Option<String>,
+    /// Browser `User-Agent` of a WebSocket sender.
+    pub user_agent: Option<String>,
+    /// Audio packets received.
+    pub packets: u64,
+    /// RTP payload bytes received.
+    pub bytes: u64,
+    /// Why the session failed, if it did.
+    pub error: Option<String>,
+}
+
+/// A stored session with its start time formatted in local time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub record: SessionRecord,
+    /// `YYYY-MM-DD HH:MM`.
+    pub started: String,
+}
+
+impl SessionRecord {
+    pub fn duration_secs(&self) -> u64 {
+        self.ended_at.saturating_sub(self.started_at)
+    }
+
+    /// Average audio bitrate over the whole session.
+    pub fn average_kbps(&self) -> f64 {
+        match self.duration_secs() {
+            0 => 0.0,
+            secs => self.bytes as f64 * 8.0 / secs as f64 / 1000.0,
+        }
+    }
+
+    /// Device name, falling back to the user agent.
+    pub fn device_label(&self) -> Option<&str> {
+        self.device.as_deref().or(self.user_agent.as_deref())
+    }
+}
+
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn db_path() -> Option<PathBuf> {
+    crate::profile::current()
+        .data_dir()
+        .map(|d| d.join("history.sqlite3"))
+}
+
+fn open() -> Result<Connection> {
+    let path = db_path().context("no data directory")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    let conn = Connection::open(&path).with_context(|| format!("open {}", path.display()))?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sessions (
+            id         INTEGER PRIMARY KEY,
+            started_at INTEGER NOT NULL,
+            ended_at   INTEGER NOT NULL,
+            client     TEXT NOT NULL,
+            bytes      INTEGER NOT NULL,
+            error      TEXT
+        );",
+    )?;
+    // Version 1 adds who the sender was and how many packets it sent
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version < 1 {
+        conn.execute_batch(
+            "ALTER TABLE sessions ADD COLUMN device TEXT;
+             ALTER TABLE sessions ADD COLUMN user_agent TEXT;
+             ALTER TABLE sessions ADD COLUMN packets INTEGER NOT NULL DEFAULT 0;
+             PRAGMA user_version = 1;",
+        )?;
+    }
+    Ok(conn)
+}
+
+pub fn record(session: &SessionRecord) -> Result<()> {
+    open()?.execute(
+        "INSERT INTO sessions
+             (started_at, ended_at, client, device, user_agent, packets, bytes, error)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            session.started_at as i64,
+            session.ended_at as i64,
+            session.client,
+            session.device,
+            session.user_agent,
+            session.packets as i64,
+            session.bytes as i64,
+            session.error,
+        ],
+    )?;
+    Ok(())
+}
+
+/// The most recent sessions, newest first.
+pub fn recent(limit: usize) -> Result<Vec<HistoryEntry>> {
+    let conn = open()?;
+    let mut stmt = conn.prepare(
+        "SELECT started_at, ended_at, client, device, user_agent, packets, bytes, error,
+                strftime('%Y-%m-%d %H:%M', started_at, 'unixepoch', 'localtime')
+         FROM sessions ORDER BY started_at DESC, id DESC LIMIT ?1",
+    )?;
+    let rows = stmt.query_map([limit as i64], |row| {
+        Ok(HistoryEntry {
+            record: SessionRecord {
+                started_at: row.get::<_, i64>(0)? as u64,
+                ended_at: row.get::<_, i64>(1)? as u64,
+                client: row.get(2)?,
+                device: row.get(3)?,
+                user_agent: row.get(4)?,
+                packets: row.get::<_, i64>(5)? as u64,
+                bytes: row.get::<_, i64>(6)? as u64,
+                error: row.get(7)?,
+            },
+            started: row.get(8)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Delete every stored session.
+pub fn clear() -> Result<()> {
+    open()?.execute("DELETE FROM sessions", [])?;
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use std::time::{Duration, Instant};
+
+/// Jitter target the Auto preset starts from; safe on a busy Wi-Fi network.
+pub const AUTO_START_MS: u32 = 150;
+
+/// Smallest jitter target Auto will settle on.
+const MIN_TARGET_MS: u32 = 20;
+
+/// Largest jitter target Auto will back off to.
+const MAX_TARGET_MS: u32 = 400;
+
+/// Shrink per clean interval while learning.
+const STEP_DOWN_MS: u32 = 10;
+
+/// Growth per interval that saw underruns.
+const STEP_UP_MS: u32 = 30;
+
+/// How long Auto keeps probing for a smaller target once audio is flowing.
+const LEARN_PERIOD: Duration = Duration::from_secs(60);
+
+/// How often the underrun count is sampled.
+pub const TUNE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Converges the jitter-buffer target for the Auto quality preset.
+///
+/// For the first minute with senders connected the target shrinks by a step
+/// every clean interval. The first underrun ends the search: the target
+/// backs off above the point that failed and stays there. After the learning
+/// period the target only grows, and only if underruns come back.
+pub struct AutoLatency {
+    target_ms: u32,
+    learning_since: Option<Instant>,
+    settled: bool,
+    last_underruns: u64,
+}
+
+impl AutoLatency {
+    pub fn new(underruns: u64) -> Self {
+        Self {
+            target_ms: AUTO_START_MS,
+            learning_since: None,
+            settled: false,
+            last_underruns: underruns,
+        }
+    }
+
+    pub fn target_ms(&self) -> u32 {
+        self.target_ms
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.settled
+    }
+
+    /// Feed the mixer's running underrun count; call every [`TUNE_INTERVAL`]
+    /// while senders are connected. Returns the new target if it changed.
+    pub fn tick(&mut self, underruns: u64) -> Option<u32> {
+        self.tick_at(underruns, Instant::now())
+    }
+
+    fn tick_at(&mut self, underruns: u64, now: Instant) -> Option<u32> {
+        let new_underruns = underruns.saturating_sub(self.last_underruns);
+        self.last_underruns = underruns;
+        let started = *self.learning_since.get_or_insert(now);
+        if !self.settled && now.duration_since(started) >= LEARN_PERIOD {
+            self.settled = true;
+        }
+
+        let target = if new_underruns > 0 {
+            self.settled = true;
+            (self.target_ms + STEP_UP_MS).min(MAX_TARGET_MS)
+        } else if !self.settled {
+            self.target_ms
+                .saturating_sub(STEP_DOWN_MS)
+                .max(MIN_TARGET_MS)
+        } else {
+            self.target_ms
+        };
+
+        if target == self.target_ms {
+            return None;
+        }
+        self.target_ms = target;
+        Some(target)
+    }
+
+    /// Forget the idle gap; underruns from senders leaving don't count.
+    pub fn resync(&mut self, underruns: u64) {
+        self.last_underruns = underruns;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_down_while_learning() {
+        let mut auto = AutoLatency::new(7);
+        assert_eq!(auto.tick(7), Some(AUTO_START_MS - STEP_DOWN_MS));
+        assert_eq!(auto.tick(7), Some(AUTO_START_MS - 2 * STEP_DOWN_MS));
+        while auto.tick(7).is_some() {}
+        assert_eq!(auto.target_ms(), MIN_TARGET_MS);
+        assert!(!auto.is_settled());
+    }
+
+    #[test]
+    fn backs_off_on_underruns() {
+        let mut auto = AutoLatency::new(0);
+        let probed = auto.tick(0).unwrap();
+        assert_eq!(auto.tick(2), Some(probed + STEP_UP_MS));
+        assert!(auto.is_settled());
+        // Clean intervals no longer shrink it
+        assert_eq!(auto.tick(2), None);
+        assert_eq!(auto.tick(3), Some(probed + 2 * STEP_UP_MS));
+        for underruns in 4..30 {
+            auto.tick(underruns);
+        }
+        assert_eq!(auto.target_ms(), MAX_TARGET_MS);
+    }
+
+    #[test]
+    fn settles_after_the_learn_period() {
+        let start = Instant::now();
+        let mut auto = AutoLatency::new(0);
+        assert!(auto.tick_at(0, start).is_some());
+        assert!(auto.tick_at(0, start + LEARN_PERIOD / 2).is_some());
+        assert!(!auto.is_settled());
+
+        let learned = auto.target_ms();
+        assert_eq!(auto.tick_at(0, start + LEARN_PERIOD), None);
+        assert!(auto.is_settled());
+        assert_eq!(auto.tick_at(0, start + 2 * LEARN_PERIOD), None);
+        assert_eq!(auto.target_ms(), learned);
+    }
+}
@@ -0,0 +1,205 @@
+use super::logs::{self, LogEntry};
+use crate::config_store;
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Size at which the size-rotated log moves to `app.1.log`.
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Rotated files kept besides the current one.
+const KEEP_FILES: usize = 7;
+
+/// When the on-disk log starts a new file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Rotation {
+    /// `app-YYYY-MM-DD.log`, one per local day.
+    #[default]
+    Daily,
+    /// `app.log`, moved to `app.1.log` … when it reaches 5 MB.
+    BySize,
+}
+
+impl Rotation {
+    pub const ALL: [Rotation; 2] = [Rotation::Daily, Rotation::BySize];
+}
+
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Rotation::Daily => "New file every day",
+            Rotation::BySize => "New file every 5 MB",
+        })
+    }
+}
+
+/// Whether the in-app log is mirrored to disk, persisted per profile in
+/// `log_file.json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFileSettings {
+    pub enabled: bool,
+    pub rotation: Rotation,
+}
+
+const STORE: &str = "log_file.json";
+
+/// Folder the log files go to.
+pub fn log_dir() -> Option<PathBuf> {
+    crate::profile::current()
+        .config_dir()
+        .map(|d| d.join("logs"))
+}
+
+/// The saved settings; a missing or unreadable file means off.
+pub fn load() -> LogFileSettings {
+    config_store::load(STORE).unwrap_or_default()
+}
+
+pub fn save(settings: &LogFileSettings) -> Result<()> {
+    config_store::save(STORE, settings)
+}
+
+// ---------------------------------------------------------------------------
+// Sink
+// ---------------------------------------------------------------------------
+
+static SINK: Mutex<Option<RotatingLog>> = parking_lot::const_mutex(None);
+
+/// Start mirroring with the saved settings. Call once at startup.
+pub fn init() {
+    if let Err(e) = configure(load()) {
+        log::warn!("Failed to open the log file: {e:#}");
+    }
+}
+
+/// Apply new settings, opening or closing the file as needed.
+pub fn configure(settings: LogFileSettings) -> Result<()> {
+    let mut sink = SINK.lock();
+    *sink = None;
+    if settings.enabled {
+        let dir = log_dir().context("no config directory")?;
+        *sink = Some(RotatingLog::open(dir, settings.rotation)?);
+    }
+    Ok(())
+}
+
+/// Append `entry` to the log file, if mirroring is on.
+pub fn write(entry: &LogEntry) {
+    if let Some(log) = SINK.lock().as_mut() {
+        log.write(entry);
+    }
+}
+
+/// The current log file and what decides when to leave it.
+struct RotatingLog {
+    dir: PathBuf,
+    rotation: Rotation,
+    /// `None` only while rotating, so the file isn't open when renamed.
+    file: Option<LineWriter<File>>,
+    path: PathBuf,
+    /// Bytes in the current file, for size rotation.
+    written: u64,
+    /// Local date of the current file, for daily rotation.
+    day: String,
+}
+
+impl RotatingLog {
+    fn open(dir: PathBuf, rotation: Rotation) -> Result<Self> {
+        std::fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+        let day = logs::local_date(logs::now_ms());
+        let path = match rotation {
+            Rotation::Daily => dir.join(format!("app-{day}.log")),
+            Rotation::BySize => dir.join("app.log"),
+        };
+        let file = File::options()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("open {}", path.display()))?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        prune(&dir, rotation);
+        Ok(Self {
+            dir,
+            rotation,
+            file: Some(LineWriter::new(file)),
+            path,
+            written,
+            day,
+        })
+    }
+
+    fn write(&mut self, entry: &LogEntry) {
+        let due = match self.rotation {
+            Rotation::Daily => logs::local_date(entry.timestamp) != self.day,
+            Rotation::BySize => self.written >= MAX_FILE_BYTES,
+        };
+        if due {
+            if let Err(e) = self.rotate() {
+                log::warn!("Failed to rotate {}: {e:#}", self.path.display());
+            }
+        }
+        let Some(file) = &mut self.file else {
+            return;
+        };
+        let line = format!("{entry}\n");
+        match file.write_all(line.as_bytes()) {
+            Ok(()) => self.written += line.len() as u64,
+            Err(e) => log::warn!("Failed to write {}: {e}", self.path.display()),
+        }
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        // Windows can't rename a file that is still open
+        self.file = None;
+        let shifted = match self.rotation {
+            Rotation::Daily => Ok(()),
+            Rotation::BySize => shift_size_logs(&self.dir),
+        };
+        // Reopen even if shifting failed, to keep logging
+        *self = Self::open(self.dir.clone(), self.rotation)?;
+        shifted.context("shift rotated logs")
+    }
+}
+
+/// Drop `app.7.log`, then move `app.6.log` → `app.7.log` … `app.log` →
+/// `app.1.log`.
+fn shift_size_logs(dir: &Path) -> std::io::Result<()> {
+    let _ = std::fs::remove_file(dir.join(format!("app.{KEEP_FILES}.log")));
+    for n in (1..KEEP_FILES).rev() {
+        let from = dir.join(format!("app.{n}.log"));
+        if from.exists() {
+            std::fs::rename(&from, dir.join(format!("app.{}.log", n + 1)))?;
+        }
+    }
+    std::fs::rename(dir.join("app.log"), dir.join("app.1.log"))
+}
+
+/// Delete daily files beyond today's and [`KEEP_FILES`] more. Size-rotated
+/// files are capped as they are renamed.
+fn prune(dir: &Path, rotation: Rotation) {
+    if rotation != Rotation::Daily {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut logs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("app-") && n.ends_with(".log"))
+        })
+        .collect();
+    // Dates sort oldest first
+    logs.sort();
+    let excess = logs.len().saturating_sub(KEEP_FILES + 1);
+    for path in &logs[..excess] {
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,171 @@
+use serde::Serialize;
+use std::fmt;
+use std::panic::Location;
+use std::sync::OnceLock;
+
+/// Severity of an in-app log entry, most severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+    ];
+
+    /// Level of a message logged without one. Most of the core logs plain
+    /// sentences, so this goes by their wording, like
+    /// [`super::errors::ErrorCategory::classify`].
+    pub fn classify(message: &str) -> Self {
+        let m = message.to_ascii_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| m.contains(n));
+
+        if has(&["failed", "error", "cannot", "could not", "panicked"]) {
+            LogLevel::Error
+        } else if has(&[
+            "invalid",
+            "ignoring",
+            "rejected",
+            "denied",
+            "dropped",
+            "timed out",
+            "lost",
+            "falling back",
+        ]) {
+            LogLevel::Warn
+        } else {
+            LogLevel::Info
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogLevel::Error => "Errors",
+            LogLevel::Warn => "Warnings",
+            LogLevel::Info => "Info",
+            LogLevel::Debug => "Everything",
+        })
+    }
+}
+
+/// One line of the in-app log.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogEntry {
+    /// Unix time in milliseconds.
+    pub timestamp: u64,
+    pub level: LogLevel,
+    /// Module that logged it, e.g. `rtp` or `webrtc_session`.
+    pub target: &'static str,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// An entry stamped now, with the caller's module as its target.
+    #[track_caller]
+    pub fn new(level: LogLevel, message: String) -> Self {
+        Self {
+            timestamp: now_ms(),
+            level,
+            target: target_of(Location::caller().file()),
+            message,
+        }
+    }
+
+    /// Local wall-clock time as `HH:MM:SS.mmm`.
+    pub fn clock(&self) -> String {
+        let local_ms = self.timestamp as i64 + local_offset_secs() * 1000;
+        let day_ms = local_ms.rem_euclid(86_400_000);
+        format!(
+            "{:02}:{:02}:{:02}.{:03}",
+            day_ms / 3_600_000,
+            day_ms / 60_000 % 60,
+            day_ms / 1000 % 60,
+            day_ms % 1000
+        )
+    }
+
+    /// Whether `query` (lowercase) appears in the message or target.
+    pub fn matches(&self, query: &str) -> bool {
+        query.is_empty()
+            || self.target.contains(query)
+            || self.message.to_lowercase().contains(query)
+    }
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<5} [{}] {}",
+            self.clock(),
+            self.level.label(),
+            self.target,
+            self.message
+        )
+    }
+}
+
+/// Current unix time in milliseconds.
+pub fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Local date of a unix time in milliseconds, as `YYYY-MM-DD`.
+pub fn local_date(unix_ms: u64) -> String {
+    let secs = (unix_ms / 1000) as i64 + local_offset_secs();
+    match time::OffsetDateTime::from_unix_timestamp(secs) {
+        Ok(t) => format!("{}-{:02}-{:02}", t.year(), u8::from(t.month()), t.day()),
+        Err(_) => "unknown".to_string(),
+    }
+}
+
+/// `src/core/signaling/rtp.rs` → `rtp`, `src/core/mod.rs` → `core`.
+fn target_of(file: &'static str) -> &'static str {
+    let mut parts = file.trim_end_matches(".rs").rsplit(['/', '\\']);
+    match parts.next() {
+        Some("mod") => parts.next().unwrap_or("app"),
+        Some(name) => name,
+        None => "app",
+    }
+}
+
+/// Seconds east of UTC, asked of SQLite once: `time` can only read the
+/// local offset with a feature this build doesn't enable. A DST change while
+/// running shifts the clock until the next launch.
+fn local_offset_secs() -> i64 {
+    static OFFSET: OnceLock<i64> = OnceLock::new();
+    *OFFSET.get_or_init(|| {
+        rusqlite::Connection::open_in_memory()
+            .and_then(|db| {
+                db.query_row(
+                    "SELECT strftime('%s', 'now', 'localtime') - strftime('%s', 'now')",
+                    [],
+                    |row| row.get(0),
+                )
+            })
+            .unwrap_or(0)
+    })
+}
@@ -0,0 +1,116 @@
+use std::fmt::Write;
+
+/// Samples per second of the mixer's queues.
+const SAMPLE_RATE: f64 = 48_000.0;
+
+/// Receiver health for the opt-in Prometheus `/metrics` route, read in one
+/// go by [`super::SharedStatus::metrics`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Metrics {
+    pub listening: bool,
+    pub connected_clients: usize,
+    pub session_seconds: f64,
+    pub audio_packets: u64,
+    pub audio_bytes: u64,
+    pub decoded_frames: u64,
+    pub concealed_packets: u64,
+    pub decode_errors: u64,
+    pub dropped_samples: u64,
+    pub rtp_packets: u64,
+    pub rtp_invalid_packets: u64,
+    /// Deepest sender queue in the mixer, in samples.
+    pub queue_depth: usize,
+    /// Mixer underruns since it started listening.
+    pub underruns: u64,
+}
+
+impl Metrics {
+    /// Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: f64| {
+            let _ = writeln!(out, "# HELP lanmic_{name} {help}");
+            let _ = writeln!(out, "# TYPE lanmic_{name} {kind}");
+            let _ = writeln!(out, "lanmic_{name} {value}");
+        };
+        metric(
+            "listening",
+            "gauge",
+            "Whether the receiver is accepting senders.",
+            f64::from(u8::from(self.listening)),
+        );
+        metric(
+            "connected_clients",
+            "gauge",
+            "Senders currently connected.",
+            self.connected_clients as f64,
+        );
+        metric(
+            "session_duration_seconds",
+            "gauge",
+            "Time since the current session's first sender connected.",
+            self.session_seconds,
+        );
+        metric(
+            "audio_packets_total",
+            "counter",
+            "Audio packets received.",
+            self.audio_packets as f64,
+        );
+        metric(
+            "audio_bytes_total",
+            "counter",
+            "Audio payload bytes received.",
+            self.audio_bytes as f64,
+        );
+        metric(
+            "decoded_frames_total",
+            "counter",
+            "Audio frames decoded.",
+            self.decoded_frames as f64,
+        );
+        metric(
+            "concealed_packets_total",
+            "counter",
+            "Lost packets filled in by FEC or loss concealment.",
+            self.concealed_packets as f64,
+        );
+        metric(
+            "decode_errors_total",
+            "counter",
+            "Corrupt audio packets skipped by the decoder.",
+            self.decode_errors as f64,
+        );
+        metric(
+            "dropped_samples_total",
+            "counter",
+            "Samples dropped because a sender's queue was full.",
+            self.dropped_samples as f64,
+        );
+        metric(
+            "rtp_packets_total",
+            "counter",
+            "Packets received on the plain RTP listener.",
+            self.rtp_packets as f64,
+        );
+        metric(
+            "rtp_invalid_packets_total",
+            "counter",
+            "Packets on the plain RTP listener that were not valid RTP.",
+            self.rtp_invalid_packets as f64,
+        );
+        metric(
+            "queue_depth_seconds",
+            "gauge",
+            "Audio buffered in the deepest sender queue.",
+            self.queue_depth as f64 / SAMPLE_RATE,
+        );
+        metric(
+            "underruns_total",
+            "counter",
+            "Times the output ran out of buffered audio.",
+            self.underruns as f64,
+        );
+        out
+    }
+}
@@ -1,15 +1,63 @@
+pub mod access;
+pub mod diagnostics;
+pub mod errors;
+pub mod history;
+pub mod latency;
+pub mod log_file;
+pub mod logs;
+pub mod metrics;
+pub mod quality;
+pub mod recovery;
+pub mod session_log;
 pub mod signaling;
+pub mod stats;
+pub mod trust;
 
 use crate::audio;
-use anyhow::Result;
-use crossbeam_queue::ArrayQueue;
+use crate::audio::captions::{CaptionEvent, Captioner};
+use crate::audio::dsp::AgcConfig;
+use crate::audio::mixer::{ClientId, Mixer};
+use crate::audio::monitor::MonitorTap;
+use crate::audio::snapcast::{SnapcastOutput, SnapcastTarget};
+use crate::recorder::{self, Recorder, RecordingFormat};
+use access::AccessList;
+use anyhow::{Context, Result};
+use errors::ErrorReport;
+use latency::AutoLatency;
+use logs::{LogEntry, LogLevel};
 use parking_lot::Mutex;
-use std::collections::VecDeque;
+use quality::QualityPreset;
+use serde::Serialize;
+use session_log::SessionLog;
+use signaling::control::ControlMessage;
+use stats::SessionStats;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot, Notify};
 use tokio_util::sync::CancellationToken;
+use trust::{ApprovalRequest, TrustedDevice};
 
 /// Maximum log lines retained in memory.
-const MAX_LOG_LINES: usize = 1500;
+pub const MAX_LOG_LINES: usize = 1500;
+
+/// Core events buffered for a slow subscriber; it only needs to know that
+/// something changed, so missing some is harmless.
+const EVENT_CAPACITY: usize = 64;
+
+/// Maximum voice-activity segments retained per sender.
+const MAX_VOICE_SEGMENTS: usize = 500;
+
+/// Most recent caption lines in a snapshot; the full transcript stays in
+/// the core for export.
+const SHOWN_CAPTIONS: usize = 50;
+
+/// Level meter ballistics per decoded frame (~20 ms): the peak falls by
+/// about 1 dB per frame, the RMS settles within a few frames.
+const LEVEL_PEAK_RELEASE: f32 = 0.89;
+const LEVEL_RMS_SMOOTHING: f32 = 0.3;
 
 // ---------------------------------------------------------------------------
 // Commands sent from the UI to the core runtime
@@ -21,11 +69,183 @@ pub enum CoreCommand {
         bind_addr: String,
         output_device: Option<String>,
         use_stun: bool,
+        /// Custom STUN/TURN servers, used in addition to the public STUN
+        /// server when `use_stun` is on.
+        ice_servers: Vec<signaling::IceServer>,
+        allow_multiple_clients: bool,
+        /// Optional second device playing a local copy of the main mix.
+        monitor_device: Option<String>,
+        monitor_gain: f32,
+        monitor_muted: bool,
+        quality: QualityPreset,
+        /// Fixed latency target overriding the preset's buffering.
+        latency_ms: Option<u32>,
+        /// Ask before accepting senders that aren't trusted yet.
+        require_approval: bool,
+        /// Another receiver to forward the incoming stream to.
+        relay_target: Option<String>,
+        /// Snapcast server to feed the main mix to, for synchronized
+        /// multi-room playback (`tcp://HOST:PORT` or a pipe path).
+        snapcast_target: Option<String>,
+        /// Vosk model folder for live captions of the main mix.
+        captions_model: Option<String>,
+        /// Hot standby for the main output.
+        standby_device: Option<String>,
+        /// Cancel the monitor speakers' echo picked up by senders.
+        echo_cancellation: bool,
+        /// Announce the receiver over mDNS; off keeps it off the network's
+        /// service lists (QR and manual connections still work).
+        advertise_mdns: bool,
+        /// Broadcast a UDP discovery beacon, for networks that block mDNS.
+        udp_beacon: bool,
+        /// Accept length-prefixed Opus or PCM over plain UDP, for senders
+        /// that can't do WebRTC.
+        udp_ingest: Option<signaling::udp_session::UdpIngest>,
+        /// Stop by itself after this many minutes without a sender.
+        idle_stop_minutes: Option<u32>,
+        /// Keep a sender's session this long after its WebSocket drops, so
+        /// it can resume without rescanning (0 = end it right away).
+        reconnect_grace_secs: u32,
+        /// Drop a sender whose WebSocket stays silent this long.
+        keepalive_timeout_secs: u32,
     },
     Stop,
     ChangeOutputDevice {
         device_name: Option<String>,
     },
+    /// Device to switch the main output to if it fails (`None` = off).
+    SetStandbyDevice {
+        device_name: Option<String>,
+    },
+    SetClientGain {
+        client_id: ClientId,
+        gain: f32,
+    },
+    SetClientMute {
+        client_id: ClientId,
+        muted: bool,
+    },
+    /// Enable (`Some`) or disable (`None`) the local monitor output.
+    SetMonitorDevice {
+        device_name: Option<String>,
+    },
+    /// Monitor volume only — the main/cable output is unaffected.
+    SetMonitorGain(f32),
+    SetMonitorMute(bool),
+    /// Master volume of everything played (0–2, above 1 boosts).
+    SetGain(f32),
+    SetMute(bool),
+    SetEchoCancellation(bool),
+    /// Level quiet and loud senders automatically (`None` = off); applies
+    /// right away while running and to later sessions.
+    SetAgc(Option<AgcConfig>),
+    /// Stereo width of stereo senders (0 = mono, 1 = as sent, up to 1.5);
+    /// applies right away while running and to later sessions.
+    SetStereoWidth(f32),
+    /// Record the main mix to a WAV file (`None` = default location).
+    StartRecording {
+        path: Option<PathBuf>,
+    },
+    StopRecording,
+    /// Format of recordings started from now on.
+    SetRecordingFormat(RecordingFormat),
+    /// Write the current/last session's per-second stats to CSV
+    /// (`None` = default location).
+    ExportStats {
+        path: Option<PathBuf>,
+    },
+    /// Write the buffered log lines to a text file (`None` = default location).
+    ExportLogs {
+        path: Option<PathBuf>,
+    },
+    /// Write the live-caption transcript to a text file (`None` = default
+    /// location).
+    ExportTranscript {
+        path: Option<PathBuf>,
+    },
+    /// Zip logs, sanitized settings, audio devices, SDP and version info
+    /// for a bug report (`None` = default location).
+    CreateDiagnosticBundle {
+        path: Option<PathBuf>,
+    },
+    /// Mark a sender as priority: it ducks the others while talking.
+    SetClientPriority {
+        client_id: ClientId,
+        priority: bool,
+    },
+    /// Ask a sender to cap its bitrate (`None` lifts the cap).
+    SetClientBitrateCap {
+        client_id: ClientId,
+        max_kbps: Option<u32>,
+    },
+    /// Route a sender to its own output device (`None` = main output).
+    SetClientOutput {
+        client_id: ClientId,
+        device_name: Option<String>,
+    },
+    /// Answer a pending sender approval; allowed devices are remembered.
+    ResolveApproval {
+        request_id: u64,
+        allow: bool,
+    },
+    ForgetTrustedDevice {
+        device_id: String,
+    },
+    /// Replace the sender IP allow/deny rules (applies to new connections).
+    SetAccessList(AccessList),
+    /// Issue a client certificate for `--mtls` and save it as PKCS#12
+    /// (`None` = default location).
+    ExportClientCertificate {
+        path: Option<PathBuf>,
+    },
+    /// Move the HTTPS listener to another address/port without restarting
+    /// the app; URLs, QR codes and discovery follow.
+    Rebind {
+        bind_addr: String,
+    },
+    /// Put this network interface's address in URLs, QR codes and mDNS
+    /// (`None` = pick automatically) and remember the choice.
+    SetInterface {
+        interface: Option<String>,
+    },
+    /// Serve the TLS certificate in these PEM files (`None` = the generated
+    /// self-signed one) and remember the choice.
+    SetCertificate {
+        cert: Option<signaling::user_cert::UserCertificate>,
+    },
+    /// Start again with the most recent `Start` settings (or defaults).
+    /// Used by remote controls that don't know the UI's settings.
+    Resume,
+}
+
+impl CoreCommand {
+    /// `Start` with default device and settings on the current listener.
+    fn default_start(listen: std::net::SocketAddr) -> Self {
+        CoreCommand::Start {
+            bind_addr: listen.to_string(),
+            output_device: None,
+            use_stun: false,
+            ice_servers: Vec::new(),
+            allow_multiple_clients: false,
+            monitor_device: None,
+            monitor_gain: 1.0,
+            monitor_muted: false,
+            quality: QualityPreset::default(),
+            latency_ms: None,
+            require_approval: false,
+            relay_target: None,
+            snapcast_target: None,
+            captions_model: None,
+            standby_device: None,
+            echo_cancellation: false,
+            advertise_mdns: true,
+            udp_beacon: false,
+            udp_ingest: None,
+            idle_stop_minutes: None,
+            reconnect_grace_secs: signaling::DEFAULT_RECONNECT_GRACE_SECS,
+            keepalive_timeout_secs: signaling::DEFAULT_KEEPALIVE_TIMEOUT_SECS,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -44,6 +264,9 @@ impl PartialEq for CoreController {
 }
 
 impl CoreController {
+    /// The error hands back the unsent command, which is as large as
+    /// `Start`; it only happens once the runtime has shut down.
+    #[allow(clippy::result_large_err)]
     pub fn send(
         &self,
         cmd: CoreCommand,
@@ -56,86 +279,1104 @@ impl CoreController {
 // Shared status — thread-safe state visible to both UI and core
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Default, Clone, PartialEq)]
+/// One talking or silence span on a sender's voice-activity timeline.
+/// Offsets are milliseconds since the client connected.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VoiceSegment {
+    pub start_ms: u64,
+    /// `None` while the segment is still open.
+    pub end_ms: Option<u64>,
+    pub talking: bool,
+}
+
+/// A sender's voice activity in the current (or last) session.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VoiceTimeline {
+    pub client: ClientId,
+    pub addr: String,
+    /// How long the sender has been connected, or was until it left.
+    pub duration_ms: u64,
+    /// Oldest first. Once more than [`MAX_VOICE_SEGMENTS`] have been seen
+    /// the oldest are dropped, so the first may start after 0.
+    pub segments: Vec<VoiceSegment>,
+}
+
+#[derive(Debug)]
+struct ClientTimeline {
+    client: ClientId,
+    addr: String,
+    connected: Instant,
+    /// Set when the sender disconnects.
+    ended_ms: Option<u64>,
+    segments: VecDeque<VoiceSegment>,
+}
+
+impl ClientTimeline {
+    /// Starts in a silence segment until speech is detected.
+    fn new(client: ClientId, addr: String) -> Self {
+        Self {
+            client,
+            addr,
+            connected: Instant::now(),
+            ended_ms: None,
+            segments: VecDeque::from([VoiceSegment {
+                start_ms: 0,
+                end_ms: None,
+                talking: false,
+            }]),
+        }
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.ended_ms
+            .unwrap_or_else(|| self.connected.elapsed().as_millis() as u64)
+    }
+
+    /// Record a transition; false if `talking` is no change.
+    fn mark(&mut self, talking: bool) -> bool {
+        let now_ms = self.now_ms();
+        if let Some(last) = self.segments.back_mut() {
+            if last.talking == talking {
+                return false;
+            }
+            last.end_ms = Some(now_ms);
+        }
+        self.segments.push_back(VoiceSegment {
+            start_ms: now_ms,
+            end_ms: None,
+            talking,
+        });
+        // Offsets stay relative to the connect time, so dropping the
+        // oldest span leaves a gap at the start instead of moving 0
+        if self.segments.len() > MAX_VOICE_SEGMENTS {
+            self.segments.pop_front();
+        }
+        true
+    }
+
+    fn end(&mut self) {
+        let now_ms = self.now_ms();
+        self.ended_ms = Some(now_ms);
+        if let Some(last) = self.segments.back_mut() {
+            last.end_ms.get_or_insert(now_ms);
+        }
+    }
+
+    fn snapshot(&self) -> VoiceTimeline {
+        VoiceTimeline {
+            client: self.client,
+            addr: self.addr.clone(),
+            duration_ms: self.now_ms(),
+            segments: self.segments.iter().cloned().collect(),
+        }
+    }
+}
+
+/// A finished caption utterance. The offset is milliseconds since
+/// captions started.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CaptionLine {
+    pub offset_ms: u64,
+    pub text: String,
+}
+
+/// The last exported mTLS client identity. The password is only ever shown
+/// in the UI, never logged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedCertificate {
+    pub path: String,
+    pub password: String,
+}
+
+/// A sender's decoded input level, as a fraction of full scale.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct AudioLevel {
+    /// Recent peak, held briefly and then released.
+    pub peak: f32,
+    /// Smoothed RMS.
+    pub rms: f32,
+}
+
+impl AudioLevel {
+    /// Fold one decoded frame into the running level.
+    fn update(&mut self, samples: &[i16]) {
+        if samples.is_empty() {
+            return;
+        }
+        let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap_or(0) as f32 / 32768.0;
+        let sum: f64 = samples.iter().map(|&s| f64::from(s) * f64::from(s)).sum();
+        let rms = (sum / samples.len() as f64).sqrt() as f32 / 32768.0;
+        self.peak = peak.max(self.peak * LEVEL_PEAK_RELEASE);
+        self.rms += (rms - self.rms) * LEVEL_RMS_SMOOTHING;
+    }
+}
+
+/// A connected sender as shown in the clients panel.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClientStatus {
+    pub id: ClientId,
+    pub addr: String,
+    /// Name from the sender's `hello`, once it has sent one.
+    pub device: Option<String>,
+    /// Negotiated audio codec of a WebRTC or RTP sender, e.g. "PCMU".
+    pub codec: Option<String>,
+    /// The sender has an open control data channel.
+    pub control: bool,
+    /// Battery level (0–1) the sender reported.
+    pub battery: Option<f32>,
+    pub charging: bool,
+    /// The user muted the microphone on the sender itself.
+    pub sender_muted: bool,
+    /// Bitrate cap asked of the sender over its control channel.
+    pub bitrate_cap_kbps: Option<u32>,
+    pub gain: f32,
+    pub muted: bool,
+    /// Ducks the other senders on its output while talking.
+    pub priority: bool,
+    /// Dedicated output device, or `None` when mixed into the main output.
+    pub output: Option<String>,
+    /// WebRTC peer connection state, e.g. "Connected" or "reconnecting";
+    /// `None` for senders without one.
+    pub pc_state: Option<String>,
+    /// RTP interarrival jitter.
+    pub jitter_ms: f32,
+    /// Receive bitrate from the WebRTC stats (0 for other senders).
+    pub bitrate_kbps: f32,
+    /// Packet loss from the sender's RTCP reports, once one has arrived.
+    pub packet_loss_pct: Option<f32>,
+    /// RTP payload bytes received.
+    pub bytes: u64,
+    /// Audio packets received.
+    pub packets: u64,
+    /// Input level, updated per decoded frame.
+    pub level: AudioLevel,
+}
+
+/// The listener's port when the requested one was in use by another
+/// program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PortFallback {
+    pub requested: u16,
+    pub port: u16,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
 pub struct StatusSnapshot {
     pub server_running: bool,
     pub ws_url: Option<String>,
+    /// Token-protected web admin dashboard (`/admin?token=…`).
+    pub admin_url: Option<String>,
+    /// Sender page URL per candidate interface, as `(interface, url)`.
+    pub sender_urls: Vec<(String, String)>,
+    /// Unencrypted `http://` URL per interface with `--http-port`; the
+    /// WebSocket is at `ws://…/ws` on the same port.
+    pub plain_urls: Vec<(String, String)>,
+    /// `lanmic://` deep link per candidate interface, for native senders.
+    pub deep_links: Vec<(String, String)>,
     pub client_connected: bool,
     pub client_addr: Option<String>,
+    pub clients: Vec<ClientStatus>,
+    /// Device the main output is actually playing on while running, which
+    /// differs from the selection after a failover.
+    pub output_device: Option<String>,
+    /// Output devices present, refreshed as devices come and go.
+    pub output_devices: Vec<String>,
+    pub monitor_device: Option<String>,
+    /// Path of the in-progress recording, if any.
+    pub recording_path: Option<String>,
+    /// How long the in-progress recording has been running (0 when idle).
+    pub recording_elapsed_ms: u64,
+    /// Peer connection state of the first connected sender.
     pub pc_state: Option<String>,
-    pub last_error: Option<String>,
+    /// Why the HTTPS listener is down (bind failure, retrying), if it is.
+    pub http_error: Option<ErrorReport>,
+    /// Set while the listener is on another port because the requested
+    /// one was taken.
+    pub port_fallback: Option<PortFallback>,
+    /// Connection state of the relay to another receiver, if enabled.
+    pub relay_state: Option<String>,
+    /// Current jitter-buffer target while running.
+    pub jitter_target_ms: Option<u32>,
+    pub last_error: Option<ErrorReport>,
     pub audio_packets: u64,
-    pub log_lines: Vec<String>,
+    /// Lost packets filled in by Opus FEC or loss concealment.
+    pub concealed_packets: u64,
+    /// Corrupt packets the Opus decoder rejected and that were skipped.
+    pub decode_errors: u64,
+    /// Total receive bitrate, worst packet loss and worst jitter across
+    /// connected senders.
+    pub bitrate_kbps: f32,
+    pub packet_loss_pct: Option<f32>,
+    pub jitter_ms: f32,
+    /// Packets received on the plain RTP listener, and how many of them
+    /// were not valid RTP.
+    pub rtp_packets: u64,
+    pub rtp_invalid_packets: u64,
+    /// Where the last statistics export was written.
+    pub stats_export_path: Option<String>,
+    pub log_lines: Vec<LogEntry>,
+    /// Milliseconds since the current client connected (0 when idle).
+    pub session_elapsed_ms: u64,
+    /// Voice activity per sender; kept after the session ends so the Stats
+    /// view still shows it.
+    pub voice_timelines: Vec<VoiceTimeline>,
+    /// Live captions are running.
+    pub captions_running: bool,
+    /// Latest finished caption lines, oldest first.
+    pub captions: Vec<CaptionLine>,
+    /// The utterance being recognized right now.
+    pub caption_partial: Option<String>,
+    /// Not serialized: the snapshot also feeds the admin dashboard and
+    /// diagnostic bundles.
+    #[serde(skip)]
+    pub client_certificate: Option<ExportedCertificate>,
+    pub trusted_devices: Vec<TrustedDevice>,
+    /// Sender address ranges allowed or denied to connect.
+    pub access: AccessList,
+    /// Senders waiting for the user to allow or deny them.
+    pub pending_approvals: Vec<ApprovalRequest>,
 }
 
 #[derive(Debug, Default)]
 struct Status {
     server_running: bool,
     ws_url: Option<String>,
+    admin_url: Option<String>,
+    sender_urls: Vec<(String, String)>,
+    plain_urls: Vec<(String, String)>,
+    deep_links: Vec<(String, String)>,
     client_connected: bool,
     client_addr: Option<String>,
-    pc_state: Option<String>,
-    last_error: Option<String>,
+    clients: Vec<ClientStatus>,
+    output_device: Option<String>,
+    output_devices: Vec<String>,
+    monitor_device: Option<String>,
+    recording_path: Option<String>,
+    recording_started: Option<Instant>,
+    http_error: Option<ErrorReport>,
+    port_fallback: Option<PortFallback>,
+    relay_state: Option<String>,
+    jitter_target_ms: Option<u32>,
+    last_error: Option<ErrorReport>,
     audio_packets: u64,
-    log_lines: VecDeque<String>,
+    concealed_packets: u64,
+    decode_errors: u64,
+    rtp_packets: u64,
+    rtp_invalid_packets: u64,
+    audio_bytes: u64,
+    dropped_samples: u64,
+    decoded_frames: u64,
+    stats: SessionStats,
+    stats_export_path: Option<String>,
+    log_lines: VecDeque<LogEntry>,
+    /// Lines ever logged, so remote followers can ask for "everything since".
+    log_total: u64,
+    trusted_devices: Vec<TrustedDevice>,
+    access: AccessList,
+    pending_approvals: Vec<ApprovalRequest>,
+    session_started: Option<Instant>,
+    /// File copy of the log while senders are connected.
+    session_log: Option<SessionLog>,
+    voice_timelines: Vec<ClientTimeline>,
+    captions_running: bool,
+    /// When captions started; caption offsets are relative to it.
+    captions_started: Option<Instant>,
+    /// Every caption line since captions started.
+    captions: Vec<CaptionLine>,
+    caption_partial: Option<String>,
+    client_certificate: Option<ExportedCertificate>,
+    /// Last SDP sent and received, for diagnostic bundles.
+    local_sdp: Option<String>,
+    remote_sdp: Option<String>,
+}
+
+impl Status {
+    fn push_log(&mut self, entry: LogEntry) {
+        if let Some(log) = &mut self.session_log {
+            log.write_line(&entry.message);
+        }
+        log_file::write(&entry);
+        self.log_lines.push_back(entry);
+        self.log_total += 1;
+        while self.log_lines.len() > MAX_LOG_LINES {
+            self.log_lines.pop_front();
+        }
+    }
+}
+
+/// A change the UI should redraw for, so it doesn't have to poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreEvent {
+    /// Connection, device, error or other displayed state changed.
+    StatusChanged,
+    /// A line was added to the log.
+    LogLine,
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct SharedStatus {
     inner: Arc<Mutex<Status>>,
+    /// Fired on state changes; per-packet counters and levels don't fire it.
+    events: broadcast::Sender<CoreEvent>,
+    /// Signalled whenever a sender connects or disconnects.
+    clients_changed: Arc<Notify>,
+    /// Sessions blocked on an approval decision, by request ID.
+    approval_waiters: Arc<Mutex<HashMap<u64, oneshot::Sender<bool>>>>,
+    /// Control data channels of connected WebRTC senders.
+    controls: Arc<Mutex<HashMap<ClientId, mpsc::UnboundedSender<ControlMessage>>>>,
+    /// Mixer of the running session, for the live level indicator.
+    meter: Arc<Mutex<Option<Arc<Mixer>>>>,
+}
+
+impl Default for SharedStatus {
+    fn default() -> Self {
+        Self {
+            inner: Arc::default(),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            clients_changed: Arc::default(),
+            approval_waiters: Arc::default(),
+            controls: Arc::default(),
+            meter: Arc::default(),
+        }
+    }
 }
 
 impl SharedStatus {
     /// Take a consistent snapshot of the entire status in one lock acquisition.
     pub fn snapshot(&self) -> StatusSnapshot {
+        self.snapshot_with(true)
+    }
+
+    /// [`Self::snapshot`] with `log_lines` left empty, for the UI, which
+    /// follows the log with [`Self::logs_since`] instead of copying all of
+    /// it every refresh.
+    pub fn snapshot_without_logs(&self) -> StatusSnapshot {
+        self.snapshot_with(false)
+    }
+
+    fn snapshot_with(&self, logs: bool) -> StatusSnapshot {
         let s = self.inner.lock();
         StatusSnapshot {
             server_running: s.server_running,
             ws_url: s.ws_url.clone(),
+            admin_url: s.admin_url.clone(),
+            sender_urls: s.sender_urls.clone(),
+            plain_urls: s.plain_urls.clone(),
+            deep_links: s.deep_links.clone(),
             client_connected: s.client_connected,
             client_addr: s.client_addr.clone(),
-            pc_state: s.pc_state.clone(),
+            clients: s.clients.clone(),
+            output_device: s.output_device.clone(),
+            output_devices: s.output_devices.clone(),
+            monitor_device: s.monitor_device.clone(),
+            recording_path: s.recording_path.clone(),
+            recording_elapsed_ms: s
+                .recording_started
+                .map_or(0, |t| t.elapsed().as_millis() as u64),
+            pc_state: s.clients.first().and_then(|c| c.pc_state.clone()),
+            http_error: s.http_error.clone(),
+            port_fallback: s.port_fallback,
+            relay_state: s.relay_state.clone(),
+            jitter_target_ms: s.jitter_target_ms,
             last_error: s.last_error.clone(),
             audio_packets: s.audio_packets,
-            log_lines: s.log_lines.iter().cloned().collect(),
+            concealed_packets: s.concealed_packets,
+            decode_errors: s.decode_errors,
+            bitrate_kbps: s.clients.iter().map(|c| c.bitrate_kbps).sum(),
+            packet_loss_pct: s
+                .clients
+                .iter()
+                .filter_map(|c| c.packet_loss_pct)
+                .reduce(f32::max),
+            jitter_ms: s.clients.iter().map(|c| c.jitter_ms).fold(0.0, f32::max),
+            rtp_packets: s.rtp_packets,
+            rtp_invalid_packets: s.rtp_invalid_packets,
+            stats_export_path: s.stats_export_path.clone(),
+            log_lines: if logs {
+                s.log_lines.iter().cloned().collect()
+            } else {
+                Vec::new()
+            },
+            session_elapsed_ms: s
+                .session_started
+                .map(|t| t.elapsed().as_millis() as u64)
+                .unwrap_or(0),
+            voice_timelines: s
+                .voice_timelines
+                .iter()
+                .map(ClientTimeline::snapshot)
+                .collect(),
+            captions_running: s.captions_running,
+            captions: s.captions[s.captions.len().saturating_sub(SHOWN_CAPTIONS)..].to_vec(),
+            caption_partial: s.caption_partial.clone(),
+            client_certificate: s.client_certificate.clone(),
+            trusted_devices: s.trusted_devices.clone(),
+            access: s.access.clone(),
+            pending_approvals: s.pending_approvals.clone(),
         }
     }
 
+    /// Receive a [`CoreEvent`] whenever something the UI shows changes.
+    pub fn subscribe(&self) -> broadcast::Receiver<CoreEvent> {
+        self.events.subscribe()
+    }
+
+    fn changed(&self) {
+        // Nobody listening (e.g. before the UI subscribes) is fine
+        let _ = self.events.send(CoreEvent::StatusChanged);
+    }
+
     fn set_server_running(&self, running: bool) {
         self.inner.lock().server_running = running;
+        self.changed();
     }
 
     fn set_ws_url(&self, url: Option<String>) {
         self.inner.lock().ws_url = url;
+        self.changed();
+    }
+
+    fn set_admin_url(&self, url: Option<String>) {
+        self.inner.lock().admin_url = url;
+        self.changed();
+    }
+
+    fn set_sender_urls(&self, urls: Vec<(String, String)>) {
+        self.inner.lock().sender_urls = urls;
+        self.changed();
+    }
+
+    fn set_plain_urls(&self, urls: Vec<(String, String)>) {
+        self.inner.lock().plain_urls = urls;
+        self.changed();
+    }
+
+    fn set_deep_links(&self, links: Vec<(String, String)>) {
+        self.inner.lock().deep_links = links;
+        self.changed();
+    }
+
+    pub fn set_local_sdp(&self, sdp: String) {
+        self.inner.lock().local_sdp = Some(sdp);
+    }
+
+    pub fn set_remote_sdp(&self, sdp: String) {
+        self.inner.lock().remote_sdp = Some(sdp);
+    }
+
+    /// The last `(local, remote)` session descriptions.
+    pub fn negotiated_sdp(&self) -> (Option<String>, Option<String>) {
+        let s = self.inner.lock();
+        (s.local_sdp.clone(), s.remote_sdp.clone())
     }
 
-    pub fn set_client_connected(&self, connected: bool) {
-        self.inner.lock().client_connected = connected;
+    /// Register a connected sender. Returns the number of connected clients.
+    pub fn add_client(&self, id: ClientId, addr: String) -> usize {
+        let mut s = self.inner.lock();
+        // The first sender starts a new session
+        if s.clients.is_empty() {
+            s.voice_timelines.clear();
+        }
+        s.voice_timelines
+            .push(ClientTimeline::new(id, addr.clone()));
+        s.clients.push(ClientStatus {
+            id,
+            addr,
+            device: None,
+            codec: None,
+            control: false,
+            battery: None,
+            charging: false,
+            sender_muted: false,
+            bitrate_cap_kbps: None,
+            gain: 1.0,
+            muted: false,
+            priority: false,
+            output: None,
+            pc_state: None,
+            jitter_ms: 0.0,
+            bitrate_kbps: 0.0,
+            packet_loss_pct: None,
+            bytes: 0,
+            packets: 0,
+            level: AudioLevel::default(),
+        });
+        s.client_connected = true;
+        s.client_addr = s.clients.first().map(|c| c.addr.clone());
+        self.clients_changed.notify_one();
+        self.changed();
+        s.clients.len()
     }
 
-    pub fn set_client_addr(&self, addr: Option<String>) {
-        self.inner.lock().client_addr = addr;
+    /// A sender resumed its session from a new address.
+    pub fn set_client_addr(&self, id: ClientId, addr: String) {
+        let mut s = self.inner.lock();
+        if let Some(c) = s.clients.iter_mut().find(|c| c.id == id) {
+            c.addr = addr;
+        }
+        s.client_addr = s.clients.first().map(|c| c.addr.clone());
+        self.changed();
     }
 
-    pub fn set_pc_state(&self, state: Option<String>) {
-        self.inner.lock().pc_state = state;
+    /// The name a sender gave in its `hello`.
+    pub fn set_client_device(&self, id: ClientId, device: String) {
+        self.update_client(id, |c| c.device = Some(device));
     }
 
+    /// The audio codec a sender's track turned out to use.
+    pub fn set_client_codec(&self, id: ClientId, codec: String) {
+        self.update_client(id, |c| c.codec = Some(codec));
+    }
+
+    /// A sender's control channel opened; see [`Self::send_control`].
+    pub fn set_client_control(&self, id: ClientId, tx: mpsc::UnboundedSender<ControlMessage>) {
+        self.controls.lock().insert(id, tx);
+        self.update_client(id, |c| c.control = true);
+    }
+
+    /// State a sender reported over its control channel; `None` fields are
+    /// left as they were.
+    pub fn set_client_sender_status(
+        &self,
+        id: ClientId,
+        device_name: Option<String>,
+        battery: Option<f32>,
+        charging: Option<bool>,
+        muted: Option<bool>,
+    ) {
+        self.update_client(id, |c| {
+            if device_name.is_some() {
+                c.device = device_name;
+            }
+            if battery.is_some() {
+                c.battery = battery;
+            }
+            if let Some(charging) = charging {
+                c.charging = charging;
+            }
+            if let Some(muted) = muted {
+                c.sender_muted = muted;
+            }
+        });
+    }
+
+    /// Send a message over a sender's control channel. Returns `false` if
+    /// it has none, e.g. an older sender or a non-WebRTC one.
+    fn send_control(&self, id: ClientId, msg: ControlMessage) -> bool {
+        self.controls
+            .lock()
+            .get(&id)
+            .is_some_and(|tx| tx.send(msg).is_ok())
+    }
+
+    /// Remove a disconnected sender. Returns the number still connected.
+    pub fn remove_client(&self, id: ClientId) -> usize {
+        self.controls.lock().remove(&id);
+        let mut s = self.inner.lock();
+        s.clients.retain(|c| c.id != id);
+        if let Some(timeline) = s.voice_timelines.iter_mut().find(|t| t.client == id) {
+            timeline.end();
+        }
+        if s.clients.is_empty() {
+            if let Some(mut log) = s.session_log.take() {
+                log.write_line("Session ended.");
+            }
+        }
+        s.client_connected = !s.clients.is_empty();
+        s.client_addr = s.clients.first().map(|c| c.addr.clone());
+        self.clients_changed.notify_one();
+        self.changed();
+        s.clients.len()
+    }
+
+    pub fn client_bytes(&self, id: ClientId) -> u64 {
+        self.inner
+            .lock()
+            .clients
+            .iter()
+            .find(|c| c.id == id)
+            .map_or(0, |c| c.bytes)
+    }
+
+    pub fn client_packets(&self, id: ClientId) -> u64 {
+        self.inner
+            .lock()
+            .clients
+            .iter()
+            .find(|c| c.id == id)
+            .map_or(0, |c| c.packets)
+    }
+
+    pub fn client_device(&self, id: ClientId) -> Option<String> {
+        self.inner
+            .lock()
+            .clients
+            .iter()
+            .find(|c| c.id == id)
+            .and_then(|c| c.device.clone())
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.inner.lock().clients.len()
+    }
+
+    /// Wait until a sender connects or disconnects.
+    async fn clients_changed(&self) {
+        self.clients_changed.notified().await;
+    }
+
+    fn set_output_device(&self, device: Option<String>) {
+        self.inner.lock().output_device = device;
+        self.changed();
+    }
+
+    /// Store the current device list; returns whether it changed.
+    fn set_output_devices(&self, devices: Vec<String>) -> bool {
+        let mut s = self.inner.lock();
+        let changed = s.output_devices != devices;
+        s.output_devices = devices;
+        if changed {
+            self.changed();
+        }
+        changed
+    }
+
+    fn set_monitor_device(&self, device: Option<String>) {
+        self.inner.lock().monitor_device = device;
+        self.changed();
+    }
+
+    fn set_recording_path(&self, path: Option<String>) {
+        let mut s = self.inner.lock();
+        s.recording_started = path.as_ref().map(|_| Instant::now());
+        s.recording_path = path;
+        self.changed();
+    }
+
+    fn update_client(&self, id: ClientId, f: impl FnOnce(&mut ClientStatus)) {
+        if let Some(c) = self.inner.lock().clients.iter_mut().find(|c| c.id == id) {
+            f(c);
+        }
+        self.changed();
+    }
+
+    pub fn set_pc_state(&self, id: ClientId, state: Option<String>) {
+        self.update_client(id, |c| c.pc_state = state);
+    }
+
+    /// Returns the previous error, so recovery can be logged once.
+    fn set_http_error(&self, err: Option<String>) -> Option<ErrorReport> {
+        let previous =
+            std::mem::replace(&mut self.inner.lock().http_error, err.map(ErrorReport::new));
+        self.changed();
+        previous
+    }
+
+    fn set_port_fallback(&self, fallback: Option<PortFallback>) {
+        self.inner.lock().port_fallback = fallback;
+        self.changed();
+    }
+
+    pub fn set_relay_state(&self, state: Option<String>) {
+        self.inner.lock().relay_state = state;
+        self.changed();
+    }
+
+    fn set_jitter_target_ms(&self, ms: Option<u32>) {
+        self.inner.lock().jitter_target_ms = ms;
+        self.changed();
+    }
+
+    /// Set (classified into a category with a hint) or clear the error
+    /// shown to the user.
     pub fn set_last_error(&self, err: Option<String>) {
-        self.inner.lock().last_error = err;
+        self.inner.lock().last_error = err.map(ErrorReport::new);
+        self.changed();
+    }
+
+    fn set_meter(&self, mixer: Option<Arc<Mixer>>) {
+        *self.meter.lock() = mixer;
+    }
+
+    /// Counters and gauges for the `/metrics` route.
+    pub fn metrics(&self) -> metrics::Metrics {
+        let (queue_depth, underruns) = self
+            .meter
+            .lock()
+            .as_ref()
+            .map_or((0, 0), |m| (m.max_buffered(), m.underruns()));
+        let s = self.inner.lock();
+        metrics::Metrics {
+            listening: s.server_running,
+            connected_clients: s.clients.len(),
+            session_seconds: s.session_started.map_or(0.0, |t| t.elapsed().as_secs_f64()),
+            audio_packets: s.audio_packets,
+            audio_bytes: s.audio_bytes,
+            decoded_frames: s.decoded_frames,
+            concealed_packets: s.concealed_packets,
+            decode_errors: s.decode_errors,
+            dropped_samples: s.dropped_samples,
+            rtp_packets: s.rtp_packets,
+            rtp_invalid_packets: s.rtp_invalid_packets,
+            queue_depth,
+            underruns,
+        }
+    }
+
+    /// Peak output level (0–1) since the previous call; 0 when stopped.
+    pub fn take_output_peak(&self) -> f32 {
+        self.meter.lock().as_ref().map_or(0.0, |m| m.take_peak())
+    }
+
+    /// Count a received RTP packet and the sender's current jitter.
+    pub fn record_audio_packet(&self, id: ClientId, bytes: usize, jitter_ms: f32) {
+        let mut s = self.inner.lock();
+        s.audio_packets = s.audio_packets.saturating_add(1);
+        s.audio_bytes = s.audio_bytes.saturating_add(bytes as u64);
+        if let Some(c) = s.clients.iter_mut().find(|c| c.id == id) {
+            c.jitter_ms = jitter_ms;
+            c.bytes = c.bytes.saturating_add(bytes as u64);
+            c.packets = c.packets.saturating_add(1);
+        }
+    }
+
+    /// Update a sender's bitrate and loss from the peer connection's stats.
+    pub fn record_link_stats(&self, id: ClientId, bitrate_kbps: f32, loss_pct: Option<f32>) {
+        let mut s = self.inner.lock();
+        if let Some(c) = s.clients.iter_mut().find(|c| c.id == id) {
+            c.bitrate_kbps = bitrate_kbps;
+            c.packet_loss_pct = loss_pct;
+        }
+    }
+
+    /// Count a datagram on the plain RTP listener.
+    pub fn record_rtp_packet(&self, valid: bool) {
+        let mut s = self.inner.lock();
+        if valid {
+            s.rtp_packets = s.rtp_packets.saturating_add(1);
+        } else {
+            s.rtp_invalid_packets = s.rtp_invalid_packets.saturating_add(1);
+        }
+    }
+
+    /// Count a decoded (mono) frame and update its sender's level meter.
+    pub fn record_level(&self, id: ClientId, samples: &[i16]) {
+        let mut s = self.inner.lock();
+        s.decoded_frames = s.decoded_frames.saturating_add(1);
+        if let Some(c) = s.clients.iter_mut().find(|c| c.id == id) {
+            c.level.update(samples);
+        }
+    }
+
+    /// Count lost packets recovered from FEC or concealed by the decoder.
+    pub fn bump_concealed_packets(&self, n: u64) {
+        let mut s = self.inner.lock();
+        s.concealed_packets = s.concealed_packets.saturating_add(n);
+    }
+
+    /// Count a packet the decoder could not decode.
+    pub fn bump_decode_errors(&self, n: u64) {
+        let mut s = self.inner.lock();
+        s.decode_errors = s.decode_errors.saturating_add(n);
     }
 
-    pub fn bump_audio_packets(&self, n: u64) {
+    pub fn bump_dropped_samples(&self, n: u64) {
         let mut s = self.inner.lock();
-        s.audio_packets = s.audio_packets.saturating_add(n);
+        s.dropped_samples = s.dropped_samples.saturating_add(n);
+    }
+
+    // -- Session statistics -------------------------------------------------
+
+    /// Add this second's row to the session stats while a session is open.
+    fn sample_stats(&self, buffer_ms: u32) {
+        let mut s = self.inner.lock();
+        let Some(started) = s.session_started else {
+            return;
+        };
+        let totals = stats::Counters {
+            packets: s.audio_packets,
+            bytes: s.audio_bytes,
+            dropped_samples: s.dropped_samples,
+        };
+        let jitter_ms = s.clients.iter().map(|c| c.jitter_ms).fold(0.0, f32::max);
+        s.stats
+            .sample(started.elapsed().as_secs(), totals, jitter_ms, buffer_ms);
+    }
+
+    fn export_stats(&self, path: Option<PathBuf>) {
+        let path = path.unwrap_or_else(stats::default_export_path);
+        // Write a copy so the UI isn't blocked on disk I/O
+        let stats = self.inner.lock().stats.clone();
+        if stats.is_empty() {
+            self.log_line("No session statistics to export yet.");
+            return;
+        }
+        match stats.write_csv(&path) {
+            Ok(()) => {
+                let display = path.display().to_string();
+                self.log_line(format!("Statistics exported to {display}"));
+                self.inner.lock().stats_export_path = Some(display);
+                self.changed();
+            }
+            Err(e) => self.log_line(format!("Failed to export statistics: {e:#}")),
+        }
+    }
+
+    fn export_logs(&self, path: Option<PathBuf>) {
+        let path = path.unwrap_or_else(|| stats::export_path("lan-mic-log", "txt"));
+        let lines: Vec<String> = self
+            .inner
+            .lock()
+            .log_lines
+            .iter()
+            .map(LogEntry::to_string)
+            .collect();
+        let write = || -> Result<()> {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("create {}", dir.display()))?;
+            }
+            let mut body = lines.join("\n");
+            body.push('\n');
+            std::fs::write(&path, body).with_context(|| format!("write {}", path.display()))?;
+            Ok(())
+        };
+        match write() {
+            Ok(()) => self.log_line(format!("Logs exported to {}", path.display())),
+            Err(e) => self.log_line(format!("Failed to export logs: {e:#}")),
+        }
+    }
+
+    fn export_transcript(&self, path: Option<PathBuf>) {
+        let lines: Vec<String> = self
+            .inner
+            .lock()
+            .captions
+            .iter()
+            .map(|line| format!("[{}] {}", format_offset(line.offset_ms), line.text))
+            .collect();
+        if lines.is_empty() {
+            self.log_line("No captions to export yet");
+            return;
+        }
+        let path = path.unwrap_or_else(|| stats::export_path("lan-mic-transcript", "txt"));
+        let write = || -> Result<()> {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("create {}", dir.display()))?;
+            }
+            let mut body = lines.join("\n");
+            body.push('\n');
+            std::fs::write(&path, body).with_context(|| format!("write {}", path.display()))?;
+            Ok(())
+        };
+        match write() {
+            Ok(()) => self.log_line(format!("Transcript exported to {}", path.display())),
+            Err(e) => self.log_line(format!("Failed to export transcript: {e:#}")),
+        }
+    }
+
+    fn set_client_certificate(&self, path: &std::path::Path, password: String) {
+        self.log_line(format!(
+            "Client certificate saved to {}; its password is in Settings.",
+            path.display()
+        ));
+        self.inner.lock().client_certificate = Some(ExportedCertificate {
+            path: path.display().to_string(),
+            password,
+        });
+        self.changed();
     }
 
+    /// Add a line to the in-app log, at a level judged from its wording.
+    #[track_caller]
     pub fn log_line(&self, line: impl Into<String>) {
+        let message = line.into();
+        self.log_at(LogLevel::classify(&message), message);
+    }
+
+    #[track_caller]
+    pub fn log_at(&self, level: LogLevel, line: impl Into<String>) {
+        let entry = LogEntry::new(level, line.into());
+        self.inner.lock().push_log(entry);
+        let _ = self.events.send(CoreEvent::LogLine);
+    }
+
+    /// Log entries with sequence number `>= seq` that are still buffered,
+    /// plus the sequence number to pass next time.
+    pub fn logs_since(&self, seq: u64) -> (Vec<LogEntry>, u64) {
+        let s = self.inner.lock();
+        let first = s.log_total - s.log_lines.len() as u64;
+        let skip = seq.saturating_sub(first) as usize;
+        (s.log_lines.iter().skip(skip).cloned().collect(), s.log_total)
+    }
+
+    // -- Trusted devices & approvals ----------------------------------------
+
+    pub fn load_trusted_devices(&self) {
+        self.inner.lock().trusted_devices = trust::load();
+    }
+
+    pub fn is_trusted_device(&self, device_id: &str) -> bool {
+        self.inner
+            .lock()
+            .trusted_devices
+            .iter()
+            .any(|d| d.id == device_id)
+    }
+
+    /// Remember an approved device and persist the list.
+    pub fn trust_device(&self, device_id: String, name: String) {
+        let devices = {
+            let mut s = self.inner.lock();
+            s.trusted_devices.retain(|d| d.id != device_id);
+            s.trusted_devices.push(TrustedDevice {
+                id: device_id,
+                name: name.clone(),
+                added_at: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+            });
+            s.trusted_devices.clone()
+        };
+        self.changed();
+        self.log_line(format!("Trusted device added: {name}"));
+        if let Err(e) = trust::save(&devices) {
+            self.log_line(format!("Failed to save trusted devices: {e}"));
+        }
+    }
+
+    fn forget_trusted_device(&self, device_id: &str) {
+        let devices = {
+            let mut s = self.inner.lock();
+            s.trusted_devices.retain(|d| d.id != device_id);
+            s.trusted_devices.clone()
+        };
+        self.changed();
+        if let Err(e) = trust::save(&devices) {
+            self.log_line(format!("Failed to save trusted devices: {e}"));
+        }
+    }
+
+    // -- Access control -----------------------------------------------------
+
+    pub fn load_access_list(&self) {
+        let access = access::load().unwrap_or_else(|e| {
+            self.log_line(format!(
+                "Refusing all senders until Sender Access is fixed: {e:#}"
+            ));
+            AccessList::deny_all()
+        });
+        self.inner.lock().access = access;
+    }
+
+    /// Whether a sender at `ip` may connect under the access rules.
+    pub fn is_ip_allowed(&self, ip: std::net::IpAddr) -> bool {
+        self.inner.lock().access.permits(ip)
+    }
+
+    /// Replace the access rules and persist them.
+    fn set_access_list(&self, list: AccessList) {
+        if let Err(e) = access::save(&list) {
+            self.log_line(format!("Failed to save access rules: {e}"));
+        }
+        self.inner.lock().access = list;
+        self.changed();
+    }
+
+    /// Queue an approval prompt for the UI. The receiver resolves to the
+    /// user's decision.
+    pub fn request_approval(
+        &self,
+        device_name: String,
+        addr: String,
+    ) -> (u64, oneshot::Receiver<bool>) {
+        static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+        let id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.approval_waiters.lock().insert(id, tx);
+        self.inner.lock().pending_approvals.push(ApprovalRequest {
+            id,
+            device_name,
+            addr,
+        });
+        self.changed();
+        (id, rx)
+    }
+
+    fn resolve_approval(&self, request_id: u64, allow: bool) {
+        if let Some(tx) = self.approval_waiters.lock().remove(&request_id) {
+            let _ = tx.send(allow);
+        }
+        self.cancel_approval(request_id);
+    }
+
+    /// Drop a prompt whose sender gave up or was already answered.
+    pub fn cancel_approval(&self, request_id: u64) {
+        self.approval_waiters.lock().remove(&request_id);
+        self.inner
+            .lock()
+            .pending_approvals
+            .retain(|r| r.id != request_id);
+        self.changed();
+    }
+
+    /// Start a session when the first sender connects: open its log file
+    /// and restart the per-second statistics.
+    pub fn begin_session(&self) {
+        // Opened outside the lock; it touches the disk
+        let session_log = SessionLog::create()
+            .map_err(|e| log::warn!("Failed to create session log: {e:#}"))
+            .ok();
         let mut s = self.inner.lock();
-        s.log_lines.push_back(line.into());
-        while s.log_lines.len() > MAX_LOG_LINES {
-            s.log_lines.pop_front();
+        if let Some(log) = &session_log {
+            let line = format!("Session {} — log file: {}", log.id(), log.path().display());
+            s.push_log(LogEntry::new(LogLevel::Info, line));
         }
+        s.session_log = session_log;
+        s.session_started = Some(Instant::now());
+        let totals = stats::Counters {
+            packets: s.audio_packets,
+            bytes: s.audio_bytes,
+            dropped_samples: s.dropped_samples,
+        };
+        s.stats.reset(totals);
+        drop(s);
+        self.changed();
+    }
+
+    /// Record a sender's talking/silence transition and log it with its
+    /// session offset.
+    pub fn mark_voice_activity(&self, client_id: ClientId, talking: bool) {
+        let mut s = self.inner.lock();
+        let Some(started) = s.session_started else {
+            return;
+        };
+        let Some(timeline) = s
+            .voice_timelines
+            .iter_mut()
+            .find(|t| t.client == client_id && t.ended_ms.is_none())
+        else {
+            return;
+        };
+        if !timeline.mark(talking) {
+            return;
+        }
+
+        let label = if talking { "talking" } else { "silence" };
+        let offset = format_offset(started.elapsed().as_millis() as u64);
+        s.push_log(LogEntry::new(
+            LogLevel::Debug,
+            format!("[{offset}] Client #{client_id} voice activity: {label}"),
+        ));
+        let _ = self.events.send(CoreEvent::LogLine);
+    }
+
+    /// End the session when the last sender disconnects.
+    pub fn end_session(&self) {
+        self.inner.lock().session_started = None;
+        self.changed();
     }
 
     /// Reset all connection-related fields in a single lock acquisition.
@@ -144,19 +1385,382 @@ impl SharedStatus {
         s.server_running = false;
         s.client_connected = false;
         s.client_addr = None;
-        s.pc_state = None;
+        s.clients.clear();
+        s.output_device = None;
+        s.monitor_device = None;
+        s.recording_path = None;
+        s.recording_started = None;
+        s.jitter_target_ms = None;
+        s.captions_running = false;
+        s.caption_partial = None;
+        drop(s);
+        self.changed();
+    }
+
+    /// Start a new transcript when captions start.
+    fn start_captions(&self) {
+        let mut s = self.inner.lock();
+        s.captions_running = true;
+        s.captions_started = Some(Instant::now());
+        s.captions.clear();
+        s.caption_partial = None;
+        drop(s);
+        self.changed();
+    }
+
+    fn push_caption(&self, event: CaptionEvent) {
+        let mut s = self.inner.lock();
+        match event {
+            CaptionEvent::Partial(text) => s.caption_partial = Some(text),
+            CaptionEvent::Final(text) => {
+                let offset_ms = s
+                    .captions_started
+                    .map_or(0, |t| t.elapsed().as_millis() as u64);
+                s.captions.push(CaptionLine { offset_ms, text });
+                s.caption_partial = None;
+            }
+        }
+        drop(s);
+        self.changed();
     }
 }
 
+/// Format a session offset as `m:ss.t` for timeline log lines.
+pub fn format_offset(ms: u64) -> String {
+    let secs = ms / 1000;
+    format!("{}:{:02}.{}", secs / 60, secs % 60, (ms % 1000) / 100)
+}
+
 // ---------------------------------------------------------------------------
 // Core runtime — runs on a dedicated thread with its own tokio runtime
 // ---------------------------------------------------------------------------
 
+/// How often to check for interface/IP changes (Wi-Fi reconnect, DHCP renew).
+const NETWORK_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// How often a row of session statistics is taken.
+const STATS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How often the main output is checked for failure; together with the
+/// stream stall timeout this keeps failover well under a second.
+const FAILOVER_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// How often the output device list is refreshed, to notice devices being
+/// plugged in or removed.
+const DEVICE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// How often the idle auto-stop timeout is checked.
+const IDLE_STOP_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
 struct Running {
     audio: audio::AudioOutput,
-    queue: Arc<ArrayQueue<i16>>,
-    _session_cancel: CancellationToken,
-    mdns: Option<signaling::MdnsRegistration>,
+    routed: audio::RoutedOutputs,
+    monitor: Option<audio::AudioOutput>,
+    monitor_tap: Arc<MonitorTap>,
+    recorder: Option<Recorder>,
+    /// Feed of the main mix into a Snapcast server, if configured.
+    snapcast: Option<SnapcastOutput>,
+    /// Live captions of the main mix, if a model is configured.
+    captions: Option<Captioner>,
+    mixer: Arc<Mixer>,
+    session_cancel: CancellationToken,
+    /// Stops the UDP discovery beacon, if enabled.
+    beacon: Option<CancellationToken>,
+    /// Output streams are paused while no sender is connected.
+    idle: bool,
+    /// When the receiver last became idle.
+    idle_since: Option<Instant>,
+    /// Auto-stop after being idle this long.
+    idle_stop: Option<Duration>,
+    standby_device: Option<String>,
+    /// The main output device that failed while nothing could replace it;
+    /// replacing it is retried whenever the device list is polled.
+    lost_output: Option<String>,
+    /// Jitter-target tuner when the Auto quality preset is selected.
+    auto_latency: Option<AutoLatency>,
+}
+
+impl Running {
+    fn start_recording(
+        &mut self,
+        path: Option<PathBuf>,
+        format: RecordingFormat,
+        shared: &SharedStatus,
+    ) {
+        self.stop_recording(shared);
+
+        let path = path.unwrap_or_else(recorder::default_recording_path);
+        match Recorder::start(path, format) {
+            Ok(rec) => {
+                let display = rec.path().display().to_string();
+                self.mixer.set_recorder(Some(rec.tap()));
+                self.mixer.mark("Recording started");
+                for client in shared.snapshot().clients {
+                    self.mixer
+                        .mark(format!("Client #{} connected ({})", client.id, client.addr));
+                }
+                shared.log_line(format!("Recording to {display}"));
+                shared.set_recording_path(Some(display));
+                self.recorder = Some(rec);
+                self.update_idle(shared);
+            }
+            Err(e) => {
+                shared.set_last_error(Some(e.to_string()));
+                shared.log_line(format!("Failed to start recording: {e}"));
+            }
+        }
+    }
+
+    /// Feed the main mix to the Snapcast server at `target`.
+    fn start_snapcast(&mut self, target: &str, shared: &SharedStatus) {
+        let log_shared = shared.clone();
+        let res = SnapcastTarget::parse(target)
+            .and_then(|t| SnapcastOutput::start(t, move |line| log_shared.log_line(line)));
+        match res {
+            Ok(out) => {
+                shared.log_line(format!("Snapcast output: {}", out.target()));
+                self.mixer.set_snapcast(Some(out.tap()));
+                self.snapcast = Some(out);
+            }
+            Err(e) => {
+                shared.set_last_error(Some(e.to_string()));
+                shared.log_line(format!("Failed to start Snapcast output: {e:#}"));
+            }
+        }
+    }
+
+    /// Caption the main mix with the Vosk model in `model`.
+    fn start_captions(&mut self, model: &str, shared: &SharedStatus) {
+        let (caption_shared, log_shared) = (shared.clone(), shared.clone());
+        let res = Captioner::start(
+            PathBuf::from(model),
+            move |event| caption_shared.push_caption(event),
+            move |line| log_shared.log_line(line),
+        );
+        match res {
+            Ok(captions) => {
+                shared.log_line(format!(
+                    "Captions: loading model {}…",
+                    captions.model().display()
+                ));
+                shared.start_captions();
+                self.mixer.set_captions(Some(captions.tap()));
+                self.captions = Some(captions);
+            }
+            Err(e) => {
+                shared.set_last_error(Some(e.to_string()));
+                shared.log_line(format!("Failed to start captions: {e:#}"));
+            }
+        }
+    }
+
+    fn stop_recording(&mut self, shared: &SharedStatus) {
+        let Some(rec) = self.recorder.take() else {
+            return;
+        };
+        self.mixer.set_recorder(None);
+        shared.set_recording_path(None);
+        let format = rec.format();
+        match rec.stop() {
+            Ok(path) if format == RecordingFormat::Flac => {
+                shared.log_line("Converting recording to FLAC…");
+                let shared = shared.clone();
+                tokio::task::spawn_blocking(move || match recorder::convert_to_flac(&path) {
+                    Ok(flac) => shared.log_line(format!("Recording saved: {}", flac.display())),
+                    Err(e) => shared.log_line(format!(
+                        "FLAC conversion failed, kept {}: {e:#}",
+                        path.display()
+                    )),
+                });
+            }
+            Ok(path) => shared.log_line(format!("Recording saved: {}", path.display())),
+            Err(e) => {
+                shared.set_last_error(Some(e.to_string()));
+                shared.log_line(format!("Failed to finalize recording: {e}"));
+            }
+        }
+        self.update_idle(shared);
+    }
+
+    /// Suspend the output streams while nobody is sending (and nothing is
+    /// being recorded); resume them as soon as a sender connects.
+    fn update_idle(&mut self, shared: &SharedStatus) {
+        let idle = shared.client_count() == 0 && self.recorder.is_none();
+        if idle == self.idle {
+            return;
+        }
+        self.idle = idle;
+        self.idle_since = idle.then(Instant::now);
+        self.apply_idle();
+        shared.log_line(if idle {
+            "No senders connected — audio output suspended."
+        } else {
+            "Audio output resumed."
+        });
+    }
+
+    /// Whether the idle auto-stop timeout has run out.
+    fn idle_expired(&self) -> bool {
+        match (self.idle_stop, self.idle_since) {
+            (Some(limit), Some(since)) => since.elapsed() >= limit,
+            _ => false,
+        }
+    }
+
+    /// Let the Auto preset adjust the jitter target from recent underruns.
+    fn tune_latency(&mut self, shared: &SharedStatus) {
+        let Some(auto) = self.auto_latency.as_mut() else {
+            return;
+        };
+        let underruns = self.mixer.underruns();
+        if self.idle {
+            auto.resync(underruns);
+            return;
+        }
+        let was_settled = auto.is_settled();
+        if let Some(ms) = auto.tick(underruns) {
+            self.mixer.set_jitter_target(ms as usize * 48);
+            shared.set_jitter_target_ms(Some(ms));
+            shared.log_line(format!("Auto latency: jitter buffer now {ms} ms"));
+        }
+        if auto.is_settled() && !was_settled {
+            shared.log_line(format!(
+                "Auto latency settled on {} ms",
+                auto.target_ms()
+            ));
+        }
+    }
+
+    /// Move the main output off its device if the stream died.
+    fn check_failover(&mut self, shared: &SharedStatus) {
+        if self.audio.is_failed() {
+            let failed = self.audio.device_name().to_string();
+            self.fail_over(failed, shared);
+        }
+    }
+
+    /// Called with the fresh device list: fail over if the main output's
+    /// device was unplugged without its stream reporting an error, and retry
+    /// a failover that found nothing to switch to.
+    fn check_devices(&mut self, devices: &[String], shared: &SharedStatus) {
+        if let Some(lost) = self.lost_output.clone() {
+            self.fail_over(lost, shared);
+            return;
+        }
+        let current = self.audio.device_name().to_string();
+        if !self.audio.is_stopped() && !devices.contains(&current) {
+            self.fail_over(current, shared);
+        }
+    }
+
+    /// Replace the main output on `failed` with the standby device, or the
+    /// system default when there is no standby or it can't start.
+    fn fail_over(&mut self, failed: String, shared: &SharedStatus) {
+        // Dropping the dead stream first frees the callback thread
+        self.audio = audio::AudioOutput::stopped();
+        let standby = self.standby_device.clone().filter(|s| *s != failed);
+        for target in [standby, None] {
+            let name = target.as_deref().unwrap_or("system default");
+            match audio::AudioOutput::start(target.as_deref(), Arc::clone(&self.mixer)) {
+                // The default may still point at the device that just vanished
+                Ok(out) if out.device_name() == failed => {}
+                Ok(out) => {
+                    let msg = format!(
+                        "Output '{failed}' failed — switched to {}'{}'",
+                        if target.is_some() { "standby " } else { "" },
+                        out.device_name()
+                    );
+                    shared.set_last_error(Some(msg.clone()));
+                    shared.log_line(msg.clone());
+                    shared.set_output_device(Some(out.device_name().to_string()));
+                    self.mixer.mark(msg);
+                    self.audio = out;
+                    self.lost_output = None;
+                    self.apply_idle();
+                    return;
+                }
+                Err(e) => log::warn!("Output failover to {name} failed: {e}"),
+            }
+        }
+        if self.lost_output.replace(failed.clone()).is_none() {
+            let msg = format!("Output '{failed}' failed and no other device could start");
+            shared.set_last_error(Some(msg.clone()));
+            shared.log_line(msg);
+            shared.set_output_device(None);
+        }
+    }
+
+    /// Re-apply the idle state, e.g. to a freshly opened stream.
+    fn apply_idle(&self) {
+        let outputs = std::iter::once(&self.audio)
+            .chain(self.monitor.as_ref())
+            .chain(self.routed.iter());
+        for out in outputs {
+            if let Err(e) = out.set_paused(self.idle) {
+                log::warn!("Failed to pause/resume {}: {e}", out.device_name());
+            }
+        }
+    }
+
+    /// Open (or close, with `None`) the monitor device and attach the tap.
+    fn set_monitor(&mut self, device_name: Option<String>, shared: &SharedStatus) {
+        if self.monitor.is_some() || device_name.is_some() {
+            self.mixer.mark(format!(
+                "Monitor device: {}",
+                device_name.as_deref().unwrap_or("off")
+            ));
+        }
+        self.mixer.set_monitor(None);
+        self.monitor = None;
+        shared.set_monitor_device(None);
+
+        let Some(name) = device_name else {
+            return;
+        };
+        match audio::AudioOutput::start_monitor(
+            Some(&name),
+            Arc::clone(&self.monitor_tap),
+            self.mixer.tuning().output_buffer_frames,
+        ) {
+            Ok(out) => {
+                shared.log_line(format!("Monitor output started: {}", out.device_name()));
+                shared.set_monitor_device(Some(out.device_name().to_string()));
+                self.monitor = Some(out);
+                self.mixer.set_monitor(Some(Arc::clone(&self.monitor_tap)));
+                self.apply_idle();
+            }
+            Err(e) => {
+                shared.set_last_error(Some(e.to_string()));
+                shared.log_line(format!("Failed to start monitor output: {e}"));
+            }
+        }
+    }
+}
+
+/// Register the mDNS service anew, e.g. after the address changed, or
+/// withdraw it when advertising is off.
+fn readvertise(
+    mdns: &mut Option<signaling::MdnsRegistration>,
+    enabled: bool,
+    server: &signaling::HttpServer,
+    ready: bool,
+    shared: &SharedStatus,
+) {
+    if let Some(m) = mdns.take() {
+        m.shutdown();
+    }
+    if enabled {
+        *mdns = signaling::MdnsRegistration::register(server, ready, shared);
+    }
+}
+
+/// Show the listener's current URLs (QR codes, deep links, admin page).
+fn publish_urls(server: &signaling::HttpServer, shared: &SharedStatus) {
+    shared.set_ws_url(Some(server.ws_url.clone()));
+    shared.set_sender_urls(server.sender_urls());
+    shared.set_plain_urls(server.plain_urls());
+    shared.set_deep_links(server.deep_links());
+    shared.set_admin_url(Some(server.admin_url()));
 }
 
 pub fn spawn_runtime(shared: SharedStatus) -> CoreController {
@@ -164,6 +1768,7 @@ pub fn spawn_runtime(shared: SharedStatus) -> CoreController {
     let tx = Arc::new(tx);
 
     let shared_for_thread = shared.clone();
+    let controller = CoreController { tx: Arc::clone(&tx) };
     std::thread::spawn(move || {
         let rt = match tokio::runtime::Builder::new_multi_thread()
             .enable_all()
@@ -179,15 +1784,19 @@ pub fn spawn_runtime(shared: SharedStatus) -> CoreController {
         };
 
         rt.block_on(async move {
+            shared.load_trusted_devices();
+            shared.load_access_list();
+
             // Start the HTTP server immediately so the web sender page is always available
-            let http_server = match signaling::start_http_server(
-                "0.0.0.0:9001".to_string(),
+            let mut http_server = match signaling::start_http_server(
+                crate::profile::current().bind_addr(),
                 shared.clone(),
+                controller,
             )
             .await
             {
                 Ok(server) => {
-                    shared.set_ws_url(Some(server.ws_url.clone()));
+                    publish_urls(&server, &shared);
                     shared.log_line(format!(
                         "Web sender available at http://{}",
                         server.bind_addr
@@ -202,50 +1811,329 @@ pub fn spawn_runtime(shared: SharedStatus) -> CoreController {
             };
 
             let mut running: Option<Running> = None;
+            let mut last_start: Option<CoreCommand> = None;
+            // Settings of the last successful start, for diagnostic bundles
+            let mut last_settings: Option<recovery::ListenState> = None;
+            // Master volume, kept across sessions
+            let mut output_gain = 1.0;
+            let mut output_muted = false;
+            let mut recording_format = RecordingFormat::default();
+            let mut agc = None;
+            let mut stereo_width = 1.0;
+            // Advertised from launch, with the last session's preference
+            let mut advertise_mdns = recovery::last_session().is_none_or(|s| s.advertise_mdns);
+            let mut mdns = None;
+            readvertise(&mut mdns, advertise_mdns, &http_server, false, &shared);
 
-            while let Some(cmd) = rx.recv().await {
+            let mut network_check = tokio::time::interval(NETWORK_POLL_INTERVAL);
+            network_check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut failover_check = tokio::time::interval(FAILOVER_CHECK_INTERVAL);
+            failover_check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut stats_tick = tokio::time::interval(STATS_INTERVAL);
+            stats_tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut latency_check = tokio::time::interval(latency::TUNE_INTERVAL);
+            latency_check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut idle_stop_check = tokio::time::interval(IDLE_STOP_CHECK_INTERVAL);
+            idle_stop_check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut device_poll = tokio::time::interval(DEVICE_POLL_INTERVAL);
+            device_poll.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                let cmd = tokio::select! {
+                    cmd = rx.recv() => match cmd {
+                        Some(cmd) => cmd,
+                        None => break,
+                    },
+                    _ = network_check.tick() => {
+                        if let Some(ip) = http_server.refresh_address() {
+                            shared.log_line(format!("Network changed — now reachable at {ip}"));
+                            publish_urls(&http_server, &shared);
+                            readvertise(
+                                &mut mdns,
+                                advertise_mdns,
+                                &http_server,
+                                running.is_some(),
+                                &shared,
+                            );
+                        }
+                        continue;
+                    }
+                    _ = failover_check.tick() => {
+                        if let Some(r) = running.as_mut() {
+                            r.check_failover(&shared);
+                        }
+                        continue;
+                    }
+                    _ = device_poll.tick() => {
+                        // Enumeration can block for a while on some hosts
+                        let devices = match tokio::task::spawn_blocking(audio::output_device_names)
+                            .await
+                        {
+                            Ok(Ok(devices)) => devices,
+                            Ok(Err(e)) => {
+                                log::debug!("Output device enumeration failed: {e}");
+                                continue;
+                            }
+                            Err(_) => continue,
+                        };
+                        if let Some(r) = running.as_mut() {
+                            r.check_devices(&devices, &shared);
+                        }
+                        if shared.set_output_devices(devices) {
+                            log::info!("Output device list changed");
+                        }
+                        continue;
+                    }
+                    _ = stats_tick.tick() => {
+                        if let Some(r) = running.as_ref() {
+                            shared.sample_stats((r.mixer.max_buffered() / 48) as u32);
+                        }
+                        continue;
+                    }
+                    _ = latency_check.tick() => {
+                        if let Some(r) = running.as_mut() {
+                            r.tune_latency(&shared);
+                        }
+                        continue;
+                    }
+                    _ = idle_stop_check.tick() => {
+                        match running.as_ref().filter(|r| r.idle_expired()) {
+                            Some(r) => {
+                                let minutes = r.idle_stop.map_or(0, |d| d.as_secs() / 60);
+                                shared.log_line(format!(
+                                    "No sender for {minutes} min — stopping automatically."
+                                ));
+                                CoreCommand::Stop
+                            }
+                            None => continue,
+                        }
+                    }
+                    _ = shared.clients_changed() => {
+                        if let Some(r) = running.as_mut() {
+                            r.update_idle(&shared);
+                        }
+                        continue;
+                    }
+                };
+                let cmd = match cmd {
+                    CoreCommand::Resume => last_start
+                        .clone()
+                        .unwrap_or_else(|| CoreCommand::default_start(http_server.requested_addr())),
+                    CoreCommand::Start { .. } => {
+                        last_start = Some(cmd.clone());
+                        cmd
+                    }
+                    cmd => cmd,
+                };
                 match cmd {
                     CoreCommand::Start {
-                        bind_addr: _,
+                        bind_addr,
                         output_device,
                         use_stun,
+                        ice_servers,
+                        allow_multiple_clients,
+                        monitor_device,
+                        monitor_gain,
+                        monitor_muted,
+                        quality,
+                        latency_ms,
+                        require_approval,
+                        relay_target,
+                        snapcast_target,
+                        captions_model,
+                        standby_device,
+                        echo_cancellation,
+                        advertise_mdns: advertise,
+                        udp_beacon,
+                        udp_ingest,
+                        idle_stop_minutes,
+                        reconnect_grace_secs,
+                        keepalive_timeout_secs,
                     } => {
+                        let listen_state = recovery::ListenState {
+                            output_device: output_device.clone(),
+                            use_stun,
+                            ice_servers: ice_servers.clone(),
+                            allow_multiple_clients,
+                            monitor_device: monitor_device.clone(),
+                            monitor_gain,
+                            monitor_muted,
+                            quality,
+                            latency_ms,
+                            require_approval,
+                            relay_target: relay_target.clone(),
+                            snapcast_target: snapcast_target.clone(),
+                            captions_model: captions_model.clone(),
+                            standby_device: standby_device.clone(),
+                            echo_cancellation,
+                            agc,
+                            stereo_width,
+                            advertise_mdns: advertise,
+                            udp_beacon,
+                            udp_ingest,
+                            idle_stop_minutes,
+                            reconnect_grace_secs,
+                            keepalive_timeout_secs,
+                        };
+
                         // Stop any existing run first
-                        if let Some(r) = running.take() {
+                        if let Some(mut r) = running.take() {
                             shared.log_line("Stopping previous session…");
+                            r.stop_recording(&shared);
                             http_server.deactivate().await;
-                            if let Some(mdns) = r.mdns {
-                                mdns.shutdown();
+                            if let Some(m) = mdns.as_mut() {
+                                m.set_ready(false);
                             }
                             shared.set_server_running(false);
+                            shared.set_meter(None);
                         }
 
                         shared.set_last_error(None);
 
-                        // Audio queue (mono i16 @ 48 kHz, ~1 second buffer)
-                        let queue = Arc::new(ArrayQueue::<i16>::new(48_000));
+                        // Move the listener if the Bind Address was edited
+                        match bind_addr.trim().parse::<std::net::SocketAddr>() {
+                            Ok(addr) if addr != http_server.requested_addr() => {
+                                shared.log_line(format!("Moving HTTP server to {addr}…"));
+                                http_server.rebind(addr, &shared).await;
+                                publish_urls(&http_server, &shared);
+                                readvertise(&mut mdns, advertise_mdns, &http_server, false, &shared);
+                            }
+                            Ok(_) => {}
+                            Err(e) => shared.log_line(format!(
+                                "Invalid bind address {bind_addr:?}, staying on {}: {e}",
+                                http_server.listen_addr()
+                            )),
+                        }
+
+                        // Mixer with one input queue per connected sender
+                        let params = match latency_ms {
+                            Some(ms) => quality.params().with_latency(ms),
+                            None => quality.params(),
+                        };
+                        let mixer = Arc::new(Mixer::new(params.audio_tuning()));
+                        mixer.set_echo_cancellation(echo_cancellation);
+                        mixer.set_agc(agc);
+                        mixer.set_stereo_width(stereo_width);
+                        mixer.set_output_gain(output_gain);
+                        mixer.set_output_muted(output_muted);
+                        shared.log_line(format!(
+                            "Quality preset: {quality} ({})",
+                            quality.describe()
+                        ));
+                        if latency_ms.is_some() {
+                            shared.log_line(format!(
+                                "Custom latency: {} ms prefill, {} ms max buffer, {} frame device buffer",
+                                params.jitter_target_ms,
+                                params.max_buffer_ms,
+                                params.output_buffer_frames
+                            ));
+                        }
+                        // A fixed latency target leaves nothing to tune
+                        let auto_latency = (quality == QualityPreset::Auto && latency_ms.is_none())
+                            .then(|| AutoLatency::new(mixer.underruns()));
 
                         // Start audio output
                         match audio::AudioOutput::start(
                             output_device.as_deref(),
-                            Arc::clone(&queue),
+                            Arc::clone(&mixer),
                         ) {
                             Ok(audio_out) => {
                                 shared.log_line(format!(
                                     "Audio output started: {}",
                                     audio_out.device_name()
                                 ));
+                                shared.set_output_device(Some(audio_out.device_name().to_string()));
+
+                                let relay = relay_target
+                                    .as_ref()
+                                    .map(|_| signaling::relay::RelayFeed::new());
+                                let ice_servers = signaling::ice_servers(use_stun, &ice_servers);
+
+                                let options = signaling::SessionOptions {
+                                    ice_servers: ice_servers.clone(),
+                                    allow_multiple_clients,
+                                    opus_fmtp: params.opus_fmtp(),
+                                    require_approval,
+                                    relay: relay.clone(),
+                                    reconnect_grace: Duration::from_secs(u64::from(
+                                        reconnect_grace_secs,
+                                    )),
+                                    keepalive_timeout: Duration::from_secs(u64::from(
+                                        keepalive_timeout_secs.max(1),
+                                    )),
+                                };
 
                                 // Activate WebSocket connections on the already-running server
                                 let session_cancel = http_server
-                                    .activate(Arc::clone(&queue), use_stun)
+                                    .activate(Arc::clone(&mixer), options.clone())
                                     .await;
 
-                                // Register mDNS for auto-discovery
-                                let mdns =
-                                    signaling::MdnsRegistration::register(9001, &shared);
+                                if let (Some(target), Some(feed)) = (&relay_target, relay) {
+                                    signaling::relay::spawn(
+                                        target,
+                                        feed,
+                                        ice_servers,
+                                        shared.clone(),
+                                        session_cancel.child_token(),
+                                    );
+                                }
+
+                                // Flag the mDNS service ready, or follow a changed setting
+                                match mdns.as_mut() {
+                                    Some(m) if advertise == advertise_mdns => m.set_ready(true),
+                                    _ => {
+                                        advertise_mdns = advertise;
+                                        readvertise(
+                                            &mut mdns,
+                                            advertise,
+                                            &http_server,
+                                            true,
+                                            &shared,
+                                        );
+                                    }
+                                }
+                                if !advertise {
+                                    shared.log_line("mDNS advertising is off.");
+                                }
+                                if let Some(port) = crate::profile::current().pcm_port {
+                                    signaling::pcm::spawn(
+                                        port,
+                                        options.clone(),
+                                        Arc::clone(&mixer),
+                                        shared.clone(),
+                                        session_cancel.child_token(),
+                                    );
+                                }
+                                if let Some(port) = crate::profile::current().rtp_port {
+                                    signaling::rtp::spawn(
+                                        port,
+                                        options.clone(),
+                                        Arc::clone(&mixer),
+                                        shared.clone(),
+                                        session_cancel.child_token(),
+                                    );
+                                }
+                                if let Some(ingest) = udp_ingest {
+                                    signaling::udp_session::spawn(
+                                        ingest,
+                                        options.clone(),
+                                        Arc::clone(&mixer),
+                                        shared.clone(),
+                                        session_cancel.child_token(),
+                                    );
+                                }
+                                let beacon = udp_beacon.then(|| {
+                                    let token = session_cancel.child_token();
+                                    signaling::beacon::spawn(
+                                        http_server.port(),
+                                        shared.clone(),
+                                        token.clone(),
+                                    );
+                                    token
+                                });
 
                                 shared.set_server_running(true);
+                                shared.set_jitter_target_ms(Some(params.jitter_target_ms));
                                 shared.log_line(format!(
                                     "Listening on {}",
                                     http_server.bind_addr
@@ -255,12 +2143,43 @@ pub fn spawn_runtime(shared: SharedStatus) -> CoreController {
                                     http_server.ws_url
                                 ));
 
-                                running = Some(Running {
+                                let mut r = Running {
                                     audio: audio_out,
-                                    queue,
-                                    _session_cancel: session_cancel,
-                                    mdns,
-                                });
+                                    routed: audio::RoutedOutputs::default(),
+                                    monitor: None,
+                                    recorder: None,
+                                    snapcast: None,
+                                    captions: None,
+                                    monitor_tap: Arc::new(MonitorTap::new(
+                                        monitor_gain,
+                                        monitor_muted,
+                                    )),
+                                    mixer,
+                                    session_cancel,
+                                    beacon,
+                                    idle: false,
+                                    idle_since: None,
+                                    idle_stop: idle_stop_minutes
+                                        .map(|m| Duration::from_secs(u64::from(m) * 60)),
+                                    standby_device,
+                                    lost_output: None,
+                                    auto_latency,
+                                };
+                                r.set_monitor(monitor_device, &shared);
+                                if let Some(target) = &snapcast_target {
+                                    r.start_snapcast(target, &shared);
+                                }
+                                if let Some(model) = &captions_model {
+                                    r.start_captions(model, &shared);
+                                }
+                                r.update_idle(&shared);
+                                shared.set_meter(Some(Arc::clone(&r.mixer)));
+                                running = Some(r);
+
+                                if let Err(e) = recovery::mark_listening(&listen_state) {
+                                    log::warn!("Failed to write recovery marker: {e:#}");
+                                }
+                                last_settings = Some(listen_state);
                             }
                             Err(e) => {
                                 shared.set_last_error(Some(e.to_string()));
@@ -271,17 +2190,31 @@ pub fn spawn_runtime(shared: SharedStatus) -> CoreController {
                         }
                     }
                     CoreCommand::Stop => {
-                        if let Some(r) = running.take() {
+                        if let Some(mut r) = running.take() {
                             shared.log_line("Stopping…");
+                            r.stop_recording(&shared);
                             http_server.deactivate().await;
-                            if let Some(mdns) = r.mdns {
-                                mdns.shutdown();
+                            if let Some(m) = mdns.as_mut() {
+                                m.set_ready(false);
                             }
                         }
                         shared.reset_connection();
+                        shared.set_meter(None);
+                        recovery::clear();
                         shared.log_line("Stopped.");
                     }
+                    CoreCommand::SetStandbyDevice { device_name } => {
+                        if let Some(r) = running.as_mut() {
+                            r.standby_device = device_name;
+                        }
+                    }
                     CoreCommand::ChangeOutputDevice { device_name } => {
+                        if let Some(CoreCommand::Start { output_device, .. }) =
+                            last_start.as_mut()
+                        {
+                            // Resume should play on the new device
+                            *output_device = device_name.clone();
+                        }
                         if let Some(ref mut r) = running {
                             let old_device = r.audio.device_name().to_string();
                             shared.log_line(format!(
@@ -289,7 +2222,6 @@ pub fn spawn_runtime(shared: SharedStatus) -> CoreController {
                             ));
 
                             // Drop old stream to stop its cpal callback
-                            let queue_ref = Arc::clone(&r.queue);
                             let old_audio = std::mem::replace(
                                 &mut r.audio,
                                 audio::AudioOutput::stopped(),
@@ -301,19 +2233,28 @@ pub fn spawn_runtime(shared: SharedStatus) -> CoreController {
                                 .await;
 
                             // Drain stale samples
-                            while queue_ref.pop().is_some() {}
+                            r.mixer.clear(None);
 
                             // Start new stream on the selected device
                             match audio::AudioOutput::start(
                                 device_name.as_deref(),
-                                Arc::clone(&r.queue),
+                                Arc::clone(&r.mixer),
                             ) {
                                 Ok(new_audio) => {
                                     shared.log_line(format!(
                                         "Audio output switched to: {}",
                                         new_audio.device_name()
                                     ));
+                                    r.mixer.mark(format!(
+                                        "Output device: {}",
+                                        new_audio.device_name()
+                                    ));
+                                    shared.set_output_device(Some(
+                                        new_audio.device_name().to_string(),
+                                    ));
                                     r.audio = new_audio;
+                                    r.lost_output = None;
+                                    r.apply_idle();
                                 }
                                 Err(e) => {
                                     shared.set_last_error(Some(e.to_string()));
@@ -322,21 +2263,390 @@ pub fn spawn_runtime(shared: SharedStatus) -> CoreController {
                                     ));
                                     if let Ok(fallback) = audio::AudioOutput::start(
                                         Some(&old_device),
-                                        Arc::clone(&r.queue),
+                                        Arc::clone(&r.mixer),
                                     ) {
                                         shared.log_line(
                                             "Reverted to previous audio device",
                                         );
                                         r.audio = fallback;
+                                        r.apply_idle();
                                     }
                                 }
                             }
                         }
                     }
+                    CoreCommand::SetClientGain { client_id, gain } => {
+                        if let Some(input) =
+                            running.as_ref().and_then(|r| r.mixer.input(client_id))
+                        {
+                            input.set_gain(gain);
+                            shared.update_client(client_id, |c| c.gain = input.gain());
+                        }
+                    }
+                    CoreCommand::SetClientOutput {
+                        client_id,
+                        device_name,
+                    } => {
+                        let Some(r) = running.as_mut() else {
+                            continue;
+                        };
+                        let Some(input) = r.mixer.input(client_id) else {
+                            continue;
+                        };
+
+                        // Routing to the main device is the same as no route
+                        let route = device_name.filter(|d| d != r.audio.device_name());
+
+                        if let Some(ref device) = route {
+                            if let Err(e) = r.routed.ensure(device, &r.mixer) {
+                                shared.set_last_error(Some(e.to_string()));
+                                shared.log_line(format!(
+                                    "Failed to open '{device}' for client #{client_id}: {e}"
+                                ));
+                                continue;
+                            }
+                        }
+
+                        input.set_route(route.clone());
+                        r.routed.retain(&r.mixer.routes());
+                        // A newly opened route stream follows the idle state
+                        r.apply_idle();
+                        r.mixer.mark(format!(
+                            "Client #{client_id} output: {}",
+                            route.as_deref().unwrap_or("main")
+                        ));
+                        shared.update_client(client_id, |c| c.output = route.clone());
+                        shared.log_line(format!(
+                            "Client #{client_id} routed to {}",
+                            route.as_deref().unwrap_or("main output")
+                        ));
+                    }
+                    CoreCommand::SetMonitorDevice { device_name } => {
+                        if let Some(r) = running.as_mut() {
+                            r.set_monitor(device_name, &shared);
+                        }
+                    }
+                    CoreCommand::SetMonitorGain(gain) => {
+                        if let Some(r) = running.as_ref() {
+                            r.monitor_tap.set_gain(gain);
+                        }
+                    }
+                    CoreCommand::SetGain(gain) => {
+                        output_gain = gain;
+                        if let Some(r) = running.as_ref() {
+                            r.mixer.set_output_gain(gain);
+                        }
+                    }
+                    CoreCommand::SetMute(muted) => {
+                        output_muted = muted;
+                        if let Some(r) = running.as_ref() {
+                            r.mixer.set_output_muted(muted);
+                            r.mixer.mark(if muted {
+                                "Output muted"
+                            } else {
+                                "Output unmuted"
+                            });
+                        }
+                    }
+                    CoreCommand::SetEchoCancellation(enabled) => {
+                        if let Some(r) = running.as_ref() {
+                            r.mixer.set_echo_cancellation(enabled);
+                            shared.log_line(if enabled {
+                                "Echo cancellation on."
+                            } else {
+                                "Echo cancellation off."
+                            });
+                        }
+                    }
+                    CoreCommand::SetAgc(config) => {
+                        // Retuning from a slider isn't worth a log line
+                        if running.is_some() && config.is_some() != agc.is_some() {
+                            shared.log_line(if config.is_some() {
+                                "Automatic gain control on."
+                            } else {
+                                "Automatic gain control off."
+                            });
+                        }
+                        agc = config;
+                        if let Some(r) = running.as_ref() {
+                            r.mixer.set_agc(agc);
+                        }
+                    }
+                    CoreCommand::SetStereoWidth(width) => {
+                        stereo_width = width;
+                        if let Some(r) = running.as_ref() {
+                            r.mixer.set_stereo_width(width);
+                        }
+                    }
+                    CoreCommand::SetMonitorMute(muted) => {
+                        if let Some(r) = running.as_ref() {
+                            r.monitor_tap.set_muted(muted);
+                            r.mixer.mark(if muted {
+                                "Monitor muted"
+                            } else {
+                                "Monitor unmuted"
+                            });
+                        }
+                    }
+                    CoreCommand::StartRecording { path } => match running.as_mut() {
+                        Some(r) => r.start_recording(path, recording_format, &shared),
+                        None => shared.log_line("Cannot record: receiver is not started."),
+                    },
+                    CoreCommand::StopRecording => {
+                        if let Some(r) = running.as_mut() {
+                            r.stop_recording(&shared);
+                        }
+                    }
+                    CoreCommand::SetRecordingFormat(format) => recording_format = format,
+                    CoreCommand::ExportStats { path } => shared.export_stats(path),
+                    CoreCommand::ExportLogs { path } => shared.export_logs(path),
+                    CoreCommand::ExportTranscript { path } => shared.export_transcript(path),
+                    CoreCommand::ExportClientCertificate { path } => {
+                        let path = path.unwrap_or_else(signaling::mtls::default_export_path);
+                        let shared = shared.clone();
+                        tokio::task::spawn_blocking(move || {
+                            match signaling::mtls::export_client_identity(&path) {
+                                Ok(password) => shared.set_client_certificate(&path, password),
+                                Err(e) => shared.log_line(format!(
+                                    "Failed to export client certificate: {e:#}"
+                                )),
+                            }
+                        });
+                    }
+                    CoreCommand::SetCertificate { cert } => {
+                        match http_server.set_certificate(cert.as_ref()) {
+                            Ok(()) => {
+                                if let Err(e) = signaling::user_cert::save(cert.as_ref()) {
+                                    shared.log_line(format!(
+                                        "Failed to save certificate setting: {e:#}"
+                                    ));
+                                }
+                                shared.log_line(match &cert {
+                                    Some(cert) => format!(
+                                        "Now serving TLS certificate {}",
+                                        cert.cert_path.display()
+                                    ),
+                                    None => {
+                                        "Now serving the self-signed TLS certificate".to_string()
+                                    }
+                                });
+                            }
+                            Err(e) => {
+                                shared.log_line(format!("TLS certificate not changed: {e:#}"))
+                            }
+                        }
+                    }
+                    CoreCommand::Rebind { bind_addr } => {
+                        let addr = match bind_addr.trim().parse::<std::net::SocketAddr>() {
+                            Ok(addr) => addr,
+                            Err(e) => {
+                                shared.log_line(format!(
+                                    "Invalid bind address {bind_addr:?}: {e}"
+                                ));
+                                continue;
+                            }
+                        };
+                        if addr == http_server.requested_addr() {
+                            shared.log_line(format!("Already listening on {addr}"));
+                            continue;
+                        }
+                        shared.log_line(format!("Moving HTTP server to {addr}…"));
+                        http_server.rebind(addr, &shared).await;
+                        publish_urls(&http_server, &shared);
+                        if let Some(CoreCommand::Start { bind_addr, .. }) = last_start.as_mut() {
+                            // Resume should stay on the new address
+                            *bind_addr = addr.to_string();
+                        }
+                        readvertise(
+                            &mut mdns,
+                            advertise_mdns,
+                            &http_server,
+                            running.is_some(),
+                            &shared,
+                        );
+                        if let Some(r) = running.as_mut() {
+                            // The announcement carries the port, so restart it
+                            if let Some(beacon) = r.beacon.take() {
+                                beacon.cancel();
+                                let token = r.session_cancel.child_token();
+                                signaling::beacon::spawn(
+                                    http_server.port(),
+                                    shared.clone(),
+                                    token.clone(),
+                                );
+                                r.beacon = Some(token);
+                            }
+                        }
+                        shared.log_line(format!(
+                            "Web sender available at https://{}",
+                            http_server.bind_addr
+                        ));
+                    }
+                    CoreCommand::SetInterface { interface } => {
+                        if let Err(e) =
+                            signaling::preferred_interface::save(interface.as_deref())
+                        {
+                            shared.log_line(format!("Failed to save interface choice: {e:#}"));
+                        }
+                        http_server.set_interface(interface);
+                        publish_urls(&http_server, &shared);
+                        readvertise(
+                            &mut mdns,
+                            advertise_mdns,
+                            &http_server,
+                            running.is_some(),
+                            &shared,
+                        );
+                        shared.log_line(format!(
+                            "Web sender available at https://{}",
+                            http_server.bind_addr
+                        ));
+                    }
+                    CoreCommand::CreateDiagnosticBundle { path } => {
+                        let path = path.unwrap_or_else(|| {
+                            stats::export_path("lan-mic-diagnostics", "zip")
+                        });
+                        let shared = shared.clone();
+                        let settings = last_settings.clone();
+                        // Device enumeration can block for a while on some hosts
+                        tokio::task::spawn_blocking(move || {
+                            match diagnostics::create_bundle(&path, &shared, settings.as_ref()) {
+                                Ok(()) => shared.log_line(format!(
+                                    "Diagnostic bundle written to {}",
+                                    path.display()
+                                )),
+                                Err(e) => shared.log_line(format!(
+                                    "Failed to create diagnostic bundle: {e:#}"
+                                )),
+                            }
+                        });
+                    }
+                    CoreCommand::SetClientBitrateCap {
+                        client_id,
+                        max_kbps,
+                    } => {
+                        let msg = ControlMessage::Bitrate { max_kbps };
+                        if shared.send_control(client_id, msg) {
+                            shared.update_client(client_id, |c| c.bitrate_cap_kbps = max_kbps);
+                            shared.log_line(match max_kbps {
+                                Some(kbps) => {
+                                    format!("Asked client #{client_id} to send at most {kbps} kbps")
+                                }
+                                None => format!("Asked client #{client_id} for full bitrate"),
+                            });
+                        } else {
+                            shared.log_line(format!(
+                                "Client #{client_id} has no control channel; bitrate unchanged."
+                            ));
+                        }
+                    }
+                    CoreCommand::SetClientPriority {
+                        client_id,
+                        priority,
+                    } => {
+                        if let Some(input) =
+                            running.as_ref().and_then(|r| r.mixer.input(client_id))
+                        {
+                            input.set_priority(priority);
+                            shared.update_client(client_id, |c| c.priority = priority);
+                            shared.log_line(format!(
+                                "Client #{client_id} priority ducking {}",
+                                if priority { "enabled" } else { "disabled" }
+                            ));
+                        }
+                    }
+                    CoreCommand::SetClientMute { client_id, muted } => {
+                        if let Some(r) = running.as_ref() {
+                            let Some(input) = r.mixer.input(client_id) else {
+                                continue;
+                            };
+                            input.set_muted(muted);
+                            r.mixer.mark(format!(
+                                "Client #{client_id} {}",
+                                if muted { "muted" } else { "unmuted" }
+                            ));
+                            shared.update_client(client_id, |c| c.muted = muted);
+                            shared.log_line(format!(
+                                "Client #{client_id} {}",
+                                if muted { "muted" } else { "unmuted" }
+                            ));
+                        }
+                    }
+                    CoreCommand::ResolveApproval { request_id, allow } => {
+                        shared.resolve_approval(request_id, allow);
+                    }
+                    CoreCommand::ForgetTrustedDevice { device_id } => {
+                        shared.forget_trusted_device(&device_id);
+                        shared.log_line("Trusted device removed.");
+                    }
+                    CoreCommand::SetAccessList(list) => {
+                        shared.set_access_list(list);
+                        shared.log_line("Access rules updated.");
+                    }
+                    // Already replaced by the stored `Start` above
+                    CoreCommand::Resume => {}
                 }
             }
+            // Close the listeners and let open requests finish
+            if let Err(e) = http_server.shutdown().await {
+                log::warn!("HTTP server stopped with: {e:#}");
+            }
+            // Say goodbye so senders drop the receiver from their lists
+            if let Some(m) = mdns {
+                m.shutdown();
+            }
         });
     });
 
     CoreController { tx }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn voice_timelines_are_per_client() {
+        let shared = SharedStatus::default();
+        shared.add_client(1, "10.0.0.1".into());
+        shared.begin_session();
+        shared.add_client(2, "10.0.0.2".into());
+        shared.mark_voice_activity(2, true);
+
+        let timelines = shared.snapshot_without_logs().voice_timelines;
+        assert_eq!(timelines.len(), 2);
+        assert_eq!(timelines[0].segments.len(), 1);
+        assert!(!timelines[0].segments[0].talking);
+        assert_eq!(timelines[1].client, 2);
+        assert_eq!(timelines[1].segments.len(), 2);
+        assert!(timelines[1].segments[1].talking);
+    }
+
+    #[test]
+    fn capped_timeline_keeps_its_origin() {
+        let mut timeline = ClientTimeline::new(1, "10.0.0.1".into());
+        timeline.connected -= Duration::from_secs(60);
+        for n in 0..MAX_VOICE_SEGMENTS * 2 {
+            timeline.mark(n % 2 == 0);
+        }
+        timeline.end();
+        let snapshot = timeline.snapshot();
+        assert_eq!(snapshot.segments.len(), MAX_VOICE_SEGMENTS);
+        // Offsets are still measured from the connect time
+        assert!(snapshot.duration_ms >= 60_000);
+        assert!(snapshot.segments.iter().all(|s| s.start_ms >= 60_000));
+        assert!(snapshot.segments.last().unwrap().end_ms.is_some());
+    }
+
+    #[test]
+    fn first_client_of_a_new_session_clears_old_timelines() {
+        let shared = SharedStatus::default();
+        shared.add_client(1, "10.0.0.1".into());
+        shared.remove_client(1);
+        assert_eq!(shared.snapshot_without_logs().voice_timelines.len(), 1);
+        shared.add_client(2, "10.0.0.2".into());
+        let timelines = shared.snapshot_without_logs().voice_timelines;
+        assert_eq!(timelines.len(), 1);
+        assert_eq!(timelines[0].client, 2);
+    }
+}
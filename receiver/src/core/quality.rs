@@ -0,0 +1,146 @@
+use crate::audio::AudioTuning;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Range of the custom latency setting.
+pub const LATENCY_RANGE_MS: RangeInclusive<u32> = 20..=500;
+
+/// One-click bundles of the latency/robustness knobs.
+///
+/// Each preset jointly picks the jitter-buffer depth, how much audio an
+/// input may hold, the output device buffer size, and the Opus parameters
+/// advertised to the sender in our SDP offer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum QualityPreset {
+    LowestLatency,
+    #[default]
+    Balanced,
+    RobustWifi,
+    /// Starts out conservative and shrinks the jitter buffer to the smallest
+    /// target that plays without underruns (see [`super::latency`]).
+    Auto,
+}
+
+/// Concrete values behind a [`QualityPreset`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QualityParams {
+    pub jitter_target_ms: u32,
+    pub max_buffer_ms: u32,
+    pub output_buffer_frames: u32,
+    pub opus_bitrate: u32,
+    pub opus_fec: bool,
+    pub opus_ptime_ms: u32,
+}
+
+impl QualityPreset {
+    pub const ALL: [QualityPreset; 4] = [
+        QualityPreset::LowestLatency,
+        QualityPreset::Balanced,
+        QualityPreset::RobustWifi,
+        QualityPreset::Auto,
+    ];
+
+    pub fn params(self) -> QualityParams {
+        match self {
+            QualityPreset::LowestLatency => QualityParams {
+                jitter_target_ms: 20,
+                max_buffer_ms: 120,
+                output_buffer_frames: 256,
+                opus_bitrate: 64_000,
+                opus_fec: false,
+                opus_ptime_ms: 10,
+            },
+            QualityPreset::Balanced => QualityParams {
+                jitter_target_ms: 60,
+                max_buffer_ms: 400,
+                output_buffer_frames: 512,
+                opus_bitrate: 48_000,
+                opus_fec: true,
+                opus_ptime_ms: 20,
+            },
+            QualityPreset::RobustWifi => QualityParams {
+                jitter_target_ms: 150,
+                max_buffer_ms: 1000,
+                output_buffer_frames: 1024,
+                opus_bitrate: 32_000,
+                opus_fec: true,
+                opus_ptime_ms: 40,
+            },
+            QualityPreset::Auto => QualityParams {
+                jitter_target_ms: super::latency::AUTO_START_MS,
+                max_buffer_ms: 1000,
+                output_buffer_frames: 512,
+                opus_bitrate: 48_000,
+                opus_fec: true,
+                opus_ptime_ms: 20,
+            },
+        }
+    }
+
+    /// One-line summary of the preset's values for the Settings view.
+    pub fn describe(self) -> String {
+        let p = self.params();
+        if self == QualityPreset::Auto {
+            return format!(
+                "Starts at {} ms and settles on the smallest stable jitter buffer \
+                 within the first minute",
+                p.jitter_target_ms
+            );
+        }
+        format!(
+            "{} ms jitter buffer · {} kbps Opus · FEC {} · {} ms packets",
+            p.jitter_target_ms,
+            p.opus_bitrate / 1000,
+            if p.opus_fec { "on" } else { "off" },
+            p.opus_ptime_ms
+        )
+    }
+}
+
+impl QualityParams {
+    /// Replace the preset's buffering with a fixed latency target: playback
+    /// starts once `ms` of audio is buffered, an input holds at most four
+    /// times that, and the device buffer is a quarter of it.
+    pub fn with_latency(self, ms: u32) -> Self {
+        let ms = ms.clamp(*LATENCY_RANGE_MS.start(), *LATENCY_RANGE_MS.end());
+        Self {
+            jitter_target_ms: ms,
+            max_buffer_ms: (ms * 4).max(120),
+            output_buffer_frames: (ms * 48 / 4).clamp(128, 2048),
+            ..self
+        }
+    }
+
+    pub fn audio_tuning(&self) -> AudioTuning {
+        AudioTuning {
+            jitter_target_samples: (self.jitter_target_ms * 48) as usize,
+            input_capacity: (self.max_buffer_ms * 48) as usize,
+            output_buffer_frames: Some(self.output_buffer_frames),
+        }
+    }
+
+    /// Opus `a=fmtp` parameters for our SDP offer; browsers honour these
+    /// when configuring their encoder. `stereo=1` lets a stereo source
+    /// (e.g. an external interface) arrive as stereo; mono mics still
+    /// send mono.
+    pub fn opus_fmtp(&self) -> String {
+        format!(
+            "minptime=10;ptime={};useinbandfec={};maxaveragebitrate={};stereo=1;sprop-stereo=1",
+            self.opus_ptime_ms,
+            u8::from(self.opus_fec),
+            self.opus_bitrate
+        )
+    }
+}
+
+impl fmt::Display for QualityPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            QualityPreset::LowestLatency => "Lowest latency",
+            QualityPreset::Balanced => "Balanced",
+            QualityPreset::RobustWifi => "Robust Wi-Fi",
+            QualityPreset::Auto => "Auto",
+        })
+    }
+}
@@ -0,0 +1,93 @@
+use super::quality::QualityPreset;
+use super::signaling::udp_session::UdpIngest;
+use super::signaling::IceServer;
+use crate::audio::dsp::AgcConfig;
+use crate::config_store;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Settings of the session that was listening, kept on disk while it runs.
+///
+/// The file is written when listening starts and removed on Stop or Quit,
+/// so finding it at launch means the last run crashed or was killed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListenState {
+    pub output_device: Option<String>,
+    pub use_stun: bool,
+    #[serde(default)]
+    pub ice_servers: Vec<IceServer>,
+    pub allow_multiple_clients: bool,
+    pub monitor_device: Option<String>,
+    pub monitor_gain: f32,
+    pub monitor_muted: bool,
+    pub quality: QualityPreset,
+    #[serde(default)]
+    pub latency_ms: Option<u32>,
+    pub require_approval: bool,
+    pub relay_target: Option<String>,
+    #[serde(default)]
+    pub snapcast_target: Option<String>,
+    #[serde(default)]
+    pub captions_model: Option<String>,
+    pub standby_device: Option<String>,
+    pub echo_cancellation: bool,
+    #[serde(default)]
+    pub agc: Option<AgcConfig>,
+    #[serde(default = "default_stereo_width")]
+    pub stereo_width: f32,
+    #[serde(default = "default_true")]
+    pub advertise_mdns: bool,
+    #[serde(default)]
+    pub udp_beacon: bool,
+    #[serde(default)]
+    pub udp_ingest: Option<UdpIngest>,
+    #[serde(default)]
+    pub idle_stop_minutes: Option<u32>,
+    #[serde(default = "default_reconnect_grace")]
+    pub reconnect_grace_secs: u32,
+    #[serde(default = "default_keepalive_timeout")]
+    pub keepalive_timeout_secs: u32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_stereo_width() -> f32 {
+    1.0
+}
+
+fn default_reconnect_grace() -> u32 {
+    super::signaling::DEFAULT_RECONNECT_GRACE_SECS
+}
+
+fn default_keepalive_timeout() -> u32 {
+    super::signaling::DEFAULT_KEEPALIVE_TIMEOUT_SECS
+}
+
+const MARKER: &str = "listening.json";
+
+/// Settings of the last successful start, kept after it stops.
+const LAST_SESSION: &str = "last_session.json";
+
+/// Record that the receiver is listening with `state`, also remembering it
+/// as the settings to start with next time.
+pub fn mark_listening(state: &ListenState) -> Result<()> {
+    config_store::save(LAST_SESSION, state)?;
+    config_store::save(MARKER, state)
+}
+
+/// Remove the marker after a clean stop.
+pub fn clear() {
+    let _ = config_store::remove(MARKER);
+}
+
+/// Settings of a run that ended without stopping, if any.
+pub fn unclean_shutdown() -> Option<ListenState> {
+    config_store::load(MARKER)
+}
+
+/// Settings the receiver last started listening with, if it ever did.
+pub fn last_session() -> Option<ListenState> {
+    config_store::load(LAST_SESSION)
+}
@@ -0,0 +1,96 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Session log files kept; the oldest are deleted when a new one starts.
+const MAX_SESSION_LOGS: usize = 50;
+
+/// The log of one session (first sender connecting until the last one
+/// leaves), written next to the in-app log so exactly the relevant part can
+/// be attached to an issue.
+#[derive(Debug)]
+pub struct SessionLog {
+    id: String,
+    path: PathBuf,
+    started: Instant,
+    file: LineWriter<File>,
+}
+
+fn log_dir() -> Option<PathBuf> {
+    crate::profile::current().data_dir().map(|d| d.join("logs"))
+}
+
+impl SessionLog {
+    /// Open `session-<unix seconds>-<id>.log` with a fresh random ID.
+    pub fn create() -> Result<Self> {
+        let dir = log_dir().context("no data directory")?;
+        std::fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+        prune(&dir, MAX_SESSION_LOGS - 1);
+
+        let id = format!("{:08x}", rand::random::<u32>());
+        let secs = super::history::unix_now();
+        let path = dir.join(format!("session-{secs}-{id}.log"));
+        let file = File::create(&path).with_context(|| format!("create {}", path.display()))?;
+        let mut log = Self {
+            id,
+            path,
+            started: Instant::now(),
+            file: LineWriter::new(file),
+        };
+        let header = format!(
+            "LAN Mic session {} ({}), started at unix time {secs}",
+            log.id,
+            crate::profile::current().service_name()
+        );
+        log.write_line(&header);
+        Ok(log)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a line prefixed with the time since the session started.
+    pub fn write_line(&mut self, line: &str) {
+        let t = self.started.elapsed();
+        let res = writeln!(
+            self.file,
+            "[+{:02}:{:02}.{:03}] {line}",
+            t.as_secs() / 60,
+            t.as_secs() % 60,
+            t.subsec_millis()
+        );
+        if let Err(e) = res {
+            log::warn!("Failed to write {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// Delete the oldest session logs so at most `keep` remain.
+fn prune(dir: &Path, keep: usize) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut logs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("session-") && n.ends_with(".log"))
+        })
+        .collect();
+    if logs.len() <= keep {
+        return;
+    }
+    // Names start with the unix time, so they sort oldest first (until 2286)
+    logs.sort();
+    for path in &logs[..logs.len() - keep] {
+        let _ = std::fs::remove_file(path);
+    }
+}
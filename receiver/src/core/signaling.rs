@@ -1,47 +1,181 @@
-use crate::core::SharedStatus;
-use anyhow::{anyhow, Result};
+use crate::audio::mixer::{ClientId, Mixer, MixerInput};
+use crate::core::quality::QualityPreset;
+use crate::core::{history, CoreController, PortFallback, SharedStatus};
+use anyhow::{anyhow, bail, Context, Result};
 use axum::{
-    extract::{ws::WebSocketUpgrade, ConnectInfo, State},
-    response::{Html, Response},
+    extract::{ws::WebSocketUpgrade, ConnectInfo, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Json, Response},
     routing::get,
     Router,
 };
 use axum_server::tls_rustls::RustlsConfig;
-use crossbeam_queue::ArrayQueue;
-use rcgen::generate_simple_self_signed;
-use std::net::SocketAddr;
+use futures_util::future::BoxFuture;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio_util::sync::CancellationToken;
+use user_cert::UserCertificate;
+use webrtc::ice_transport::ice_server::RTCIceServer;
 
 #[cfg(not(target_os = "macos"))]
 use mdns_sd::{ServiceDaemon, ServiceInfo};
 
+pub mod acme;
+mod admin;
+mod api;
+pub mod beacon;
+pub mod control;
+mod decode;
+pub mod mtls;
+pub mod pcm;
+pub mod preferred_interface;
+pub mod relay;
+pub mod rtp;
+mod self_signed;
+#[cfg(test)]
+mod tests;
+pub mod udp_session;
+pub mod user_cert;
 mod webrtc_session;
 
 /// mDNS service type for LAN Mic discovery.
 const MDNS_SERVICE_TYPE: &str = "_lanmic._tcp.local.";
 
+/// Advertised in the mDNS TXT record so senders can find `/config.json`.
+const MDNS_CONFIG_PATH: &str = "/config.json";
+
+/// URI scheme a native sender registers to connect straight from a QR scan:
+/// `lanmic://connect?host=…&port=…&token=…`.
+pub const DEEP_LINK_SCHEME: &str = "lanmic";
+
+/// Source of unique per-connection client IDs.
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// First wait before retrying a failed bind; doubles up to [`BIND_RETRY_MAX`].
+const BIND_RETRY_MIN: Duration = Duration::from_secs(1);
+const BIND_RETRY_MAX: Duration = Duration::from_secs(30);
+
+/// How long in-flight requests get to finish when the listener shuts down.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
 /// Embed the web sender app at compile time.
 const SENDER_HTML: &str = include_str!("../../../sender(web)/index.html");
 
+/// Sender page with the receiver's advertised name in its title.
+fn sender_page(name: &str) -> String {
+    let escaped = name
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    SENDER_HTML.replacen(
+        "<title>LAN Mic</title>",
+        &format!("<title>{escaped} · LAN Mic</title>"),
+        1,
+    )
+}
+
 /// Shared state for the axum server.
 ///
 /// `session_state` is `None` until the user clicks START, at which point
-/// it is populated with the queue, STUN flag, and cancellation token.
+/// it is populated with the mixer, session options, and cancellation token.
 /// WebSocket connections are rejected while it is `None`.
 #[derive(Clone)]
 struct AppState {
     shared: SharedStatus,
+    /// Drives the core runtime from the web admin dashboard.
+    controller: CoreController,
+    /// Random per-launch token guarding `/admin`.
+    admin_token: Arc<str>,
+    /// Random per-launch token carried by the deep-link QR code.
+    pairing_token: Arc<str>,
+    /// `SENDER_HTML`, titled with the receiver's name.
+    sender_html: Arc<str>,
     /// Populated when the user clicks START; cleared on STOP.
     session_state: Arc<tokio::sync::RwLock<Option<SessionState>>>,
 }
 
+/// Public STUN server used when "Use STUN server" is on.
+const DEFAULT_STUN_URL: &str = "stun:stun.l.google.com:19302";
+
+/// A user-configured STUN or TURN server, for networks where senders can't
+/// reach the receiver directly.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct IceServer {
+    /// `stun:`, `turn:` or `turns:` URLs of the same server.
+    pub urls: Vec<String>,
+    /// TURN long-term credentials; empty for STUN.
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub credential: String,
+}
+
+/// ICE servers for a peer connection: the public STUN server if enabled,
+/// then the configured ones. URLs with an unknown scheme are skipped.
+pub fn ice_servers(use_stun: bool, servers: &[IceServer]) -> Vec<RTCIceServer> {
+    let mut ice = Vec::new();
+    if use_stun {
+        ice.push(RTCIceServer {
+            urls: vec![DEFAULT_STUN_URL.to_owned()],
+            ..Default::default()
+        });
+    }
+    for server in servers {
+        let (urls, unknown): (Vec<_>, Vec<_>) = server.urls.iter().cloned().partition(|url| {
+            ["stun:", "stuns:", "turn:", "turns:"]
+                .iter()
+                .any(|scheme| url.starts_with(scheme))
+        });
+        for url in unknown {
+            log::warn!("Ignoring ICE server {url:?}: expected stun:, turn: or turns:");
+        }
+        if !urls.is_empty() {
+            ice.push(RTCIceServer {
+                urls,
+                username: server.username.clone(),
+                credential: server.credential.clone(),
+            });
+        }
+    }
+    ice
+}
+
+/// Per-START options that apply to every sender session.
+#[derive(Debug, Clone)]
+pub struct SessionOptions {
+    /// STUN/TURN servers for gathering candidates.
+    pub ice_servers: Vec<RTCIceServer>,
+    pub allow_multiple_clients: bool,
+    /// Opus `a=fmtp` line offered to senders (bitrate, FEC, ptime).
+    pub opus_fmtp: String,
+    /// Prompt before accepting devices that aren't trusted yet.
+    pub require_approval: bool,
+    /// Forward a sender's stream to another receiver (relay mode).
+    pub relay: Option<relay::RelayFeed>,
+    /// How long a sender whose WebSocket dropped may resume its session.
+    pub reconnect_grace: Duration,
+    /// How long a sender's WebSocket may stay silent, pings included,
+    /// before the connection counts as dropped.
+    pub keepalive_timeout: Duration,
+}
+
+/// Reconnect grace period unless the user picks another.
+pub const DEFAULT_RECONNECT_GRACE_SECS: u32 = 20;
+
+/// Keepalive timeout unless the user picks another.
+pub const DEFAULT_KEEPALIVE_TIMEOUT_SECS: u32 = 20;
+
 #[derive(Clone)]
 struct SessionState {
-    queue: Arc<ArrayQueue<i16>>,
-    use_stun: bool,
-    active: Arc<tokio::sync::Mutex<bool>>,
+    mixer: Arc<Mixer>,
+    options: SessionOptions,
+    /// Number of currently connected senders.
+    active: Arc<tokio::sync::Mutex<usize>>,
     session_cancel: CancellationToken,
+    /// Sessions senders can resume after their socket drops.
+    resumable: webrtc_session::ResumeRegistry,
 }
 
 /// Platform-specific mDNS handle.
@@ -78,6 +212,25 @@ impl MdnsHandle {
             }
         }
     }
+
+    /// Re-announce the service with a new `ready` TXT flag.
+    fn set_ready(&mut self, ip: &str, port: u16, ready: bool) -> Result<()> {
+        match self {
+            #[cfg(target_os = "macos")]
+            MdnsHandle::NativeProcess(child) => {
+                // dns-sd can't change a TXT record in place
+                let _ = child.kill();
+                let _ = child.wait();
+                *self = register_mdns(ip, port, ready)?;
+            }
+            #[cfg(not(target_os = "macos"))]
+            MdnsHandle::CrateDaemon { daemon, .. } => {
+                // Registering the same name again replaces its records
+                daemon.register(service_info(ip, port, ready)?)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -87,25 +240,72 @@ impl MdnsHandle {
 pub struct HttpServer {
     pub bind_addr: String,
     pub ws_url: String,
+    /// Advertised LAN address; tracked so network changes can be detected.
+    ip: String,
+    /// All candidate interfaces, for the per-interface QR codes.
+    addresses: Vec<LanAddress>,
+    /// Interface picked in Settings to advertise first (`None` = automatic).
+    interface: Option<String>,
+    admin_token: Arc<str>,
+    pairing_token: Arc<str>,
+    listen: SocketAddr,
+    /// The address asked for; `listen` differs when its port was taken.
+    requested: SocketAddr,
+    tls_config: RustlsConfig,
+    /// Serving the generated certificate, which must follow address changes
+    /// (not a user-supplied or ACME one).
+    self_signed: bool,
+    app: Router,
     session_state: Arc<tokio::sync::RwLock<Option<SessionState>>>,
     shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
     join: tokio::task::JoinHandle<Result<()>>,
+    plain: Option<PlainListener>,
+}
+
+/// The unencrypted listener for `--http-port`, on the same interface as the
+/// HTTPS one. It serves only the sender page, `/ws` and the auto-config
+/// route: the admin dashboard and API carry tokens that must not travel in
+/// cleartext.
+struct PlainListener {
+    port: u16,
+    app: Router,
+    shutdown_tx: tokio::sync::oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<Result<()>>,
+}
+
+impl PlainListener {
+    fn spawn(ip: IpAddr, port: u16, app: Router, shared: SharedStatus) -> Self {
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let addr = SocketAddr::new(ip, port);
+        let join = tokio::spawn(supervise(addr, None, app.clone(), shutdown_rx, shared));
+        Self {
+            port,
+            app,
+            shutdown_tx,
+            join,
+        }
+    }
+
+    async fn stop(self) -> Result<()> {
+        let _ = self.shutdown_tx.send(());
+        match self.join.await {
+            Ok(r) => r,
+            Err(e) => Err(anyhow!("plain HTTP task join error: {e}")),
+        }
+    }
 }
 
 impl HttpServer {
     /// Activate WebSocket connections. Called when user clicks START.
     /// Returns the `SessionCancel` token for tracking active sessions.
-    pub async fn activate(
-        &self,
-        queue: Arc<ArrayQueue<i16>>,
-        use_stun: bool,
-    ) -> CancellationToken {
+    pub async fn activate(&self, mixer: Arc<Mixer>, options: SessionOptions) -> CancellationToken {
         let cancel = CancellationToken::new();
         let state = SessionState {
-            queue,
-            use_stun,
-            active: Arc::new(tokio::sync::Mutex::new(false)),
+            mixer,
+            options,
+            active: Arc::new(tokio::sync::Mutex::new(0)),
             session_cancel: cancel.clone(),
+            resumable: Arc::default(),
         };
         *self.session_state.write().await = Some(state);
         cancel
@@ -118,12 +318,215 @@ impl HttpServer {
         }
     }
 
+    pub fn port(&self) -> u16 {
+        self.listen.port()
+    }
+
+    /// The socket address the listener binds.
+    pub fn listen_addr(&self) -> SocketAddr {
+        self.listen
+    }
+
+    /// The address the listener was asked to bind, before any fallback to a
+    /// free port.
+    pub fn requested_addr(&self) -> SocketAddr {
+        self.requested
+    }
+
+    /// Web admin dashboard URL, including its access token.
+    pub fn admin_url(&self) -> String {
+        format!(
+            "https://{}:{}/admin?token={}",
+            url_host(&self.ip),
+            self.listen.port(),
+            self.admin_token
+        )
+    }
+
+    /// Sender page URL for every candidate interface, as `(interface, url)`.
+    pub fn sender_urls(&self) -> Vec<(String, String)> {
+        self.addresses
+            .iter()
+            .map(|a| {
+                let url = format!("https://{}:{}", url_host(&a.ip), self.listen.port());
+                (a.interface.clone(), url)
+            })
+            .collect()
+    }
+
+    /// Plain `http://` URL for every candidate interface, as `(interface,
+    /// url)`; empty without `--http-port`.
+    pub fn plain_urls(&self) -> Vec<(String, String)> {
+        let Some(plain) = &self.plain else {
+            return Vec::new();
+        };
+        self.addresses
+            .iter()
+            .map(|a| {
+                let url = format!("http://{}:{}", url_host(&a.ip), plain.port);
+                (a.interface.clone(), url)
+            })
+            .collect()
+    }
+
+    /// Deep link for every candidate interface, as `(interface, uri)`. A
+    /// native sender opening one connects to `/ws?token=…`, which skips the
+    /// approval prompt: scanning the QR is proof of being at this screen.
+    pub fn deep_links(&self) -> Vec<(String, String)> {
+        self.addresses
+            .iter()
+            .map(|a| {
+                let uri = format!(
+                    "{DEEP_LINK_SCHEME}://connect?host={}&port={}&token={}",
+                    url_host(&a.ip),
+                    self.listen.port(),
+                    self.pairing_token
+                );
+                (a.interface.clone(), uri)
+            })
+            .collect()
+    }
+
+    /// Re-pick the LAN address (e.g. after a Wi-Fi reconnect or DHCP renew)
+    /// and update `bind_addr`/`ws_url`. Returns the new IP if it, or the set
+    /// of candidate interfaces, changed.
+    pub fn refresh_address(&mut self) -> Option<String> {
+        let (ip, addresses) = lan_addresses(self.listen, self.interface.as_deref());
+        if ip == self.ip && addresses == self.addresses {
+            return None;
+        }
+        self.addresses = addresses;
+        self.bind_addr = format!("{}:{}", url_host(&ip), self.listen.port());
+        self.ws_url = format!("wss://{}:{}/ws", url_host(&ip), self.listen.port());
+        self.ip = ip.clone();
+        if self.self_signed {
+            self.renew_self_signed();
+        }
+        Some(ip)
+    }
+
+    /// Advertise `interface`'s address (`None` = pick automatically). The
+    /// other interfaces stay available in the QR switcher.
+    pub fn set_interface(&mut self, interface: Option<String>) {
+        self.interface = interface;
+        // Force the URLs to be rebuilt even if the address stays the same
+        self.ip.clear();
+        self.refresh_address();
+    }
+
+    /// Addresses to announce over mDNS: the chosen interface's, or every
+    /// one a wildcard listener accepts, or the bound one.
+    fn mdns_ips(&self) -> String {
+        if !self.listen.ip().is_unspecified() {
+            return self.listen.ip().to_string();
+        }
+        let mut ips: Vec<String> = wildcard_addresses(self.listen)
+            .into_iter()
+            .map(|a| a.ip)
+            .collect();
+        if let Some(name) = &self.interface {
+            let chosen: Vec<String> = self
+                .addresses
+                .iter()
+                .filter(|a| &a.interface == name)
+                .map(|a| a.ip.clone())
+                .collect();
+            if !chosen.is_empty() {
+                ips = chosen;
+            }
+        }
+        if ips.is_empty() {
+            "0.0.0.0".to_string()
+        } else {
+            ips.join(",")
+        }
+    }
+
+    /// Swap in a certificate covering the current addresses if the stored one
+    /// does not; otherwise browsers would warn about a name mismatch.
+    fn renew_self_signed(&self) {
+        let names = certificate_names(&self.ip, &self.addresses);
+        let res = self_signed::load_or_create(&names).and_then(|(cert_pem, key_pem, generated)| {
+            if generated {
+                let config = mtls::server_config(cert_pem.as_bytes(), key_pem.as_bytes())?;
+                self.tls_config.reload_from_config(config);
+                log::info!("Generated a TLS certificate for {}", names.join(", "));
+            }
+            Ok(())
+        });
+        if let Err(e) = res {
+            log::warn!("Failed to renew TLS certificate: {e:#}");
+        }
+    }
+
+    /// Serve `cert` from now on, or the self-signed certificate for `None`.
+    /// Connections already open keep the certificate they started with.
+    pub fn set_certificate(&mut self, cert: Option<&UserCertificate>) -> Result<()> {
+        if crate::profile::current().acme.is_some() {
+            bail!("the certificate is managed by --acme-domain");
+        }
+        let config = match cert {
+            Some(cert) => user_server_config(cert)?,
+            None => {
+                let names = certificate_names(&self.ip, &self.addresses);
+                let (cert_pem, key_pem, _) = self_signed::load_or_create(&names)?;
+                mtls::server_config(cert_pem.as_bytes(), key_pem.as_bytes())?
+            }
+        };
+        self.tls_config.reload_from_config(config);
+        self.self_signed = cert.is_none();
+        Ok(())
+    }
+
+    /// Move the listener to `addr` (or a free port after it) while the app
+    /// keeps running. The old listener shuts down gracefully first so the
+    /// port can be reused on another interface; the active session state
+    /// and the access tokens carry over.
+    pub async fn rebind(&mut self, requested: SocketAddr, shared: &SharedStatus) {
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        if let Some(tx) = self.shutdown_tx.replace(shutdown_tx) {
+            let _ = tx.send(());
+        }
+        match (&mut self.join).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => log::warn!("HTTP server on {} stopped with: {e}", self.listen),
+            Err(e) => log::warn!("HTTP server task join error: {e}"),
+        }
+        shared.log_line(format!("HTTP server on {} stopped", self.listen));
+        let addr = free_addr(requested, shared);
+        if let Some(plain) = self.plain.take() {
+            let (port, app) = (plain.port, plain.app.clone());
+            if let Err(e) = plain.stop().await {
+                log::warn!("Plain HTTP server stopped with: {e}");
+            }
+            self.plain = Some(PlainListener::spawn(addr.ip(), port, app, shared.clone()));
+        }
+
+        self.listen = addr;
+        self.requested = requested;
+        self.join = tokio::spawn(supervise(
+            addr,
+            Some(self.tls_config.clone()),
+            self.app.clone(),
+            shutdown_rx,
+            shared.clone(),
+        ));
+        // Force the URLs to be rebuilt for the new port
+        self.ip.clear();
+        self.refresh_address();
+    }
+
     /// Shut down the HTTP server entirely.
     pub async fn shutdown(mut self) -> Result<()> {
         self.deactivate().await;
         if let Some(tx) = self.shutdown_tx.take() {
             let _ = tx.send(());
         }
+        if let Some(plain) = self.plain.take() {
+            if let Err(e) = plain.stop().await {
+                log::warn!("Plain HTTP server stopped with: {e}");
+            }
+        }
         match self.join.await {
             Ok(r) => r,
             Err(e) => Err(anyhow!("server task join error: {e}")),
@@ -132,17 +535,31 @@ impl HttpServer {
 }
 
 /// Handles for mDNS that live alongside the server but are separate.
+/// Registered from launch so senders list the receiver before it listens;
+/// the `ready` TXT flag tells them whether it does.
 pub struct MdnsRegistration {
     handle: MdnsHandle,
+    ip: String,
+    port: u16,
+    ready: bool,
 }
 
 impl MdnsRegistration {
-    pub fn register(port: u16, shared: &SharedStatus) -> Option<Self> {
-        let ip = pick_local_ip().unwrap_or_else(|| "0.0.0.0".to_string());
-        match register_mdns(&ip, port) {
+    /// Advertise `server`: on its own IP when bound to one interface, else
+    /// on the interface picked in Settings or every LAN address it accepts
+    /// (A and AAAA records for a dual-stack `[::]`).
+    pub fn register(server: &HttpServer, ready: bool, shared: &SharedStatus) -> Option<Self> {
+        let ip = server.mdns_ips();
+        let port = server.port();
+        match register_mdns(&ip, port, ready) {
             Ok(handle) => {
                 shared.log_line("mDNS service registered");
-                Some(Self { handle })
+                Some(Self {
+                    handle,
+                    ip,
+                    port,
+                    ready,
+                })
             }
             Err(e) => {
                 shared.log_line(format!("mDNS registration failed (non-fatal): {e}"));
@@ -152,6 +569,17 @@ impl MdnsRegistration {
         }
     }
 
+    /// Flag whether a session is active, i.e. senders can connect.
+    pub fn set_ready(&mut self, ready: bool) {
+        if ready == self.ready {
+            return;
+        }
+        self.ready = ready;
+        if let Err(e) = self.handle.set_ready(&self.ip, self.port, ready) {
+            log::warn!("mDNS update error: {e}");
+        }
+    }
+
     pub fn shutdown(self) {
         self.handle.shutdown();
     }
@@ -164,75 +592,468 @@ impl MdnsRegistration {
 pub async fn start_http_server(
     bind_addr: String,
     shared: SharedStatus,
+    controller: CoreController,
 ) -> Result<HttpServer> {
-    // Generate self-signed certificate
-    let subject_alt_names = vec!["localhost".to_string(), "lan-mic-receiver".to_string()];
-    let cert = generate_simple_self_signed(subject_alt_names)?;
-    let tls_config = RustlsConfig::from_pem(
-        cert.cert.pem().into_bytes(),
-        cert.key_pair.serialize_pem().into_bytes(),
-    )
-    .await?;
+    let requested: SocketAddr = bind_addr.parse()?;
+    let addr = free_addr(requested, &shared);
+    let interface = preferred_interface::load();
+    let (ip, addresses) = lan_addresses(addr, interface.as_deref());
 
-    let addr: SocketAddr = bind_addr.parse()?;
-    let ip = pick_local_ip().unwrap_or_else(|| addr.ip().to_string());
-    let ws_url = format!("wss://{}:{}/ws", ip, addr.port());
+    let acme_options = crate::profile::current().acme.as_ref();
+    // A certificate from the user's own CA, unless ACME manages it
+    let user_config = match (acme_options, user_cert::load()) {
+        (None, Some(cert)) => match user_server_config(&cert) {
+            Ok(config) => {
+                shared.log_line(format!(
+                    "Using TLS certificate {}",
+                    cert.cert_path.display()
+                ));
+                Some(config)
+            }
+            Err(e) => {
+                shared.log_line(format!(
+                    "Custom TLS certificate not loaded, using the self-signed one: {e:#}"
+                ));
+                None
+            }
+        },
+        _ => None,
+    };
+    let self_signed = acme_options.is_none() && user_config.is_none();
+    let server_config = match user_config {
+        Some(config) => config,
+        None => {
+            let (chain_pem, key_pem) = match acme_options.and_then(acme::cached_certificate) {
+                Some(cert) => (cert.chain_pem, cert.key_pem),
+                None => {
+                    let names = certificate_names(&ip, &addresses);
+                    let (cert_pem, key_pem, generated) = self_signed::load_or_create(&names)?;
+                    if generated && self_signed {
+                        shared.log_line("Generated a new TLS certificate; phones will warn once");
+                    }
+                    (cert_pem, key_pem)
+                }
+            };
+            mtls::server_config(chain_pem.as_bytes(), key_pem.as_bytes())?
+        }
+    };
+    let tls_config = RustlsConfig::from_config(server_config);
+    if crate::profile::current().mtls {
+        shared.log_line("Client certificates required (mTLS)");
+    }
+    if let Some(options) = acme_options {
+        acme::spawn(options.clone(), tls_config.clone(), shared.clone());
+    }
+
+    let ws_url = format!("wss://{}:{}/ws", url_host(&ip), addr.port());
 
     let session_state: Arc<tokio::sync::RwLock<Option<SessionState>>> =
         Arc::new(tokio::sync::RwLock::new(None));
 
+    let admin_token: Arc<str> = format!("{:032x}", rand::random::<u128>()).into();
+    let pairing_token: Arc<str> = format!("{:032x}", rand::random::<u128>()).into();
+
     let state = AppState {
         shared: shared.clone(),
+        controller,
+        admin_token: Arc::clone(&admin_token),
+        pairing_token: Arc::clone(&pairing_token),
+        sender_html: sender_page(&crate::profile::current().service_name()).into(),
         session_state: session_state.clone(),
     };
 
-    let app = Router::new()
-        .route("/", get(|| async { Html(SENDER_HTML) }))
+    let sender_routes = Router::new()
+        .route("/", get(sender_page_handler))
         .route("/ws", get(ws_handler))
-        .with_state(state);
+        .route(MDNS_CONFIG_PATH, get(config_handler));
+    let app = sender_routes
+        .clone()
+        .merge(admin::routes())
+        .merge(api::routes())
+        .with_state(state.clone());
 
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
 
-    let join = tokio::spawn(async move {
-        let handle = axum_server::Handle::new();
-        let handle_clone = handle.clone();
-        
-        // Spawn a task to listen for shutdown signal
-        tokio::spawn(async move {
-            let _ = shutdown_rx.await;
-            handle_clone.graceful_shutdown(None);
-        });
+    let plain = match crate::profile::current().http_port {
+        // A plain listener would let senders skip the client certificate
+        Some(_) if crate::profile::current().mtls => {
+            shared.log_line("Plain HTTP listener not started: it can't be used with mTLS");
+            None
+        }
+        Some(port) => Some(PlainListener::spawn(
+            addr.ip(),
+            port,
+            sender_routes.with_state(state),
+            shared.clone(),
+        )),
+        None => None,
+    };
 
-        axum_server::bind_rustls(addr, tls_config)
-            .handle(handle)
-            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
-            .await
-            .map_err(|e| anyhow!("axum serve error: {e}"))?;
-        Ok(())
-    });
+    let join = tokio::spawn(supervise(
+        addr,
+        Some(tls_config.clone()),
+        app.clone(),
+        shutdown_rx,
+        shared,
+    ));
 
-    let bind_addr_str = format!("{}:{}", ip, addr.port());
+    let bind_addr_str = format!("{}:{}", url_host(&ip), addr.port());
 
     Ok(HttpServer {
         bind_addr: bind_addr_str,
         ws_url,
+        ip,
+        addresses,
+        interface,
+        admin_token,
+        pairing_token,
+        listen: addr,
+        requested,
+        tls_config,
+        self_signed,
+        app,
         session_state,
         shutdown_tx: Some(shutdown_tx),
         join,
+        plain,
     })
 }
 
+/// Keep a listener up: bind with backoff until the port is free (or the
+/// interface exists), and bind again if serving ever fails. Serves HTTPS
+/// with `tls_config`, else plain HTTP; only the HTTPS listener reports into
+/// the status banner.
+async fn supervise(
+    addr: SocketAddr,
+    tls_config: Option<RustlsConfig>,
+    app: Router,
+    mut shutdown_rx: tokio::sync::oneshot::Receiver<()>,
+    shared: SharedStatus,
+) -> Result<()> {
+    let plain = tls_config.is_none();
+    let mut delay = BIND_RETRY_MIN;
+    loop {
+        match bind_listener(addr) {
+            Ok(listener) => {
+                if plain {
+                    shared.log_line(format!("Plain HTTP listening on {addr}"));
+                } else if shared.set_http_error(None).is_some() {
+                    shared.log_line(format!("HTTP server listening on {addr}"));
+                }
+                delay = BIND_RETRY_MIN;
+
+                let handle = axum_server::Handle::new();
+                let service = app
+                    .clone()
+                    .into_make_service_with_connect_info::<SocketAddr>();
+                let mut server: BoxFuture<'static, std::io::Result<()>> = match &tls_config {
+                    Some(tls) => Box::pin(
+                        axum_server::from_tcp_rustls(listener, tls.clone())
+                            .handle(handle.clone())
+                            .serve(service),
+                    ),
+                    None => Box::pin(
+                        axum_server::from_tcp(listener)
+                            .handle(handle.clone())
+                            .serve(service),
+                    ),
+                };
+                tokio::select! {
+                    res = &mut server => {
+                        let reason = match res {
+                            Ok(()) => "stopped".to_string(),
+                            Err(e) => e.to_string(),
+                        };
+                        let msg = format!("HTTP server failed ({reason}) — restarting");
+                        if !plain {
+                            shared.set_http_error(Some(msg.clone()));
+                        }
+                        shared.log_line(msg);
+                    }
+                    _ = &mut shutdown_rx => {
+                        handle.graceful_shutdown(Some(SHUTDOWN_GRACE));
+                        return server.await.map_err(|e| anyhow!("axum serve error: {e}"));
+                    }
+                }
+            }
+            Err(e) => {
+                let msg = format!(
+                    "Cannot listen on {addr}: {e} — retrying in {}s",
+                    delay.as_secs()
+                );
+                if !plain {
+                    shared.set_http_error(Some(msg.clone()));
+                }
+                shared.log_line(msg);
+            }
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = &mut shutdown_rx => return Ok(()),
+        }
+        delay = (delay * 2).min(BIND_RETRY_MAX);
+    }
+}
+
+fn bind_listener(addr: SocketAddr) -> std::io::Result<std::net::TcpListener> {
+    let listener = std::net::TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+/// `requested`, or the first of the next `--port-fallback` ports that is
+/// free when another program holds its port. Other bind errors are left for
+/// `supervise` to report and retry, since another port won't fix them.
+fn free_addr(requested: SocketAddr, shared: &SharedStatus) -> SocketAddr {
+    let fallback = crate::profile::current().port_fallback;
+    // Windows reports ports reserved by another program as access denied
+    let taken = match bind_listener(requested) {
+        Err(e) => matches!(
+            e.kind(),
+            std::io::ErrorKind::AddrInUse | std::io::ErrorKind::PermissionDenied
+        ),
+        Ok(_) => false,
+    };
+    let free = (taken && requested.port() != 0)
+        .then(|| {
+            (1..=fallback)
+                .filter_map(|offset| requested.port().checked_add(offset))
+                .map(|port| SocketAddr::new(requested.ip(), port))
+                .find(|addr| bind_listener(*addr).is_ok())
+        })
+        .flatten();
+    match free {
+        Some(addr) => {
+            shared.log_line(format!(
+                "Port {} is in use by another program — listening on {} instead",
+                requested.port(),
+                addr.port()
+            ));
+            shared.set_port_fallback(Some(PortFallback {
+                requested: requested.port(),
+                port: addr.port(),
+            }));
+            addr
+        }
+        None => {
+            if taken && fallback > 0 {
+                shared.log_line(format!(
+                    "Port {} and the {fallback} after it are all in use",
+                    requested.port()
+                ));
+            }
+            shared.set_port_fallback(None);
+            requested
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sender auto-configuration
+// ---------------------------------------------------------------------------
+
+async fn sender_page_handler(State(state): State<AppState>) -> Html<String> {
+    Html(state.sender_html.to_string())
+}
+
+/// Everything a native sender needs to connect without hard-coded
+/// assumptions: protocol version, WebSocket path and encodings, auth, and
+/// the codec parameters of the current (or default) quality preset.
+async fn config_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let session = state.session_state.read().await.clone();
+    let opus_fmtp = match &session {
+        Some(s) => s.options.opus_fmtp.clone(),
+        None => QualityPreset::default().params().opus_fmtp(),
+    };
+    let require_approval = session.as_ref().is_some_and(|s| s.options.require_approval);
+    let mtls = crate::profile::current().mtls;
+    let codecs: Vec<_> = webrtc_session::supported_codecs(&opus_fmtp)
+        .into_iter()
+        .map(|(mime_type, clock_rate, channels, fmtp, payload_type)| {
+            serde_json::json!({
+                "name": mime_type.trim_start_matches("audio/").to_ascii_lowercase(),
+                "clock_rate": clock_rate,
+                "channels": channels,
+                "fmtp": fmtp,
+                "payload_type": payload_type,
+            })
+        })
+        .collect();
+
+    Json(serde_json::json!({
+        "name": crate::profile::current().service_name(),
+        "protocol_version": webrtc_session::PROTOCOL_VERSION,
+        "capabilities": webrtc_session::CAPABILITIES,
+        "started": session.is_some(),
+        "websocket": {
+            "path": "/ws",
+            "subprotocols": [
+                webrtc_session::SignalEncoding::MSGPACK_PROTOCOL,
+                webrtc_session::SignalEncoding::JSON_PROTOCOL,
+            ],
+        },
+        "auth": {
+            "required": require_approval || mtls,
+            // New devices wait for the user to allow them
+            "approval": require_approval,
+            // The QR code's `/ws?token=` skips that wait
+            "pairing_token": require_approval,
+            // Only senders with a client certificate from this receiver
+            "mtls": mtls,
+        },
+        // Opus is preferred; the rest are for senders that can't encode it
+        "codec": {
+            "name": "opus",
+            "clock_rate": 48_000,
+            "channels": 2,
+            "fmtp": opus_fmtp,
+            "supported": codecs,
+        },
+        "multiple_senders": session.is_some_and(|s| s.options.allow_multiple_clients),
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// Sender bookkeeping, shared by every transport
+// ---------------------------------------------------------------------------
+
+/// A connected sender's mixer input, client-list entry and session history.
+struct IngestClient {
+    id: ClientId,
+    addr: String,
+    started_at: u64,
+}
+
+impl IngestClient {
+    /// Give the sender at `addr` a mixer input and list it as a client; the
+    /// first one starts the session.
+    fn connect(addr: String, mixer: &Mixer, shared: &SharedStatus) -> (Self, Arc<MixerInput>) {
+        let id: ClientId = NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed);
+        let input = mixer.add_input(id);
+        mixer.mark(format!("Client #{id} connected ({addr})"));
+        if shared.add_client(id, addr.clone()) == 1 {
+            shared.begin_session();
+        }
+        let client = Self {
+            id,
+            addr,
+            started_at: history::unix_now(),
+        };
+        (client, input)
+    }
+
+    /// Save the session to history and remove the sender again; the last
+    /// one ends the session.
+    fn disconnect(
+        &self,
+        mixer: &Mixer,
+        shared: &SharedStatus,
+        user_agent: Option<String>,
+        error: Option<String>,
+    ) {
+        let id = self.id;
+        let record = history::SessionRecord {
+            started_at: self.started_at,
+            ended_at: history::unix_now(),
+            client: self.addr.clone(),
+            device: shared.client_device(id),
+            user_agent,
+            packets: shared.client_packets(id),
+            bytes: shared.client_bytes(id),
+            error,
+        };
+        let history_shared = shared.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = history::record(&record) {
+                history_shared.log_line(format!("Failed to save session history: {e:#}"));
+            }
+        });
+
+        mixer.remove_input(id);
+        mixer.mark(format!("Client #{id} disconnected"));
+        if shared.remove_client(id) == 0 {
+            shared.end_session();
+        }
+    }
+}
+
+/// Why a sender on a raw transport (PCM, RTP or UDP) is turned away, if it
+/// is. These carry no device identity to approve, so they are refused
+/// while approval is required.
+fn ingest_refusal(options: &SessionOptions, shared: &SharedStatus) -> Option<&'static str> {
+    if options.require_approval {
+        Some("senders need approval, which raw streams can't give")
+    } else if !options.allow_multiple_clients && shared.client_count() > 0 {
+        Some("already connected")
+    } else {
+        None
+    }
+}
+
+/// Next datagram on an ingest socket. A receive error only concerns one
+/// datagram (e.g. ICMP port unreachable surfacing on Windows), so it is
+/// logged and skipped.
+async fn recv_datagram(
+    socket: &tokio::net::UdpSocket,
+    buf: &mut [u8],
+    what: &str,
+) -> (usize, SocketAddr) {
+    loop {
+        match socket.recv_from(buf).await {
+            Ok(received) => return received,
+            Err(e) => log::debug!("{what} receive error: {e}"),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // WebSocket handler
 // ---------------------------------------------------------------------------
 
+/// Query string of `/ws`.
+#[derive(serde::Deserialize)]
+struct WsQuery {
+    /// Pairing token from a deep link, if the sender came from one.
+    token: Option<String>,
+    /// Session ID from a previous `hello`, to pick that session back up.
+    resume: Option<String>,
+}
+
 async fn ws_handler(
     ws: WebSocketUpgrade,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<WsQuery>,
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> Response {
+    if !state.shared.is_ip_allowed(addr.ip()) {
+        state.shared.log_line(format!(
+            "Rejected WebSocket from {}: not allowed by access rules.",
+            addr.ip()
+        ));
+        return StatusCode::FORBIDDEN.into_response();
+    }
     let client_ip = addr.to_string();
-    ws.on_upgrade(move |socket| async move {
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|ua| ua.to_str().ok())
+        .map(str::to_string);
+    let paired = query
+        .token
+        .is_some_and(|t| admin::token_eq(&t, &state.pairing_token));
+    // Prefer MessagePack when the sender offers it; browsers send no
+    // subprotocol and stay on JSON.
+    let ws = ws.protocols([
+        webrtc_session::SignalEncoding::MSGPACK_PROTOCOL,
+        webrtc_session::SignalEncoding::JSON_PROTOCOL,
+    ]);
+    ws.on_upgrade(move |mut socket| async move {
+        let encoding = webrtc_session::SignalEncoding::from_protocol(
+            socket.protocol().and_then(|p| p.to_str().ok()),
+        );
+        let resume = query.resume;
+
         // Check if server is activated (user clicked START)
         let session = {
             let guard = state.session_state.read().await;
@@ -245,33 +1066,83 @@ async fn ws_handler(
                 state
                     .shared
                     .log_line("Rejected WebSocket: server not started.");
+                webrtc_session::send_error(
+                    &mut socket,
+                    encoding,
+                    webrtc_session::SessionError::NotStarted,
+                    None,
+                )
+                .await;
                 return;
             }
         };
 
-        // One active connection at a time
+        // Hand the socket to the session it belongs to, if that is waiting
+        let waiting = resume
+            .as_ref()
+            .and_then(|id| session.resumable.lock().get(id).cloned());
+        if let Some(tx) = waiting {
+            let resumed = webrtc_session::Resume {
+                socket,
+                encoding,
+                peer: client_ip.clone(),
+            };
+            match tx.try_send(resumed) {
+                Ok(()) => return,
+                // Its session just ended; start a new one
+                Err(e) => socket = e.into_inner().socket,
+            }
+        } else if resume.is_some() {
+            state
+                .shared
+                .log_line("Session to resume has ended; starting a new one.");
+        }
+
+        // One active connection at a time unless multi-client mode is on
         {
             let mut active = session.active.lock().await;
-            if *active {
+            if *active > 0 && !session.options.allow_multiple_clients {
                 state
                     .shared
                     .log_line("Rejected WebSocket: already connected.");
+                drop(active);
+                webrtc_session::send_error(
+                    &mut socket,
+                    encoding,
+                    webrtc_session::SessionError::AlreadyConnected,
+                    None,
+                )
+                .await;
                 return;
             }
-            *active = true;
+            *active += 1;
         }
 
-        state.shared.set_client_connected(true);
-        state.shared.set_client_addr(Some(client_ip));
-        state.shared.set_pc_state(Some("new".into()));
-        state.shared.log_line("WebSocket client connected.");
+        let (client, input) =
+            IngestClient::connect(client_ip.clone(), &session.mixer, &state.shared);
+        let client_id = client.id;
+        state.shared.set_pc_state(client_id, Some("new".into()));
+        state.shared.log_line(format!(
+            "WebSocket client #{client_id} connected ({encoding:?} signaling)."
+        ));
+
+        let mut options = session.options.clone();
+        if paired && options.require_approval {
+            state
+                .shared
+                .log_line(format!("Sender paired via QR code ({client_ip}); no approval needed."));
+            options.require_approval = false;
+        }
 
         let res = webrtc_session::run(
             socket,
-            session.queue,
-            session.use_stun,
+            encoding,
+            &client_ip,
+            input,
+            &options,
             state.shared.clone(),
             session.session_cancel,
+            &session.resumable,
         )
         .await;
 
@@ -280,13 +1151,21 @@ async fn ws_handler(
             state.shared.log_line(format!("Session error: {e}"));
         }
 
-        state.shared.set_client_connected(false);
-        state.shared.set_client_addr(None);
-        state.shared.set_pc_state(None);
-        state.shared.log_line("WebSocket client disconnected.");
+        client.disconnect(
+            &session.mixer,
+            &state.shared,
+            user_agent,
+            res.as_ref().err().map(|e| e.to_string()),
+        );
+        if let Some(relay) = &session.options.relay {
+            relay.release(client_id);
+        }
+        state
+            .shared
+            .log_line(format!("WebSocket client #{client_id} disconnected."));
 
         let mut active = session.active.lock().await;
-        *active = false;
+        *active = active.saturating_sub(1);
     })
 }
 
@@ -294,16 +1173,30 @@ async fn ws_handler(
 // mDNS registration — platform-specific
 // ---------------------------------------------------------------------------
 
+/// TXT value of the `ready` flag.
+fn ready_flag(ready: bool) -> &'static str {
+    if ready {
+        "1"
+    } else {
+        "0"
+    }
+}
+
 /// macOS: use native `dns-sd -R` command (integrates with mDNSResponder).
 #[cfg(target_os = "macos")]
-fn register_mdns(_ip: &str, port: u16) -> Result<MdnsHandle> {
-    let hostname = gethostname::gethostname()
-        .into_string()
-        .unwrap_or_else(|_| "lan-mic-receiver".to_string());
-    let service_name = format!("LAN Mic Receiver ({})", hostname);
+fn register_mdns(_ip: &str, port: u16, ready: bool) -> Result<MdnsHandle> {
+    let service_name = crate::profile::current().service_name();
 
     let child = std::process::Command::new("dns-sd")
-        .args(["-R", &service_name, MDNS_SERVICE_TYPE, "local.", &port.to_string()])
+        .args([
+            "-R",
+            &service_name,
+            MDNS_SERVICE_TYPE,
+            "local.",
+            &port.to_string(),
+            &format!("config={MDNS_CONFIG_PATH}"),
+            &format!("ready={}", ready_flag(ready)),
+        ])
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
         .spawn()
@@ -318,51 +1211,158 @@ fn register_mdns(_ip: &str, port: u16) -> Result<MdnsHandle> {
 
 /// Windows/Linux: use the `mdns-sd` crate.
 #[cfg(not(target_os = "macos"))]
-fn register_mdns(ip: &str, port: u16) -> Result<MdnsHandle> {
+fn register_mdns(ip: &str, port: u16, ready: bool) -> Result<MdnsHandle> {
     let daemon = ServiceDaemon::new()?;
-    let hostname = gethostname::gethostname()
-        .into_string()
-        .unwrap_or_else(|_| "lan-mic-receiver".to_string());
+    let service = service_info(ip, port, ready)?;
+
+    let fullname = service.get_fullname().to_string();
+    daemon.register(service)?;
 
-    let service_name = format!("LAN Mic Receiver ({})", hostname);
-    let host = format!("{hostname}.local.");
+    log::info!("mDNS: advertising {fullname} on {ip}, port {port}");
+    Ok(MdnsHandle::CrateDaemon { daemon, fullname })
+}
 
-    let service = ServiceInfo::new(
+#[cfg(not(target_os = "macos"))]
+fn service_info(ip: &str, port: u16, ready: bool) -> Result<ServiceInfo> {
+    let service_name = crate::profile::current().service_name();
+    let host = format!("{}.local.", crate::profile::hostname());
+    Ok(ServiceInfo::new(
         MDNS_SERVICE_TYPE,
         &service_name,
         &host,
         ip,
         port,
-        None,
-    )?;
+        &[("config", MDNS_CONFIG_PATH), ("ready", ready_flag(ready))][..],
+    )?)
+}
 
-    let fullname = service.get_fullname().to_string();
-    daemon.register(service)?;
+/// A LAN address senders may be able to reach us on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanAddress {
+    pub interface: String,
+    pub ip: String,
+}
 
-    log::info!("mDNS: advertising {fullname} at {ip}:{port}");
-    Ok(MdnsHandle::CrateDaemon { daemon, fullname })
+/// Every usable address (no loopback or IPv4 link-local): private IPv4
+/// first, then other IPv4, then IPv6 with link-local ones last.
+pub fn list_lan_addresses() -> Vec<LanAddress> {
+    let Ok(list) = local_ip_address::list_afinet_netifas() else {
+        return Vec::new();
+    };
+    let mut addrs: Vec<(u8, LanAddress)> = list
+        .into_iter()
+        .filter_map(|(name, ip)| {
+            let rank = match ip {
+                IpAddr::V4(v4) if v4.is_loopback() || v4.is_link_local() => return None,
+                IpAddr::V4(v4) if v4.is_private() => 0,
+                IpAddr::V4(_) => 1,
+                IpAddr::V6(v6) if v6.is_loopback() || v6.is_unspecified() => return None,
+                IpAddr::V6(v6) if v6.is_unicast_link_local() => 3,
+                IpAddr::V6(_) => 2,
+            };
+            Some((
+                rank,
+                LanAddress {
+                    interface: name,
+                    ip: ip.to_string(),
+                },
+            ))
+        })
+        .collect();
+    // Stable sort keeps the OS order within each group
+    addrs.sort_by_key(|(rank, _)| *rank);
+    addrs.dedup_by(|a, b| a.1.ip == b.1.ip);
+    addrs.into_iter().map(|(_, a)| a).collect()
 }
 
-/// Best-effort: pick an IPv4 LAN address to show in UI.
-fn pick_local_ip() -> Option<String> {
-    match local_ip_address::list_afinet_netifas() {
-        Ok(list) => {
-            // Prefer private IPv4 addresses
-            for (_name, ip) in &list {
-                if let std::net::IpAddr::V4(v4) = ip {
-                    if v4.is_private() {
-                        return Some(v4.to_string());
-                    }
-                }
-            }
-            // Else first IPv4
-            for (_name, ip) in &list {
-                if let std::net::IpAddr::V4(v4) = ip {
-                    return Some(v4.to_string());
-                }
-            }
-            None
+/// The LAN addresses a wildcard listener accepts: IPv4 ones for
+/// `0.0.0.0`, all of them for a dual-stack `[::]`.
+fn wildcard_addresses(listen: SocketAddr) -> Vec<LanAddress> {
+    let all = list_lan_addresses();
+    if listen.is_ipv6() {
+        return all;
+    }
+    all.into_iter()
+        .filter(|a| a.ip.parse::<IpAddr>().is_ok_and(|ip| ip.is_ipv4()))
+        .collect()
+}
+
+/// `ip` as the host part of a URL: IPv6 literals go in brackets.
+pub fn url_host(ip: &str) -> std::borrow::Cow<'_, str> {
+    match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V6(_)) => format!("[{ip}]").into(),
+        _ => ip.into(),
+    }
+}
+
+/// Addresses senders can reach a listener bound to `listen` on: the ACME
+/// domain if there is one (its certificate is only valid for that name),
+/// all interfaces for a wildcard bind, otherwise just the bound one.
+fn reachable_addresses(listen: SocketAddr) -> Vec<LanAddress> {
+    if let Some(acme) = &crate::profile::current().acme {
+        return vec![LanAddress {
+            interface: "DNS name".to_string(),
+            ip: acme.domain.clone(),
+        }];
+    }
+    if listen.ip().is_unspecified() {
+        return wildcard_addresses(listen);
+    }
+    let all = list_lan_addresses();
+    let ip = listen.ip().to_string();
+    let interface = all
+        .into_iter()
+        .find(|a| a.ip == ip)
+        .map_or_else(|| ip.clone(), |a| a.interface);
+    vec![LanAddress { interface, ip }]
+}
+
+/// The IP for URLs and every reachable address for a listener bound to
+/// `listen`, with `interface`'s addresses first when it is present.
+fn lan_addresses(listen: SocketAddr, interface: Option<&str>) -> (String, Vec<LanAddress>) {
+    let mut addresses = reachable_addresses(listen);
+    if let Some(name) = interface {
+        // Stable sort keeps the preference order within each group
+        addresses.sort_by_key(|a| a.interface != name);
+        if let Some(chosen) = addresses.first().filter(|a| a.interface == name) {
+            return (chosen.ip.clone(), addresses);
         }
-        Err(_) => local_ip_address::local_ip().ok().map(|ip| ip.to_string()),
+    }
+    (advertised_ip(listen), addresses)
+}
+
+/// TLS settings serving the user's certificate files.
+fn user_server_config(cert: &UserCertificate) -> Result<Arc<rustls::ServerConfig>> {
+    let (chain_pem, key_pem) = cert.read()?;
+    mtls::server_config(chain_pem.as_bytes(), key_pem.as_bytes())
+        .with_context(|| format!("load {}", cert.cert_path.display()))
+}
+
+/// Every address in URLs for this listener, for the self-signed
+/// certificate's SAN list.
+fn certificate_names(ip: &str, addresses: &[LanAddress]) -> Vec<String> {
+    let mut names = vec![ip.to_string()];
+    names.extend(addresses.iter().map(|a| a.ip.clone()));
+    names
+}
+
+/// The IP to put in URLs for a listener bound to `listen`.
+fn advertised_ip(listen: SocketAddr) -> String {
+    if let Some(acme) = &crate::profile::current().acme {
+        acme.domain.clone()
+    } else if listen.ip().is_unspecified() {
+        pick_local_ip(listen).unwrap_or_else(|| listen.ip().to_string())
+    } else {
+        listen.ip().to_string()
+    }
+}
+
+/// Best-effort: pick a LAN address for a wildcard listener to show in UI.
+/// IPv4 is preferred; a dual-stack `[::]` falls back to IPv6 on IPv6-only
+/// LANs.
+fn pick_local_ip(listen: SocketAddr) -> Option<String> {
+    match wildcard_addresses(listen).into_iter().next() {
+        Some(a) => Some(a.ip),
+        None => local_ip_address::local_ip().ok().map(|ip| ip.to_string()),
     }
 }
@@ -0,0 +1,330 @@
+use crate::core::{history, SharedStatus};
+use crate::profile::AcmeOptions;
+use anyhow::{anyhow, bail, Context, Result};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use instant_acme::{
+    Account, AccountCredentials, AuthorizationStatus, ChallengeType, Identifier, LetsEncrypt,
+    NewAccount, NewOrder, OrderStatus, RetryPolicy,
+};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Let's Encrypt fetches HTTP-01 responses from port 80.
+const HTTP_CHALLENGE_PORT: u16 = 80;
+
+/// Renew once the certificate is this old; Let's Encrypt issues 90-day ones.
+const RENEW_AFTER: Duration = Duration::from_secs(60 * 24 * 60 * 60);
+/// How often the certificate's age is checked.
+const RENEW_CHECK_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
+/// Wait after a failed attempt (Let's Encrypt rate-limits failures).
+const RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Time for a DNS-01 TXT record to reach the authoritative servers.
+const DNS_PROPAGATION_DELAY: Duration = Duration::from_secs(60);
+
+/// How long Let's Encrypt gets to validate the challenges.
+const VALIDATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A certificate chain and its private key, both PEM.
+pub struct Certificate {
+    pub chain_pem: String,
+    pub key_pem: String,
+}
+
+/// Stored next to the PEM files to know when to renew.
+#[derive(Debug, Serialize, Deserialize)]
+struct CertMeta {
+    domain: String,
+    staging: bool,
+    issued_at: u64,
+}
+
+fn acme_dir() -> Option<PathBuf> {
+    crate::profile::current()
+        .config_dir()
+        .map(|d| d.join("acme"))
+}
+
+/// The stored certificate for `opts.domain`, if one was issued, with its age.
+fn stored(opts: &AcmeOptions) -> Option<(Certificate, Duration)> {
+    let dir = acme_dir()?;
+    let meta: CertMeta =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("cert.json")).ok()?).ok()?;
+    if meta.domain != opts.domain || meta.staging != opts.staging {
+        return None;
+    }
+    let cert = Certificate {
+        chain_pem: std::fs::read_to_string(dir.join("cert.pem")).ok()?,
+        key_pem: std::fs::read_to_string(dir.join("key.pem")).ok()?,
+    };
+    let age = history::unix_now().saturating_sub(meta.issued_at);
+    Some((cert, Duration::from_secs(age)))
+}
+
+fn store(opts: &AcmeOptions, cert: &Certificate) -> Result<()> {
+    let dir = acme_dir().context("no config directory")?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+    std::fs::write(dir.join("cert.pem"), &cert.chain_pem)?;
+    super::mtls::write_private(&dir.join("key.pem"), cert.key_pem.as_bytes())?;
+    let meta = CertMeta {
+        domain: opts.domain.clone(),
+        staging: opts.staging,
+        issued_at: history::unix_now(),
+    };
+    std::fs::write(dir.join("cert.json"), serde_json::to_string_pretty(&meta)?)?;
+    Ok(())
+}
+
+/// A previously issued certificate to serve right away, so restarts don't
+/// fall back to the self-signed one while renewal is pending.
+pub fn cached_certificate(opts: &AcmeOptions) -> Option<Certificate> {
+    stored(opts).map(|(cert, _)| cert)
+}
+
+/// Obtain the certificate if there is none (or it is due for renewal) and
+/// swap it into `tls_config`; keep checking for the life of the runtime.
+pub fn spawn(opts: AcmeOptions, tls_config: RustlsConfig, shared: SharedStatus) {
+    tokio::spawn(async move {
+        loop {
+            let due = stored(&opts).is_none_or(|(_, age)| age >= RENEW_AFTER);
+            if due {
+                shared.log_line(format!("Requesting a certificate for {}…", opts.domain));
+                let res = match provision(&opts, &shared).await {
+                    Ok(cert) => install(&opts, &cert, &tls_config).await,
+                    Err(e) => Err(e),
+                };
+                match res {
+                    Ok(()) => shared
+                        .log_line(format!("Trusted certificate for {} installed", opts.domain)),
+                    Err(e) => {
+                        shared.log_line(format!(
+                            "Certificate request for {} failed: {e:#} — retrying in {} min",
+                            opts.domain,
+                            RETRY_AFTER.as_secs() / 60
+                        ));
+                        tokio::time::sleep(RETRY_AFTER).await;
+                        continue;
+                    }
+                }
+            }
+            tokio::time::sleep(RENEW_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+async fn install(opts: &AcmeOptions, cert: &Certificate, tls_config: &RustlsConfig) -> Result<()> {
+    let config = super::mtls::server_config(cert.chain_pem.as_bytes(), cert.key_pem.as_bytes())
+        .context("load issued certificate")?;
+    tls_config.reload_from_config(config);
+    store(opts, cert).context("save issued certificate")
+}
+
+/// Restore the ACME account for this directory, or register one.
+async fn account(opts: &AcmeOptions) -> Result<Account> {
+    let file = if opts.staging {
+        "account-staging.json"
+    } else {
+        "account.json"
+    };
+    let path = acme_dir().context("no config directory")?.join(file);
+    if let Ok(json) = std::fs::read_to_string(&path) {
+        let credentials: AccountCredentials =
+            serde_json::from_str(&json).with_context(|| format!("parse {}", path.display()))?;
+        return Ok(Account::builder()?.from_credentials(credentials).await?);
+    }
+
+    let directory = if opts.staging {
+        LetsEncrypt::Staging
+    } else {
+        LetsEncrypt::Production
+    };
+    let contact = opts.email.as_ref().map(|e| format!("mailto:{e}"));
+    let contact: Vec<&str> = contact.iter().map(String::as_str).collect();
+    let (account, credentials) = Account::builder()?
+        .create(
+            &NewAccount {
+                contact: &contact,
+                terms_of_service_agreed: true,
+                only_return_existing: false,
+            },
+            directory.url().to_owned(),
+            None,
+        )
+        .await?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    // The credentials hold the account's private key
+    super::mtls::write_private(&path, &serde_json::to_vec_pretty(&credentials)?)?;
+    Ok(account)
+}
+
+/// Run one order through to an issued certificate.
+async fn provision(opts: &AcmeOptions, shared: &SharedStatus) -> Result<Certificate> {
+    let account = account(opts).await?;
+    let identifiers = [Identifier::Dns(opts.domain.clone())];
+    let mut order = account.new_order(&NewOrder::new(&identifiers)).await?;
+
+    let http = match &opts.dns_hook {
+        Some(_) => None,
+        None => Some(ChallengeServer::start(shared).await?),
+    };
+    let mut dns_records = Vec::new();
+    let ready = async {
+        let mut authorizations = order.authorizations();
+        while let Some(authz) = authorizations.next().await {
+            let mut authz = authz?;
+            match authz.status {
+                AuthorizationStatus::Pending => {}
+                AuthorizationStatus::Valid => continue,
+                status => bail!("authorization is {status:?}"),
+            }
+            let challenge_type = match &http {
+                Some(_) => ChallengeType::Http01,
+                None => ChallengeType::Dns01,
+            };
+            let mut challenge = authz
+                .challenge(challenge_type.clone())
+                .ok_or_else(|| anyhow!("no {challenge_type:?} challenge offered"))?;
+            let key_authorization = challenge.key_authorization();
+            match (&http, &opts.dns_hook) {
+                (Some(server), _) => server.respond(&challenge.token, key_authorization.as_str()),
+                (None, Some(hook)) => {
+                    let name = format!("_acme-challenge.{}", opts.domain);
+                    let value = key_authorization.dns_value();
+                    run_hook(hook, "present", &name, &value).await?;
+                    dns_records.push((name, value));
+                    shared.log_line(format!(
+                        "Waiting {}s for the DNS record to propagate…",
+                        DNS_PROPAGATION_DELAY.as_secs()
+                    ));
+                    tokio::time::sleep(DNS_PROPAGATION_DELAY).await;
+                }
+                (None, None) => unreachable!("HTTP-01 server exists without a DNS hook"),
+            }
+            challenge.set_ready().await?;
+        }
+        Ok(())
+    }
+    .await;
+
+    let status = match ready {
+        Ok(()) => {
+            let policy = RetryPolicy::new()
+                .initial_delay(Duration::from_secs(2))
+                .timeout(VALIDATION_TIMEOUT);
+            order.poll_ready(&policy).await.map_err(Into::into)
+        }
+        Err(e) => Err(e),
+    };
+    drop(http);
+    if let Some(hook) = &opts.dns_hook {
+        for (name, value) in &dns_records {
+            if let Err(e) = run_hook(hook, "cleanup", name, value).await {
+                log::warn!("ACME DNS cleanup failed: {e:#}");
+            }
+        }
+    }
+    match status? {
+        OrderStatus::Ready => {}
+        status => bail!("validation did not succeed (order is {status:?})"),
+    }
+
+    let key_pem = order.finalize().await?;
+    let chain_pem = order.poll_certificate(&RetryPolicy::default()).await?;
+    Ok(Certificate { chain_pem, key_pem })
+}
+
+/// Run the user's DNS hook: `HOOK present|cleanup NAME VALUE`.
+async fn run_hook(hook: &str, action: &str, name: &str, value: &str) -> Result<()> {
+    let (hook, action, name, value) = (
+        hook.to_string(),
+        action.to_string(),
+        name.to_string(),
+        value.to_string(),
+    );
+    let status = tokio::task::spawn_blocking(move || {
+        std::process::Command::new(&hook)
+            .args([&action, &name, &value])
+            .status()
+            .with_context(|| format!("run {hook}"))
+    })
+    .await??;
+    if !status.success() {
+        bail!("DNS hook exited with {status}");
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// HTTP-01 responder — plain HTTP on port 80 while an order is validated
+// ---------------------------------------------------------------------------
+
+type Responses = Arc<Mutex<HashMap<String, String>>>;
+
+struct ChallengeServer {
+    responses: Responses,
+    cancel: CancellationToken,
+}
+
+impl ChallengeServer {
+    async fn start(shared: &SharedStatus) -> Result<Self> {
+        let listener = tokio::net::TcpListener::bind((Ipv4Addr::UNSPECIFIED, HTTP_CHALLENGE_PORT))
+            .await
+            .with_context(|| {
+                format!("listen on port {HTTP_CHALLENGE_PORT} for the HTTP-01 challenge")
+            })?;
+        let responses = Responses::default();
+        let app = Router::new()
+            .route("/.well-known/acme-challenge/:token", get(challenge_handler))
+            .with_state(responses.clone());
+        let cancel = CancellationToken::new();
+        let stop = cancel.clone();
+        tokio::spawn(async move {
+            let serve = axum::serve(listener, app)
+                .with_graceful_shutdown(async move { stop.cancelled().await });
+            if let Err(e) = serve.await {
+                log::warn!("ACME challenge server error: {e}");
+            }
+        });
+        shared.log_line(format!(
+            "Answering the HTTP-01 challenge on port {HTTP_CHALLENGE_PORT}"
+        ));
+        Ok(Self { responses, cancel })
+    }
+
+    fn respond(&self, token: &str, key_authorization: &str) {
+        self.responses
+            .lock()
+            .insert(token.to_string(), key_authorization.to_string());
+    }
+}
+
+impl Drop for ChallengeServer {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+async fn challenge_handler(
+    State(responses): State<Responses>,
+    Path(token): Path<String>,
+) -> Result<String, StatusCode> {
+    responses
+        .lock()
+        .get(&token)
+        .cloned()
+        .ok_or(StatusCode::NOT_FOUND)
+}
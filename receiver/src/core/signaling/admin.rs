@@ -0,0 +1,201 @@
+use super::AppState;
+use crate::audio::mixer::ClientId;
+use crate::core::logs::LogEntry;
+use crate::core::CoreCommand;
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::time::Duration;
+
+/// Embed the admin dashboard at compile time.
+const ADMIN_HTML: &str = include_str!("admin.html");
+
+/// Token-protected dashboard for managing a headless receiver from another
+/// device. The page polls `/admin/api/status` and posts commands.
+pub(super) fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin", get(admin_page))
+        .route("/admin/api/status", get(admin_status))
+        .route("/admin/api/command", post(admin_command))
+        .route("/logs", get(logs))
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Accept the admin token as `Authorization: Bearer …` or `?token=…`.
+pub(super) fn authorized(state: &AppState, headers: &HeaderMap, query_token: Option<&str>) -> bool {
+    bearer(headers)
+        .or(query_token)
+        .is_some_and(|t| token_eq(t, &state.admin_token))
+}
+
+/// The token a request carries, from the `Authorization` header or the
+/// query.
+pub(super) fn request_token<'a>(headers: &'a HeaderMap, query: &'a TokenQuery) -> Option<&'a str> {
+    bearer(headers).or(query.token.as_deref())
+}
+
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+/// Compare without short-circuiting so response time doesn't leak how much
+/// of a guessed token was right.
+pub(super) fn token_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+pub(super) fn unauthorized() -> Response {
+    (StatusCode::UNAUTHORIZED, "Missing or invalid admin token").into_response()
+}
+
+async fn admin_page(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    if !authorized(&state, &headers, query.token.as_deref()) {
+        return unauthorized();
+    }
+    Html(ADMIN_HTML).into_response()
+}
+
+async fn admin_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    if !authorized(&state, &headers, query.token.as_deref()) {
+        return unauthorized();
+    }
+    Json(state.shared.snapshot()).into_response()
+}
+
+/// Actions the dashboard can trigger, e.g. `{"action":"set_client_mute",
+/// "client_id":3,"muted":true}`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum AdminAction {
+    Start,
+    Stop,
+    SetClientMute { client_id: ClientId, muted: bool },
+    SetClientGain { client_id: ClientId, gain: f32 },
+    StartRecording,
+    StopRecording,
+}
+
+impl From<AdminAction> for CoreCommand {
+    fn from(action: AdminAction) -> Self {
+        match action {
+            AdminAction::Start => CoreCommand::Resume,
+            AdminAction::Stop => CoreCommand::Stop,
+            AdminAction::SetClientMute { client_id, muted } => {
+                CoreCommand::SetClientMute { client_id, muted }
+            }
+            AdminAction::SetClientGain { client_id, gain } => {
+                CoreCommand::SetClientGain { client_id, gain }
+            }
+            AdminAction::StartRecording => CoreCommand::StartRecording { path: None },
+            AdminAction::StopRecording => CoreCommand::StopRecording,
+        }
+    }
+}
+
+async fn admin_command(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TokenQuery>,
+    Json(action): Json<AdminAction>,
+) -> Response {
+    if !authorized(&state, &headers, query.token.as_deref()) {
+        return unauthorized();
+    }
+    state.shared.log_line(format!("Admin command: {action:?}"));
+    match state.controller.send(action.into()) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => (StatusCode::SERVICE_UNAVAILABLE, "Core runtime stopped").into_response(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Remote logs
+// ---------------------------------------------------------------------------
+
+/// How often a `follow` stream checks for new log lines.
+const LOG_FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Deserialize)]
+struct LogsQuery {
+    token: Option<String>,
+    /// `json` (default) or `text`.
+    format: Option<String>,
+    /// Only lines from this sequence number on (the `next` of a previous
+    /// JSON response).
+    since: Option<u64>,
+    /// Keep the response open and stream new lines as plain text.
+    #[serde(default)]
+    follow: bool,
+}
+
+/// Recent log buffer for debugging a headless receiver: `/logs` returns
+/// JSON, `/logs?format=text` plain text, `/logs?follow=true` a live tail.
+async fn logs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<LogsQuery>,
+) -> Response {
+    if !authorized(&state, &headers, query.token.as_deref()) {
+        return unauthorized();
+    }
+    let (entries, next) = state.shared.logs_since(query.since.unwrap_or(0));
+
+    if query.follow {
+        let backlog = join_lines(&entries);
+        let shared = state.shared.clone();
+        let tail = futures_util::stream::unfold(next, move |seq| {
+            let shared = shared.clone();
+            async move {
+                tokio::time::sleep(LOG_FOLLOW_INTERVAL).await;
+                let (lines, next) = shared.logs_since(seq);
+                Some((Ok::<_, Infallible>(join_lines(&lines)), next))
+            }
+        });
+        let stream = futures_util::stream::once(async move { Ok(backlog) }).chain(tail);
+        return text_response(Body::from_stream(stream));
+    }
+
+    match query.format.as_deref() {
+        Some("text") => text_response(Body::from(join_lines(&entries))),
+        _ => {
+            let lines: Vec<String> = entries.iter().map(ToString::to_string).collect();
+            Json(serde_json::json!({ "lines": lines, "entries": entries, "next": next }))
+                .into_response()
+        }
+    }
+}
+
+fn join_lines(entries: &[LogEntry]) -> String {
+    entries.iter().map(|e| format!("{e}\n")).collect()
+}
+
+fn text_response(body: Body) -> Response {
+    ([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], body).into_response()
+}
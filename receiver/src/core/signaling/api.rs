@@ -0,0 +1,206 @@
+use super::admin::{self, TokenQuery};
+use super::AppState;
+use crate::core::{AudioLevel, CoreCommand, StatusSnapshot};
+use axum::{
+    extract::{ConnectInfo, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+/// JSON API for scripts, dashboards, Stream Deck buttons and home
+/// automation on the LAN, plus `/metrics` with `--metrics`.
+pub(super) fn routes() -> Router<AppState> {
+    let router = Router::new()
+        .route("/api/status", get(status))
+        .route("/api/start", post(start))
+        .route("/api/stop", post(stop))
+        .route("/api/device", post(device));
+    if crate::profile::current().metrics {
+        router.route("/metrics", get(metrics))
+    } else {
+        router
+    }
+}
+
+/// `GET /api/status` body.
+///
+/// Open to every address the Sender Access rules allow, so it carries
+/// connection health only: no tokens, trusted device IDs, access rules,
+/// pending approvals or captions.
+#[derive(Debug, Serialize)]
+struct ApiStatus {
+    server_running: bool,
+    client_connected: bool,
+    client_addr: Option<String>,
+    pc_state: Option<String>,
+    audio_packets: u64,
+    concealed_packets: u64,
+    decode_errors: u64,
+    session_elapsed_ms: u64,
+    clients: Vec<ApiClient>,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiClient {
+    addr: String,
+    device: Option<String>,
+    codec: Option<String>,
+    pc_state: Option<String>,
+    packets: u64,
+    muted: bool,
+    level: AudioLevel,
+}
+
+impl From<StatusSnapshot> for ApiStatus {
+    fn from(s: StatusSnapshot) -> Self {
+        Self {
+            server_running: s.server_running,
+            client_connected: s.client_connected,
+            client_addr: s.client_addr,
+            pc_state: s.pc_state,
+            audio_packets: s.audio_packets,
+            concealed_packets: s.concealed_packets,
+            decode_errors: s.decode_errors,
+            session_elapsed_ms: s.session_elapsed_ms,
+            clients: s
+                .clients
+                .into_iter()
+                .map(|c| ApiClient {
+                    addr: c.addr,
+                    device: c.device,
+                    codec: c.codec,
+                    pc_state: c.pc_state,
+                    packets: c.packets,
+                    muted: c.muted,
+                    level: c.level,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The connection health subset of the status, e.g. `server_running`,
+/// `client_addr`, `pc_state`, `audio_packets` and each sender's `level`.
+async fn status(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(state): State<AppState>,
+) -> Response {
+    if !state.shared.is_ip_allowed(addr.ip()) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    Json(ApiStatus::from(state.shared.snapshot_without_logs())).into_response()
+}
+
+/// Prometheus scrape target, open like `/api/status`.
+async fn metrics(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(state): State<AppState>,
+) -> Response {
+    if !state.shared.is_ip_allowed(addr.ip()) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.shared.metrics().render(),
+    )
+        .into_response()
+}
+
+// ---------------------------------------------------------------------------
+// Remote control
+// ---------------------------------------------------------------------------
+
+/// Control requests carry the `--api-token` or the admin token, as
+/// `Authorization: Bearer …` or `?token=…`. Returns the response refusing
+/// a request that doesn't.
+fn rejection(
+    state: &AppState,
+    addr: SocketAddr,
+    headers: &HeaderMap,
+    query: &TokenQuery,
+) -> Option<Response> {
+    if !state.shared.is_ip_allowed(addr.ip()) {
+        return Some(StatusCode::FORBIDDEN.into_response());
+    }
+    let token = admin::request_token(headers, query);
+    let api_token = crate::profile::current().api_token.as_deref();
+    let valid = token.is_some_and(|t| {
+        admin::token_eq(t, &state.admin_token) || api_token.is_some_and(|a| admin::token_eq(t, a))
+    });
+    (!valid).then(admin::unauthorized)
+}
+
+fn send(state: &AppState, addr: SocketAddr, cmd: CoreCommand) -> Response {
+    state
+        .shared
+        .log_line(format!("API command from {}: {cmd:?}", addr.ip()));
+    match state.controller.send(cmd) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => (StatusCode::SERVICE_UNAVAILABLE, "Core runtime stopped").into_response(),
+    }
+}
+
+/// Start listening with the most recent settings.
+async fn start(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    if let Some(response) = rejection(&state, addr, &headers, &query) {
+        return response;
+    }
+    send(&state, addr, CoreCommand::Resume)
+}
+
+async fn stop(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    if let Some(response) = rejection(&state, addr, &headers, &query) {
+        return response;
+    }
+    send(&state, addr, CoreCommand::Stop)
+}
+
+/// `{"device":"Speakers (Realtek)"}`, or `null` for the system default.
+#[derive(Debug, Deserialize)]
+struct DeviceRequest {
+    device: Option<String>,
+}
+
+/// Switch the main output, or pick it for the next start while stopped.
+async fn device(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TokenQuery>,
+    Json(request): Json<DeviceRequest>,
+) -> Response {
+    if let Some(response) = rejection(&state, addr, &headers, &query) {
+        return response;
+    }
+    if let Some(name) = &request.device {
+        if !state
+            .shared
+            .snapshot_without_logs()
+            .output_devices
+            .contains(name)
+        {
+            return (StatusCode::NOT_FOUND, format!("No output device '{name}'")).into_response();
+        }
+    }
+    send(
+        &state,
+        addr,
+        CoreCommand::ChangeOutputDevice {
+            device_name: request.device,
+        },
+    )
+}
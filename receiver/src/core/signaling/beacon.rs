@@ -0,0 +1,105 @@
+use super::webrtc_session::PROTOCOL_VERSION;
+use super::MDNS_CONFIG_PATH;
+use crate::core::SharedStatus;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio_util::sync::CancellationToken;
+
+/// UDP port receivers announce on and answer queries at.
+pub const BEACON_PORT: u16 = 9010;
+
+/// Pause between unsolicited broadcast announcements.
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Sent by a sender (to the broadcast address) to ask receivers to answer.
+const QUERY_TYPE: &str = "lanmic-query";
+/// Broadcast periodically and sent back in reply to a query.
+const ANNOUNCE_TYPE: &str = "lanmic-announce";
+
+/// A beacon datagram: one JSON object.
+///
+/// The receiver's IP is the datagram's source address, so announcements stay
+/// correct across network changes without re-registering anything.
+#[derive(Debug, Serialize, Deserialize)]
+struct Beacon {
+    #[serde(rename = "type")]
+    msg_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    /// HTTPS/WebSocket port.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    port: Option<u16>,
+    /// Path of the sender configuration document.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    config: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<u32>,
+}
+
+fn announcement(http_port: u16) -> Beacon {
+    Beacon {
+        msg_type: ANNOUNCE_TYPE.to_string(),
+        name: Some(crate::profile::current().service_name()),
+        port: Some(http_port),
+        config: Some(MDNS_CONFIG_PATH.to_string()),
+        version: Some(PROTOCOL_VERSION),
+    }
+}
+
+fn is_query(datagram: &[u8]) -> bool {
+    serde_json::from_slice::<Beacon>(datagram).is_ok_and(|b| b.msg_type == QUERY_TYPE)
+}
+
+/// Announce the receiver and answer queries until `cancel` fires.
+pub fn spawn(http_port: u16, shared: SharedStatus, cancel: CancellationToken) {
+    tokio::spawn(async move {
+        if let Err(e) = run(http_port, &shared, &cancel).await {
+            shared.log_line(format!("UDP beacon stopped: {e:#}"));
+        }
+    });
+}
+
+async fn run(http_port: u16, shared: &SharedStatus, cancel: &CancellationToken) -> Result<()> {
+    let announce = serde_json::to_vec(&announcement(http_port))?;
+
+    // Another receiver on this PC may hold the port; announcing still works
+    // from any port, only queries go unanswered.
+    let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, BEACON_PORT)).await {
+        Ok(socket) => socket,
+        Err(e) => {
+            shared.log_line(format!(
+                "UDP beacon: port {BEACON_PORT} unavailable ({e}); announcing only."
+            ));
+            UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?
+        }
+    };
+    socket.set_broadcast(true)?;
+    shared.log_line(format!("UDP discovery beacon on port {BEACON_PORT}"));
+
+    let mut ticker = tokio::time::interval(ANNOUNCE_INTERVAL);
+    let mut buf = [0u8; 512];
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => return Ok(()),
+            _ = ticker.tick() => {
+                let to = (Ipv4Addr::BROADCAST, BEACON_PORT);
+                if let Err(e) = socket.send_to(&announce, to).await {
+                    log::debug!("UDP beacon announce failed: {e}");
+                }
+            }
+            res = socket.recv_from(&mut buf) => match res {
+                Ok((n, from)) if is_query(&buf[..n]) => {
+                    if let Err(e) = socket.send_to(&announce, from).await {
+                        log::debug!("UDP beacon reply to {from} failed: {e}");
+                    }
+                }
+                Ok(_) => {}
+                // e.g. ICMP port-unreachable surfacing on Windows
+                Err(e) => log::debug!("UDP beacon receive error: {e}"),
+            },
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::audio::mixer::ClientId;
+use crate::core::SharedStatus;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use webrtc::data_channel::data_channel_message::DataChannelMessage;
+use webrtc::peer_connection::RTCPeerConnection;
+
+/// Label of the data channel the receiver opens with every WebRTC sender.
+const LABEL: &str = "control";
+
+/// What "Lower Bitrate" asks a sender for: Opus speech stays clear at this
+/// rate on a congested network.
+pub const LOW_BITRATE_KBPS: u32 = 16;
+
+/// JSON message on the `control` data channel, e.g.
+/// `{"type":"status","battery":0.8,"charging":false}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ControlMessage {
+    /// Sender → receiver, whenever something changes. Fields the sender
+    /// can't report are left out.
+    #[serde(rename_all = "camelCase")]
+    Status {
+        #[serde(default)]
+        device_name: Option<String>,
+        /// Battery level, 0–1.
+        #[serde(default)]
+        battery: Option<f32>,
+        #[serde(default)]
+        charging: Option<bool>,
+        /// The user muted the microphone on the sender.
+        #[serde(default)]
+        muted: Option<bool>,
+    },
+    /// Receiver → sender: cap the encoder's bitrate; `null` lifts the cap.
+    #[serde(rename_all = "camelCase")]
+    Bitrate { max_kbps: Option<u32> },
+}
+
+/// Open the control channel on `pc`, before the offer is created so it is
+/// part of it. Status messages update the sender's entry in the client
+/// list; messages for the sender go through [`SharedStatus::send_control`]
+/// until `cancel` fires.
+pub(super) async fn open(
+    pc: &RTCPeerConnection,
+    client_id: ClientId,
+    shared: SharedStatus,
+    cancel: CancellationToken,
+) -> Result<()> {
+    let channel = pc.create_data_channel(LABEL, None).await?;
+    let (tx, mut rx) = mpsc::unbounded_channel::<ControlMessage>();
+
+    let open_shared = shared.clone();
+    channel.on_open(Box::new(move || {
+        open_shared.set_client_control(client_id, tx);
+        Box::pin(async {})
+    }));
+
+    channel.on_message(Box::new(move |msg: DataChannelMessage| {
+        let shared = shared.clone();
+        Box::pin(async move {
+            match serde_json::from_slice::<ControlMessage>(&msg.data) {
+                Ok(ControlMessage::Status {
+                    device_name,
+                    battery,
+                    charging,
+                    muted,
+                }) => shared.set_client_sender_status(
+                    client_id,
+                    device_name,
+                    battery.map(|b| b.clamp(0.0, 1.0)),
+                    charging,
+                    muted,
+                ),
+                Ok(other) => log::debug!("Client #{client_id} sent unexpected {other:?}"),
+                Err(e) => log::debug!("Client #{client_id} sent a bad control message: {e}"),
+            }
+        })
+    }));
+
+    let sender = channel.clone();
+    tokio::spawn(async move {
+        loop {
+            let msg = tokio::select! {
+                msg = rx.recv() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+                _ = cancel.cancelled() => break,
+            };
+            let Ok(json) = serde_json::to_string(&msg) else {
+                continue;
+            };
+            if let Err(e) = sender.send_text(json).await {
+                log::warn!("Failed to send control message to client #{client_id}: {e}");
+            }
+        }
+        let _ = sender.close().await;
+    });
+    Ok(())
+}
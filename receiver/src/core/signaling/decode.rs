@@ -0,0 +1,548 @@
+use crate::audio::mixer::MixerInput;
+use crate::audio::vad::VoiceActivityDetector;
+use crate::audio::{convert, g711, stretch};
+use crate::core::SharedStatus;
+use anyhow::{anyhow, Result};
+use opus::{Channels, Decoder as OpusDecoder};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::time::Instant;
+use webrtc::rtp::packet::Packet;
+
+/// RTP packets buffered between the network task and the decode thread.
+/// 64 × 20 ms ≈ 1.3 s — only reached if decoding stalls badly.
+const PACKET_QUEUE_DEPTH: usize = 64;
+
+/// Buffer large enough for the max Opus frame (120 ms @ 48 kHz) per channel.
+const MAX_SAMPLES_PER_CHANNEL: usize = 5760;
+
+/// Playout backlog beyond the jitter target that triggers time-compression
+/// (40 ms), e.g. after a burst of late packets arrives at once.
+const CATCH_UP_THRESHOLD: usize = 1_920;
+
+/// Compress at most every this many packets, capping the speed-up at
+/// roughly 10–25 % so catching up stays unobtrusive.
+const CATCH_UP_EVERY: u64 = 2;
+
+/// Longest run of lost packets filled in by Opus concealment (~100 ms at
+/// 20 ms packets). Longer outages are left to the jitter buffer to rebuffer,
+/// since concealed audio decays to noise.
+const MAX_CONCEALED_PACKETS: u16 = 5;
+
+/// Packets held back while waiting for a missing one (4 × 20 ms). Only adds
+/// delay while there is a gap; in-order packets pass straight through.
+const REORDER_WINDOW: usize = 4;
+
+/// Undecodable packets in a row after which the decoder is recreated, in
+/// case its state rather than the packets is at fault.
+const MAX_DECODE_FAILURES: u32 = 5;
+
+/// A forward jump in sequence numbers this large is a stream restart (e.g.
+/// the sender's track was replaced), not loss.
+const MAX_SEQUENCE_JUMP: u16 = 1_000;
+
+/// Audio codecs a sender may negotiate, in the receiver's order of
+/// preference. Only Opus has loss concealment and can be relayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioCodec {
+    Opus,
+    /// G.711 µ-law, 8 kHz.
+    Pcmu,
+    /// G.711 A-law, 8 kHz.
+    Pcma,
+    /// Uncompressed 16-bit big-endian PCM at 48 kHz.
+    L16,
+}
+
+impl AudioCodec {
+    pub const L16_MIME_TYPE: &'static str = "audio/L16";
+
+    /// The codec for an SDP MIME type such as `audio/PCMU`.
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        let subtype = mime_type.split_once('/').map_or(mime_type, |(_, s)| s);
+        [Self::Opus, Self::Pcmu, Self::Pcma, Self::L16]
+            .into_iter()
+            .find(|codec| codec.to_string().eq_ignore_ascii_case(subtype))
+    }
+}
+
+impl std::fmt::Display for AudioCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AudioCodec::Opus => "Opus",
+            AudioCodec::Pcmu => "PCMU",
+            AudioCodec::Pcma => "PCMA",
+            AudioCodec::L16 => "L16",
+        })
+    }
+}
+
+/// Per-codec decoder state.
+enum FrameDecoder {
+    Opus(OpusDecoder),
+    G711(g711::Decoder),
+    L16,
+}
+
+impl FrameDecoder {
+    fn new(codec: AudioCodec, channels: usize) -> Result<Self> {
+        Ok(match codec {
+            AudioCodec::Opus => {
+                let channels = if channels >= 2 {
+                    Channels::Stereo
+                } else {
+                    Channels::Mono
+                };
+                FrameDecoder::Opus(
+                    OpusDecoder::new(48_000, channels)
+                        .map_err(|e| anyhow!("opus decoder init: {e:?}"))?,
+                )
+            }
+            AudioCodec::Pcmu => FrameDecoder::G711(g711::Decoder::new(g711::Law::MuLaw)),
+            AudioCodec::Pcma => FrameDecoder::G711(g711::Decoder::new(g711::Law::ALaw)),
+            AudioCodec::L16 => FrameDecoder::L16,
+        })
+    }
+
+    /// Decode one packet into interleaved `pcm`; returns samples per
+    /// channel.
+    fn decode(&mut self, payload: &[u8], pcm: &mut [i16], channels: usize) -> Result<usize> {
+        match self {
+            FrameDecoder::Opus(dec) => dec
+                .decode(payload, pcm, false)
+                .map_err(|e| anyhow!("{e:?}")),
+            FrameDecoder::G711(dec) => Ok(dec.decode(payload, pcm) / channels),
+            FrameDecoder::L16 => {
+                let mut n = 0;
+                for (bytes, out) in payload.chunks_exact(2).zip(pcm.iter_mut()) {
+                    *out = i16::from_be_bytes([bytes[0], bytes[1]]);
+                    n += 1;
+                }
+                Ok(n / channels)
+            }
+        }
+    }
+}
+
+/// Puts RTP packets back in sequence-number order and drops duplicates and
+/// packets that arrive after their slot was given up on.
+///
+/// Opus decodes each packet against the previous one's state, so feeding it
+/// out of order garbles audio on lossy Wi-Fi where reordering is common.
+#[derive(Default)]
+pub struct ReorderBuffer {
+    /// Sequence number expected next; `None` before the first packet.
+    next: Option<u16>,
+    /// Early packets, in arrival order.
+    pending: Vec<Packet>,
+    reordered: u64,
+    duplicates: u64,
+    late: u64,
+    lost: u64,
+}
+
+impl ReorderBuffer {
+    /// Add an arrived packet and pass every packet that is now in order to
+    /// `emit`.
+    pub fn push(&mut self, packet: Packet, mut emit: impl FnMut(Packet)) {
+        let seq = packet.header.sequence_number;
+        let Some(next) = self.next else {
+            self.next = Some(seq.wrapping_add(1));
+            emit(packet);
+            return;
+        };
+
+        let ahead = seq.wrapping_sub(next);
+        if ahead == 0 {
+            self.next = Some(seq.wrapping_add(1));
+            emit(packet);
+            self.drain(&mut emit);
+        } else if ahead > u16::MAX / 2 {
+            // Behind the expected number: already played or skipped
+            if self.pending.is_empty() && seq.wrapping_add(1) == next {
+                self.duplicates += 1;
+            } else {
+                self.late += 1;
+            }
+        } else if ahead >= MAX_SEQUENCE_JUMP {
+            self.flush(&mut emit);
+            self.next = Some(seq.wrapping_add(1));
+            emit(packet);
+        } else if self.pending.iter().any(|p| p.header.sequence_number == seq) {
+            self.duplicates += 1;
+        } else {
+            self.pending.push(packet);
+            if self.pending.len() > REORDER_WINDOW {
+                // Give up on the gap and resume from the earliest held packet
+                self.skip_to_earliest();
+                self.drain(&mut emit);
+            }
+        }
+    }
+
+    /// Emit held packets that continue the sequence.
+    fn drain(&mut self, emit: &mut impl FnMut(Packet)) {
+        while let Some(next) = self.next {
+            let Some(i) = self
+                .pending
+                .iter()
+                .position(|p| p.header.sequence_number == next)
+            else {
+                return;
+            };
+            self.reordered += 1;
+            self.next = Some(next.wrapping_add(1));
+            emit(self.pending.swap_remove(i));
+        }
+    }
+
+    fn skip_to_earliest(&mut self) {
+        let Some(next) = self.next else {
+            return;
+        };
+        if let Some(earliest) = self
+            .pending
+            .iter()
+            .map(|p| p.header.sequence_number)
+            .min_by_key(|&s| s.wrapping_sub(next))
+        {
+            self.lost += u64::from(earliest.wrapping_sub(next));
+            self.next = Some(earliest);
+        }
+    }
+
+    /// Emit everything held, in order, skipping any remaining gaps.
+    fn flush(&mut self, emit: &mut impl FnMut(Packet)) {
+        while !self.pending.is_empty() {
+            self.skip_to_earliest();
+            self.drain(emit);
+        }
+    }
+
+    /// Take the counters accumulated since the last call.
+    pub fn take_counts(&mut self) -> (u64, u64, u64, u64) {
+        let counts = (self.reordered, self.duplicates, self.late, self.lost);
+        self.reordered = 0;
+        self.duplicates = 0;
+        self.late = 0;
+        self.lost = 0;
+        counts
+    }
+}
+
+/// Opus decode, downmix and VAD for one sender, on its own OS thread.
+///
+/// The async `read_rtp` task only forwards packets through a bounded channel,
+/// so decoder and DSP cost never stalls a tokio worker. Dropping the handle
+/// closes the channel and the thread exits after draining it.
+pub struct DecodeThread {
+    tx: SyncSender<Packet>,
+    dropped_packets: u64,
+    last_drop_log: Instant,
+    shared: SharedStatus,
+}
+
+impl DecodeThread {
+    pub fn spawn(
+        input: Arc<MixerInput>,
+        codec: AudioCodec,
+        channels: usize,
+        shared: SharedStatus,
+    ) -> Result<Self> {
+        let (tx, rx) = mpsc::sync_channel(PACKET_QUEUE_DEPTH);
+        let thread_shared = shared.clone();
+        std::thread::Builder::new()
+            .name(format!("decode-{}", input.id))
+            .spawn(move || {
+                if let Err(e) = decode_loop(rx, &input, codec, channels, &thread_shared) {
+                    thread_shared.log_line(format!("Audio decode stopped: {e}"));
+                    thread_shared.set_last_error(Some(e.to_string()));
+                }
+            })?;
+
+        Ok(Self {
+            tx,
+            dropped_packets: 0,
+            last_drop_log: Instant::now(),
+            shared,
+        })
+    }
+
+    /// Hand a packet to the decode thread without blocking. Returns `false`
+    /// once the thread has exited.
+    pub fn submit(&mut self, packet: Packet) -> bool {
+        match self.tx.try_send(packet) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                self.dropped_packets += 1;
+                if self.last_drop_log.elapsed().as_secs() >= 5 {
+                    self.shared.log_line(format!(
+                        "Decoder falling behind: {} packets dropped",
+                        self.dropped_packets
+                    ));
+                    self.dropped_packets = 0;
+                    self.last_drop_log = Instant::now();
+                }
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+fn decode_loop(
+    rx: Receiver<Packet>,
+    input: &MixerInput,
+    codec: AudioCodec,
+    channels: usize,
+    shared: &SharedStatus,
+) -> Result<()> {
+    let mut dec = FrameDecoder::new(codec, channels)?;
+
+    let mut pcm = vec![0i16; MAX_SAMPLES_PER_CHANNEL * channels];
+    let mut mono = Vec::with_capacity(MAX_SAMPLES_PER_CHANNEL);
+    // One decoded frame as queued, stereo kept
+    let mut frame = Vec::with_capacity(MAX_SAMPLES_PER_CHANNEL * channels);
+    let mut vad = VoiceActivityDetector::new();
+
+    // Sequence number expected next, and the previous packet's frame size
+    // (per channel), which concealed frames reuse
+    let mut next_seq: Option<u16> = None;
+    let mut last_frame = 0;
+    // Packets in a row the decoder has rejected
+    let mut failures = 0u32;
+
+    // Track dropped, time-compressed and concealed samples for periodic logging
+    let mut dropped = 0u64;
+    let mut compressed = 0u64;
+    let mut concealed = 0u64;
+    let mut skipped = 0u64;
+    let mut packets = 0u64;
+    let mut last_log = Instant::now();
+
+    while let Ok(rtp) = rx.recv() {
+        let seq = rtp.header.sequence_number;
+        let missing = next_seq.map_or(0, |next| seq.wrapping_sub(next));
+        next_seq = Some(seq.wrapping_add(1));
+
+        // Packets arrive in order (see `ReorderBuffer`), so a gap is loss:
+        // synthesize the missing audio instead of letting the queue run dry.
+        // The last missing frame is recovered from this packet's in-band
+        // FEC data when the sender includes it; earlier ones are concealed.
+        // G.711 and L16 have nothing to conceal with; their gaps are left
+        // to the jitter buffer.
+        if let (FrameDecoder::Opus(opus), 1..=MAX_CONCEALED_PACKETS, true) =
+            (&mut dec, missing, last_frame > 0)
+        {
+            for i in 1..=missing {
+                let fec = i == missing && !rtp.payload.is_empty();
+                let payload: &[u8] = if fec { &rtp.payload } else { &[] };
+                let n = match opus.decode(payload, &mut pcm[..last_frame * channels], fec) {
+                    Ok(n) => n,
+                    // Not fatal: the gap is left to the jitter buffer
+                    Err(e) => {
+                        log::debug!("Opus concealment failed: {e:?}");
+                        break;
+                    }
+                };
+                shared.bump_concealed_packets(1);
+                concealed += n as u64;
+                let overflowed = input.push_frame(&mut pcm[..n * channels], channels);
+                if overflowed > 0 {
+                    dropped += overflowed;
+                    shared.bump_dropped_samples(overflowed);
+                }
+            }
+        }
+
+        if rtp.payload.is_empty() {
+            continue;
+        }
+        // A corrupt packet is skipped like a lost one; only a run of them
+        // suggests the decoder itself is stuck
+        let n = match dec.decode(&rtp.payload, &mut pcm, channels) {
+            Ok(n) => n,
+            Err(e) => {
+                log::debug!("Skipping undecodable packet {seq}: {e:?}");
+                shared.bump_decode_errors(1);
+                skipped += 1;
+                failures += 1;
+                if failures >= MAX_DECODE_FAILURES {
+                    shared.log_line(format!(
+                        "{failures} packets in a row failed to decode — resetting the {codec} decoder."
+                    ));
+                    dec = FrameDecoder::new(codec, channels)?;
+                    failures = 0;
+                    last_frame = 0;
+                }
+                continue;
+            }
+        };
+        failures = 0;
+
+        if n == 0 {
+            continue;
+        }
+        last_frame = n;
+
+        // Levels and voice activity go by the mono downmix; the queue
+        // keeps stereo
+        to_mono(&pcm[..n * channels], channels, &mut mono);
+
+        shared.record_level(input.id, &mono);
+        if let Some(talking) = vad.process(&mono) {
+            input.set_talking(talking);
+            shared.mark_voice_activity(input.id, talking);
+        }
+
+        // Bring a grown playout buffer back down without dropping audio
+        frame.clear();
+        frame.extend_from_slice(&pcm[..n * channels]);
+        packets += 1;
+        if input.buffered() > input.jitter_target() + CATCH_UP_THRESHOLD
+            && packets.is_multiple_of(CATCH_UP_EVERY)
+        {
+            compressed += stretch::remove_period(&mut frame, channels) as u64;
+        }
+
+        let overflowed = input.push_frame(&mut frame, channels);
+        if overflowed > 0 {
+            dropped += overflowed;
+            shared.bump_dropped_samples(overflowed);
+        }
+
+        // Periodically log drops, catch-up, concealment and corrupt packets
+        if (dropped > 0 || compressed > 0 || concealed > 0 || skipped > 0)
+            && last_log.elapsed().as_secs() >= 5
+        {
+            if dropped > 0 {
+                shared.log_line(format!("Audio queue overflow: {dropped} samples dropped"));
+            }
+            if compressed > 0 {
+                shared.log_line(format!(
+                    "Catching up on buffered audio: {} ms time-compressed",
+                    compressed / 48
+                ));
+            }
+            if concealed > 0 {
+                shared.log_line(format!(
+                    "Packet loss: {} ms of audio concealed",
+                    concealed / 48
+                ));
+            }
+            if skipped > 0 {
+                shared.log_line(format!("Corrupt audio: {skipped} packets skipped"));
+            }
+            last_log = Instant::now();
+            dropped = 0;
+            compressed = 0;
+            concealed = 0;
+            skipped = 0;
+        }
+    }
+
+    Ok(())
+}
+
+/// Downmix interleaved stereo, or copy mono, into `mono`.
+fn to_mono(pcm: &[i16], channels: usize, mono: &mut Vec<i16>) {
+    if channels >= 2 {
+        convert::downmix_stereo(pcm, mono);
+    } else {
+        mono.clear();
+        mono.extend_from_slice(pcm);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webrtc::rtp::header::Header;
+
+    fn packet(sequence_number: u16) -> Packet {
+        Packet {
+            header: Header {
+                sequence_number,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Push `arrivals` in order; returns the emitted sequence numbers.
+    fn run(buffer: &mut ReorderBuffer, arrivals: &[u16]) -> Vec<u16> {
+        let mut out = Vec::new();
+        for &seq in arrivals {
+            buffer.push(packet(seq), |p| out.push(p.header.sequence_number));
+        }
+        out
+    }
+
+    #[test]
+    fn in_order_packets_pass_straight_through() {
+        let mut buffer = ReorderBuffer::default();
+        assert_eq!(run(&mut buffer, &[7, 8, 9]), [7, 8, 9]);
+        assert_eq!(buffer.take_counts(), (0, 0, 0, 0));
+    }
+
+    #[test]
+    fn swapped_packets_are_put_back_in_order() {
+        let mut buffer = ReorderBuffer::default();
+        assert_eq!(run(&mut buffer, &[1, 3, 4, 2, 5]), [1, 2, 3, 4, 5]);
+        assert_eq!(buffer.take_counts(), (2, 0, 0, 0));
+    }
+
+    #[test]
+    fn sequence_numbers_wrap_around() {
+        let mut buffer = ReorderBuffer::default();
+        assert_eq!(
+            run(&mut buffer, &[65_534, 0, 65_535, 1]),
+            [65_534, 65_535, 0, 1]
+        );
+        assert_eq!(buffer.take_counts(), (1, 0, 0, 0));
+    }
+
+    #[test]
+    fn duplicates_are_dropped() {
+        let mut buffer = ReorderBuffer::default();
+        // Once played, and once while held back for a gap
+        assert_eq!(run(&mut buffer, &[1, 2, 2, 4, 4, 3]), [1, 2, 3, 4]);
+        assert_eq!(buffer.take_counts(), (1, 2, 0, 0));
+    }
+
+    #[test]
+    fn window_overflow_skips_to_the_earliest_held_packet() {
+        let mut buffer = ReorderBuffer::default();
+        // 2 and 3 never come; the fifth held packet gives up on them
+        assert_eq!(run(&mut buffer, &[1, 5, 4, 6, 7]), [1]);
+        assert_eq!(run(&mut buffer, &[8]), [4, 5, 6, 7, 8]);
+        assert_eq!(buffer.take_counts(), (5, 0, 0, 2));
+
+        // Too late now
+        assert!(run(&mut buffer, &[2]).is_empty());
+        assert_eq!(buffer.take_counts(), (0, 0, 1, 0));
+    }
+
+    #[test]
+    fn skip_to_earliest_orders_across_the_wrap() {
+        let mut buffer = ReorderBuffer::default();
+        run(&mut buffer, &[65_530]);
+        // 65 531 is lost; 65 532 comes before 0 to 2 despite being larger
+        assert!(run(&mut buffer, &[65_532, 0, 1, 65_533]).is_empty());
+        assert_eq!(run(&mut buffer, &[2]), [65_532, 65_533]);
+        assert_eq!(
+            run(&mut buffer, &[65_534, 65_535]),
+            [65_534, 65_535, 0, 1, 2]
+        );
+        let (_, _, _, lost) = buffer.take_counts();
+        assert_eq!(lost, 1);
+    }
+
+    #[test]
+    fn large_jump_is_a_restart() {
+        let mut buffer = ReorderBuffer::default();
+        assert_eq!(run(&mut buffer, &[10, 12, 5_000]), [10, 12, 5_000]);
+        assert_eq!(run(&mut buffer, &[5_001]), [5_001]);
+        assert_eq!(buffer.take_counts(), (1, 0, 0, 1));
+    }
+}
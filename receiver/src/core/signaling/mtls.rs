@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use rand::Rng;
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa,
+    KeyPair, KeyUsagePurpose,
+};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Subject of the receiver's client CA.
+const CA_COMMON_NAME: &str = "LAN Mic Receiver Client CA";
+
+/// Length of the random password protecting exported identities.
+const PASSWORD_LEN: usize = 12;
+
+fn mtls_dir() -> Option<PathBuf> {
+    crate::profile::current()
+        .config_dir()
+        .map(|d| d.join("mtls"))
+}
+
+/// The receiver's client CA: its certificate and signing key.
+struct ClientCa {
+    cert: Certificate,
+    key: KeyPair,
+}
+
+fn ca_params() -> Result<CertificateParams> {
+    let mut params = CertificateParams::new(Vec::new())?;
+    params
+        .distinguished_name
+        .push(DnType::CommonName, CA_COMMON_NAME);
+    params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+    params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+    Ok(params)
+}
+
+impl ClientCa {
+    /// Load the CA from the settings directory, creating it on first use.
+    ///
+    /// Only the key and the PEM handed to the verifier are stored; the
+    /// certificate object used for signing is rebuilt from the same subject
+    /// and key, so issued certificates chain to the stored one.
+    fn load_or_create() -> Result<(Self, String)> {
+        let dir = mtls_dir().context("no config directory")?;
+        let cert_path = dir.join("ca.pem");
+        let key_path = dir.join("ca-key.pem");
+        if let (Ok(cert_pem), Ok(key_pem)) = (
+            std::fs::read_to_string(&cert_path),
+            std::fs::read_to_string(&key_path),
+        ) {
+            let key = KeyPair::from_pem(&key_pem)
+                .with_context(|| format!("parse {}", key_path.display()))?;
+            // Older builds wrote the key with default permissions
+            #[cfg(unix)]
+            let _ = std::fs::set_permissions(
+                &key_path,
+                std::os::unix::fs::PermissionsExt::from_mode(0o600),
+            );
+            let cert = ca_params()?.self_signed(&key)?;
+            return Ok((Self { cert, key }, cert_pem));
+        }
+
+        let key = KeyPair::generate()?;
+        let cert = ca_params()?.self_signed(&key)?;
+        let cert_pem = cert.pem();
+        std::fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+        write_private(&key_path, key.serialize_pem().as_bytes())?;
+        std::fs::write(&cert_path, &cert_pem)
+            .with_context(|| format!("write {}", cert_path.display()))?;
+        Ok((Self { cert, key }, cert_pem))
+    }
+}
+
+/// TLS settings for the HTTPS listener serving `chain_pem`/`key_pem`. With
+/// `--mtls`, the handshake fails unless the client presents a certificate
+/// issued by this receiver's client CA.
+pub fn server_config(chain_pem: &[u8], key_pem: &[u8]) -> Result<Arc<rustls::ServerConfig>> {
+    let chain = CertificateDer::pem_slice_iter(chain_pem)
+        .collect::<Result<Vec<_>, _>>()
+        .context("parse certificate chain")?;
+    let key = PrivateKeyDer::from_pem_slice(key_pem).context("parse private key")?;
+
+    let builder = rustls::ServerConfig::builder();
+    let mut config = if crate::profile::current().mtls {
+        let (_, ca_pem) = ClientCa::load_or_create().context("load client CA")?;
+        let mut roots = rustls::RootCertStore::empty();
+        for cert in CertificateDer::pem_slice_iter(ca_pem.as_bytes()) {
+            roots.add(cert.context("parse client CA")?)?;
+        }
+        let verifier = WebPkiClientVerifier::builder(Arc::new(roots)).build()?;
+        builder
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(chain, key)?
+    } else {
+        builder.with_no_client_auth().with_single_cert(chain, key)?
+    };
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+/// Default location for an exported client identity.
+pub fn default_export_path() -> PathBuf {
+    super::super::stats::export_path("lan-mic-client", "p12")
+}
+
+/// Issue a client certificate and write it with its key to `path` as a
+/// password-protected PKCS#12 file phones can install. Returns the password.
+pub fn export_client_identity(path: &Path) -> Result<String> {
+    let (ca, _) = ClientCa::load_or_create().context("load client CA")?;
+
+    let id = format!("{:08x}", rand::random::<u32>());
+    let mut params = CertificateParams::new(Vec::new())?;
+    params
+        .distinguished_name
+        .push(DnType::CommonName, format!("LAN Mic sender {id}"));
+    params.key_usages = vec![KeyUsagePurpose::DigitalSignature];
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+    let key = KeyPair::generate()?;
+    let cert = params.signed_by(&key, &ca.cert, &ca.key)?;
+
+    let chain = [p12_keystore::Certificate::from_der(cert.der())?];
+    let mut store = p12_keystore::KeyStore::new();
+    store.add_entry(
+        &format!("lan-mic-sender-{id}"),
+        p12_keystore::KeyStoreEntry::PrivateKeyChain(p12_keystore::PrivateKeyChain::new(
+            key.serialize_der(),
+            id.as_bytes(),
+            chain,
+        )),
+    );
+    let password = random_password();
+    let data = store.writer(&password).write()?;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    write_private(path, &data)?;
+    Ok(password)
+}
+
+/// Write a file holding a private key, readable by this user only.
+pub(super) fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("write {}", path.display()))?;
+    // `mode` only applies to new files
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))
+        .with_context(|| format!("restrict {}", path.display()))?;
+    file.write_all(data)
+        .with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
+
+/// Lowercase letters and digits without look-alikes, easy to type on a phone.
+fn random_password() -> String {
+    const CHARS: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+    let mut rng = rand::thread_rng();
+    (0..PASSWORD_LEN)
+        .map(|_| CHARS[rng.gen_range(0..CHARS.len())] as char)
+        .collect()
+}
@@ -0,0 +1,290 @@
+use super::{ingest_refusal, IngestClient, SessionOptions};
+use crate::audio::convert;
+use crate::audio::mixer::{ClientId, Mixer, MixerInput};
+use crate::audio::vad::VoiceActivityDetector;
+use crate::core::SharedStatus;
+use anyhow::{bail, Context, Result};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::sync::CancellationToken;
+
+/// First line a client sends, e.g. `LANMIC-PCM/1 rate=48000 channels=2`,
+/// followed by interleaved signed 16-bit little-endian frames.
+const HEADER_MAGIC: &str = "LANMIC-PCM/1";
+
+/// Longest header line accepted.
+const MAX_HEADER_LEN: usize = 256;
+
+/// The mixer runs at 48 kHz and PCM is not resampled.
+const SAMPLE_RATE: u32 = 48_000;
+
+/// Frames read and queued at a time (20 ms).
+const CHUNK_FRAMES: usize = 960;
+
+/// Buffered audio beyond the jitter target before reading pauses (100 ms),
+/// so a file piped faster than real time is throttled by TCP instead of
+/// overflowing the queue.
+const PACING_HEADROOM: usize = 4_800;
+const PACING_SLEEP: Duration = Duration::from_millis(10);
+
+/// Stream format announced in the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Format {
+    channels: usize,
+}
+
+/// Parse `LANMIC-PCM/1 rate=48000 channels=1|2`; keys may come in any order.
+fn parse_header(line: &str) -> Result<Format> {
+    let mut parts = line.split_whitespace();
+    if parts.next() != Some(HEADER_MAGIC) {
+        bail!("header must start with {HEADER_MAGIC}");
+    }
+    let mut rate = None;
+    let mut channels = None;
+    for part in parts {
+        match part.split_once('=') {
+            Some(("rate", v)) => rate = v.parse::<u32>().ok(),
+            Some(("channels", v)) => channels = v.parse::<usize>().ok(),
+            _ => bail!("unknown header field '{part}'"),
+        }
+    }
+    if rate != Some(SAMPLE_RATE) {
+        bail!("rate must be {SAMPLE_RATE}");
+    }
+    match channels {
+        Some(channels @ (1 | 2)) => Ok(Format { channels }),
+        _ => bail!("channels must be 1 or 2"),
+    }
+}
+
+/// Accept PCM streams on `port` into `mixer` until `cancel` fires. Each
+/// connection is a sender like any WebRTC one: it gets a mixer input with
+/// gain, mute and routing, shows up in the client list, and is subject to
+/// the session's approval and multiple-sender settings.
+pub fn spawn(
+    port: u16,
+    options: SessionOptions,
+    mixer: Arc<Mixer>,
+    shared: SharedStatus,
+    cancel: CancellationToken,
+) {
+    tokio::spawn(async move {
+        let listener = match TcpListener::bind((Ipv4Addr::UNSPECIFIED, port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                shared.log_line(format!("PCM ingest: cannot listen on port {port}: {e}"));
+                return;
+            }
+        };
+        shared.log_line(format!("Raw PCM ingest on TCP port {port}"));
+        loop {
+            let (stream, peer) = tokio::select! {
+                _ = cancel.cancelled() => return,
+                res = listener.accept() => match res {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        log::warn!("PCM ingest accept error: {e}");
+                        continue;
+                    }
+                },
+            };
+            if !shared.is_ip_allowed(peer.ip()) {
+                shared.log_line(format!(
+                    "PCM ingest: rejected {}: not allowed by access rules.",
+                    peer.ip()
+                ));
+                continue;
+            }
+            if let Some(reason) = ingest_refusal(&options, &shared) {
+                shared.log_line(format!("PCM ingest: rejected {}: {reason}.", peer.ip()));
+                continue;
+            }
+            let (mixer, shared, cancel) = (Arc::clone(&mixer), shared.clone(), cancel.clone());
+            tokio::spawn(async move {
+                tokio::select! {
+                    _ = cancel.cancelled() => {}
+                    _ = handle(stream, peer, &mixer, &shared) => {}
+                }
+            });
+        }
+    });
+}
+
+async fn handle(stream: TcpStream, peer: SocketAddr, mixer: &Mixer, shared: &SharedStatus) {
+    let mut reader = BufReader::new(stream);
+    let format = match read_header(&mut reader).await {
+        Ok(format) => format,
+        Err(e) => {
+            shared.log_line(format!("PCM ingest: rejected {peer}: {e:#}"));
+            return;
+        }
+    };
+
+    let (client, input) = IngestClient::connect(format!("{} (PCM)", peer.ip()), mixer, shared);
+    let client_id = client.id;
+    shared.log_line(format!(
+        "PCM client #{client_id} connected from {peer} ({} ch)",
+        format.channels
+    ));
+
+    let res = stream_audio(&mut reader, format, client_id, &input, shared).await;
+
+    let error = res.as_ref().err().map(|e| format!("{e:#}"));
+    client.disconnect(mixer, shared, None, error);
+    match res {
+        Ok(()) => shared.log_line(format!("PCM client #{client_id} disconnected.")),
+        Err(e) => shared.log_line(format!("PCM client #{client_id} failed: {e:#}")),
+    }
+}
+
+async fn read_header(reader: &mut BufReader<TcpStream>) -> Result<Format> {
+    let mut line = Vec::new();
+    let mut limited = (&mut *reader).take(MAX_HEADER_LEN as u64);
+    limited
+        .read_until(b'\n', &mut line)
+        .await
+        .context("read header")?;
+    if line.last() != Some(&b'\n') {
+        bail!("no header line within {MAX_HEADER_LEN} bytes");
+    }
+    parse_header(
+        std::str::from_utf8(&line)
+            .context("header is not UTF-8")?
+            .trim(),
+    )
+}
+
+async fn stream_audio(
+    reader: &mut BufReader<TcpStream>,
+    format: Format,
+    client_id: ClientId,
+    input: &MixerInput,
+    shared: &SharedStatus,
+) -> Result<()> {
+    let frame_bytes = 2 * format.channels;
+    let mut buf = vec![0u8; CHUNK_FRAMES * frame_bytes];
+    let mut filled = 0;
+    let mut samples = Vec::with_capacity(CHUNK_FRAMES * format.channels);
+    let mut mono = Vec::with_capacity(CHUNK_FRAMES);
+    let mut vad = VoiceActivityDetector::new();
+
+    loop {
+        while input.buffered() > input.jitter_target() + PACING_HEADROOM {
+            tokio::time::sleep(PACING_SLEEP).await;
+        }
+
+        let n = reader.read(&mut buf[filled..]).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        shared.record_audio_packet(client_id, n, 0.0);
+        filled += n;
+        // Only whole frames; a partial one waits for the next read
+        let usable = filled - filled % frame_bytes;
+        if usable == 0 {
+            continue;
+        }
+
+        samples.clear();
+        samples.extend(
+            buf[..usable]
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]])),
+        );
+        buf.copy_within(usable..filled, 0);
+        filled -= usable;
+
+        // Levels and voice activity go by the mono downmix; the queue
+        // keeps stereo
+        let levels = if format.channels == 2 {
+            convert::downmix_stereo(&samples, &mut mono);
+            &mono
+        } else {
+            &samples
+        };
+        shared.record_level(client_id, levels);
+        if let Some(talking) = vad.process(levels) {
+            input.set_talking(talking);
+            shared.mark_voice_activity(client_id, talking);
+        }
+        let overflowed = input.push_frame(&mut samples, format.channels);
+        if overflowed > 0 {
+            shared.bump_dropped_samples(overflowed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_accepts_mono_and_stereo() {
+        assert_eq!(
+            parse_header("LANMIC-PCM/1 rate=48000 channels=1").unwrap(),
+            Format { channels: 1 }
+        );
+        assert_eq!(
+            parse_header("LANMIC-PCM/1 rate=48000 channels=2").unwrap(),
+            Format { channels: 2 }
+        );
+    }
+
+    #[test]
+    fn header_fields_may_come_in_any_order() {
+        assert_eq!(
+            parse_header("LANMIC-PCM/1 channels=2 rate=48000").unwrap(),
+            Format { channels: 2 }
+        );
+    }
+
+    #[test]
+    fn header_needs_the_magic_first() {
+        for line in [
+            "",
+            "LANMIC-PCM/2 rate=48000 channels=1",
+            "rate=48000 channels=1 LANMIC-PCM/1",
+        ] {
+            let err = parse_header(line).unwrap_err().to_string();
+            assert!(err.contains(HEADER_MAGIC), "{line:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn header_needs_a_48_khz_rate() {
+        for line in [
+            "LANMIC-PCM/1 channels=1",
+            "LANMIC-PCM/1 rate=44100 channels=1",
+            "LANMIC-PCM/1 rate=fast channels=1",
+        ] {
+            let err = parse_header(line).unwrap_err().to_string();
+            assert!(err.contains("rate"), "{line:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn header_needs_one_or_two_channels() {
+        for line in [
+            "LANMIC-PCM/1 rate=48000",
+            "LANMIC-PCM/1 rate=48000 channels=0",
+            "LANMIC-PCM/1 rate=48000 channels=6",
+        ] {
+            let err = parse_header(line).unwrap_err().to_string();
+            assert!(err.contains("channels"), "{line:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn header_rejects_unknown_fields() {
+        for line in [
+            "LANMIC-PCM/1 rate=48000 channels=1 bits=24",
+            "LANMIC-PCM/1 rate=48000 channels=1 stereo",
+        ] {
+            let err = parse_header(line).unwrap_err().to_string();
+            assert!(err.contains("unknown header field"), "{line:?}: {err}");
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::config_store;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// The network interface whose address goes in URLs, QR codes and mDNS,
+/// chosen by the user when the automatic pick lands on e.g. a VPN adapter.
+///
+/// Saved as `interface.json` in the settings directory:
+/// `{"interface": "Wi-Fi"}`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Stored {
+    interface: String,
+}
+
+const STORE: &str = "interface.json";
+
+/// The chosen interface name, if any; an unreadable file means automatic.
+pub fn load() -> Option<String> {
+    config_store::load::<Stored>(STORE).map(|s| s.interface)
+}
+
+/// Remember `interface` for future launches; `None` goes back to picking
+/// the address automatically.
+pub fn save(interface: Option<&str>) -> Result<()> {
+    match interface {
+        Some(interface) => config_store::save(
+            STORE,
+            &Stored {
+                interface: interface.to_string(),
+            },
+        ),
+        None => config_store::remove(STORE),
+    }
+}
@@ -0,0 +1,508 @@
+use super::webrtc_session::{SignalMessage, PROTOCOL_VERSION};
+use crate::audio::mixer::ClientId;
+use crate::core::SharedStatus;
+use anyhow::{anyhow, bail, Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, mpsc};
+use tokio_rustls::client::TlsStream;
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+use tokio_tungstenite::WebSocketStream;
+use tokio_util::sync::CancellationToken;
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+
+use webrtc::api::interceptor_registry::register_default_interceptors;
+use webrtc::api::media_engine::{MediaEngine, MIME_TYPE_OPUS};
+use webrtc::api::APIBuilder;
+use webrtc::ice_transport::ice_candidate::{RTCIceCandidate, RTCIceCandidateInit};
+use webrtc::ice_transport::ice_server::RTCIceServer;
+use webrtc::interceptor::registry::Registry;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::rtp::packet::Packet;
+use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
+use webrtc::track::track_local::{TrackLocal, TrackLocalWriter};
+
+/// RTP packets buffered for the relay connection before it starts skipping.
+const RELAY_QUEUE_DEPTH: usize = 256;
+
+/// Pause between attempts to reach the downstream receiver.
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+/// Maximum outbound signaling messages before backpressure.
+const SIGNAL_CHANNEL_SIZE: usize = 64;
+
+type RelaySocket = WebSocketStream<TlsStream<TcpStream>>;
+
+// ---------------------------------------------------------------------------
+// Feed — RTP from the upstream sender
+// ---------------------------------------------------------------------------
+
+/// Fan-out of one upstream sender's RTP packets to the relay connection.
+///
+/// The Opus payload is forwarded untouched, so only one sender can feed the
+/// relay at a time — interleaving a second stream's sequence numbers would
+/// corrupt the downstream decode. The first sender to deliver audio claims
+/// the feed until it disconnects.
+#[derive(Debug, Clone)]
+pub struct RelayFeed {
+    tx: broadcast::Sender<Packet>,
+    source: Arc<Mutex<Option<ClientId>>>,
+}
+
+impl RelayFeed {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(RELAY_QUEUE_DEPTH);
+        Self {
+            tx,
+            source: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn forward(&self, client_id: ClientId, packet: &Packet) {
+        {
+            let mut source = self.source.lock();
+            match *source {
+                Some(id) if id != client_id => return,
+                Some(_) => {}
+                None => *source = Some(client_id),
+            }
+        }
+        // Fails only while the relay is between connections
+        let _ = self.tx.send(packet.clone());
+    }
+
+    /// Let another sender take over once `client_id` disconnects.
+    pub fn release(&self, client_id: ClientId) {
+        let mut source = self.source.lock();
+        if *source == Some(client_id) {
+            *source = None;
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Connection — this receiver acting as a sender to another one
+// ---------------------------------------------------------------------------
+
+/// Keep forwarding `feed` to the receiver at `target` until `cancel` fires,
+/// reconnecting whenever the downstream session ends.
+pub fn spawn(
+    target: &str,
+    feed: RelayFeed,
+    ice_servers: Vec<RTCIceServer>,
+    shared: SharedStatus,
+    cancel: CancellationToken,
+) {
+    let url = relay_url(target);
+    tokio::spawn(async move {
+        shared.log_line(format!("Relaying audio to {url}"));
+        while !cancel.is_cancelled() {
+            shared.set_relay_state(Some("connecting".into()));
+            match run_once(&url, &feed, &ice_servers, &shared, &cancel).await {
+                Ok(()) => shared.log_line("Relay session ended."),
+                Err(e) => shared.log_line(format!("Relay to {url} failed: {e}")),
+            }
+            shared.set_relay_state(Some("disconnected".into()));
+            tokio::select! {
+                _ = tokio::time::sleep(RECONNECT_DELAY) => {}
+                _ = cancel.cancelled() => {}
+            }
+        }
+        shared.set_relay_state(None);
+    });
+}
+
+/// Accept `host`, `host:port`, `https://host:port` or a full `wss://` URL.
+/// IPv6 hosts may be bare (`fe80::1`) or bracketed (`[fe80::1]:9001`).
+fn relay_url(target: &str) -> String {
+    let target = target.trim().trim_end_matches('/');
+    if target.starts_with("wss://") || target.starts_with("ws://") {
+        return target.to_string();
+    }
+    let host = target
+        .trim_start_matches("https://")
+        .trim_start_matches("http://");
+    if host.parse::<std::net::IpAddr>().is_ok() || host.ends_with(']') {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        format!(
+            "wss://{}:{}/ws",
+            super::url_host(host),
+            crate::profile::DEFAULT_PORT
+        )
+    } else if host.contains(':') {
+        format!("wss://{host}/ws")
+    } else {
+        format!("wss://{host}:{}/ws", crate::profile::DEFAULT_PORT)
+    }
+}
+
+async fn run_once(
+    url: &str,
+    feed: &RelayFeed,
+    ice_servers: &[RTCIceServer],
+    shared: &SharedStatus,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let mut ws = connect(url).await?;
+    shared.set_relay_state(Some("signaling".into()));
+
+    let (out_tx, mut out_rx) = mpsc::channel::<SignalMessage>(SIGNAL_CHANNEL_SIZE);
+    let track = Arc::new(TrackLocalStaticRTP::new(
+        RTCRtpCodecCapability {
+            mime_type: MIME_TYPE_OPUS.to_owned(),
+            clock_rate: 48_000,
+            channels: 2,
+            ..Default::default()
+        },
+        "audio".to_owned(),
+        "lan-mic-relay".to_owned(),
+    ));
+    let pc = create_peer_connection(ice_servers, Arc::clone(&track), out_tx, shared).await?;
+    let mut packets = feed.tx.subscribe();
+    let mut pending_ice = Vec::new();
+
+    let result = async {
+        loop {
+            tokio::select! {
+                msg = ws.next() => {
+                    let text = match msg {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_))) | None => return Ok(()),
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => return Err(e.into()),
+                    };
+                    let signal: SignalMessage = match serde_json::from_str(&text) {
+                        Ok(signal) => signal,
+                        Err(e) => {
+                            shared.log_line(format!("Bad relay signaling message: {e}"));
+                            continue;
+                        }
+                    };
+                    if let Some(reply) =
+                        handle_signal(signal, &pc, &mut pending_ice, shared).await?
+                    {
+                        ws.send(Message::Text(serde_json::to_string(&reply)?)).await?;
+                    }
+                }
+
+                out = out_rx.recv() => {
+                    if let Some(out) = out {
+                        ws.send(Message::Text(serde_json::to_string(&out)?)).await?;
+                    }
+                }
+
+                packet = packets.recv() => match packet {
+                    Ok(packet) => {
+                        if let Err(e) = track.write_rtp(&packet).await {
+                            log::warn!("Relay RTP write failed: {e}");
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        log::warn!("Relay fell behind, skipped {n} packets");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                },
+
+                _ = cancel.cancelled() => {
+                    let bye = SignalMessage {
+                        msg_type: "bye".to_string(),
+                        ..Default::default()
+                    };
+                    let _ = ws.send(Message::Text(serde_json::to_string(&bye)?)).await;
+                    return Ok(());
+                }
+            }
+        }
+    }
+    .await;
+
+    let _ = ws.close(None).await;
+    pc.close().await?;
+    result
+}
+
+/// Process one message from the downstream receiver, returning the reply to
+/// send, if any.
+async fn handle_signal(
+    signal: SignalMessage,
+    pc: &Arc<RTCPeerConnection>,
+    pending_ice: &mut Vec<RTCIceCandidateInit>,
+    shared: &SharedStatus,
+) -> Result<Option<SignalMessage>> {
+    match signal.msg_type.as_str() {
+        "hello" => {
+            let profile = crate::profile::current();
+            let hostname = gethostname::gethostname().to_string_lossy().into_owned();
+            return Ok(Some(SignalMessage {
+                msg_type: "hello".to_string(),
+                version: Some(PROTOCOL_VERSION),
+                capabilities: Some(vec!["opus".to_string()]),
+                device_id: Some(format!("relay-{hostname}-{}", profile.port)),
+                device_name: Some(match &profile.display_name {
+                    Some(name) => format!("{name} (relay)"),
+                    None => format!("{} relay on {hostname}", profile.title),
+                }),
+                ..Default::default()
+            }));
+        }
+        "offer" => {
+            let sdp = signal.sdp.ok_or_else(|| anyhow!("offer without SDP"))?;
+            let offer =
+                RTCSessionDescription::offer(sdp).map_err(|e| anyhow!("parse offer: {e}"))?;
+            pc.set_remote_description(offer).await?;
+            for candidate in pending_ice.drain(..) {
+                if let Err(e) = pc.add_ice_candidate(candidate).await {
+                    log::warn!("Failed to add queued relay ICE candidate: {e}");
+                }
+            }
+            let answer = pc.create_answer(None).await?;
+            pc.set_local_description(answer).await?;
+            let local = pc
+                .local_description()
+                .await
+                .ok_or_else(|| anyhow!("no local description"))?;
+            return Ok(Some(SignalMessage {
+                msg_type: "answer".to_string(),
+                sdp: Some(local.sdp),
+                ..Default::default()
+            }));
+        }
+        "ice" => {
+            if let Some(candidate) = signal.candidate {
+                let init = RTCIceCandidateInit {
+                    candidate,
+                    sdp_mid: Some(signal.sdp_mid.unwrap_or_default()),
+                    sdp_mline_index: Some(signal.sdp_mline_index.unwrap_or(0) as u16),
+                    username_fragment: Some(String::new()),
+                };
+                if pc.remote_description().await.is_none() {
+                    pending_ice.push(init);
+                } else if let Err(e) = pc.add_ice_candidate(init).await {
+                    log::warn!("Failed to add relay ICE candidate: {e}");
+                }
+            }
+        }
+        "pending" => {
+            shared.set_relay_state(Some("waiting for approval".into()));
+            shared.log_line("Relay target is asking its user to allow this receiver.");
+        }
+        "diagnostic" => {
+            shared.log_line(format!(
+                "Relay target diagnosis: {}",
+                signal.message.unwrap_or_default()
+            ));
+        }
+        "error" => {
+            bail!(
+                "{} ({})",
+                signal.message.unwrap_or_default(),
+                signal.code.unwrap_or_default()
+            );
+        }
+        "bye" => bail!("relay target said bye"),
+        _ => {}
+    }
+    Ok(None)
+}
+
+async fn create_peer_connection(
+    ice_servers: &[RTCIceServer],
+    track: Arc<TrackLocalStaticRTP>,
+    out_tx: mpsc::Sender<SignalMessage>,
+    shared: &SharedStatus,
+) -> Result<Arc<RTCPeerConnection>> {
+    let mut m = MediaEngine::default();
+    m.register_default_codecs()?;
+    let mut registry = Registry::new();
+    registry = register_default_interceptors(registry, &mut m)?;
+
+    let api = APIBuilder::new()
+        .with_media_engine(m)
+        .with_interceptor_registry(registry)
+        .build();
+
+    let pc = Arc::new(
+        api.new_peer_connection(RTCConfiguration {
+            ice_servers: ice_servers.to_vec(),
+            ..Default::default()
+        })
+        .await?,
+    );
+
+    let sender = pc
+        .add_track(track as Arc<dyn TrackLocal + Send + Sync>)
+        .await?;
+    // Drain RTCP so the interceptors (NACK, reports) keep working
+    tokio::spawn(async move {
+        let mut buf = vec![0u8; 1500];
+        while sender.read(&mut buf).await.is_ok() {}
+    });
+
+    let shared_pc = shared.clone();
+    pc.on_peer_connection_state_change(Box::new(move |s: RTCPeerConnectionState| {
+        let shared_pc = shared_pc.clone();
+        Box::pin(async move {
+            shared_pc.set_relay_state(Some(format!("{s:?}")));
+        })
+    }));
+
+    pc.on_ice_candidate(Box::new(move |c: Option<RTCIceCandidate>| {
+        let out_tx = out_tx.clone();
+        Box::pin(async move {
+            let Some(init) = c.and_then(|c| c.to_json().ok()) else {
+                return;
+            };
+            let msg = SignalMessage {
+                msg_type: "ice".to_string(),
+                candidate: Some(init.candidate),
+                sdp_mid: init.sdp_mid,
+                sdp_mline_index: init.sdp_mline_index.map(|v| v as i32),
+                ..Default::default()
+            };
+            if let Err(e) = out_tx.send(msg).await {
+                log::warn!("Failed to send relay ICE candidate: {e}");
+            }
+        })
+    }));
+
+    Ok(pc)
+}
+
+// ---------------------------------------------------------------------------
+// TLS WebSocket client
+// ---------------------------------------------------------------------------
+
+pub(super) async fn connect(url: &str) -> Result<RelaySocket> {
+    let request = url.into_client_request()?;
+    let uri = request.uri();
+    if uri.scheme_str() != Some("wss") {
+        bail!("receivers only accept wss:// connections");
+    }
+    // IPv6 hosts keep the URL's brackets
+    let host = uri.host().context("relay URL has no host")?;
+    let host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let ip = host.parse::<IpAddr>().ok();
+    let port = uri.port_u16().unwrap_or(443);
+
+    let tcp = match ip {
+        Some(ip) => TcpStream::connect((ip, port)).await,
+        None => TcpStream::connect((host.as_str(), port)).await,
+    }
+    .with_context(|| format!("connect to {}:{port}", super::url_host(&host)))?;
+    let provider = CryptoProvider::get_default()
+        .cloned()
+        .unwrap_or_else(|| Arc::new(rustls::crypto::ring::default_provider()));
+    let config = rustls::ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+        .with_no_client_auth();
+    let server_name = match ip {
+        Some(ip) => ServerName::IpAddress(ip.into()),
+        None => ServerName::try_from(host)?,
+    };
+    let tls = tokio_rustls::TlsConnector::from(Arc::new(config))
+        .connect(server_name, tcp)
+        .await
+        .context("TLS handshake")?;
+
+    let (ws, _) = tokio_tungstenite::client_async(request, tls).await?;
+    Ok(ws)
+}
+
+/// Receivers serve self-signed certificates, so there is no chain to check;
+/// as with the web sender, the LAN itself is the trust boundary. Signatures
+/// are still verified so the handshake is a real one.
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::relay_url;
+    use crate::profile::DEFAULT_PORT;
+
+    #[test]
+    fn relay_url_fills_in_scheme_port_and_path() {
+        assert_eq!(
+            relay_url("192.168.1.5"),
+            format!("wss://192.168.1.5:{DEFAULT_PORT}/ws")
+        );
+        assert_eq!(relay_url("192.168.1.5:9002"), "wss://192.168.1.5:9002/ws");
+        assert_eq!(
+            relay_url("https://studio.local:9002/"),
+            "wss://studio.local:9002/ws"
+        );
+        assert_eq!(relay_url("wss://studio.local/ws"), "wss://studio.local/ws");
+    }
+
+    #[test]
+    fn relay_url_brackets_ipv6_hosts() {
+        let expected = format!("wss://[fe80::1]:{DEFAULT_PORT}/ws");
+        assert_eq!(relay_url("fe80::1"), expected);
+        assert_eq!(relay_url("[fe80::1]"), expected);
+        assert_eq!(relay_url("[fe80::1]:9002"), "wss://[fe80::1]:9002/ws");
+        assert_eq!(
+            relay_url("https://[fe80::1]:9002"),
+            "wss://[fe80::1]:9002/ws"
+        );
+    }
+}
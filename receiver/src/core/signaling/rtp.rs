@@ -0,0 +1,163 @@
+use super::decode::AudioCodec;
+use super::webrtc_session::PacketFeed;
+use super::{ingest_refusal, recv_datagram, IngestClient, SessionOptions};
+use crate::audio::mixer::Mixer;
+use crate::core::SharedStatus;
+use anyhow::Result;
+use std::collections::hash_map::{Entry, HashMap};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tokio_util::sync::CancellationToken;
+use webrtc::rtp::packet::Packet;
+use webrtc::util::Unmarshal;
+
+/// Largest datagram accepted.
+const MAX_DATAGRAM: usize = 65_507;
+
+/// A stream that stays silent this long is treated as disconnected.
+const SENDER_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// RTCP packet types 200–204 as they read when parsed as an RTP header
+/// (marker bit stripped), for senders that multiplex RTCP onto this port.
+const RTCP_PAYLOAD_TYPES: std::ops::RangeInclusive<u8> = 72..=76;
+
+/// Receive plain (unencrypted) RTP Opus on `port` into `mixer` until
+/// `cancel` fires, e.g. from `ffmpeg -f rtp rtp://RECEIVER:PORT`.
+///
+/// Each source address becomes its own sender. Packets go through the same
+/// reordering, concealment and decode path as WebRTC tracks; the payload
+/// type is not checked since there's no SDP to agree on one. New senders
+/// are subject to the session's approval and multiple-sender settings.
+pub fn spawn(
+    port: u16,
+    options: SessionOptions,
+    mixer: Arc<Mixer>,
+    shared: SharedStatus,
+    cancel: CancellationToken,
+) {
+    tokio::spawn(async move {
+        let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port)).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                shared.log_line(format!("RTP listener: cannot listen on port {port}: {e}"));
+                return;
+            }
+        };
+        shared.log_line(format!("Plain RTP (Opus) listener on UDP port {port}"));
+        receive_loop(&socket, &options, &mixer, &shared, &cancel).await;
+    });
+}
+
+async fn receive_loop(
+    socket: &UdpSocket,
+    options: &SessionOptions,
+    mixer: &Mixer,
+    shared: &SharedStatus,
+    cancel: &CancellationToken,
+) {
+    let mut senders: HashMap<SocketAddr, RtpSender> = HashMap::new();
+    // Refused sources and when they last sent, so each is logged once
+    let mut refused: HashMap<SocketAddr, Instant> = HashMap::new();
+    let mut buf = vec![0u8; MAX_DATAGRAM];
+    let mut expiry = tokio::time::interval(Duration::from_secs(1));
+
+    loop {
+        let (n, peer) = tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = expiry.tick() => {
+                senders.retain(|_, sender| {
+                    let alive = sender.last_seen.elapsed() < SENDER_TIMEOUT;
+                    if !alive {
+                        sender.disconnect(mixer, shared);
+                    }
+                    alive
+                });
+                refused.retain(|_, last_seen| last_seen.elapsed() < SENDER_TIMEOUT);
+                continue;
+            }
+            received = recv_datagram(socket, &mut buf, "RTP") => received,
+        };
+        if !shared.is_ip_allowed(peer.ip()) {
+            continue;
+        }
+        let packet = match Packet::unmarshal(&mut &buf[..n]) {
+            Ok(packet) if !RTCP_PAYLOAD_TYPES.contains(&packet.header.payload_type) => packet,
+            _ => {
+                shared.record_rtp_packet(false);
+                continue;
+            }
+        };
+        shared.record_rtp_packet(true);
+
+        let sender = match senders.entry(peer) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                if let Some(reason) = ingest_refusal(options, shared) {
+                    if refused.insert(peer, Instant::now()).is_none() {
+                        shared.log_line(format!("RTP listener: rejected {peer}: {reason}."));
+                    }
+                    continue;
+                }
+                refused.remove(&peer);
+                match RtpSender::connect(peer, mixer, shared) {
+                    Ok(sender) => e.insert(sender),
+                    Err(e) => {
+                        log::warn!("RTP listener: {e:#}");
+                        continue;
+                    }
+                }
+            }
+        };
+        sender.last_seen = Instant::now();
+        if !sender.feed.push(packet) {
+            // Decode thread exited and has already logged why
+            if let Some(sender) = senders.remove(&peer) {
+                sender.disconnect(mixer, shared);
+            }
+        }
+    }
+    for sender in senders.values() {
+        sender.disconnect(mixer, shared);
+    }
+}
+
+/// One RTP source and its mixer input.
+struct RtpSender {
+    client: IngestClient,
+    feed: PacketFeed,
+    last_seen: Instant,
+}
+
+impl RtpSender {
+    fn connect(peer: SocketAddr, mixer: &Mixer, shared: &SharedStatus) -> Result<Self> {
+        let (client, input) = IngestClient::connect(format!("{} (RTP)", peer.ip()), mixer, shared);
+        let client_id = client.id;
+        // Opus packets say whether they are stereo, so a stereo decoder
+        // handles either
+        let feed = match PacketFeed::spawn(input, AudioCodec::Opus, 2, None, shared.clone()) {
+            Ok(feed) => feed,
+            Err(e) => {
+                client.disconnect(mixer, shared, None, Some(format!("{e:#}")));
+                return Err(e);
+            }
+        };
+
+        shared.set_client_codec(client_id, AudioCodec::Opus.to_string());
+        shared.log_line(format!(
+            "RTP client #{client_id} started sending from {peer}"
+        ));
+
+        Ok(Self {
+            client,
+            feed,
+            last_seen: Instant::now(),
+        })
+    }
+
+    fn disconnect(&self, mixer: &Mixer, shared: &SharedStatus) {
+        self.client.disconnect(mixer, shared, None, None);
+        shared.log_line(format!("RTP client #{} stopped sending.", self.client.id));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::core::history;
+use anyhow::{Context, Result};
+use rcgen::{CertificateParams, DnType, KeyPair};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Lifetime of a generated certificate, inside the 398-day limit browsers
+/// apply to server certificates.
+const CERT_VALIDITY: Duration = Duration::from_secs(397 * 24 * 60 * 60);
+
+/// Replace the certificate this long before it expires, so it never lapses
+/// while the receiver is running.
+const RENEW_BEFORE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Names every generated certificate covers besides the LAN addresses.
+const FIXED_NAMES: [&str; 2] = ["localhost", "lan-mic-receiver"];
+
+/// Stored next to the PEM files to know when to regenerate.
+#[derive(Debug, Serialize, Deserialize)]
+struct CertMeta {
+    subject_alt_names: Vec<String>,
+    expires_at: u64,
+}
+
+fn tls_dir() -> Option<PathBuf> {
+    crate::profile::current()
+        .config_dir()
+        .map(|d| d.join("tls"))
+}
+
+/// The self-signed certificate and key (both PEM) for a listener reachable
+/// at `addresses`.
+///
+/// The certificate is kept in the `tls` folder of the settings directory
+/// and reused across restarts, so a phone that accepted it once is not
+/// warned again. It is only regenerated when it is about to expire or an
+/// address is missing from its SAN list. Returns whether it was generated.
+pub fn load_or_create(addresses: &[String]) -> Result<(String, String, bool)> {
+    let mut names: Vec<String> = FIXED_NAMES.iter().map(|n| n.to_string()).collect();
+    for ip in addresses {
+        if !names.contains(ip) {
+            names.push(ip.clone());
+        }
+    }
+
+    if let Some((cert_pem, key_pem)) = stored(&names) {
+        return Ok((cert_pem, key_pem, false));
+    }
+    let (cert_pem, key_pem, expires_at) = generate(&names)?;
+    if let Err(e) = store(&names, &cert_pem, &key_pem, expires_at) {
+        log::warn!("Failed to save TLS certificate: {e:#}");
+    }
+    Ok((cert_pem, key_pem, true))
+}
+
+/// The stored certificate, if it is still valid and covers `names`.
+fn stored(names: &[String]) -> Option<(String, String)> {
+    let dir = tls_dir()?;
+    let meta: CertMeta =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("cert.json")).ok()?).ok()?;
+    let fresh = meta.expires_at > history::unix_now() + RENEW_BEFORE.as_secs();
+    if !fresh || !names.iter().all(|n| meta.subject_alt_names.contains(n)) {
+        return None;
+    }
+    Some((
+        std::fs::read_to_string(dir.join("cert.pem")).ok()?,
+        std::fs::read_to_string(dir.join("key.pem")).ok()?,
+    ))
+}
+
+fn generate(names: &[String]) -> Result<(String, String, u64)> {
+    let mut params = CertificateParams::new(names.to_vec())?;
+    params
+        .distinguished_name
+        .push(DnType::CommonName, "LAN Mic Receiver");
+    let now = time::OffsetDateTime::now_utc();
+    // Backdated a little for phones whose clock runs behind
+    params.not_before = now - time::Duration::days(1);
+    params.not_after = now + CERT_VALIDITY;
+    let expires_at = params.not_after.unix_timestamp().max(0) as u64;
+    let key = KeyPair::generate()?;
+    let cert = params.self_signed(&key)?;
+    Ok((cert.pem(), key.serialize_pem(), expires_at))
+}
+
+fn store(names: &[String], cert_pem: &str, key_pem: &str, expires_at: u64) -> Result<()> {
+    let dir = tls_dir().context("no config directory")?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+    std::fs::write(dir.join("cert.pem"), cert_pem)?;
+    super::mtls::write_private(&dir.join("key.pem"), key_pem.as_bytes())?;
+    let meta = CertMeta {
+        subject_alt_names: names.to_vec(),
+        expires_at,
+    };
+    std::fs::write(dir.join("cert.json"), serde_json::to_string_pretty(&meta)?)?;
+    Ok(())
+}
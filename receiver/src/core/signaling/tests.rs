@@ -0,0 +1,479 @@
+//! End-to-end loopback: the relay's WebRTC sender connects to this
+//! receiver's `/ws` endpoint over real TLS, signaling and ICE, and streams
+//! a known Opus tone into a sender's mixer input queue. The relay's
+//! WebSocket client also drives the signaling checks directly, and the raw
+//! ingests are held to the same sender gates.
+
+use super::udp_session::{UdpFormat, UdpIngest};
+use super::{pcm, relay, rtp, start_http_server, udp_session, HttpServer, SessionOptions};
+use crate::audio::mixer::{Mixer, MixerInput};
+use crate::core::quality::QualityPreset;
+use crate::core::{CoreController, SharedStatus};
+use futures_util::{SinkExt, StreamExt};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_util::sync::CancellationToken;
+use webrtc::rtp::header::Header;
+use webrtc::rtp::packet::Packet;
+use webrtc::util::Marshal;
+
+const SAMPLE_RATE: usize = 48_000;
+/// 20 ms Opus frames, as the senders use.
+const FRAME: usize = 960;
+/// Frames streamed: one second of audio.
+const FRAMES: usize = 50;
+const TONE_HZ: f32 = 1_000.0;
+const TONE_AMPLITUDE: f32 = 8_000.0;
+/// Generous, since ICE gathering on a loaded CI machine can be slow.
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Keep settings, certificates and databases out of the real profile.
+fn isolate_profile() {
+    let dir = std::env::temp_dir().join(format!("lan-mic-loopback-{}", std::process::id()));
+    std::env::set_var("XDG_CONFIG_HOME", dir.join("config"));
+    std::env::set_var("XDG_DATA_HOME", dir.join("data"));
+    crate::profile::init(crate::profile::Profile {
+        name: Some("loopback-test".to_string()),
+        ..Default::default()
+    });
+}
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|l| l.local_addr())
+        .expect("no free port")
+        .port()
+}
+
+/// `FRAMES` Opus packets of a continuous sine tone, in RTP.
+fn tone_packets() -> Vec<Packet> {
+    let mut encoder = opus::Encoder::new(
+        SAMPLE_RATE as u32,
+        opus::Channels::Mono,
+        opus::Application::Audio,
+    )
+    .expect("opus encoder");
+    let mut out = vec![0u8; 4000];
+    (0..FRAMES)
+        .map(|i| {
+            let pcm: Vec<i16> = (0..FRAME)
+                .map(|n| {
+                    let t = (i * FRAME + n) as f32 / SAMPLE_RATE as f32;
+                    (TONE_AMPLITUDE * (2.0 * std::f32::consts::PI * TONE_HZ * t).sin()) as i16
+                })
+                .collect();
+            let len = encoder.encode(&pcm, &mut out).expect("opus encode");
+            Packet {
+                header: Header {
+                    version: 2,
+                    marker: i == 0,
+                    payload_type: 111,
+                    sequence_number: i as u16,
+                    timestamp: (i * FRAME) as u32,
+                    ssrc: 0x4c4d_4943,
+                    ..Default::default()
+                },
+                payload: out[..len].to_vec().into(),
+            }
+        })
+        .collect()
+}
+
+/// Relative power of `hz` in `samples` (Goertzel).
+fn power_at(samples: &[i16], hz: f32) -> f64 {
+    let w = 2.0 * std::f64::consts::PI * f64::from(hz) / SAMPLE_RATE as f64;
+    let coeff = 2.0 * w.cos();
+    let (mut s1, mut s2) = (0.0f64, 0.0f64);
+    for &x in samples {
+        let s0 = f64::from(x) + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+    s1 * s1 + s2 * s2 - coeff * s1 * s2
+}
+
+fn rms(samples: &[i16]) -> f64 {
+    let sum: f64 = samples.iter().map(|&s| f64::from(s).powi(2)).sum();
+    (sum / samples.len() as f64).sqrt()
+}
+
+/// Poll `f` until it returns `Some`, failing the test after [`TIMEOUT`].
+async fn wait_for<T>(what: &str, mut f: impl FnMut() -> Option<T>) -> T {
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        if let Some(value) = f() {
+            return value;
+        }
+        assert!(Instant::now() < deadline, "timed out waiting for {what}");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+/// Empty `input` into `samples` until `count` have arrived, then a little
+/// longer so extra samples would be caught too.
+async fn collect(input: &MixerInput, count: usize) -> Vec<i16> {
+    let mut samples = Vec::with_capacity(count);
+    let deadline = Instant::now() + TIMEOUT;
+    let mut settled = None;
+    loop {
+        // Mono senders fill both sides alike
+        while let Some([left, _]) = input.pop() {
+            samples.push(left);
+        }
+        if samples.len() >= count {
+            let since = *settled.get_or_insert_with(Instant::now);
+            if since.elapsed() > Duration::from_millis(300) {
+                return samples;
+            }
+        }
+        assert!(
+            Instant::now() < deadline,
+            "only {} of {count} samples arrived",
+            samples.len()
+        );
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+/// Options for a session with no ICE servers and no relay.
+fn session_options(allow_multiple_clients: bool, require_approval: bool) -> SessionOptions {
+    SessionOptions {
+        ice_servers: Vec::new(),
+        allow_multiple_clients,
+        opus_fmtp: QualityPreset::default().params().opus_fmtp(),
+        require_approval,
+        relay: None,
+        reconnect_grace: Duration::ZERO,
+        keepalive_timeout: Duration::from_secs(20),
+    }
+}
+
+fn new_mixer() -> Arc<Mixer> {
+    Arc::new(Mixer::new(QualityPreset::default().params().audio_tuning()))
+}
+
+/// Whether `shared` has logged a line containing `text`.
+fn logged(shared: &SharedStatus, text: &str) -> bool {
+    shared
+        .snapshot()
+        .log_lines
+        .iter()
+        .any(|l| l.message.contains(text))
+}
+
+/// A started receiver on a free loopback port.
+struct Receiver {
+    shared: SharedStatus,
+    port: u16,
+    mixer: Arc<Mixer>,
+    server: HttpServer,
+    session_cancel: CancellationToken,
+}
+
+impl Receiver {
+    async fn start() -> Self {
+        Self::start_with(false).await
+    }
+
+    async fn start_with(allow_multiple_clients: bool) -> Self {
+        Self::start_on(Ipv4Addr::LOCALHOST.into(), allow_multiple_clients).await
+    }
+
+    async fn start_on(ip: IpAddr, allow_multiple_clients: bool) -> Self {
+        isolate_profile();
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let shared = SharedStatus::default();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let controller = CoreController { tx: Arc::new(tx) };
+        let port = free_port();
+        let listen = SocketAddr::new(ip, port);
+        let server = start_http_server(listen.to_string(), shared.clone(), controller)
+            .await
+            .expect("start HTTP server");
+        // The listener is bound in the background
+        wait_for("the server to listen", || {
+            std::net::TcpStream::connect(listen).ok().map(drop)
+        })
+        .await;
+
+        let mixer = new_mixer();
+        let session_cancel = server
+            .activate(
+                Arc::clone(&mixer),
+                session_options(allow_multiple_clients, false),
+            )
+            .await;
+        Self {
+            shared,
+            port,
+            mixer,
+            server,
+            session_cancel,
+        }
+    }
+
+    async fn stop(self) {
+        self.session_cancel.cancel();
+        self.server.deactivate().await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn opus_tone_arrives_in_the_mixer_queue() {
+    let receiver = Receiver::start().await;
+    let (shared, port, mixer) = (
+        receiver.shared.clone(),
+        receiver.port,
+        Arc::clone(&receiver.mixer),
+    );
+
+    // The relay is this crate's own WebRTC sender
+    let feed = relay::RelayFeed::new();
+    let sender_cancel = CancellationToken::new();
+    relay::spawn(
+        &format!("127.0.0.1:{port}"),
+        feed.clone(),
+        Vec::new(),
+        shared.clone(),
+        sender_cancel.clone(),
+    );
+    wait_for("the sender to connect", || {
+        let status = shared.snapshot_without_logs();
+        (status.relay_state.as_deref() == Some("Connected")).then_some(())
+    })
+    .await;
+    let input = wait_for("the receiver to add a mixer input", || {
+        let status = shared.snapshot_without_logs();
+        status.clients.first().and_then(|c| mixer.input(c.id))
+    })
+    .await;
+
+    // Paced like a live sender, so nothing is dropped for arriving early
+    let packets = tone_packets();
+    let stream = tokio::spawn(async move {
+        let mut tick = tokio::time::interval(Duration::from_millis(20));
+        for packet in &packets {
+            tick.tick().await;
+            feed.forward(0, packet);
+        }
+    });
+    let samples = collect(&input, FRAMES * FRAME).await;
+    stream.await.unwrap();
+
+    assert_eq!(
+        samples.len(),
+        FRAMES * FRAME,
+        "one decoded frame per packet"
+    );
+    assert_eq!(shared.snapshot_without_logs().clients.len(), 1);
+
+    // Skip the codec's start-up, then expect the tone and nothing else
+    let steady = &samples[5 * FRAME..];
+    let expected_rms = f64::from(TONE_AMPLITUDE) / std::f64::consts::SQRT_2;
+    let level = rms(steady);
+    assert!(
+        (level - expected_rms).abs() < expected_rms * 0.15,
+        "RMS {level:.0}, expected about {expected_rms:.0}"
+    );
+    let tone = power_at(steady, TONE_HZ);
+    for other in [250.0, 2_000.0, 3_000.0] {
+        let ratio = tone / power_at(steady, other);
+        assert!(ratio > 100.0, "{other} Hz only {ratio:.1}x below the tone");
+    }
+
+    sender_cancel.cancel();
+    receiver.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn hello_without_a_version_is_rejected() {
+    let receiver = Receiver::start().await;
+    let mut ws = relay::connect(&format!("wss://127.0.0.1:{}/ws", receiver.port))
+        .await
+        .expect("connect");
+    ws.send(Message::Text(
+        r#"{"type":"hello","capabilities":["opus"]}"#.to_string(),
+    ))
+    .await
+    .expect("send hello");
+
+    let error = wait_for_message(&mut ws, "error").await;
+    assert_eq!(error["code"], "unsupported_version");
+    assert!(error["message"].as_str().unwrap().contains("v0"));
+    receiver.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn relay_connects_to_a_bracketed_ipv6_host() {
+    let receiver = Receiver::start_on(Ipv6Addr::LOCALHOST.into(), false).await;
+    let mut ws = relay::connect(&format!("wss://[::1]:{}/ws", receiver.port))
+        .await
+        .expect("connect");
+    ws.send(Message::Text(r#"{"type":"hello"}"#.to_string()))
+        .await
+        .expect("send hello");
+
+    wait_for_message(&mut ws, "error").await;
+    receiver.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn second_sender_waits_for_multiple_clients_mode() {
+    let receiver = Receiver::start().await;
+    let url = format!("wss://127.0.0.1:{}/ws", receiver.port);
+    let _first = relay::connect(&url).await.expect("connect");
+    wait_for("the first sender to be added", || {
+        (receiver.shared.snapshot_without_logs().clients.len() == 1).then_some(())
+    })
+    .await;
+
+    let mut second = relay::connect(&url).await.expect("connect");
+    let error = wait_for_message(&mut second, "error").await;
+    assert_eq!(error["code"], "already_connected");
+    assert_eq!(receiver.shared.snapshot_without_logs().clients.len(), 1);
+    receiver.stop().await;
+
+    // With multiple clients allowed, each gets its own row and mixer input
+    let receiver = Receiver::start_with(true).await;
+    let url = format!("wss://127.0.0.1:{}/ws", receiver.port);
+    let _first = relay::connect(&url).await.expect("connect");
+    let _second = relay::connect(&url).await.expect("connect");
+    let clients = wait_for("both senders to be added", || {
+        let clients = receiver.shared.snapshot_without_logs().clients;
+        (clients.len() == 2).then_some(clients)
+    })
+    .await;
+    for client in &clients {
+        assert!(receiver.mixer.input(client.id).is_some());
+    }
+    receiver.stop().await;
+}
+
+/// Connect to a PCM ingest with `options` and expect to be hung up on
+/// before sending a header.
+async fn assert_pcm_refused(shared: &SharedStatus, options: SessionOptions) {
+    let port = free_port();
+    let cancel = CancellationToken::new();
+    pcm::spawn(port, options, new_mixer(), shared.clone(), cancel.clone());
+    let mut stream = wait_for("the PCM listener", || {
+        std::net::TcpStream::connect(("127.0.0.1", port)).ok()
+    })
+    .await;
+    stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+    // Reset or closed, either way without waiting for a header
+    let _ = std::io::Read::read_to_end(&mut stream, &mut Vec::new());
+    assert!(logged(shared, "PCM ingest: rejected"));
+    cancel.cancel();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pcm_stream_is_refused_while_approval_is_required() {
+    isolate_profile();
+    let shared = SharedStatus::default();
+    assert_pcm_refused(&shared, session_options(true, true)).await;
+    assert_eq!(shared.client_count(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn second_pcm_stream_waits_for_multiple_clients_mode() {
+    isolate_profile();
+    let shared = SharedStatus::default();
+    shared.add_client(u64::MAX, "127.0.0.1".to_string());
+    assert_pcm_refused(&shared, session_options(false, false)).await;
+    assert_eq!(shared.client_count(), 1);
+}
+
+/// Send to a UDP ingest with `options` until it logs refusing the sender.
+async fn assert_udp_refused(shared: &SharedStatus, options: SessionOptions) {
+    let port = free_port();
+    let cancel = CancellationToken::new();
+    let ingest = UdpIngest {
+        port,
+        format: UdpFormat::PcmMono,
+    };
+    udp_session::spawn(ingest, options, new_mixer(), shared.clone(), cancel.clone());
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    // Length-prefixed 20 ms of silence, resent until the listener is up
+    let mut datagram = 1_920u16.to_be_bytes().to_vec();
+    datagram.resize(2 + 1_920, 0);
+    wait_for("the UDP sender to be refused", || {
+        socket.send_to(&datagram, ("127.0.0.1", port)).unwrap();
+        logged(shared, "UDP ingest: rejected").then_some(())
+    })
+    .await;
+    cancel.cancel();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn udp_sender_is_refused_while_approval_is_required() {
+    isolate_profile();
+    let shared = SharedStatus::default();
+    assert_udp_refused(&shared, session_options(true, true)).await;
+    assert_eq!(shared.client_count(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn second_udp_sender_waits_for_multiple_clients_mode() {
+    isolate_profile();
+    let shared = SharedStatus::default();
+    shared.add_client(u64::MAX, "127.0.0.1".to_string());
+    assert_udp_refused(&shared, session_options(false, false)).await;
+    assert_eq!(shared.client_count(), 1);
+}
+
+/// Send RTP to a listener with `options` until it logs refusing the sender.
+async fn assert_rtp_refused(shared: &SharedStatus, options: SessionOptions) {
+    let port = free_port();
+    let cancel = CancellationToken::new();
+    rtp::spawn(port, options, new_mixer(), shared.clone(), cancel.clone());
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let datagram = tone_packets()[0].marshal().unwrap();
+    wait_for("the RTP sender to be refused", || {
+        socket.send_to(&datagram, ("127.0.0.1", port)).unwrap();
+        logged(shared, "RTP listener: rejected").then_some(())
+    })
+    .await;
+    cancel.cancel();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rtp_sender_is_refused_while_approval_is_required() {
+    isolate_profile();
+    let shared = SharedStatus::default();
+    assert_rtp_refused(&shared, session_options(true, true)).await;
+    assert_eq!(shared.client_count(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn second_rtp_sender_waits_for_multiple_clients_mode() {
+    isolate_profile();
+    let shared = SharedStatus::default();
+    shared.add_client(u64::MAX, "127.0.0.1".to_string());
+    assert_rtp_refused(&shared, session_options(false, false)).await;
+    assert_eq!(shared.client_count(), 1);
+}
+
+/// Read signaling messages until one of type `kind` arrives.
+async fn wait_for_message(
+    ws: &mut (impl StreamExt<Item = tokio_tungstenite::tungstenite::Result<Message>> + Unpin),
+    kind: &str,
+) -> serde_json::Value {
+    let read = async {
+        while let Some(message) = ws.next().await {
+            if let Ok(Message::Text(text)) = message {
+                let value: serde_json::Value = serde_json::from_str(&text).expect("JSON");
+                if value["type"] == kind {
+                    return value;
+                }
+            }
+        }
+        panic!("socket closed before a {kind} message");
+    };
+    tokio::time::timeout(TIMEOUT, read)
+        .await
+        .unwrap_or_else(|_| panic!("timed out waiting for a {kind} message"))
+}
@@ -0,0 +1,383 @@
+use super::{ingest_refusal, recv_datagram, IngestClient, SessionOptions};
+use crate::audio::convert;
+use crate::audio::mixer::{Mixer, MixerInput};
+use crate::audio::vad::VoiceActivityDetector;
+use crate::core::SharedStatus;
+use anyhow::{anyhow, bail, Result};
+use opus::{Channels, Decoder as OpusDecoder};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::{Entry, HashMap};
+use std::fmt;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tokio_util::sync::CancellationToken;
+
+/// Largest datagram accepted.
+const MAX_DATAGRAM: usize = 65_507;
+
+/// Buffer for the largest Opus frame (120 ms @ 48 kHz, mono).
+const MAX_OPUS_SAMPLES: usize = 5_760;
+
+/// A sender that stays silent this long is treated as disconnected.
+const SENDER_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// What the packets in each datagram carry. Always 48 kHz, since the mixer
+/// runs at that rate and nothing is resampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UdpFormat {
+    /// One Opus packet (mono or stereo, mixed down).
+    #[default]
+    Opus,
+    /// Signed 16-bit little-endian samples.
+    PcmMono,
+    /// Interleaved signed 16-bit little-endian samples, mixed down.
+    PcmStereo,
+}
+
+impl UdpFormat {
+    pub const ALL: [UdpFormat; 3] = [UdpFormat::Opus, UdpFormat::PcmMono, UdpFormat::PcmStereo];
+}
+
+impl fmt::Display for UdpFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            UdpFormat::Opus => "Opus",
+            UdpFormat::PcmMono => "PCM 16-bit mono",
+            UdpFormat::PcmStereo => "PCM 16-bit stereo",
+        })
+    }
+}
+
+/// Raw UDP ingest for senders that can't do WebRTC, such as
+/// microcontrollers.
+///
+/// Each datagram holds one or more packets, each a big-endian `u16` length
+/// followed by that many bytes in the session's [`UdpFormat`]. Every
+/// source address becomes its own sender in the client list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UdpIngest {
+    pub port: u16,
+    pub format: UdpFormat,
+}
+
+/// Receive on the configured port into `mixer` until `cancel` fires. The
+/// socket is closed as soon as the task ends, so a restarted session can
+/// bind the port again straight away. New senders are subject to the
+/// session's approval and multiple-sender settings.
+pub fn spawn(
+    ingest: UdpIngest,
+    options: SessionOptions,
+    mixer: Arc<Mixer>,
+    shared: SharedStatus,
+    cancel: CancellationToken,
+) {
+    tokio::spawn(async move {
+        let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, ingest.port)).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                shared.log_line(format!(
+                    "UDP ingest: cannot listen on port {}: {e}",
+                    ingest.port
+                ));
+                return;
+            }
+        };
+        shared.log_line(format!(
+            "UDP ingest ({}) on port {}",
+            ingest.format, ingest.port
+        ));
+        receive_loop(&socket, ingest.format, &options, &mixer, &shared, &cancel).await;
+    });
+}
+
+async fn receive_loop(
+    socket: &UdpSocket,
+    format: UdpFormat,
+    options: &SessionOptions,
+    mixer: &Mixer,
+    shared: &SharedStatus,
+    cancel: &CancellationToken,
+) {
+    let mut senders: HashMap<SocketAddr, UdpSender> = HashMap::new();
+    // Refused sources and when they last sent, so each is logged once
+    let mut refused: HashMap<SocketAddr, Instant> = HashMap::new();
+    let mut buf = vec![0u8; MAX_DATAGRAM];
+    let mut expiry = tokio::time::interval(Duration::from_secs(1));
+
+    loop {
+        let (n, peer) = tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = expiry.tick() => {
+                senders.retain(|_, sender| {
+                    let alive = sender.last_seen.elapsed() < SENDER_TIMEOUT;
+                    if !alive {
+                        sender.disconnect(mixer, shared);
+                    }
+                    alive
+                });
+                refused.retain(|_, last_seen| last_seen.elapsed() < SENDER_TIMEOUT);
+                continue;
+            }
+            received = recv_datagram(socket, &mut buf, "UDP ingest") => received,
+        };
+        if !shared.is_ip_allowed(peer.ip()) {
+            continue;
+        }
+        let sender = match senders.entry(peer) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                if let Some(reason) = ingest_refusal(options, shared) {
+                    if refused.insert(peer, Instant::now()).is_none() {
+                        shared.log_line(format!("UDP ingest: rejected {peer}: {reason}."));
+                    }
+                    continue;
+                }
+                refused.remove(&peer);
+                match UdpSender::connect(peer, format, mixer, shared) {
+                    Ok(sender) => e.insert(sender),
+                    Err(e) => {
+                        log::warn!("UDP ingest: {e:#}");
+                        continue;
+                    }
+                }
+            }
+        };
+        sender.receive(&buf[..n], shared);
+    }
+    for sender in senders.values() {
+        sender.disconnect(mixer, shared);
+    }
+}
+
+/// Per-source state: its mixer input and decoder.
+struct UdpSender {
+    client: IngestClient,
+    input: Arc<MixerInput>,
+    format: UdpFormat,
+    decoder: Option<OpusDecoder>,
+    vad: VoiceActivityDetector,
+    /// Opus decoder output.
+    decoded: Vec<i16>,
+    /// PCM payload before downmixing.
+    samples: Vec<i16>,
+    mono: Vec<i16>,
+    last_seen: Instant,
+    /// Malformed packets received; only the first is logged.
+    malformed: u64,
+}
+
+impl UdpSender {
+    fn connect(
+        peer: SocketAddr,
+        format: UdpFormat,
+        mixer: &Mixer,
+        shared: &SharedStatus,
+    ) -> Result<Self> {
+        let decoder = match format {
+            UdpFormat::Opus => Some(
+                OpusDecoder::new(48_000, Channels::Mono)
+                    .map_err(|e| anyhow!("opus decoder init: {e:?}"))?,
+            ),
+            UdpFormat::PcmMono | UdpFormat::PcmStereo => None,
+        };
+
+        let (client, input) = IngestClient::connect(format!("{} (UDP)", peer.ip()), mixer, shared);
+        shared.log_line(format!(
+            "UDP client #{} started sending from {peer}",
+            client.id
+        ));
+
+        Ok(Self {
+            client,
+            input,
+            format,
+            decoder,
+            vad: VoiceActivityDetector::new(),
+            decoded: vec![0; MAX_OPUS_SAMPLES],
+            samples: Vec::new(),
+            mono: Vec::with_capacity(MAX_OPUS_SAMPLES),
+            last_seen: Instant::now(),
+            malformed: 0,
+        })
+    }
+
+    /// Queue every packet in `datagram`.
+    fn receive(&mut self, datagram: &[u8], shared: &SharedStatus) {
+        self.last_seen = Instant::now();
+        shared.record_audio_packet(self.client.id, datagram.len(), 0.0);
+
+        let mut rest = datagram;
+        while !rest.is_empty() {
+            let res = match rest.split_first_chunk::<2>() {
+                Some((len, tail)) if tail.len() >= usize::from(u16::from_be_bytes(*len)) => {
+                    let (payload, next) = tail.split_at(usize::from(u16::from_be_bytes(*len)));
+                    rest = next;
+                    self.decode(payload)
+                }
+                // The rest of the datagram can't be framed
+                _ => {
+                    rest = &[];
+                    Err(anyhow!("truncated packet"))
+                }
+            };
+            if let Err(e) = res {
+                self.malformed += 1;
+                if self.malformed == 1 {
+                    shared.log_line(format!(
+                        "UDP client #{}: bad packet ({e:#}); further ones are dropped silently",
+                        self.client.id
+                    ));
+                }
+                continue;
+            }
+            self.queue(shared);
+        }
+    }
+
+    /// Decode one packet into `self.mono`.
+    fn decode(&mut self, payload: &[u8]) -> Result<()> {
+        self.mono.clear();
+        match (&mut self.decoder, self.format) {
+            (Some(decoder), _) => {
+                let n = decoder
+                    .decode(payload, &mut self.decoded, false)
+                    .map_err(|e| anyhow!("opus decode: {e:?}"))?;
+                self.mono.extend_from_slice(&self.decoded[..n]);
+            }
+            (None, format) => {
+                let frame_bytes = if format == UdpFormat::PcmStereo { 4 } else { 2 };
+                if !payload.len().is_multiple_of(frame_bytes) {
+                    bail!("{} bytes is not a whole number of frames", payload.len());
+                }
+                self.samples.clear();
+                self.samples.extend(
+                    payload
+                        .chunks_exact(2)
+                        .map(|b| i16::from_le_bytes([b[0], b[1]])),
+                );
+                if format == UdpFormat::PcmStereo {
+                    convert::downmix_stereo(&self.samples, &mut self.mono);
+                } else {
+                    self.mono.extend_from_slice(&self.samples);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn queue(&mut self, shared: &SharedStatus) {
+        shared.record_level(self.client.id, &self.mono);
+        if let Some(talking) = self.vad.process(&self.mono) {
+            self.input.set_talking(talking);
+            shared.mark_voice_activity(self.client.id, talking);
+        }
+        // Raw stereo PCM is queued as sent; everything else is mono
+        let overflowed = if self.format == UdpFormat::PcmStereo {
+            self.input.push_frame(&mut self.samples, 2)
+        } else {
+            self.input.push_frame(&mut self.mono, 1)
+        };
+        if overflowed > 0 {
+            shared.bump_dropped_samples(overflowed);
+        }
+    }
+
+    fn disconnect(&self, mixer: &Mixer, shared: &SharedStatus) {
+        self.client.disconnect(mixer, shared, None, None);
+        shared.log_line(format!("UDP client #{} stopped sending.", self.client.id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::Frame;
+    use crate::core::quality::QualityPreset;
+
+    /// A PCM sender that isn't in the client list.
+    fn sender(format: UdpFormat) -> UdpSender {
+        let mixer = Mixer::new(QualityPreset::default().params().audio_tuning());
+        UdpSender {
+            client: IngestClient {
+                id: 1,
+                addr: "127.0.0.1 (UDP)".to_string(),
+                started_at: 0,
+            },
+            input: mixer.add_input(1),
+            format,
+            decoder: None,
+            vad: VoiceActivityDetector::new(),
+            decoded: Vec::new(),
+            samples: Vec::new(),
+            mono: Vec::new(),
+            last_seen: Instant::now(),
+            malformed: 0,
+        }
+    }
+
+    /// `payload` with its length prefix.
+    fn framed(payload: &[u8]) -> Vec<u8> {
+        let mut packet = (payload.len() as u16).to_be_bytes().to_vec();
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    fn queued(sender: &UdpSender) -> Vec<Frame> {
+        std::iter::from_fn(|| sender.input.pop()).collect()
+    }
+
+    #[test]
+    fn every_packet_in_a_datagram_is_queued() {
+        let mut sender = sender(UdpFormat::PcmMono);
+        let mut datagram = framed(&[1, 0, 2, 0]);
+        datagram.extend(framed(&[3, 0]));
+        sender.receive(&datagram, &SharedStatus::default());
+
+        assert_eq!(queued(&sender), [[1, 1], [2, 2], [3, 3]]);
+        assert_eq!(sender.malformed, 0);
+    }
+
+    #[test]
+    fn stereo_packets_keep_both_sides() {
+        let mut sender = sender(UdpFormat::PcmStereo);
+        sender.receive(&framed(&[1, 0, 2, 0, 3, 0, 4, 0]), &SharedStatus::default());
+
+        assert_eq!(queued(&sender), [[1, 2], [3, 4]]);
+    }
+
+    #[test]
+    fn truncated_prefix_drops_the_rest_of_the_datagram() {
+        let shared = SharedStatus::default();
+        let mut sender = sender(UdpFormat::PcmMono);
+        // One byte can't hold a length
+        let mut datagram = framed(&[1, 0]);
+        datagram.push(0);
+        sender.receive(&datagram, &shared);
+        assert_eq!(queued(&sender), [[1, 1]]);
+        assert_eq!(sender.malformed, 1);
+
+        // The length promises more than the datagram holds
+        sender.receive(&[0, 4, 1, 0], &shared);
+        assert!(queued(&sender).is_empty());
+        assert_eq!(sender.malformed, 2);
+    }
+
+    #[test]
+    fn partial_pcm_frames_are_dropped() {
+        let shared = SharedStatus::default();
+        let mut mono = sender(UdpFormat::PcmMono);
+        let mut datagram = framed(&[1, 0, 2]);
+        datagram.extend(framed(&[3, 0]));
+        mono.receive(&datagram, &shared);
+        // The bad packet is skipped, the next one still plays
+        assert_eq!(queued(&mono), [[3, 3]]);
+        assert_eq!(mono.malformed, 1);
+
+        let mut stereo = sender(UdpFormat::PcmStereo);
+        stereo.receive(&framed(&[1, 0, 2, 0, 3, 0]), &shared);
+        assert!(queued(&stereo).is_empty());
+        assert_eq!(stereo.malformed, 1);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::config_store;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A certificate chain and private key in PEM files chosen by the user,
+/// e.g. issued by their own CA that the phones already trust.
+///
+/// Saved as `certificate.json` in the settings directory:
+/// `{"cert_path": "/path/fullchain.pem", "key_path": "/path/key.pem"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserCertificate {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+impl UserCertificate {
+    /// Read both files, returning `(chain_pem, key_pem)`.
+    pub fn read(&self) -> Result<(String, String)> {
+        let chain_pem = std::fs::read_to_string(&self.cert_path)
+            .with_context(|| format!("read {}", self.cert_path.display()))?;
+        let key_pem = std::fs::read_to_string(&self.key_path)
+            .with_context(|| format!("read {}", self.key_path.display()))?;
+        Ok((chain_pem, key_pem))
+    }
+}
+
+const STORE: &str = "certificate.json";
+
+/// The configured certificate, if any; an unreadable file means none.
+pub fn load() -> Option<UserCertificate> {
+    config_store::load(STORE)
+}
+
+/// Remember `cert` for future launches; `None` goes back to the generated
+/// self-signed certificate.
+pub fn save(cert: Option<&UserCertificate>) -> Result<()> {
+    match cert {
+        Some(cert) => config_store::save(STORE, cert),
+        None => config_store::remove(STORE),
+    }
+}
@@ -1,75 +1,304 @@
+use super::decode::{AudioCodec, DecodeThread, ReorderBuffer};
+use super::relay::RelayFeed;
+use super::SessionOptions;
+use crate::audio::mixer::{ClientId, MixerInput};
+use crate::core::stats::InterarrivalJitter;
 use crate::core::SharedStatus;
 use anyhow::{anyhow, Result};
 use axum::extract::ws::{Message, WebSocket};
-use crossbeam_queue::ArrayQueue;
-use opus::{Channels, Decoder as OpusDecoder};
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
 use webrtc::api::interceptor_registry::register_default_interceptors;
-use webrtc::api::media_engine::MediaEngine;
+use webrtc::api::media_engine::{MediaEngine, MIME_TYPE_OPUS, MIME_TYPE_PCMA, MIME_TYPE_PCMU};
 use webrtc::api::APIBuilder;
 use webrtc::ice_transport::ice_candidate::{RTCIceCandidate, RTCIceCandidateInit};
-use webrtc::ice_transport::ice_server::RTCIceServer;
 use webrtc::interceptor::registry::Registry;
 use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::offer_answer_options::RTCOfferOptions;
 use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
 use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
-use webrtc::rtp_transceiver::rtp_codec::RTPCodecType;
+use webrtc::rtp::packet::Packet;
+use webrtc::rtp_transceiver::rtp_codec::{
+    RTCRtpCodecCapability, RTCRtpCodecParameters, RTPCodecType,
+};
 use webrtc::rtp_transceiver::rtp_transceiver_direction::RTCRtpTransceiverDirection;
 use webrtc::rtp_transceiver::RTCRtpTransceiverInit;
+use webrtc::stats::{StatsReport, StatsReportType};
 
 // ---------------------------------------------------------------------------
 // Signaling message format — matches the iOS sender's flat JSON schema:
 //   SDP:  {"type":"offer"|"answer", "sdp":"v=0..."}
 //   ICE:  {"type":"ice", "candidate":"...", "sdpMid":"0", "sdpMLineIndex":0}
+// Senders that negotiate the `lanmic.msgpack` subprotocol get the same maps
+// encoded as MessagePack in binary frames.
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
-struct SignalMessage {
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(super) struct SignalMessage {
     #[serde(rename = "type")]
-    msg_type: String,
+    pub(super) msg_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    sdp: Option<String>,
+    pub(super) sdp: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    candidate: Option<String>,
+    pub(super) candidate: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "sdpMid")]
-    sdp_mid: Option<String>,
+    pub(super) sdp_mid: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "sdpMLineIndex")]
-    sdp_mline_index: Option<i32>,
+    pub(super) sdp_mline_index: Option<i32>,
+    /// Human-readable text for `diagnostic` and `error` messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) message: Option<String>,
+    /// Machine-readable [`SessionError`] code for `error` messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) code: Option<String>,
+    /// Protocol version, sent in `hello`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) version: Option<u32>,
+    /// Optional features supported by the side sending `hello`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) capabilities: Option<Vec<String>>,
+    /// Stable random ID the sender keeps across sessions, sent in `hello`.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "deviceId")]
+    pub(super) device_id: Option<String>,
+    /// Friendly sender name for the approval prompt, sent in `hello`.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "deviceName"
+    )]
+    pub(super) device_name: Option<String>,
+    /// Secret the sender passes as `/ws?resume=…` to pick its session back
+    /// up after the socket drops, sent in `hello`.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "sessionId")]
+    pub(super) session_id: Option<String>,
+}
+
+/// Signaling protocol spoken by this receiver. Bump when message semantics
+/// change; optional features get a capability string instead.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Oldest sender protocol still accepted. Senders that predate the `hello`
+/// handshake are never asked; a `hello` without a version counts as 0.
+const MIN_SENDER_VERSION: u32 = 1;
+
+/// Optional features this receiver supports, advertised in `hello`.
+pub const CAPABILITIES: &[&str] = &["opus", "msgpack", "diagnostic", "resume"];
+
+/// Why a session was rejected or failed. Sent to the sender as
+/// `{"type":"error","code":"...","message":"..."}` before the socket closes,
+/// so it can show a reason instead of a bare disconnect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionError {
+    NotStarted,
+    AlreadyConnected,
+    UnsupportedVersion,
+    CodecMismatch,
+    NegotiationFailed,
+    Unauthorized,
+}
+
+impl SessionError {
+    pub fn code(self) -> &'static str {
+        match self {
+            SessionError::NotStarted => "not_started",
+            SessionError::AlreadyConnected => "already_connected",
+            SessionError::UnsupportedVersion => "unsupported_version",
+            SessionError::CodecMismatch => "codec_mismatch",
+            SessionError::NegotiationFailed => "negotiation_failed",
+            SessionError::Unauthorized => "unauthorized",
+        }
+    }
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SessionError::NotStarted => "The receiver is not started. Press START on the receiver.",
+            SessionError::AlreadyConnected => {
+                "Another sender is already connected. Enable multiple senders on the receiver \
+                 or disconnect the other device."
+            }
+            SessionError::UnsupportedVersion => "This sender is too old. Please update the app.",
+            SessionError::CodecMismatch => {
+                "The sender does not offer a supported audio codec (Opus, PCMU, PCMA or L16)."
+            }
+            SessionError::NegotiationFailed => "The audio session could not be negotiated.",
+            SessionError::Unauthorized => "The receiver did not allow this device.",
+        })
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+/// Send a typed error to the sender and close the socket.
+pub async fn send_error(
+    socket: &mut WebSocket,
+    encoding: SignalEncoding,
+    error: SessionError,
+    detail: Option<String>,
+) {
+    let msg = SignalMessage {
+        msg_type: "error".to_string(),
+        code: Some(error.code().to_string()),
+        message: Some(detail.unwrap_or_else(|| error.to_string())),
+        ..Default::default()
+    };
+    if let Ok(frame) = encoding.encode(&msg) {
+        let _ = socket.send(frame).await;
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+/// Wire encoding for signaling messages, negotiated via the WebSocket
+/// subprotocol. Browsers stay on JSON; constrained native senders can ask
+/// for MessagePack to save bytes and parse time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalEncoding {
+    Json,
+    MessagePack,
+}
+
+impl SignalEncoding {
+    pub const JSON_PROTOCOL: &'static str = "lanmic.json";
+    pub const MSGPACK_PROTOCOL: &'static str = "lanmic.msgpack";
+
+    /// Encoding for the subprotocol the server selected (JSON if none).
+    pub fn from_protocol(protocol: Option<&str>) -> Self {
+        match protocol {
+            Some(Self::MSGPACK_PROTOCOL) => SignalEncoding::MessagePack,
+            _ => SignalEncoding::Json,
+        }
+    }
+
+    fn encode(self, msg: &SignalMessage) -> Result<Message> {
+        Ok(match self {
+            SignalEncoding::Json => Message::Text(serde_json::to_string(msg)?),
+            // Named fields keep the map layout identical to the JSON schema
+            SignalEncoding::MessagePack => Message::Binary(rmp_serde::to_vec_named(msg)?),
+        })
+    }
+
+    /// Decode by frame type, so a sender may mix both during migration.
+    /// Returns `None` for frames that carry no signaling.
+    fn decode(msg: &Message) -> Option<Result<SignalMessage>> {
+        match msg {
+            Message::Text(txt) => Some(serde_json::from_str(txt).map_err(Into::into)),
+            Message::Binary(bytes) => Some(rmp_serde::from_slice(bytes).map_err(Into::into)),
+            _ => None,
+        }
+    }
 }
 
 /// Maximum outbound signaling messages before backpressure.
 const SIGNAL_CHANNEL_SIZE: usize = 64;
 
+/// How long signaling may succeed without a media path before we diagnose
+/// the session. Browsers take ~30 s to declare ICE failed on their own.
+const ICE_DIAGNOSIS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How long to wait for the sender's `hello`, which carries its device ID.
+const HELLO_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How long a sender may wait for the user to answer an approval prompt.
+const APPROVAL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// How often per-client RTP reordering counters are logged (when non-zero).
+const REORDER_REPORT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How often bitrate and loss are pulled from the peer connection's stats.
+const LINK_STATS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// A sender's new WebSocket, handed to its waiting session by
+/// `/ws?resume=…`.
+pub struct Resume {
+    pub socket: WebSocket,
+    pub encoding: SignalEncoding,
+    pub peer: String,
+}
+
+/// Sessions a sender can resume, by the `sessionId` sent in `hello`.
+pub type ResumeRegistry = Arc<Mutex<HashMap<String, mpsc::Sender<Resume>>>>;
+
+/// Removes a session from the [`ResumeRegistry`] however it ends.
+struct Resumable<'a> {
+    registry: &'a ResumeRegistry,
+    id: String,
+}
+
+impl Drop for Resumable<'_> {
+    fn drop(&mut self) {
+        self.registry.lock().remove(&self.id);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
     mut socket: WebSocket,
-    queue: Arc<ArrayQueue<i16>>,
-    use_stun: bool,
+    mut encoding: SignalEncoding,
+    peer: &str,
+    input: Arc<MixerInput>,
+    options: &SessionOptions,
     shared: SharedStatus,
     server_cancel: CancellationToken,
+    resumable: &ResumeRegistry,
 ) -> Result<()> {
+    // Offer resumption only when there is a grace period to resume in
+    let (resume_tx, mut resume_rx) = mpsc::channel::<Resume>(1);
+    let registration = (!options.reconnect_grace.is_zero()).then(|| {
+        let id = format!("{:032x}", rand::random::<u128>());
+        resumable.lock().insert(id.clone(), resume_tx);
+        Resumable {
+            registry: resumable,
+            id,
+        }
+    });
+    let session_id = registration.as_ref().map(|r| r.id.clone());
+
+    // --- Version/capability handshake — always the first message ---
+    socket
+        .send(encoding.encode(&hello(session_id.clone()))?)
+        .await
+        .map_err(|e| anyhow!("Failed to send hello over WebSocket: {e}"))?;
+
+    if options.require_approval
+        && !approve_sender(
+            &mut socket,
+            encoding,
+            input.id,
+            peer,
+            &shared,
+            &server_cancel,
+        )
+        .await?
+    {
+        return Ok(());
+    }
+
     let (out_tx, mut out_rx) = mpsc::channel::<SignalMessage>(SIGNAL_CHANNEL_SIZE);
     let cancel_token = CancellationToken::new();
 
     let pc = create_peer_connection(
-        use_stun,
+        options,
         shared.clone(),
-        queue.clone(),
+        input.clone(),
         out_tx.clone(),
         cancel_token.clone(),
     )
     .await?;
-    shared.set_pc_state(Some("created".into()));
+    shared.set_pc_state(input.id, Some("created".into()));
 
     // --- Create SDP offer and send to sender ---
     let offer = pc.create_offer(None).await?;
     pc.set_local_description(offer).await?;
 
     if let Some(local_desc) = pc.local_description().await {
+        shared.set_local_sdp(local_desc.sdp.clone());
         shared.log_line(format!(
             "Created SDP offer ({:?}), sending to sender",
             local_desc.sdp_type
@@ -77,13 +306,10 @@ pub async fn run(
         let msg = SignalMessage {
             msg_type: "offer".to_string(),
             sdp: Some(local_desc.sdp),
-            candidate: None,
-            sdp_mid: None,
-            sdp_mline_index: None,
+            ..Default::default()
         };
-        let txt = serde_json::to_string(&msg)?;
         socket
-            .send(Message::Text(txt))
+            .send(encoding.encode(&msg)?)
             .await
             .map_err(|e| anyhow!("Failed to send offer over WebSocket: {e}"))?;
     }
@@ -92,36 +318,84 @@ pub async fn run(
     let pending_ice: Arc<tokio::sync::Mutex<Vec<RTCIceCandidateInit>>> =
         Arc::new(tokio::sync::Mutex::new(Vec::new()));
 
+    // Reachability watchdog — see `diagnose_unreachable`
+    let ice_deadline = tokio::time::sleep(ICE_DIAGNOSIS_TIMEOUT);
+    tokio::pin!(ice_deadline);
+    let mut diagnosed = false;
+    let mut remote_candidates = 0usize;
+
+    let mut link_stats = LinkStats::default();
+    let mut link_stats_tick = tokio::time::interval(LINK_STATS_INTERVAL);
+
+    // Keepalive — a half-open socket never errors, so ping it and count
+    // any frame from the sender (pongs included) as a sign of life
+    let mut keepalive_tick = tokio::time::interval(options.keepalive_timeout / 4);
+    let mut last_heard = Instant::now();
+
     loop {
-        tokio::select! {
+        // A new socket from the same sender, after its old one dropped or
+        // while that one is still half-open
+        let resumed = tokio::select! {
             // Inbound WebSocket messages
-            msg = socket.recv() => {
-                let msg = match msg {
-                    Some(Ok(m)) => m,
-                    Some(Err(_)) | None => break,
-                };
-
-                match msg {
-                    Message::Text(txt) => {
-                        match serde_json::from_str::<SignalMessage>(&txt) {
-                            Ok(signal) => {
-                                if signal.msg_type == "bye" {
-                                    shared.log_line("Received bye from sender — stopping.");
+            msg = socket.recv() => match msg {
+                Some(Ok(msg)) => {
+                    last_heard = Instant::now();
+                    if let Message::Close(_) = msg {
+                        break;
+                    }
+                    match SignalEncoding::decode(&msg) {
+                        Some(Ok(signal)) => {
+                            if signal.msg_type == "bye" {
+                                shared.log_line("Received bye from sender — stopping.");
+                                break;
+                            }
+                            if signal.msg_type == "hello" {
+                                if !check_sender_hello(&mut socket, encoding, input.id, &signal, &shared).await {
                                     break;
                                 }
-                                handle_signal_message(
-                                    &signal, &pc, &out_tx, &pending_ice, &shared,
-                                ).await?;
+                                continue;
+                            }
+                            if signal.msg_type == "ice" {
+                                remote_candidates += 1;
                             }
-                            Err(e) => {
-                                shared.log_line(format!("Bad signaling message: {e}"));
+                            if let Err(e) = handle_signal_message(
+                                &signal, &pc, &out_tx, &pending_ice, &shared,
+                            ).await {
+                                let error = e
+                                    .downcast_ref::<SessionError>()
+                                    .copied()
+                                    .unwrap_or(SessionError::NegotiationFailed);
+                                send_error(&mut socket, encoding, error, None).await;
+                                cancel_token.cancel();
+                                let _ = pc.close().await;
+                                return Err(e);
                             }
                         }
+                        Some(Err(e)) => {
+                            shared.log_line(format!("Bad signaling message: {e}"));
+                        }
+                        None => {}
                     }
-                    Message::Close(_) => break,
-                    _ => {}
+                    None
                 }
-            }
+                // Dropped without a `bye`: the sender may come back
+                Some(Err(_)) | None => {
+                    let resumed = wait_for_resume(
+                        input.id,
+                        &mut resume_rx,
+                        options.reconnect_grace,
+                        &shared,
+                        &server_cancel,
+                    )
+                    .await;
+                    if resumed.is_none() {
+                        break;
+                    }
+                    resumed
+                }
+            },
+
+            resumed = resume_rx.recv() => resumed,
 
             // Outbound WebSocket messages (ICE candidates, SDP answers)
             out = out_rx.recv() => {
@@ -129,11 +403,60 @@ pub async fn run(
                     Some(m) => m,
                     None => break,
                 };
-                let txt = serde_json::to_string(&out)?;
-                if let Err(e) = socket.send(Message::Text(txt)).await {
+                if let Err(e) = socket.send(encoding.encode(&out)?).await {
                     log::warn!("WebSocket send failed: {e}");
                     break;
                 }
+                None
+            }
+
+            // Signaling worked but media never connected
+            _ = &mut ice_deadline, if !diagnosed => {
+                diagnosed = true;
+                if pc.connection_state() != RTCPeerConnectionState::Connected {
+                    let has_answer = pc.remote_description().await.is_some();
+                    let hint = diagnose_unreachable(has_answer, remote_candidates);
+                    shared.log_line(format!("Diagnosis: {hint}"));
+                    let _ = out_tx.try_send(SignalMessage {
+                        msg_type: "diagnostic".to_string(),
+                        message: Some(hint.to_string()),
+                        ..Default::default()
+                    });
+                }
+                None
+            }
+
+            _ = keepalive_tick.tick() => {
+                if last_heard.elapsed() < options.keepalive_timeout {
+                    if let Err(e) = socket.send(Message::Ping(Vec::new())).await {
+                        log::warn!("WebSocket ping failed: {e}");
+                    }
+                    None
+                } else {
+                    shared.log_line(format!(
+                        "WebRTC client #{} stopped answering pings for {} s — connection is stale.",
+                        input.id,
+                        options.keepalive_timeout.as_secs(),
+                    ));
+                    let resumed = wait_for_resume(
+                        input.id,
+                        &mut resume_rx,
+                        options.reconnect_grace,
+                        &shared,
+                        &server_cancel,
+                    )
+                    .await;
+                    if resumed.is_none() {
+                        break;
+                    }
+                    resumed
+                }
+            }
+
+            _ = link_stats_tick.tick() => {
+                let (bitrate_kbps, loss_pct) = link_stats.update(&pc.get_stats().await);
+                shared.record_link_stats(input.id, bitrate_kbps, loss_pct);
+                None
             }
 
             // Server shutdown — receiver clicked STOP
@@ -141,6 +464,30 @@ pub async fn run(
                 shared.log_line("Server shutting down — stopping session.");
                 break;
             }
+        };
+
+        if let Some(resumed) = resumed {
+            if let Err(e) = resume_session(
+                &pc,
+                &mut socket,
+                &mut encoding,
+                resumed,
+                session_id.clone(),
+                input.id,
+                &shared,
+            )
+            .await
+            {
+                shared.log_line(format!("Failed to resume session: {e}"));
+                break;
+            }
+            // Give the restarted ICE as long to connect as the first
+            ice_deadline
+                .as_mut()
+                .reset(tokio::time::Instant::now() + ICE_DIAGNOSIS_TIMEOUT);
+            diagnosed = false;
+            remote_candidates = 0;
+            last_heard = Instant::now();
         }
     }
 
@@ -152,6 +499,293 @@ pub async fn run(
     Ok(())
 }
 
+/// Turns the cumulative receive counters in webrtc-rs' stats into the
+/// current bitrate and packet loss.
+#[derive(Default)]
+struct LinkStats {
+    /// Payload bytes received at the previous pull.
+    bytes: Option<(Instant, u64)>,
+    /// Sender reports seen, and packets the sender had sent versus packets
+    /// received when the last one arrived.
+    report: Option<(u64, u64, u64)>,
+    loss_pct: Option<f32>,
+}
+
+impl LinkStats {
+    /// Returns `(bitrate_kbps, loss_pct)`. Loss is measured between RTCP
+    /// sender reports and stays `None` until two have arrived.
+    fn update(&mut self, report: &StatsReport) -> (f32, Option<f32>) {
+        let (mut bytes, mut received) = (0, 0);
+        let (mut reports, mut sent) = (0, 0);
+        for stats in report.reports.values() {
+            match stats {
+                StatsReportType::InboundRTP(s) if s.kind == "audio" => {
+                    bytes += s.bytes_received;
+                    received += s.packets_received;
+                }
+                StatsReportType::RemoteOutboundRTP(s) if s.kind == "audio" => {
+                    reports += s.reports_sent;
+                    sent += s.packets_sent;
+                }
+                _ => {}
+            }
+        }
+
+        let now = Instant::now();
+        let bitrate_kbps = match self.bytes.replace((now, bytes)) {
+            Some((then, last)) if bytes >= last => {
+                let secs = now.duration_since(then).as_secs_f64().max(0.001);
+                ((bytes - last) as f64 * 8.0 / 1000.0 / secs) as f32
+            }
+            _ => 0.0,
+        };
+
+        match self.report {
+            Some((last_reports, _, _)) if last_reports == reports => {}
+            Some((_, last_sent, last_received)) if sent > last_sent => {
+                let expected = (sent - last_sent) as f64;
+                let got = received.saturating_sub(last_received) as f64;
+                let pct = ((1.0 - got / expected) * 100.0).clamp(0.0, 100.0) as f32;
+                // Smoothed, since reports and our counters aren't sampled
+                // at quite the same moment
+                self.loss_pct = Some(self.loss_pct.map_or(pct, |l| l + (pct - l) / 4.0));
+                self.report = Some((reports, sent, received));
+            }
+            _ if reports > 0 => self.report = Some((reports, sent, received)),
+            _ => {}
+        }
+        (bitrate_kbps, self.loss_pct)
+    }
+}
+
+/// Hold the session after the sender's socket dropped, until the sender
+/// comes back with its session ID, the grace period runs out or the
+/// receiver stops. Audio from it simply pauses meanwhile.
+async fn wait_for_resume(
+    client_id: ClientId,
+    resume_rx: &mut mpsc::Receiver<Resume>,
+    grace: std::time::Duration,
+    shared: &SharedStatus,
+    server_cancel: &CancellationToken,
+) -> Option<Resume> {
+    if grace.is_zero() {
+        return None;
+    }
+    shared.log_line(format!(
+        "Sender connection lost — keeping its session for {} s.",
+        grace.as_secs()
+    ));
+    shared.set_pc_state(client_id, Some("reconnecting".into()));
+    tokio::select! {
+        resumed = resume_rx.recv() => resumed,
+        _ = tokio::time::sleep(grace) => {
+            shared.log_line("Sender did not reconnect in time.");
+            None
+        }
+        _ = server_cancel.cancelled() => None,
+    }
+}
+
+/// Carry the session on over the sender's new socket. The sender is
+/// likely on a new network path, so ICE is restarted with a fresh offer.
+async fn resume_session(
+    pc: &webrtc::peer_connection::RTCPeerConnection,
+    socket: &mut WebSocket,
+    encoding: &mut SignalEncoding,
+    resumed: Resume,
+    session_id: Option<String>,
+    client_id: ClientId,
+    shared: &SharedStatus,
+) -> Result<()> {
+    // Dropping the old socket closes it, if it was still half-open
+    *socket = resumed.socket;
+    *encoding = resumed.encoding;
+    shared.log_line(format!("Sender resumed its session from {}.", resumed.peer));
+    shared.set_client_addr(client_id, resumed.peer);
+
+    socket
+        .send(encoding.encode(&hello(session_id))?)
+        .await
+        .map_err(|e| anyhow!("Failed to send hello over WebSocket: {e}"))?;
+
+    let offer = pc
+        .create_offer(Some(RTCOfferOptions {
+            ice_restart: true,
+            ..Default::default()
+        }))
+        .await?;
+    pc.set_local_description(offer).await?;
+    if let Some(local_desc) = pc.local_description().await {
+        shared.set_local_sdp(local_desc.sdp.clone());
+        shared.log_line("Restarting ICE for the resumed session");
+        let msg = SignalMessage {
+            msg_type: "offer".to_string(),
+            sdp: Some(local_desc.sdp),
+            ..Default::default()
+        };
+        socket
+            .send(encoding.encode(&msg)?)
+            .await
+            .map_err(|e| anyhow!("Failed to send offer over WebSocket: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Our `hello`, always the first message on a socket.
+fn hello(session_id: Option<String>) -> SignalMessage {
+    SignalMessage {
+        msg_type: "hello".to_string(),
+        version: Some(PROTOCOL_VERSION),
+        capabilities: Some(CAPABILITIES.iter().map(|c| c.to_string()).collect()),
+        session_id,
+        ..Default::default()
+    }
+}
+
+/// Log the sender's `hello`, note its device name and reject protocols that
+/// are too old. Returns `false` once the socket has been closed with an error.
+async fn check_sender_hello(
+    socket: &mut WebSocket,
+    encoding: SignalEncoding,
+    client_id: ClientId,
+    hello: &SignalMessage,
+    shared: &SharedStatus,
+) -> bool {
+    if let Some(name) = &hello.device_name {
+        shared.set_client_device(client_id, name.clone());
+    }
+    let version = hello.version.unwrap_or(0);
+    let caps = hello.capabilities.clone().unwrap_or_default();
+    shared.log_line(format!(
+        "Sender protocol v{version}, capabilities: [{}]",
+        caps.join(", ")
+    ));
+    if version < MIN_SENDER_VERSION {
+        let detail = format!(
+            "This sender speaks protocol v{version}, but the receiver needs \
+             v{MIN_SENDER_VERSION} or newer. Please update the LAN Mic app."
+        );
+        send_error(
+            socket,
+            encoding,
+            SessionError::UnsupportedVersion,
+            Some(detail),
+        )
+        .await;
+        shared.log_line("Rejected sender: protocol too old.");
+        return false;
+    }
+    true
+}
+
+/// Hold a new sender until the user allows it, unless its device ID is
+/// already trusted. Returns `false` if the session must not continue.
+///
+/// Senders that predate device IDs (or skip `hello`) are always prompted and
+/// can't be remembered.
+async fn approve_sender(
+    socket: &mut WebSocket,
+    encoding: SignalEncoding,
+    client_id: ClientId,
+    peer: &str,
+    shared: &SharedStatus,
+    server_cancel: &CancellationToken,
+) -> Result<bool> {
+    let mut hello = None;
+    let _ = tokio::time::timeout(HELLO_TIMEOUT, async {
+        while let Some(Ok(msg)) = socket.recv().await {
+            if let Some(Ok(signal)) = SignalEncoding::decode(&msg) {
+                if signal.msg_type == "hello" {
+                    hello = Some(signal);
+                    break;
+                }
+            }
+        }
+    })
+    .await;
+
+    if let Some(h) = &hello {
+        if !check_sender_hello(socket, encoding, client_id, h, shared).await {
+            return Ok(false);
+        }
+    }
+    let device_id = hello.as_ref().and_then(|h| h.device_id.clone());
+    let device_name = hello
+        .as_ref()
+        .and_then(|h| h.device_name.clone())
+        .unwrap_or_else(|| "Unknown device".to_string());
+
+    if device_id
+        .as_deref()
+        .is_some_and(|id| shared.is_trusted_device(id))
+    {
+        shared.log_line(format!("Trusted device connected: {device_name}"));
+        return Ok(true);
+    }
+
+    let (request_id, decision) = shared.request_approval(device_name.clone(), peer.to_string());
+    shared.log_line(format!("Waiting for approval: {device_name} ({peer})"));
+    let pending = SignalMessage {
+        msg_type: "pending".to_string(),
+        message: Some("Waiting for the receiver to allow this device…".to_string()),
+        ..Default::default()
+    };
+    socket
+        .send(encoding.encode(&pending)?)
+        .await
+        .map_err(|e| anyhow!("Failed to send pending over WebSocket: {e}"))?;
+
+    let allowed = tokio::select! {
+        decision = decision => decision.unwrap_or(false),
+        _ = tokio::time::sleep(APPROVAL_TIMEOUT) => false,
+        _ = server_cancel.cancelled() => false,
+        _ = wait_for_close(socket) => false,
+    };
+    shared.cancel_approval(request_id);
+
+    if !allowed {
+        shared.log_line(format!("Denied sender: {device_name} ({peer})"));
+        send_error(socket, encoding, SessionError::Unauthorized, None).await;
+        return Ok(false);
+    }
+    shared.log_line(format!("Allowed sender: {device_name} ({peer})"));
+    if let Some(id) = device_id {
+        shared.trust_device(id, device_name);
+    }
+    Ok(true)
+}
+
+/// Resolve once the sender closes the socket, ignoring keep-alives.
+async fn wait_for_close(socket: &mut WebSocket) {
+    while let Some(Ok(msg)) = socket.recv().await {
+        if let Message::Close(_) = msg {
+            break;
+        }
+    }
+}
+
+/// Explain why a session whose signaling succeeded has no media path.
+///
+/// The WebSocket goes through the same Wi-Fi as the media, so if the sender
+/// answered and exchanged candidates but ICE still can't connect, the usual
+/// cause is an access point blocking client-to-client UDP ("AP/client
+/// isolation", common on guest and public networks).
+fn diagnose_unreachable(has_answer: bool, remote_candidates: usize) -> &'static str {
+    if !has_answer {
+        "The sender opened the page but never answered the audio offer. \
+         Reload the sender page and allow microphone access."
+    } else if remote_candidates == 0 {
+        "The sender answered but shared no network candidates. Its browser may \
+         be blocking WebRTC (privacy extensions, VPN, or 'Block WebRTC' settings)."
+    } else {
+        "Signaling works but no audio path could be established. This is the \
+         classic Wi-Fi 'client isolation' pattern: the network lets devices reach \
+         the receiver's web page but blocks device-to-device UDP. Disable AP/client \
+         isolation on the router, avoid guest networks, or connect both devices to \
+         the same non-guest network (or a phone hotspot)."
+    }
+}
+
 /// Process a single inbound signaling message.
 async fn handle_signal_message(
     signal: &SignalMessage,
@@ -166,6 +800,11 @@ async fn handle_signal_message(
                 let is_offer = signal.msg_type == "offer";
                 shared.log_line(format!("Got SDP: {}", signal.msg_type));
 
+                // Catch senders offering nothing we decode up front
+                if !offers_supported_codec(sdp_str) {
+                    return Err(SessionError::CodecMismatch.into());
+                }
+
                 let desc = if is_offer {
                     RTCSessionDescription::offer(sdp_str.clone())
                         .map_err(|e| anyhow!("parse offer: {e}"))?
@@ -174,6 +813,7 @@ async fn handle_signal_message(
                         .map_err(|e| anyhow!("parse answer: {e}"))?
                 };
                 pc.set_remote_description(desc).await?;
+                shared.set_remote_sdp(sdp_str.clone());
 
                 // Apply any ICE candidates that arrived before the remote description
                 let mut pend = pending_ice.lock().await;
@@ -188,13 +828,12 @@ async fn handle_signal_message(
                     let answer = pc.create_answer(None).await?;
                     pc.set_local_description(answer).await?;
                     if let Some(local) = pc.local_description().await {
+                        shared.set_local_sdp(local.sdp.clone());
                         out_tx
                             .send(SignalMessage {
                                 msg_type: "answer".to_string(),
                                 sdp: Some(local.sdp),
-                                candidate: None,
-                                sdp_mid: None,
-                                sdp_mline_index: None,
+                                ..Default::default()
                             })
                             .await
                             .map_err(|e| anyhow!("Failed to send answer: {e}"))?;
@@ -225,16 +864,67 @@ async fn handle_signal_message(
     Ok(())
 }
 
+/// Audio codecs registered with the media engine as `(MIME type, clock
+/// rate, channels, fmtp, payload type)`: Opus with the quality preset's
+/// fmtp parameters first, then G.711 and L16 for senders that can't encode
+/// Opus.
+pub(super) fn supported_codecs(opus_fmtp: &str) -> [(&'static str, u32, u16, String, u8); 5] {
+    [
+        (MIME_TYPE_OPUS, 48_000, 2, opus_fmtp.to_string(), 111),
+        (MIME_TYPE_PCMU, 8_000, 1, String::new(), 0),
+        (MIME_TYPE_PCMA, 8_000, 1, String::new(), 8),
+        (AudioCodec::L16_MIME_TYPE, 48_000, 2, String::new(), 118),
+        (AudioCodec::L16_MIME_TYPE, 48_000, 1, String::new(), 119),
+    ]
+}
+
+/// Whether an SDP offer has an `a=rtpmap` line for a codec in
+/// [`supported_codecs`], e.g. `a=rtpmap:0 PCMU/8000`.
+fn offers_supported_codec(sdp: &str) -> bool {
+    let codecs = supported_codecs("");
+    sdp.lines()
+        .filter_map(|line| line.trim().strip_prefix("a=rtpmap:"))
+        .filter_map(|map| map.split_once(' ').map(|(_, encoding)| encoding))
+        .any(|encoding| {
+            let mut parts = encoding.split('/');
+            let (Some(name), Some(rate)) = (parts.next(), parts.next()) else {
+                return false;
+            };
+            codecs.iter().any(|(mime_type, clock_rate, ..)| {
+                mime_type
+                    .strip_prefix("audio/")
+                    .is_some_and(|m| m.eq_ignore_ascii_case(name))
+                    && rate.parse() == Ok(*clock_rate)
+            })
+        })
+}
+
 async fn create_peer_connection(
-    use_stun: bool,
+    options: &SessionOptions,
     shared: SharedStatus,
-    queue: Arc<ArrayQueue<i16>>,
+    input: Arc<MixerInput>,
     out_tx: mpsc::Sender<SignalMessage>,
     cancel_token: CancellationToken,
 ) -> Result<Arc<webrtc::peer_connection::RTCPeerConnection>> {
-    // Media engine + codecs
     let mut m = MediaEngine::default();
-    m.register_default_codecs()?;
+    for (mime_type, clock_rate, channels, sdp_fmtp_line, payload_type) in
+        supported_codecs(&options.opus_fmtp)
+    {
+        m.register_codec(
+            RTCRtpCodecParameters {
+                capability: RTCRtpCodecCapability {
+                    mime_type: mime_type.to_owned(),
+                    clock_rate,
+                    channels,
+                    sdp_fmtp_line,
+                    rtcp_feedback: vec![],
+                },
+                payload_type,
+                ..Default::default()
+            },
+            RTPCodecType::Audio,
+        )?;
+    }
 
     // Interceptors (NACK, RTCP reports, etc.)
     let mut registry = Registry::new();
@@ -245,17 +935,8 @@ async fn create_peer_connection(
         .with_interceptor_registry(registry)
         .build();
 
-    let ice_servers = if use_stun {
-        vec![RTCIceServer {
-            urls: vec!["stun:stun.l.google.com:19302".to_owned()],
-            ..Default::default()
-        }]
-    } else {
-        vec![]
-    };
-
     let config = RTCConfiguration {
-        ice_servers,
+        ice_servers: options.ice_servers.clone(),
         ..Default::default()
     };
 
@@ -271,12 +952,21 @@ async fn create_peer_connection(
     )
     .await?;
 
+    // Sender status in, receiver requests out
+    super::control::open(&pc, input.id, shared.clone(), cancel_token.clone()).await?;
+
     // PeerConnection state change callback
     let shared_pc = shared.clone();
+    let client_id = input.id;
     pc.on_peer_connection_state_change(Box::new(move |s: RTCPeerConnectionState| {
         let shared_pc = shared_pc.clone();
         Box::pin(async move {
-            shared_pc.set_pc_state(Some(format!("{s:?}")));
+            shared_pc.set_pc_state(client_id, Some(format!("{s:?}")));
+            if s == RTCPeerConnectionState::Failed {
+                shared_pc.set_last_error(Some(
+                    "ICE connection failed: no network path to the sender".into(),
+                ));
+            }
         })
     }));
 
@@ -289,10 +979,10 @@ async fn create_peer_connection(
                 if let Ok(init) = c.to_json() {
                     let msg = SignalMessage {
                         msg_type: "ice".to_string(),
-                        sdp: None,
                         candidate: Some(init.candidate),
                         sdp_mid: init.sdp_mid,
                         sdp_mline_index: init.sdp_mline_index.map(|v| v as i32),
+                        ..Default::default()
                     };
                     if let Err(e) = ice_tx.send(msg).await {
                         log::warn!("Failed to send ICE candidate: {e}");
@@ -302,12 +992,14 @@ async fn create_peer_connection(
         })
     }));
 
-    // When remote audio track arrives, decode Opus and push to CPAL queue
+    // When remote audio track arrives, decode Opus and push into the mixer
     let shared_track = shared.clone();
+    let relay = options.relay.clone();
     pc.on_track(Box::new(move |track, _receiver, _transceiver| {
-        let queue = queue.clone();
+        let input = input.clone();
         let shared_track = shared_track.clone();
         let token = cancel_token.clone();
+        let relay = relay.clone();
 
         Box::pin(async move {
             if track.kind() != RTPCodecType::Audio {
@@ -316,13 +1008,25 @@ async fn create_peer_connection(
 
             let codec = track.codec();
             shared_track.log_line(format!("Audio track: {}", codec.capability.mime_type));
+            let Some(audio_codec) = AudioCodec::from_mime_type(&codec.capability.mime_type) else {
+                shared_track.log_line("Unsupported audio codec — no audio from this sender.");
+                return;
+            };
+            shared_track.set_client_codec(input.id, audio_codec.to_string());
             let ch = codec.capability.channels as usize;
             let channels = if ch >= 2 { 2 } else { 1 };
 
             tokio::spawn(async move {
-                if let Err(e) =
-                    decode_track_to_queue(track, queue, channels, shared_track.clone(), token)
-                        .await
+                if let Err(e) = decode_track_to_queue(
+                    track,
+                    input,
+                    audio_codec,
+                    channels,
+                    relay,
+                    shared_track.clone(),
+                    token,
+                )
+                .await
                 {
                     shared_track.log_line(format!("Audio decode stopped: {e}"));
                 }
@@ -333,28 +1037,18 @@ async fn create_peer_connection(
     Ok(pc)
 }
 
+/// Forward RTP packets from the track to the sender's decode thread, and to
+/// the relay target if relay mode is on.
 async fn decode_track_to_queue(
     track: Arc<webrtc::track::track_remote::TrackRemote>,
-    queue: Arc<ArrayQueue<i16>>,
+    input: Arc<MixerInput>,
+    codec: AudioCodec,
     channels: usize,
+    relay: Option<RelayFeed>,
     shared: SharedStatus,
     cancel_token: CancellationToken,
 ) -> Result<()> {
-    let opus_channels = if channels >= 2 {
-        Channels::Stereo
-    } else {
-        Channels::Mono
-    };
-    let mut dec =
-        OpusDecoder::new(48_000, opus_channels).map_err(|e| anyhow!("opus decoder init: {e:?}"))?;
-
-    // Buffer large enough for max Opus frame (60ms @ 48kHz) × stereo
-    let max_samples_per_channel = 5760;
-    let mut pcm = vec![0i16; max_samples_per_channel * channels];
-
-    // Track dropped samples for periodic logging
-    let dropped = AtomicU64::new(0);
-    let mut last_log = std::time::Instant::now();
+    let mut feed = PacketFeed::spawn(input, codec, channels, relay, shared.clone())?;
 
     loop {
         let (rtp, _attr) = tokio::select! {
@@ -366,48 +1060,215 @@ async fn decode_track_to_queue(
                 return Ok(());
             }
         };
-        shared.bump_audio_packets(1);
-
-        if rtp.payload.is_empty() {
-            continue;
+        if !feed.push(rtp) {
+            // Decode thread exited and has already logged why.
+            return Ok(());
         }
+    }
+}
 
-        let n = dec
-            .decode(&rtp.payload, &mut pcm, false)
-            .map_err(|e| anyhow!("opus decode: {e:?}"))?;
+/// The receive path from RTP packets to a sender's queue, shared by WebRTC
+/// tracks and the plain RTP listener: jitter and byte accounting,
+/// reordering, relay forwarding and the decode thread.
+pub(super) struct PacketFeed {
+    client_id: ClientId,
+    /// Its buffering follows the measured jitter.
+    input: Arc<MixerInput>,
+    decoder: DecodeThread,
+    jitter: InterarrivalJitter,
+    reorder: ReorderBuffer,
+    last_reorder_report: Instant,
+    relay: Option<RelayFeed>,
+    shared: SharedStatus,
+}
 
-        if n == 0 {
-            continue;
+impl PacketFeed {
+    pub(super) fn spawn(
+        input: Arc<MixerInput>,
+        codec: AudioCodec,
+        channels: usize,
+        relay: Option<RelayFeed>,
+        shared: SharedStatus,
+    ) -> Result<Self> {
+        // The relay's own track is Opus
+        if relay.is_some() && codec != AudioCodec::Opus {
+            shared.log_line(format!(
+                "Client #{} sends {codec}, which can't be relayed.",
+                input.id
+            ));
         }
+        let relay = relay.filter(|_| codec == AudioCodec::Opus);
+        Ok(Self {
+            client_id: input.id,
+            input: Arc::clone(&input),
+            decoder: DecodeThread::spawn(input, codec, channels, shared.clone())?,
+            jitter: InterarrivalJitter::default(),
+            reorder: ReorderBuffer::default(),
+            last_reorder_report: Instant::now(),
+            relay,
+            shared,
+        })
+    }
 
-        let mut local_dropped = 0u64;
+    /// Account for one received packet and pass it on once it is in order.
+    /// Returns `false` once the decode thread has exited.
+    pub(super) fn push(&mut self, rtp: Packet) -> bool {
+        let client_id = self.client_id;
+        let jitter_ms = self.jitter.update(rtp.header.timestamp);
+        self.shared
+            .record_audio_packet(client_id, rtp.payload.len(), jitter_ms);
+        self.input.adapt_to_jitter(jitter_ms);
 
-        if channels >= 2 {
-            // Downmix stereo to mono for the output queue
-            for i in 0..n {
-                let l = pcm[i * 2] as i32;
-                let r = pcm[i * 2 + 1] as i32;
-                let m = ((l + r) / 2) as i16;
-                if queue.push(m).is_err() {
-                    local_dropped += 1;
-                }
+        let mut decoder_alive = true;
+        let (relay, decoder) = (&self.relay, &mut self.decoder);
+        // Empty packets still go through so their sequence numbers don't
+        // look like loss; the decoder skips them.
+        self.reorder.push(rtp, |rtp| {
+            if let (Some(relay), false) = (relay, rtp.payload.is_empty()) {
+                relay.forward(client_id, &rtp);
             }
-        } else {
-            for i in 0..n {
-                if queue.push(pcm[i]).is_err() {
-                    local_dropped += 1;
-                }
+            decoder_alive &= decoder.submit(rtp);
+        });
+
+        if self.last_reorder_report.elapsed() >= REORDER_REPORT_INTERVAL {
+            self.last_reorder_report = Instant::now();
+            let (reordered, duplicates, late, lost) = self.reorder.take_counts();
+            if reordered + duplicates + late + lost > 0 {
+                log::debug!(
+                    "Client #{client_id} RTP: {reordered} reordered, {duplicates} duplicate, \
+                     {late} late, {lost} lost"
+                );
             }
         }
+        decoder_alive
+    }
+}
 
-        // Accumulate and periodically log drops
-        if local_dropped > 0 {
-            let total = dropped.fetch_add(local_dropped, Ordering::Relaxed) + local_dropped;
-            if last_log.elapsed().as_secs() >= 5 {
-                shared.log_line(format!("Audio queue overflow: {} samples dropped", total));
-                last_log = std::time::Instant::now();
-                dropped.store(0, Ordering::Relaxed);
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::mixer::Mixer;
+    use crate::core::quality::QualityPreset;
+    use std::time::Duration;
+    use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSample;
+
+    #[test]
+    fn every_registered_codec_is_accepted() {
+        for rtpmap in [
+            "a=rtpmap:111 opus/48000/2",
+            "a=rtpmap:0 PCMU/8000",
+            "a=rtpmap:8 PCMA/8000",
+            "a=rtpmap:118 L16/48000/2",
+            "a=rtpmap:119 L16/48000",
+        ] {
+            let sdp = format!("v=0\r\nm=audio 9 UDP/TLS/RTP/SAVPF 0\r\n{rtpmap}\r\n");
+            assert!(offers_supported_codec(&sdp), "{rtpmap} rejected");
+        }
+    }
+
+    #[test]
+    fn unregistered_codecs_are_rejected() {
+        for rtpmap in [
+            "a=rtpmap:9 G722/8000",
+            "a=rtpmap:0 PCMU/16000",
+            "a=rtpmap:96 L16/44100/2",
+        ] {
+            let sdp = format!("v=0\r\nm=audio 9 UDP/TLS/RTP/SAVPF 0\r\n{rtpmap}\r\n");
+            assert!(!offers_supported_codec(&sdp), "{rtpmap} accepted");
+        }
+        // A codec name in a session attribute is not an rtpmap
+        assert!(!offers_supported_codec("v=0\r\ns=opus/48000\r\n"));
+    }
+
+    /// A sender that can only encode G.711 μ-law, like a simple SIP device.
+    async fn pcmu_sender() -> Arc<webrtc::peer_connection::RTCPeerConnection> {
+        let mut m = MediaEngine::default();
+        m.register_codec(
+            RTCRtpCodecParameters {
+                capability: RTCRtpCodecCapability {
+                    mime_type: MIME_TYPE_PCMU.to_owned(),
+                    clock_rate: 8_000,
+                    channels: 1,
+                    ..Default::default()
+                },
+                payload_type: 0,
+                ..Default::default()
+            },
+            RTPCodecType::Audio,
+        )
+        .unwrap();
+        let api = APIBuilder::new().with_media_engine(m).build();
+        let pc = Arc::new(
+            api.new_peer_connection(RTCConfiguration::default())
+                .await
+                .unwrap(),
+        );
+        let track = Arc::new(TrackLocalStaticSample::new(
+            RTCRtpCodecCapability {
+                mime_type: MIME_TYPE_PCMU.to_owned(),
+                clock_rate: 8_000,
+                channels: 1,
+                ..Default::default()
+            },
+            "audio".to_owned(),
+            "pcmu-sender".to_owned(),
+        ));
+        pc.add_track(track).await.unwrap();
+        pc
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn pcmu_only_sender_negotiates() {
+        let shared = SharedStatus::default();
+        let params = QualityPreset::default().params();
+        let mixer = Mixer::new(params.audio_tuning());
+        let options = SessionOptions {
+            ice_servers: Vec::new(),
+            allow_multiple_clients: false,
+            opus_fmtp: params.opus_fmtp(),
+            require_approval: false,
+            relay: None,
+            reconnect_grace: Duration::ZERO,
+            keepalive_timeout: Duration::from_secs(20),
+        };
+        let (out_tx, mut out_rx) = mpsc::channel(SIGNAL_CHANNEL_SIZE);
+        let cancel = CancellationToken::new();
+        let receiver = create_peer_connection(
+            &options,
+            shared.clone(),
+            mixer.add_input(1),
+            out_tx.clone(),
+            cancel.clone(),
+        )
+        .await
+        .unwrap();
+        let offer = receiver.create_offer(None).await.unwrap();
+        receiver.set_local_description(offer.clone()).await.unwrap();
+
+        let sender = pcmu_sender().await;
+        sender.set_remote_description(offer).await.unwrap();
+        let answer = sender.create_answer(None).await.unwrap();
+        sender.set_local_description(answer.clone()).await.unwrap();
+        assert!(!answer.sdp.to_ascii_lowercase().contains("opus"));
+
+        let signal = SignalMessage {
+            msg_type: "answer".to_string(),
+            sdp: Some(answer.sdp),
+            ..Default::default()
+        };
+        let pending_ice = Arc::default();
+        handle_signal_message(&signal, &receiver, &out_tx, &pending_ice, &shared)
+            .await
+            .expect("PCMU answer accepted");
+        let remote = receiver.remote_description().await.expect("remote SDP set");
+        assert!(remote.sdp.contains("PCMU/8000"));
+
+        // Nothing but trickled candidates goes back to the sender
+        while let Ok(message) = out_rx.try_recv() {
+            assert_eq!(message.msg_type, "ice");
         }
+        cancel.cancel();
+        let _ = receiver.close().await;
+        let _ = sender.close().await;
     }
 }
@@ -0,0 +1,144 @@
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Rows kept per session (one per second, 24 h); older rows are discarded.
+const MAX_ROWS: usize = 86_400;
+
+/// RTP clock rate of Opus.
+const RTP_CLOCK_HZ: f64 = 48_000.0;
+
+/// One second of a session, as exported to CSV.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsRow {
+    /// Seconds since the session started.
+    pub elapsed_s: u64,
+    pub packets: u64,
+    pub bitrate_kbps: f32,
+    /// Worst RTP interarrival jitter across connected senders.
+    pub jitter_ms: f32,
+    /// Samples dropped because an input queue overflowed.
+    pub dropped_samples: u64,
+    /// Deepest playout buffer across connected senders.
+    pub buffer_ms: u32,
+}
+
+/// Per-second statistics of the current (or last) session.
+#[derive(Debug, Clone, Default)]
+pub struct SessionStats {
+    rows: VecDeque<StatsRow>,
+    last_packets: u64,
+    last_bytes: u64,
+    last_dropped: u64,
+}
+
+/// Running totals the per-second rows are differenced from.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Counters {
+    pub packets: u64,
+    pub bytes: u64,
+    pub dropped_samples: u64,
+}
+
+impl SessionStats {
+    /// Start a new session; `totals` are the counters at its start.
+    pub fn reset(&mut self, totals: Counters) {
+        self.rows.clear();
+        self.last_packets = totals.packets;
+        self.last_bytes = totals.bytes;
+        self.last_dropped = totals.dropped_samples;
+    }
+
+    /// Append a row with everything counted since the previous one.
+    pub fn sample(&mut self, elapsed_s: u64, totals: Counters, jitter_ms: f32, buffer_ms: u32) {
+        let bytes = totals.bytes.saturating_sub(self.last_bytes);
+        self.rows.push_back(StatsRow {
+            elapsed_s,
+            packets: totals.packets.saturating_sub(self.last_packets),
+            bitrate_kbps: (bytes * 8) as f32 / 1000.0,
+            jitter_ms,
+            dropped_samples: totals.dropped_samples.saturating_sub(self.last_dropped),
+            buffer_ms,
+        });
+        if self.rows.len() > MAX_ROWS {
+            self.rows.pop_front();
+        }
+        self.last_packets = totals.packets;
+        self.last_bytes = totals.bytes;
+        self.last_dropped = totals.dropped_samples;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn write_csv(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        let file =
+            std::fs::File::create(path).with_context(|| format!("create {}", path.display()))?;
+        let mut out = std::io::BufWriter::new(file);
+        writeln!(
+            out,
+            "elapsed_s,packets,bitrate_kbps,jitter_ms,dropped_samples,buffer_ms"
+        )?;
+        for r in &self.rows {
+            writeln!(
+                out,
+                "{},{},{:.1},{:.1},{},{}",
+                r.elapsed_s, r.packets, r.bitrate_kbps, r.jitter_ms, r.dropped_samples, r.buffer_ms
+            )?;
+        }
+        out.flush()
+            .with_context(|| format!("write {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// Default file name for an export: `lan-mic-stats-<unix seconds>.csv`.
+pub fn default_export_path() -> PathBuf {
+    export_path("lan-mic-stats", "csv")
+}
+
+/// `Documents/LAN Mic/<stem>-<unix seconds>.<extension>`.
+pub fn export_path(stem: &str, extension: &str) -> PathBuf {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let dir = dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| PathBuf::from("."));
+    dir.join("LAN Mic")
+        .join(format!("{stem}-{secs}.{extension}"))
+}
+
+/// RFC 3550 interarrival jitter of one RTP stream.
+#[derive(Debug, Default)]
+pub struct InterarrivalJitter {
+    start: Option<Instant>,
+    last_transit: Option<f64>,
+    /// Smoothed jitter in RTP clock units.
+    jitter: f64,
+}
+
+impl InterarrivalJitter {
+    /// Account for a packet arriving now; returns the jitter in ms.
+    pub fn update(&mut self, rtp_timestamp: u32) -> f32 {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let arrival = start.elapsed().as_secs_f64() * RTP_CLOCK_HZ;
+        let transit = arrival - rtp_timestamp as f64;
+        if let Some(last) = self.last_transit {
+            let d = (transit - last).abs();
+            // A timestamp wrap shows up as one huge jump; don't let it in
+            if d < RTP_CLOCK_HZ {
+                self.jitter += (d - self.jitter) / 16.0;
+            }
+        }
+        self.last_transit = Some(transit);
+        (self.jitter * 1000.0 / RTP_CLOCK_HZ) as f32
+    }
+}
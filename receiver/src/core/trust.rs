@@ -0,0 +1,35 @@
+use crate::config_store;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// A sender the user approved once and that may reconnect without asking.
+///
+/// Identified by the random device ID the sender keeps in its own storage —
+/// browsers regenerate their DTLS certificate per session, so the
+/// fingerprint is not stable enough to remember.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrustedDevice {
+    pub id: String,
+    pub name: String,
+    /// Unix seconds when the device was approved.
+    pub added_at: u64,
+}
+
+/// A sender waiting for the user to allow or deny it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApprovalRequest {
+    pub id: u64,
+    pub device_name: String,
+    pub addr: String,
+}
+
+const STORE: &str = "trusted_devices.json";
+
+/// Load the trusted-device list; a missing or unreadable file means none.
+pub fn load() -> Vec<TrustedDevice> {
+    config_store::load(STORE).unwrap_or_default()
+}
+
+pub fn save(devices: &[TrustedDevice]) -> Result<()> {
+    config_store::save(STORE, devices)
+}
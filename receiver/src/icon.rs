@@ -1,7 +1,35 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Sizes in the packaged icon set.
+pub const ICON_SIZES: [u32; 8] = [16, 20, 24, 32, 48, 64, 128, 256];
+
+/// Tray icon edge: the platform's tray size at 2× scale, so it stays sharp
+/// on high-DPI displays and is only ever scaled down.
+#[cfg(target_os = "macos")]
+pub const TRAY_ICON_SIZE: u32 = 44;
+#[cfg(target_os = "windows")]
+pub const TRAY_ICON_SIZE: u32 = 32;
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub const TRAY_ICON_SIZE: u32 = 48;
+
+/// Window icon edge; taskbars and task switchers take it down to their size.
+pub const WINDOW_ICON_SIZE: u32 = 256;
+
 /// Generate an RGBA icon at the given `size` (e.g. 32 for tray, 64 for window).
 ///
 /// Draws a blue circle with a white microphone silhouette.
 pub fn create_icon(size: u32) -> Vec<u8> {
+    render(size, None)
+}
+
+/// The icon with the mic capsule filled green from the bottom up to `level`
+/// (0–1), used as a live level indicator in the tray.
+pub fn create_level_icon(size: u32, level: f32) -> Vec<u8> {
+    render(size, Some(level.clamp(0.0, 1.0)))
+}
+
+fn render(size: u32, level: Option<f32>) -> Vec<u8> {
     let s = size as f32;
     let mut data = vec![0u8; (size * size * 4) as usize];
     let cx = s / 2.0;
@@ -74,18 +102,23 @@ pub fn create_icon(size: u32) -> Vec<u8> {
             let arc_r_outer = 0.42;
             let arc_r_inner = 0.32;
             let arc_dist = (nx * nx + (ny - arc_cy_n).powi(2)).sqrt();
-            let in_arc = ny >= arc_cy_n
-                && ny <= 0.38
-                && arc_dist >= arc_r_inner
-                && arc_dist <= arc_r_outer;
+            let in_arc =
+                ny >= arc_cy_n && ny <= 0.38 && arc_dist >= arc_r_inner && arc_dist <= arc_r_outer;
 
             // Stand (vertical bar)
-            let in_stand = nx.abs() <= 0.06 && ny >= 0.35 && ny <= 0.55;
+            let in_stand = nx.abs() <= 0.06 && (0.35..=0.55).contains(&ny);
 
             // Base (horizontal bar)
-            let in_base = nx.abs() <= 0.25 && ny >= 0.50 && ny <= 0.60;
+            let in_base = nx.abs() <= 0.25 && (0.50..=0.60).contains(&ny);
+
+            // Level fill: lower part of the capsule, green
+            let in_level = level.is_some_and(|l| ny >= cap_bot - l * (cap_bot - cap_top));
 
-            if in_capsule || in_arc || in_stand || in_base {
+            if in_capsule && in_level {
+                r = 52;
+                g = 199;
+                b = 140;
+            } else if in_capsule || in_arc || in_stand || in_base {
                 r = 255;
                 g = 255;
                 b = 255;
@@ -100,3 +133,86 @@ pub fn create_icon(size: u32) -> Vec<u8> {
     }
     data
 }
+
+// ---------------------------------------------------------------------------
+// Packaging formats
+// ---------------------------------------------------------------------------
+
+fn png(size: u32) -> Result<Vec<u8>> {
+    let mut out = std::io::Cursor::new(Vec::new());
+    image::RgbaImage::from_raw(size, size, create_icon(size))
+        .context("icon buffer size")?
+        .write_to(&mut out, image::ImageFormat::Png)?;
+    Ok(out.into_inner())
+}
+
+/// Windows `.ico` with every size in [`ICON_SIZES`], PNG-compressed.
+pub fn ico() -> Result<Vec<u8>> {
+    let images = ICON_SIZES
+        .iter()
+        .map(|&size| Ok((size, png(size)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&0u16.to_le_bytes()); // reserved
+    out.extend_from_slice(&1u16.to_le_bytes()); // type: icon
+    out.extend_from_slice(&(images.len() as u16).to_le_bytes());
+    let mut offset = 6 + 16 * images.len();
+    for (size, data) in &images {
+        // 256 is stored as 0
+        let dim = if *size >= 256 { 0 } else { *size as u8 };
+        out.extend_from_slice(&[dim, dim, 0, 0]);
+        out.extend_from_slice(&1u16.to_le_bytes()); // colour planes
+        out.extend_from_slice(&32u16.to_le_bytes()); // bits per pixel
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += data.len();
+    }
+    for (_, data) in &images {
+        out.extend_from_slice(data);
+    }
+    Ok(out)
+}
+
+/// macOS `.icns` with PNG entries.
+pub fn icns() -> Result<Vec<u8>> {
+    // (OSType, pixel size); the @2x types reuse the larger renders
+    const ENTRIES: [(&[u8; 4], u32); 7] = [
+        (b"icp4", 16),
+        (b"icp5", 32),
+        (b"ic11", 32),
+        (b"icp6", 64),
+        (b"ic12", 64),
+        (b"ic07", 128),
+        (b"ic08", 256),
+    ];
+
+    let mut body = Vec::new();
+    for (kind, size) in ENTRIES {
+        let data = png(size)?;
+        body.extend_from_slice(kind);
+        body.extend_from_slice(&(8 + data.len() as u32).to_be_bytes());
+        body.extend_from_slice(&data);
+    }
+    let mut out = Vec::with_capacity(8 + body.len());
+    out.extend_from_slice(b"icns");
+    out.extend_from_slice(&(8 + body.len() as u32).to_be_bytes());
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+/// Write `icon.ico`, `icon.icns` and `icon-<size>.png` for installers and
+/// app bundles (`--export-icons DIR`).
+pub fn export(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    let write = |name: String, data: Vec<u8>| {
+        let path = dir.join(name);
+        std::fs::write(&path, data).with_context(|| format!("write {}", path.display()))
+    };
+    write("icon.ico".into(), ico()?)?;
+    write("icon.icns".into(), icns()?)?;
+    for size in ICON_SIZES {
+        write(format!("icon-{size}.png"), png(size)?)?;
+    }
+    Ok(())
+}
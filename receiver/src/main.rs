@@ -1,42 +1,94 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
+mod app_settings;
+mod appearance;
 mod audio;
+mod config_store;
 mod core;
 mod icon;
+mod palette;
+mod platform;
+mod profile;
+mod recorder;
 
 use single_instance::SingleInstance;
 use std::sync::mpsc::channel;
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
     TrayIconBuilder,
 };
 
-const APP_INSTANCE_NAME: &str = "lan-mic-receiver-single-instance";
+/// Menu ID prefix of the output device entries; the rest is the device name.
+pub const TRAY_OUTPUT_PREFIX: &str = "output:";
 
 #[derive(Debug, Clone)]
 pub enum TrayMessage {
     Show,
     Hide,
+    Start,
+    Stop,
+    SelectOutput(String),
     Quit,
 }
 
+/// Tray menu entries the app updates as its state changes.
+pub struct TrayMenu {
+    pub start: MenuItem,
+    pub stop: MenuItem,
+    /// Rebuilt whenever the device list or selection changes.
+    pub outputs: Submenu,
+}
+
 fn main() -> iced::Result {
     // Install default crypto provider to avoid panic in axum-server/rustls
     rustls::crypto::ring::default_provider()
         .install_default()
         .expect("Failed to install rustls crypto provider");
 
-    // Ensure only one instance of the app is running
-    let instance = SingleInstance::new(APP_INSTANCE_NAME).unwrap();
+    // Packaging helper: write the icon set and exit
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("--export-icons") {
+        let dir = args.get(1).map(String::as_str).unwrap_or("icons");
+        if let Err(e) = icon::export(std::path::Path::new(dir)) {
+            eprintln!("Failed to export icons: {e:#}");
+            std::process::exit(1);
+        }
+        println!("Icons written to {dir}");
+        std::process::exit(0);
+    }
+
+    let profile = match profile::Profile::from_args(args) {
+        Ok(profile) => profile,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!(
+                "Usage: lan-mic-webrtc-receiver [--profile NAME] [--port PORT] [--name \"DISPLAY NAME\"]"
+            );
+            eprintln!(
+                "       [--acme-domain HOST [--acme-email ADDR] [--acme-staging] [--acme-dns-hook CMD]]"
+            );
+            eprintln!("       [--mtls] [--pcm-port PORT] [--rtp-port PORT] [--http-port PORT]");
+            eprintln!("       [--api-token TOKEN] [--metrics] [--minimized] [--ipv6] [--port-fallback N]");
+            eprintln!("       lan-mic-webrtc-receiver --export-icons [DIR]");
+            std::process::exit(2);
+        }
+    };
+    profile::init(profile);
+    let profile = profile::current();
+
+    // Ensure only one instance per profile/port is running
+    let instance = SingleInstance::new(&profile.instance_name()).unwrap();
     if !instance.is_single() {
-        eprintln!("LAN Mic Receiver is already running.");
+        eprintln!("{} is already running.", profile.title);
         std::process::exit(0);
     }
 
     env_logger::Builder::from_default_env()
         .filter_level(log::LevelFilter::Info)
         .init();
+    // Mirror the in-app log to disk if enabled in Settings
+    core::log_file::init();
 
     // Create channel for tray communication
     let (tx, rx) = channel::<TrayMessage>();
@@ -45,26 +97,36 @@ fn main() -> iced::Result {
     let tray_menu = Menu::new();
     let show_item = MenuItem::new("Show", true, None);
     let hide_item = MenuItem::new("Hide", true, None);
+    let start_item = MenuItem::new("Start Receiving", true, None);
+    let stop_item = MenuItem::new("Stop Receiving", false, None);
+    let outputs_menu = Submenu::new("Output Device", true);
     let quit_item = MenuItem::new("Quit", true, None);
     tray_menu.append(&show_item).unwrap();
     tray_menu.append(&hide_item).unwrap();
     tray_menu.append(&PredefinedMenuItem::separator()).unwrap();
+    tray_menu.append(&start_item).unwrap();
+    tray_menu.append(&stop_item).unwrap();
+    tray_menu.append(&outputs_menu).unwrap();
+    tray_menu.append(&PredefinedMenuItem::separator()).unwrap();
     tray_menu.append(&quit_item).unwrap();
 
     // Create tray icon
-    let icon_data = icon::create_icon(32);
+    let icon_data = icon::create_icon(icon::TRAY_ICON_SIZE);
     let tray_icon_img =
-        tray_icon::Icon::from_rgba(icon_data, 32, 32).expect("Failed to create tray icon");
+        tray_icon::Icon::from_rgba(icon_data, icon::TRAY_ICON_SIZE, icon::TRAY_ICON_SIZE)
+            .expect("Failed to create tray icon");
 
     // Store IDs for menu items
     let show_id = show_item.id().clone();
     let hide_id = hide_item.id().clone();
+    let start_id = start_item.id().clone();
+    let stop_id = stop_item.id().clone();
     let quit_id = quit_item.id().clone();
 
     // Build tray icon
-    let _tray_icon = TrayIconBuilder::new()
+    let tray_icon = TrayIconBuilder::new()
         .with_menu(Box::new(tray_menu))
-        .with_tooltip("LAN Mic Receiver")
+        .with_tooltip(&profile.title)
         .with_icon(tray_icon_img)
         .build()
         .unwrap();
@@ -79,6 +141,12 @@ fn main() -> iced::Result {
                     let _ = tx_clone.send(TrayMessage::Show);
                 } else if event.id == hide_id {
                     let _ = tx_clone.send(TrayMessage::Hide);
+                } else if event.id == start_id {
+                    let _ = tx_clone.send(TrayMessage::Start);
+                } else if event.id == stop_id {
+                    let _ = tx_clone.send(TrayMessage::Stop);
+                } else if let Some(device) = event.id.0.strip_prefix(TRAY_OUTPUT_PREFIX) {
+                    let _ = tx_clone.send(TrayMessage::SelectOutput(device.to_string()));
                 } else if event.id == quit_id {
                     let _ = tx_clone.send(TrayMessage::Quit);
                     break;
@@ -90,5 +158,11 @@ fn main() -> iced::Result {
     let shared = core::SharedStatus::default();
     let controller = core::spawn_runtime(shared.clone());
 
-    app::launch_app(controller, shared, rx)
+    let tray_menu = TrayMenu {
+        start: start_item,
+        stop: stop_item,
+        outputs: outputs_menu,
+    };
+
+    app::launch_app(controller, shared, rx, tray_icon, tray_menu)
 }
@@ -0,0 +1,58 @@
+// ---------------------------------------------------------------------------
+// Command palette — fuzzy matching of action names
+// ---------------------------------------------------------------------------
+
+/// Bonus for a match right after the previous one ("sto" in "Stop").
+const CONSECUTIVE_BONUS: i32 = 5;
+/// Bonus for a match at the start of a word ("ol" in "Open Logs").
+const WORD_START_BONUS: i32 = 3;
+
+/// How well `query` matches `candidate`, or `None` if it doesn't.
+///
+/// Every non-space query character must appear in the candidate in order,
+/// ignoring case. Higher scores rank first.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let mut score = 0;
+    let mut matched = 0;
+    let mut prev_match: Option<usize> = None;
+    let mut prev_char = ' ';
+    for (i, c) in candidate.chars().enumerate() {
+        if matched == query.len() {
+            break;
+        }
+        if c.to_lowercase().eq(std::iter::once(query[matched])) {
+            score += 1;
+            if prev_match.is_some_and(|p| p + 1 == i) {
+                score += CONSECUTIVE_BONUS;
+            }
+            if !prev_char.is_alphanumeric() {
+                score += WORD_START_BONUS;
+            }
+            prev_match = Some(i);
+            matched += 1;
+        }
+        prev_char = c;
+    }
+    (matched == query.len()).then_some(score)
+}
+
+/// Indices of the candidates matching `query`, best first. Equal scores
+/// keep their original order.
+pub fn rank<S: AsRef<str>>(query: &str, candidates: &[S]) -> Vec<usize> {
+    let mut scored: Vec<(usize, i32)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(i, c)| fuzzy_score(query, c.as_ref()).map(|s| (i, s)))
+        .collect();
+    scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(i, _)| i).collect()
+}
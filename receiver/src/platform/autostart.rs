@@ -0,0 +1,236 @@
+use anyhow::{anyhow, Context, Result};
+
+/// Flag added to the login command when the window should stay hidden.
+pub const MINIMIZED_FLAG: &str = "--minimized";
+
+/// Whether this profile is registered to launch at login: `None` if not,
+/// otherwise whether it starts minimized to the tray.
+pub fn status() -> Option<bool> {
+    let command = read_entry()?;
+    Some(command.contains(MINIMIZED_FLAG))
+}
+
+/// Launch this profile at login, replacing any earlier registration.
+pub fn enable(minimized: bool) -> Result<()> {
+    let exe = std::env::current_exe().context("Failed to locate the executable")?;
+    // Re-run with the same profile options this process was started with
+    let mut args: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| arg != MINIMIZED_FLAG)
+        .collect();
+    if minimized {
+        args.push(MINIMIZED_FLAG.to_string());
+    }
+    write_entry(&exe, &args)
+}
+
+/// Stop launching this profile at login. Not registered is not an error.
+pub fn disable() -> Result<()> {
+    remove_entry()
+}
+
+/// Identifier of this profile's login entry, distinct per profile so each
+/// receiver can be registered on its own.
+fn entry_id() -> String {
+    match &crate::profile::current().name {
+        Some(name) => format!("lan-mic-receiver-{name}"),
+        None => "lan-mic-receiver".to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Windows: HKCU\...\Run value
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "windows")]
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+/// Run `reg.exe` without flashing a console window.
+#[cfg(target_os = "windows")]
+fn reg(args: &[&str]) -> Result<std::process::Output> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    std::process::Command::new("reg")
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| anyhow!("Failed to run reg.exe: {e}"))
+}
+
+#[cfg(target_os = "windows")]
+fn read_entry() -> Option<String> {
+    let id = entry_id();
+    let output = reg(&["query", RUN_KEY, "/v", id.as_str()]).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // "    <name>    REG_SZ    <command>"
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            line.split_once("REG_SZ")
+                .map(|(_, cmd)| cmd.trim().to_string())
+        })
+}
+
+#[cfg(target_os = "windows")]
+fn write_entry(exe: &std::path::Path, args: &[String]) -> Result<()> {
+    // Windows paths keep their backslashes; only quotes need escaping
+    let command = std::iter::once(format!("\"{}\"", exe.display()))
+        .chain(args.iter().map(|arg| {
+            if arg.contains([' ', '"']) {
+                format!("\"{}\"", arg.replace('"', "\\\""))
+            } else {
+                arg.clone()
+            }
+        }))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let id = entry_id();
+    let output = reg(&[
+        "add",
+        RUN_KEY,
+        "/v",
+        id.as_str(),
+        "/t",
+        "REG_SZ",
+        "/d",
+        command.as_str(),
+        "/f",
+    ])?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "reg add failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn remove_entry() -> Result<()> {
+    if read_entry().is_none() {
+        return Ok(());
+    }
+    let id = entry_id();
+    let output = reg(&["delete", RUN_KEY, "/v", id.as_str(), "/f"])?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "reg delete failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// macOS: ~/Library/LaunchAgents plist; Linux: XDG autostart .desktop file
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "macos")]
+fn entry_path() -> Option<std::path::PathBuf> {
+    Some(
+        dirs::home_dir()?
+            .join("Library/LaunchAgents")
+            .join(format!("com.lanmic.{}.plist", entry_id())),
+    )
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn entry_path() -> Option<std::path::PathBuf> {
+    Some(
+        dirs::config_dir()?
+            .join("autostart")
+            .join(format!("{}.desktop", entry_id())),
+    )
+}
+
+#[cfg(not(target_os = "windows"))]
+fn read_entry() -> Option<String> {
+    std::fs::read_to_string(entry_path()?).ok()
+}
+
+#[cfg(target_os = "macos")]
+fn entry_contents(exe: &std::path::Path, args: &[String]) -> String {
+    fn xml_escape(s: &str) -> String {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+    let program_args: String = std::iter::once(exe.display().to_string())
+        .chain(args.iter().cloned())
+        .map(|arg| format!("        <string>{}</string>\n", xml_escape(&arg)))
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.lanmic.{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{program_args}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        entry_id()
+    )
+}
+
+/// Quote `arg` for a desktop entry `Exec=` line if it has spaces or quotes.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"', '\'']) {
+        return arg.to_string();
+    }
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn command_line(exe: &std::path::Path, args: &[String]) -> String {
+    std::iter::once(quote(&exe.display().to_string()))
+        .chain(args.iter().map(|arg| quote(arg)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn entry_contents(exe: &std::path::Path, args: &[String]) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name={}\n\
+         Exec={}\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        crate::profile::current().title,
+        command_line(exe, args)
+    )
+}
+
+#[cfg(not(target_os = "windows"))]
+fn write_entry(exe: &std::path::Path, args: &[String]) -> Result<()> {
+    let path = entry_path().ok_or_else(|| anyhow!("No home directory"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(&path, entry_contents(exe, args))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn remove_entry() -> Result<()> {
+    let Some(path) = entry_path() else {
+        return Ok(());
+    };
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(anyhow!("Failed to remove {}: {e}", path.display())),
+    }
+}
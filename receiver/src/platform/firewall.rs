@@ -0,0 +1,124 @@
+//! Windows Firewall: an inbound allow rule so LAN senders can reach us.
+//!
+//! Windows blocks inbound connections for an unknown program, and unlike
+//! an interactive prompt the block is silent for senders: the page never
+//! loads and ICE never connects. The rule allows this executable, from the
+//! local subnet only, on every network profile.
+
+use anyhow::Result;
+
+/// Display name of the rule in Windows Defender Firewall.
+#[cfg(target_os = "windows")]
+const RULE_NAME: &str = "LAN Mic Receiver";
+
+/// State of our inbound rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub enum RuleStatus {
+    /// A rule allows this executable.
+    Present,
+    /// No rule yet.
+    Missing,
+    /// The rule allows another copy of the executable, e.g. from before
+    /// the app was moved or updated.
+    Stale,
+}
+
+impl std::fmt::Display for RuleStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RuleStatus::Present => "Allowed through Windows Firewall",
+            RuleStatus::Missing => "No firewall rule — senders may be blocked",
+            RuleStatus::Stale => "Firewall rule points at an older copy of the app",
+        })
+    }
+}
+
+/// Our rule's state; `None` where there is no Windows Firewall or it could
+/// not be queried.
+#[cfg(target_os = "windows")]
+pub fn status() -> Option<RuleStatus> {
+    use std::os::windows::process::CommandExt;
+
+    let exe = std::env::current_exe().ok()?;
+    let output = std::process::Command::new("netsh")
+        .raw_arg(format!(
+            "advfirewall firewall show rule name=\"{RULE_NAME}\" verbose"
+        ))
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        // "No rules match the specified criteria."
+        return Some(RuleStatus::Missing);
+    }
+    // Field labels are localized, so look for the path alone
+    let listing = String::from_utf8_lossy(&output.stdout).to_lowercase();
+    let exe = exe.display().to_string().to_lowercase();
+    Some(if listing.contains(&exe) {
+        RuleStatus::Present
+    } else {
+        RuleStatus::Stale
+    })
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn status() -> Option<RuleStatus> {
+    None
+}
+
+/// Create the rule, or point a stale one at this executable. Blocks until
+/// the user answers the UAC prompt; declining it is an error.
+#[cfg(target_os = "windows")]
+pub fn allow() -> Result<()> {
+    use anyhow::{anyhow, bail, Context};
+    use std::io::Write;
+    use std::os::windows::process::CommandExt;
+
+    let exe = std::env::current_exe().context("Failed to locate the executable")?;
+    let rule = format!(
+        "program=\"{}\" dir=in action=allow enable=yes profile=any remoteip=localsubnet",
+        exe.display()
+    );
+    let netsh_args = match status() {
+        Some(RuleStatus::Stale) => {
+            format!("advfirewall firewall set rule name=\"{RULE_NAME}\" new {rule}")
+        }
+        _ => format!("advfirewall firewall add rule name=\"{RULE_NAME}\" {rule}"),
+    };
+    // netsh needs elevation; PowerShell's `-Verb RunAs` shows the UAC
+    // prompt. The script goes through stdin to stay clear of quoting.
+    let script = format!(
+        "$p = Start-Process -FilePath netsh -ArgumentList '{}' -Verb RunAs -Wait -PassThru -WindowStyle Hidden\r\n\
+         exit $p.ExitCode\r\n",
+        netsh_args.replace('\'', "''")
+    );
+    let mut child = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", "-"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run PowerShell: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(script.as_bytes())?;
+    }
+    let exit = child.wait()?;
+    if !exit.success() {
+        // Also the exit when the UAC prompt is declined
+        bail!("Administrator permission declined or netsh failed ({exit})");
+    }
+    match status() {
+        Some(RuleStatus::Present) => Ok(()),
+        _ => bail!("The rule was not created"),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn allow() -> Result<()> {
+    anyhow::bail!("Firewall rules are only managed on Windows")
+}
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
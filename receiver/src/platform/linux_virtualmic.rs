@@ -0,0 +1,182 @@
+//! Linux virtual microphone: a "LAN Mic" source that video-call and
+//! recording apps can pick like any other microphone.
+//!
+//! Both PulseAudio and PipeWire (through pipewire-pulse) take `pactl`
+//! commands. A null sink receives the receiver's playback, and a remapped
+//! source exposes that sink's monitor as an ordinary input; many apps hide
+//! monitor sources from their microphone lists.
+//!
+//! Only the main output on the system default device plays into the sink;
+//! the monitor, per-sender outputs and a device the user picked stay where
+//! they are.
+
+use anyhow::Result;
+#[cfg(target_os = "linux")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set while the sink exists, so new main outputs are routed into it.
+#[cfg(target_os = "linux")]
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Null sink the receiver plays into.
+#[cfg(target_os = "linux")]
+const SINK_NAME: &str = "lan_mic_output";
+
+/// The virtual microphone other apps record from.
+#[cfg(target_os = "linux")]
+const SOURCE_NAME: &str = "lan_mic";
+
+/// Whether a sound server that takes `pactl` commands is running.
+#[cfg(target_os = "linux")]
+pub fn available() -> bool {
+    pactl(&["info"]).is_ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn available() -> bool {
+    false
+}
+
+/// Create the "LAN Mic" source, replacing one left behind by a crash. Main
+/// outputs opened from now on play into it (see [`route_new_streams`]).
+#[cfg(target_os = "linux")]
+pub fn create() -> Result<()> {
+    remove()?;
+    pactl(&[
+        "load-module",
+        "module-null-sink",
+        &format!("sink_name={SINK_NAME}"),
+        "sink_properties=\"device.description='LAN Mic Output'\"",
+    ])?;
+    let source = pactl(&[
+        "load-module",
+        "module-remap-source",
+        &format!("master={SINK_NAME}.monitor"),
+        &format!("source_name={SOURCE_NAME}"),
+        "source_properties=\"device.description='LAN Mic'\"",
+    ]);
+    if let Err(e) = source {
+        // Don't leave a sink nothing records from
+        let _ = remove();
+        return Err(e);
+    }
+    ACTIVE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn create() -> Result<()> {
+    anyhow::bail!("The virtual microphone is only available on Linux")
+}
+
+/// Unload the source and sink. Not created is not an error.
+#[cfg(target_os = "linux")]
+pub fn remove() -> Result<()> {
+    ACTIVE.store(false, Ordering::Relaxed);
+    // "<index>\t<module>\t<arguments>", e.g.
+    // "536870913\tmodule-null-sink\tsink_name=lan_mic_output …"
+    let modules = pactl(&["list", "short", "modules"])?;
+    let ours = [
+        format!("sink_name={SINK_NAME}"),
+        format!("source_name={SOURCE_NAME}"),
+    ];
+    // The source goes first, while its master still exists
+    let mut indexes: Vec<&str> = modules
+        .lines()
+        .filter(|line| {
+            line.split('\t')
+                .nth(2)
+                .is_some_and(|args| args.split(' ').any(|arg| ours.iter().any(|o| o == arg)))
+        })
+        .filter_map(|line| line.split('\t').next())
+        .collect();
+    indexes.reverse();
+    for index in indexes {
+        pactl(&["unload-module", index])?;
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn remove() -> Result<()> {
+    Ok(())
+}
+
+/// Run `open`, which opens the main output stream, and move the playback
+/// stream it created into the virtual microphone while that exists.
+///
+/// Every stream of this process looks alike to the sound server ("ALSA
+/// Playback"), so the main output's is told apart as the one that appeared
+/// during `open`. Streams opened directly on a hardware device (`hw:`,
+/// `plughw:`) bypass the sound server and can't be moved. A failed move is
+/// logged; the output still plays, just not into the microphone.
+#[cfg(target_os = "linux")]
+pub fn route_new_streams<T>(open: impl FnOnce() -> Result<T>) -> Result<T> {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return open();
+    }
+    let before = our_sink_inputs().unwrap_or_default();
+    let opened = open()?;
+    let moved = our_sink_inputs().and_then(|after| {
+        let new: Vec<_> = after.iter().filter(|i| !before.contains(i)).collect();
+        for index in &new {
+            pactl(&["move-sink-input", index, SINK_NAME])?;
+        }
+        Ok(new.len())
+    });
+    match moved {
+        Ok(0) => {
+            log::info!("Main output isn't on the sound server; the virtual microphone stays silent")
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("Failed to route output to the virtual microphone: {e:#}"),
+    }
+    Ok(opened)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn route_new_streams<T>(open: impl FnOnce() -> Result<T>) -> Result<T> {
+    open()
+}
+
+/// Sink-input indexes of this process's playback streams.
+#[cfg(target_os = "linux")]
+fn our_sink_inputs() -> Result<Vec<String>> {
+    let pid = format!("\"{}\"", std::process::id());
+    let listing = pactl(&["list", "sink-inputs"])?;
+    // "Sink Input #42" blocks, each with an
+    // "application.process.id = "1234"" property
+    let mut current = None;
+    let mut ours = Vec::new();
+    for line in listing.lines() {
+        let line = line.trim();
+        if let Some(index) = line.strip_prefix("Sink Input #") {
+            current = Some(index.to_string());
+        } else if let Some(value) = line.strip_prefix("application.process.id = ") {
+            if value == pid {
+                ours.extend(current.take());
+            }
+        }
+    }
+    Ok(ours)
+}
+
+/// Run `pactl` with untranslated output; returns its stdout.
+#[cfg(target_os = "linux")]
+fn pactl(args: &[&str]) -> Result<String> {
+    use anyhow::{bail, Context};
+
+    let output = std::process::Command::new("pactl")
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .context("Failed to run pactl")?;
+    if !output.status.success() {
+        bail!(
+            "pactl {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
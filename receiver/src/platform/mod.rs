@@ -0,0 +1,5 @@
+//! Integration with the host operating system.
+
+pub mod autostart;
+pub mod firewall;
+pub mod linux_virtualmic;
@@ -0,0 +1,295 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Port the HTTP/WebSocket server listens on unless `--port` is given.
+pub const DEFAULT_PORT: u16 = 9001;
+
+/// Ports after the requested one to try when it is taken, unless
+/// `--port-fallback` is given.
+pub const DEFAULT_PORT_FALLBACK: u16 = 10;
+
+const APP_INSTANCE_NAME: &str = "lan-mic-receiver-single-instance";
+
+/// DNS-SD instance names are a single DNS label.
+const MAX_DISPLAY_NAME_BYTES: usize = 63;
+
+/// Which receiver this process is, selected on the command line.
+///
+/// Running `--profile roomB --port 9002` next to the default instance gives
+/// it its own single-instance lock, settings directory and mDNS name, so two
+/// receivers (e.g. two rooms or two virtual cables) can share one PC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: Option<String>,
+    pub port: u16,
+    /// Window, tray and mDNS display name.
+    pub title: String,
+    /// `--name`: what senders see ("Studio PC"), replacing the default
+    /// "<title> (<hostname>)".
+    pub display_name: Option<String>,
+    /// `--acme-domain`: get a trusted certificate for this hostname.
+    pub acme: Option<AcmeOptions>,
+    /// `--mtls`: only senders with a client certificate issued by this
+    /// receiver may connect.
+    pub mtls: bool,
+    /// `--pcm-port`: accept raw PCM over TCP on this port while listening.
+    pub pcm_port: Option<u16>,
+    /// `--rtp-port`: accept plain RTP Opus over UDP on this port while
+    /// listening.
+    pub rtp_port: Option<u16>,
+    /// `--http-port`: also serve plain `http://`/`ws://` on this port, for
+    /// senders that can't accept the self-signed certificate.
+    pub http_port: Option<u16>,
+    /// `--api-token`: fixed token for the remote control API, for buttons
+    /// and scripts that can't learn the per-launch admin token.
+    pub api_token: Option<String>,
+    /// `--metrics`: serve Prometheus metrics at `/metrics`.
+    pub metrics: bool,
+    /// `--minimized`: start hidden in the tray, as when launched at login.
+    pub minimized: bool,
+    /// `--ipv6`: bind `[::]` by default, for IPv6 as well as IPv4 senders.
+    pub ipv6: bool,
+    /// `--port-fallback N`: try up to N following ports when the port is
+    /// taken by another program (0 = wait for it instead).
+    pub port_fallback: u16,
+}
+
+/// Certificate provisioning from Let's Encrypt for a receiver reachable
+/// under a real DNS name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcmeOptions {
+    pub domain: String,
+    /// Account contact, for expiry notices.
+    pub email: Option<String>,
+    /// Use the Let's Encrypt staging directory (untrusted test certificates).
+    pub staging: bool,
+    /// Prove control with DNS-01 by running `HOOK present|cleanup NAME VALUE`
+    /// instead of answering HTTP-01 on port 80.
+    pub dns_hook: Option<String>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: None,
+            port: DEFAULT_PORT,
+            title: "LAN Mic Receiver".to_string(),
+            display_name: None,
+            acme: None,
+            mtls: false,
+            pcm_port: None,
+            rtp_port: None,
+            http_port: None,
+            api_token: None,
+            metrics: false,
+            minimized: false,
+            ipv6: false,
+            port_fallback: DEFAULT_PORT_FALLBACK,
+        }
+    }
+}
+
+static CURRENT: OnceLock<Profile> = OnceLock::new();
+
+/// The profile this process was started with.
+pub fn current() -> &'static Profile {
+    CURRENT.get_or_init(Profile::default)
+}
+
+/// Fix the profile for the rest of the process. Call once, before anything
+/// reads [`current`].
+pub fn init(profile: Profile) {
+    let _ = CURRENT.set(profile);
+}
+
+impl Profile {
+    /// Parse `--profile NAME`, `--port N`, `--name "DISPLAY NAME"` and the
+    /// `--acme-*`, `--mtls`, `--pcm-port N`, `--rtp-port N`, `--http-port N`,
+    /// `--api-token TOKEN`, `--metrics`, `--minimized`, `--ipv6` and
+    /// `--port-fallback N` options (also `--flag=value`).
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut profile = Profile::default();
+        let mut acme_domain = None;
+        let mut acme = AcmeOptions {
+            domain: String::new(),
+            email: None,
+            staging: false,
+            dns_hook: None,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{flag} needs a value"))
+            };
+            match flag.as_str() {
+                "--profile" => {
+                    let name = value()?;
+                    if name.is_empty()
+                        || !name
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                    {
+                        return Err(format!(
+                            "invalid profile name '{name}' (use letters, digits, '-' or '_')"
+                        ));
+                    }
+                    profile.title = format!("LAN Mic Receiver — {name}");
+                    profile.name = Some(name);
+                }
+                "--port" => {
+                    let port = value()?;
+                    profile.port = port
+                        .parse()
+                        .ok()
+                        .filter(|&p| p != 0)
+                        .ok_or_else(|| format!("invalid port '{port}'"))?;
+                }
+                "--name" => {
+                    let name = value()?.trim().to_string();
+                    if name.is_empty() || name.len() > MAX_DISPLAY_NAME_BYTES {
+                        return Err(format!(
+                            "invalid name '{name}' (1 to {MAX_DISPLAY_NAME_BYTES} bytes)"
+                        ));
+                    }
+                    profile.display_name = Some(name);
+                }
+                "--acme-domain" => {
+                    let domain = value()?.trim().trim_end_matches('.').to_ascii_lowercase();
+                    if domain.is_empty()
+                        || !domain.contains('.')
+                        || !domain
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+                    {
+                        return Err(format!("invalid domain '{domain}'"));
+                    }
+                    acme_domain = Some(domain);
+                }
+                "--acme-email" => acme.email = Some(value()?),
+                "--acme-staging" => acme.staging = true,
+                "--acme-dns-hook" => acme.dns_hook = Some(value()?),
+                "--mtls" => profile.mtls = true,
+                "--pcm-port" => {
+                    let port = value()?;
+                    profile.pcm_port = Some(
+                        port.parse()
+                            .ok()
+                            .filter(|&p| p != 0)
+                            .ok_or_else(|| format!("invalid port '{port}'"))?,
+                    );
+                }
+                "--rtp-port" => {
+                    let port = value()?;
+                    profile.rtp_port = Some(
+                        port.parse()
+                            .ok()
+                            .filter(|&p| p != 0)
+                            .ok_or_else(|| format!("invalid port '{port}'"))?,
+                    );
+                }
+                "--http-port" => {
+                    let port = value()?;
+                    profile.http_port = Some(
+                        port.parse()
+                            .ok()
+                            .filter(|&p| p != 0)
+                            .ok_or_else(|| format!("invalid port '{port}'"))?,
+                    );
+                }
+                "--api-token" => {
+                    let token = value()?;
+                    if token.len() < 8 {
+                        return Err("--api-token needs at least 8 characters".to_string());
+                    }
+                    profile.api_token = Some(token);
+                }
+                "--metrics" => profile.metrics = true,
+                "--ipv6" => profile.ipv6 = true,
+                "--port-fallback" => {
+                    let count = value()?;
+                    profile.port_fallback = count
+                        .parse()
+                        .map_err(|_| format!("invalid port count '{count}'"))?;
+                }
+                crate::platform::autostart::MINIMIZED_FLAG => profile.minimized = true,
+                other => return Err(format!("unknown argument '{other}'")),
+            }
+        }
+        let acme_set = acme.email.is_some() || acme.staging || acme.dns_hook.is_some();
+        match acme_domain {
+            Some(domain) => profile.acme = Some(AcmeOptions { domain, ..acme }),
+            None if acme_set => return Err("--acme-* options need --acme-domain".to_string()),
+            None => {}
+        }
+        if let Some(port) = profile.http_port {
+            if port == profile.port {
+                return Err(format!("--http-port {port} is already the HTTPS port"));
+            }
+            if profile.mtls {
+                // A plain listener would let senders skip the client certificate
+                return Err("--http-port can't be combined with --mtls".to_string());
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Single-instance lock name. The default profile keeps the historical
+    /// name so older builds still see it.
+    pub fn instance_name(&self) -> String {
+        if self.name.is_none() && self.port == DEFAULT_PORT {
+            return APP_INSTANCE_NAME.to_string();
+        }
+        format!(
+            "{APP_INSTANCE_NAME}-{}-{}",
+            self.name.as_deref().unwrap_or("default"),
+            self.port
+        )
+    }
+
+    /// Where this profile's persistent files live.
+    pub fn config_dir(&self) -> Option<PathBuf> {
+        let base = dirs::config_dir()?.join("lan-mic");
+        Some(match &self.name {
+            Some(name) => base.join("profiles").join(name),
+            None => base,
+        })
+    }
+
+    /// Where this profile's databases and other app data live.
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        let base = dirs::data_dir()?.join("lan-mic");
+        Some(match &self.name {
+            Some(name) => base.join("profiles").join(name),
+            None => base,
+        })
+    }
+
+    /// Name advertised over mDNS and shown on the sender page.
+    pub fn service_name(&self) -> String {
+        match &self.display_name {
+            Some(name) => name.clone(),
+            None => format!("{} ({})", self.title, hostname()),
+        }
+    }
+
+    pub fn bind_addr(&self) -> String {
+        if self.ipv6 {
+            format!("[::]:{}", self.port)
+        } else {
+            format!("0.0.0.0:{}", self.port)
+        }
+    }
+}
+
+pub fn hostname() -> String {
+    gethostname::gethostname()
+        .into_string()
+        .unwrap_or_else(|_| "lan-mic-receiver".to_string())
+}
@@ -0,0 +1,383 @@
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam_queue::ArrayQueue;
+use flacenc::bitsink::ByteSink;
+use flacenc::component::{BitRepr, Stream};
+use flacenc::error::Verify;
+use flacenc::source::{Fill, FrameBuf};
+use parking_lot::Mutex;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const SAMPLE_RATE: u32 = 48_000;
+
+/// Tap buffer capacity (mono i16 @ 48 kHz, ~2 seconds of disk stalls).
+const TAP_CAPACITY: usize = 96_000;
+
+/// Bytes before the sample data in the WAV files written here.
+const WAV_HEADER_LEN: u64 = 44;
+
+/// Samples per FLAC frame (the reference encoder's default).
+const FLAC_BLOCK_SIZE: usize = 4096;
+
+/// File format of a finished recording.
+///
+/// Audio is always captured to WAV, which survives a crash mid-recording;
+/// FLAC recordings are converted once stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordingFormat {
+    #[default]
+    Wav,
+    Flac,
+}
+
+impl RecordingFormat {
+    pub const ALL: [RecordingFormat; 2] = [RecordingFormat::Wav, RecordingFormat::Flac];
+}
+
+impl fmt::Display for RecordingFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RecordingFormat::Wav => "WAV (uncompressed)",
+            RecordingFormat::Flac => "FLAC (lossless, about half the size)",
+        })
+    }
+}
+
+/// A cue point at a sample offset into the recording.
+#[derive(Debug, Clone)]
+pub struct Marker {
+    pub sample: u64,
+    pub label: String,
+}
+
+/// Receives the main mix from the output callback plus session-event markers.
+///
+/// The callback only pushes into a lock-free queue; a writer thread drains it
+/// to disk so file I/O never blocks audio.
+pub struct RecordingTap {
+    queue: ArrayQueue<i16>,
+    /// Samples handed to the tap so far — the timeline markers are placed on.
+    samples_pushed: AtomicU64,
+    dropped: AtomicU64,
+    markers: Mutex<Vec<Marker>>,
+}
+
+impl RecordingTap {
+    fn new() -> Self {
+        Self {
+            queue: ArrayQueue::new(TAP_CAPACITY),
+            samples_pushed: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            markers: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn push(&self, samples: &[i16]) {
+        for &s in samples {
+            if self.queue.push(s).is_err() {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.samples_pushed
+            .fetch_add(samples.len() as u64, Ordering::Relaxed);
+    }
+
+    /// Drop a cue marker at the current recording position.
+    pub fn mark(&self, label: impl Into<String>) {
+        let sample = self.samples_pushed.load(Ordering::Relaxed);
+        self.markers.lock().push(Marker {
+            sample,
+            label: label.into(),
+        });
+    }
+}
+
+/// An in-progress 48 kHz mono 16-bit WAV recording.
+pub struct Recorder {
+    path: PathBuf,
+    format: RecordingFormat,
+    tap: Arc<RecordingTap>,
+    stop: Arc<AtomicBool>,
+    join: Option<std::thread::JoinHandle<Result<()>>>,
+}
+
+impl Recorder {
+    /// Create the file and start the writer thread.
+    pub fn start(path: PathBuf, format: RecordingFormat) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        let file = File::create(&path).with_context(|| format!("create {}", path.display()))?;
+
+        let tap = Arc::new(RecordingTap::new());
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_tap = Arc::clone(&tap);
+        let thread_stop = Arc::clone(&stop);
+        let sidecar = marker_sidecar_path(&path);
+        let join = std::thread::Builder::new()
+            .name("recorder".into())
+            .spawn(move || write_loop(file, &sidecar, &thread_tap, &thread_stop))?;
+
+        Ok(Self {
+            path,
+            format,
+            tap,
+            stop,
+            join: Some(join),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> RecordingFormat {
+        self.format
+    }
+
+    pub fn tap(&self) -> Arc<RecordingTap> {
+        Arc::clone(&self.tap)
+    }
+
+    /// Flush remaining samples, write markers, and finalize the WAV header.
+    pub fn stop(mut self) -> Result<PathBuf> {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(join) = self.join.take() {
+            join.join()
+                .map_err(|_| anyhow!("recorder thread panicked"))??;
+        }
+        let dropped = self.tap.dropped.load(Ordering::Relaxed);
+        if dropped > 0 {
+            log::warn!("Recorder dropped {dropped} samples (disk too slow)");
+        }
+        Ok(self.path.clone())
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        // Make sure the file is finalized even if `stop` was never called.
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(join) = self.join.take() {
+            let _ = join.join();
+        }
+    }
+}
+
+/// Default file name for a new recording: `lan-mic-<unix seconds>.wav`.
+pub fn default_recording_path() -> PathBuf {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let dir = dirs::audio_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| PathBuf::from("."));
+    dir.join("LAN Mic").join(format!("lan-mic-{secs}.wav"))
+}
+
+/// Audacity-style label track next to the recording (`foo.wav` → `foo.txt`).
+fn marker_sidecar_path(path: &Path) -> PathBuf {
+    path.with_extension("txt")
+}
+
+// ---------------------------------------------------------------------------
+// Writer thread
+// ---------------------------------------------------------------------------
+
+fn write_loop(file: File, sidecar: &Path, tap: &RecordingTap, stop: &AtomicBool) -> Result<()> {
+    let mut out = BufWriter::new(file);
+    write_wav_header(&mut out, 0)?;
+
+    let mut data_bytes: u64 = 0;
+    loop {
+        let stopping = stop.load(Ordering::Relaxed);
+        while let Some(s) = tap.queue.pop() {
+            out.write_all(&s.to_le_bytes())?;
+            data_bytes += 2;
+        }
+        if stopping {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    let markers = std::mem::take(&mut *tap.markers.lock());
+    write_cue_chunks(&mut out, &markers)?;
+
+    // Patch RIFF and data sizes now that the length is known.
+    let end = out.stream_position()?;
+    out.seek(SeekFrom::Start(4))?;
+    out.write_all(&((end - 8) as u32).to_le_bytes())?;
+    out.seek(SeekFrom::Start(40))?;
+    out.write_all(&(data_bytes as u32).to_le_bytes())?;
+    out.flush()?;
+
+    if !markers.is_empty() {
+        let mut labels = String::new();
+        for m in &markers {
+            let t = m.sample as f64 / SAMPLE_RATE as f64;
+            labels.push_str(&format!("{t:.3}\t{t:.3}\t{}\n", m.label));
+        }
+        std::fs::write(sidecar, labels)?;
+    }
+    Ok(())
+}
+
+fn write_wav_header(out: &mut impl Write, data_bytes: u32) -> Result<()> {
+    let channels: u16 = 1;
+    let bits: u16 = 16;
+    let block_align = channels * bits / 8;
+    let byte_rate = SAMPLE_RATE * block_align as u32;
+
+    out.write_all(b"RIFF")?;
+    out.write_all(&(36 + data_bytes).to_le_bytes())?;
+    out.write_all(b"WAVE")?;
+    out.write_all(b"fmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?; // PCM
+    out.write_all(&channels.to_le_bytes())?;
+    out.write_all(&SAMPLE_RATE.to_le_bytes())?;
+    out.write_all(&byte_rate.to_le_bytes())?;
+    out.write_all(&block_align.to_le_bytes())?;
+    out.write_all(&bits.to_le_bytes())?;
+    out.write_all(b"data")?;
+    out.write_all(&data_bytes.to_le_bytes())?;
+    Ok(())
+}
+
+/// Append `cue ` points and a `LIST/adtl` chunk with one `labl` per marker —
+/// the layout Audacity, Reaper and most DAWs read as markers.
+fn write_cue_chunks(out: &mut impl Write, markers: &[Marker]) -> Result<()> {
+    if markers.is_empty() {
+        return Ok(());
+    }
+
+    out.write_all(b"cue ")?;
+    out.write_all(&(4 + 24 * markers.len() as u32).to_le_bytes())?;
+    out.write_all(&(markers.len() as u32).to_le_bytes())?;
+    for (i, m) in markers.iter().enumerate() {
+        let id = i as u32 + 1;
+        let pos = m.sample.min(u32::MAX as u64) as u32;
+        out.write_all(&id.to_le_bytes())?;
+        out.write_all(&pos.to_le_bytes())?;
+        out.write_all(b"data")?;
+        out.write_all(&0u32.to_le_bytes())?; // chunk start
+        out.write_all(&0u32.to_le_bytes())?; // block start
+        out.write_all(&pos.to_le_bytes())?;
+    }
+
+    let mut adtl = Vec::new();
+    adtl.extend_from_slice(b"adtl");
+    for (i, m) in markers.iter().enumerate() {
+        let text = m.label.as_bytes();
+        let size = 4 + text.len() as u32 + 1;
+        adtl.extend_from_slice(b"labl");
+        adtl.extend_from_slice(&size.to_le_bytes());
+        adtl.extend_from_slice(&(i as u32 + 1).to_le_bytes());
+        adtl.extend_from_slice(text);
+        adtl.push(0);
+        if size % 2 == 1 {
+            adtl.push(0);
+        }
+    }
+    out.write_all(b"LIST")?;
+    out.write_all(&(adtl.len() as u32).to_le_bytes())?;
+    out.write_all(&adtl)?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// FLAC conversion
+// ---------------------------------------------------------------------------
+
+/// Encode a finished recording from this module as FLAC next to it
+/// (`foo.wav` → `foo.flac`) and remove the WAV. Streams frame by frame, so
+/// long recordings don't need to fit in memory. Cue markers stay in the
+/// `.txt` label file.
+pub fn convert_to_flac(wav: &Path) -> Result<PathBuf> {
+    let mut input = File::open(wav).with_context(|| format!("open {}", wav.display()))?;
+    let mut header = [0u8; WAV_HEADER_LEN as usize];
+    input.read_exact(&mut header).context("read WAV header")?;
+    if &header[..4] != b"RIFF" || &header[36..40] != b"data" {
+        bail!("{} is not a recording from this app", wav.display());
+    }
+    let data_bytes = u32::from_le_bytes([header[40], header[41], header[42], header[43]]);
+    let mut input = BufReader::new(input).take(u64::from(data_bytes));
+
+    let path = wav.with_extension("flac");
+    let mut out =
+        BufWriter::new(File::create(&path).with_context(|| format!("create {}", path.display()))?);
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| anyhow!("FLAC config: {e}"))?;
+    let mut stream = Stream::new(SAMPLE_RATE as usize, 1, 16)?;
+    let mut sink = ByteSink::new();
+
+    // Placeholder header, rewritten with the final stream info at the end
+    stream
+        .write(&mut sink)
+        .map_err(|e| anyhow!("FLAC header: {e}"))?;
+    out.write_all(sink.as_slice())?;
+
+    let mut framebuf = FrameBuf::with_size(1, FLAC_BLOCK_SIZE)?;
+    let mut context = flacenc::source::Context::new(16, 1);
+    let mut block = vec![0u8; FLAC_BLOCK_SIZE * 2];
+    loop {
+        let n = read_full(&mut input, &mut block)?;
+        if n < 2 {
+            break;
+        }
+        let bytes = &block[..n - n % 2];
+        (&mut framebuf, &mut context)
+            .fill_le_bytes(bytes, 2)
+            .map_err(|e| anyhow!("FLAC input: {e}"))?;
+        let frame_number = context.current_frame_number().unwrap_or(0);
+        let frame = flacenc::encode_fixed_size_frame(
+            &config,
+            &framebuf,
+            frame_number,
+            stream.stream_info(),
+        )
+        .map_err(|e| anyhow!("FLAC encode: {e}"))?;
+        stream.stream_info_mut().update_frame_info(&frame);
+        sink.clear();
+        frame
+            .write(&mut sink)
+            .map_err(|e| anyhow!("FLAC frame: {e}"))?;
+        out.write_all(sink.as_slice())?;
+    }
+
+    stream
+        .stream_info_mut()
+        .set_md5_digest(&context.md5_digest());
+    sink.clear();
+    stream
+        .write(&mut sink)
+        .map_err(|e| anyhow!("FLAC header: {e}"))?;
+    out.seek(SeekFrom::Start(0))?;
+    out.write_all(sink.as_slice())?;
+    out.flush()
+        .with_context(|| format!("write {}", path.display()))?;
+
+    std::fs::remove_file(wav).with_context(|| format!("remove {}", wav.display()))?;
+    Ok(path)
+}
+
+/// Fill `buf` as far as the reader allows; returns the bytes read.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}